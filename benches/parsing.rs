@@ -0,0 +1,133 @@
+//! Measures the speed of the parser, so that regressions and optimizations
+//! can be compared between two revisions:
+//!
+//! ```sh
+//! cargo bench --bench parsing
+//! ```
+//!
+//! The measurements are made by criterion, which keeps the results of the
+//! previous run in `target/criterion` and reports the change against them.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use csv_tools::parser;
+use csv_tools::{system_clock, CSVFile, CSVReader, ReaderOptions};
+
+const ROWS: usize = 50_000;
+
+/// Generates a file mixing plain, quoted and escaped fields.
+fn generate_text() -> String {
+    let mut text = String::from("id,name,description,price,date\n");
+    for i in 0..ROWS {
+        if i % 3 == 0 {
+            text.push_str(&format!(
+                "{},Item {},\"A description, with \\\"quotes\\\"\",{}.99,2024-01-{:02}\n",
+                i,
+                i,
+                i % 100,
+                i % 28 + 1
+            ));
+        } else {
+            text.push_str(&format!(
+                "{},Item {},A plain description,{}.50,2024-02-{:02}\n",
+                i,
+                i,
+                i % 100,
+                i % 28 + 1
+            ));
+        }
+    }
+    text
+}
+
+/// Generates a file with a few short columns, where the cost of
+/// allocating each row outweighs the cost of parsing it.
+fn generate_narrow_text() -> String {
+    let mut text = String::from("id,code,ok\n");
+    for i in 0..ROWS {
+        text.push_str(&format!("{},C{},{}\n", i, i % 10, i % 2));
+    }
+    text
+}
+
+fn parsing(c: &mut Criterion) {
+    let text = generate_text();
+    let lines: Vec<&str> = text.lines().skip(1).collect();
+    let options = ReaderOptions::default();
+
+    let mut group = c.benchmark_group("parsing");
+    group.sample_size(20);
+    group.throughput(Throughput::Bytes(text.len() as u64));
+
+    group.bench_function("parser::parse_line", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(parser::parse_line(black_box(line), ',').unwrap());
+            }
+        })
+    });
+
+    group.bench_function("parser::split_line", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(parser::split_line(black_box(line), ','));
+            }
+        })
+    });
+
+    group.bench_function("CSVReader", |b| {
+        b.iter(|| {
+            let reader = CSVReader::from_reader(text.as_bytes(), &options).unwrap();
+            for row in reader {
+                black_box(row.unwrap());
+            }
+        })
+    });
+
+    group.bench_function("CSVFile::from_text", |b| {
+        b.iter(|| black_box(CSVFile::from_text(&text, &options).unwrap()))
+    });
+
+    let file = CSVFile::from_text(&text, &options).unwrap();
+    group.bench_function("CSVFile::to_string", |b| {
+        b.iter(|| black_box(file.to_string()))
+    });
+
+    let narrow_text = generate_narrow_text();
+    group.throughput(Throughput::Bytes(narrow_text.len() as u64));
+    group.bench_function("CSVReader (narrow)", |b| {
+        b.iter(|| {
+            let reader = CSVReader::from_reader(narrow_text.as_bytes(), &options).unwrap();
+            for row in reader {
+                black_box(row.unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// Reads the file with the timing counters of [ReadStats](`csv_tools::ReadStats`)
+/// enabled, to measure what they cost compared to the untimed reads.
+fn read_stats(c: &mut Criterion) {
+    let text = generate_text();
+    let options = ReaderOptions {
+        clock: Some(system_clock),
+        ..ReaderOptions::default()
+    };
+
+    let mut group = c.benchmark_group("read_stats");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function("CSVFile::from_text (timed)", |b| {
+        b.iter(|| {
+            let file = CSVFile::from_text(&text, &options).unwrap();
+            black_box(file.read_stats().unwrap().rows_per_second())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parsing, read_stats);
+criterion_main!(benches);
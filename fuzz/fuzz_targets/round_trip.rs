@@ -0,0 +1,26 @@
+//! Reads arbitrary text, and makes sure that whatever could be parsed
+//! is read back identically once written.
+//!
+//! ```sh
+//! cargo +nightly fuzz run round_trip
+//! ```
+
+#![no_main]
+
+use csv_tools::{CSVFile, ReaderOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let options = ReaderOptions::default();
+    let Ok(file) = CSVFile::from_text(text, &options) else {
+        return;
+    };
+
+    let written = file.to_string();
+    let read = CSVFile::from_text(&written, &options).expect("the written text can't be read");
+    assert_eq!(read.columns, file.columns);
+    assert_eq!(read.rows, file.rows);
+});
@@ -0,0 +1,56 @@
+//! Alternative names of the columns, for the files whose header varies from one source to another.
+
+use std::collections::HashMap;
+
+use crate::CSVFile;
+
+impl CSVFile {
+    /// Sets the aliases of the columns, replacing the previous ones. Each canonical name
+    /// is given along with the names the column may have in the files, such as
+    /// `"customer_id"` for the files using `"CustID"` or `"customer id"`.
+    ///
+    /// The canonical names can then be given to all the methods taking the name of a column.
+    /// A column actually named like the canonical name is always preferred,
+    /// and otherwise the first variant found in the header is used.
+    /// The header itself isn't modified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("CustID,total\n42,10.5\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let mut aliases = HashMap::new();
+    /// aliases.insert("customer_id".to_string(), vec!["CustID".to_string(), "customer id".to_string()]);
+    /// file.set_column_aliases(&aliases);
+    ///
+    /// let customer_id = "customer_id".to_string();
+    /// assert_eq!(file.get_column_idx(&customer_id), Some(0));
+    /// assert_eq!(file.resolve_column(&customer_id), Some(&"CustID".to_string()));
+    /// assert_eq!(file.query("customer_id = 42").unwrap(), vec![0]);
+    /// ```
+    pub fn set_column_aliases(&mut self, aliases: &HashMap<String, Vec<String>>) {
+        self.aliases = aliases.clone();
+    }
+
+    /// Returns the aliases of the columns, indexed by canonical name.
+    pub fn column_aliases(&self) -> &HashMap<String, Vec<String>> {
+        &self.aliases
+    }
+
+    /// Returns the name that a column, given by name or by alias, has in the header.
+    /// It returns `None` if the column doesn't exist.
+    pub fn resolve_column(&self, column_name: &String) -> Option<&String> {
+        self.get_column_idx(column_name)
+            .map(|idx| &self.columns[idx])
+    }
+
+    /// Finds the index of the first variant of a canonical name present in the header.
+    pub(crate) fn alias_idx(&self, column_name: &String) -> Option<usize> {
+        self.aliases
+            .get(column_name)?
+            .iter()
+            .find_map(|alias| self.columns.iter().position(|c| c == alias))
+    }
+}
@@ -0,0 +1,107 @@
+//! Appending rows to the end of a file on disk without reading it entirely.
+
+use std::fs::OpenOptions;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use crate::{CSVFile, CSVReader, ReaderOptions, WriterOptions};
+
+impl CSVFile {
+    /// Appends rows to the end of an existing CSV file, for example to write logs continuously.
+    /// Only the header of the file is read, to make sure that it has the given columns
+    /// in the same order and that the rows have as many fields, so the size of the file
+    /// doesn't matter.
+    ///
+    /// The rows are written with the options (the delimiter being a comma when the options
+    /// don't specify one), and the formatting rules apply to the columns of the header.
+    /// The byte order mark option is ignored. If the file doesn't end with a line break,
+    /// one is added before the new rows.
+    ///
+    /// It may return an error if the file can't be opened, if its header can't be read
+    /// or isn't made of the given columns, or if a row doesn't have the right number of fields,
+    /// in which case nothing is written.
+    ///
+    /// The rows aren't appended in a single write when they are long, so the rows appended
+    /// at the same time by another process may be mixed with them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, WriterOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_append_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "time,level\n10:00,info\n").unwrap();
+    ///
+    /// let columns = vec!["time".to_string(), "level".to_string()];
+    /// let rows = vec![vec!["10:05".to_string(), "warning, disk".to_string()]];
+    /// CSVFile::append_rows_to_file(&filename, &columns, &rows, &WriterOptions::default()).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&filename).unwrap(),
+    ///     "time,level\n10:00,info\n10:05,\"warning, disk\"\n"
+    /// );
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn append_rows_to_file(
+        filename: &String,
+        columns: &Vec<String>,
+        rows: &Vec<Vec<String>>,
+        options: &WriterOptions,
+    ) -> Result<(), Error> {
+        let delimiter = options.delimiter.unwrap_or(',');
+        let reader_options = ReaderOptions {
+            delimiter,
+            escape: options.escape,
+            ..ReaderOptions::default()
+        };
+        let file_columns = CSVReader::open(filename, &reader_options)?
+            .columns()
+            .clone();
+        if &file_columns != columns {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The columns of the file are {}, but the rows are for {}",
+                    file_columns.join(", "),
+                    columns.join(", ")
+                ),
+            ));
+        }
+
+        for row in rows {
+            if row.len() != columns.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid number of fields, {} were given, but expected {}",
+                        row.len(),
+                        columns.len()
+                    ),
+                ));
+            }
+        }
+
+        // The rows are written like a whole file would be, without its header.
+        let options = WriterOptions {
+            byte_order_mark: Some(false),
+            ..options.clone()
+        };
+        let header = CSVFile::from_rows(delimiter, columns.clone(), Vec::new())
+            .to_string_with_options(&options);
+        let written = CSVFile::from_rows(delimiter, columns.clone(), rows.clone())
+            .to_string_with_options(&options);
+        let mut text = written[header.len()..].to_string();
+
+        let mut file = OpenOptions::new().read(true).append(true).open(filename)?;
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                text.insert_str(0, options.line_ending.unwrap_or_default().as_str());
+            }
+        }
+
+        // The text is written at once to limit the interleaving with the concurrent appends,
+        // but `write_all` may still need several writes, each going to the end of the file.
+        file.write_all(text.as_bytes())?;
+        file.flush()
+    }
+}
@@ -0,0 +1,172 @@
+//! Converting a CSV file to and from Apache Arrow record batches, and writing Parquet files,
+//! so that the data can be handed to the analytics tools without going through text again.
+
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::stats::parse_number;
+use crate::timeseries::parse_timestamp;
+use crate::{CSVFile, CSVSchema, ColumnType};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Returns the Arrow type of the values of a column.
+fn arrow_type(column_type: ColumnType) -> DataType {
+    match column_type {
+        ColumnType::Text => DataType::Utf8,
+        ColumnType::Integer => DataType::Int64,
+        ColumnType::Number => DataType::Float64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Date => DataType::Date32,
+    }
+}
+
+impl CSVFile {
+    /// Converts the file to an Arrow record batch with the [inferred schema](`CSVFile::infer_schema`)
+    /// of the file. See [to_record_batch_with_schema](`CSVFile::to_record_batch_with_schema`).
+    ///
+    /// This method requires the `arrow` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// # use arrow_schema::DataType;
+    /// let file = CSVFile::from_text("name,age\nThomas,20\nJohn,\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let batch = file.to_record_batch().unwrap();
+    /// assert_eq!(batch.num_rows(), 2);
+    /// assert_eq!(batch.schema().field(1).data_type(), &DataType::Int64);
+    /// assert!(batch.column(1).is_null(1));
+    ///
+    /// assert_eq!(CSVFile::from_record_batch(&batch).unwrap().rows, file.rows);
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        self.to_record_batch_with_schema(&self.infer_schema())
+    }
+
+    /// Converts the file to an Arrow record batch whose columns have the types of a schema,
+    /// which must describe every column of the file. The text columns keep their values as they are,
+    /// while the empty values of the other columns are nulls, and the dates are days since 1970-01-01.
+    /// The values missing from the ragged rows are empty values.
+    ///
+    /// It may return an error if a column of the file isn't in the schema, if a value doesn't have
+    /// the type of its column, or if a required column has an empty value.
+    ///
+    /// This method requires the `arrow` feature.
+    pub fn to_record_batch_with_schema(&self, schema: &CSVSchema) -> Result<RecordBatch, Error> {
+        let mut fields = Vec::with_capacity(self.columns.len());
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.columns.len());
+        for (idx, name) in self.columns.iter().enumerate() {
+            let column = schema.column(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("The column '{}' isn't in the schema", name),
+                )
+            })?;
+            let column_type = column.column_type;
+
+            let array: ArrayRef = if column_type == ColumnType::Text {
+                Arc::new(StringArray::from_iter_values(
+                    self.rows
+                        .iter()
+                        .map(|row| row.get(idx).map_or("", String::as_str)),
+                ))
+            } else {
+                let coerced = self.coerced_column(idx, column_type)?;
+                match column_type {
+                    ColumnType::Integer => Arc::new(Int64Array::from_iter(
+                        coerced
+                            .iter()
+                            .map(|v| v.as_ref().and_then(|v| v.parse().ok())),
+                    )),
+                    ColumnType::Number => Arc::new(Float64Array::from_iter(
+                        coerced.iter().map(|v| v.as_deref().and_then(parse_number)),
+                    )),
+                    ColumnType::Boolean => Arc::new(BooleanArray::from_iter(
+                        coerced.iter().map(|v| v.as_ref().map(|v| v == "true")),
+                    )),
+                    ColumnType::Date => Arc::new(Date32Array::from_iter(coerced.iter().map(|v| {
+                        v.as_deref()
+                            .and_then(parse_timestamp)
+                            .map(|seconds| seconds.div_euclid(SECONDS_PER_DAY) as i32)
+                    }))),
+                    ColumnType::Text => unreachable!("the text columns are handled above"),
+                }
+            };
+            fields.push(Field::new(
+                name.clone(),
+                arrow_type(column_type),
+                !column.required,
+            ));
+            arrays.push(array);
+        }
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Creates a new CSVFile from an Arrow record batch. The values are written as text
+    /// the way Arrow formats them, the dates as `YYYY-MM-DD`, and the nulls become empty values.
+    /// The delimiter is a comma.
+    ///
+    /// It may return an error if a column has a type that can't be written as text,
+    /// such as a list or a struct.
+    ///
+    /// This method requires the `arrow` feature.
+    pub fn from_record_batch(batch: &RecordBatch) -> Result<Self, Error> {
+        let columns: Vec<String> = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+
+        let mut rows = vec![Vec::with_capacity(columns.len()); batch.num_rows()];
+        for (name, array) in columns.iter().zip(batch.columns()) {
+            let text = arrow_cast::cast(array, &DataType::Utf8).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} can't be written as text: {}", name, e),
+                )
+            })?;
+            let text = text.as_string::<i32>();
+            for (row, value) in rows.iter_mut().zip(text.iter()) {
+                row.push(value.unwrap_or_default().to_string());
+            }
+        }
+
+        Ok(CSVFile::from_rows(',', columns, rows))
+    }
+
+    /// Writes the file in the Parquet format, with the [inferred schema](`CSVFile::infer_schema`)
+    /// of the file and the types of [to_record_batch](`CSVFile::to_record_batch`).
+    ///
+    /// It may return an error if the file can't be converted or written.
+    ///
+    /// This method requires the `parquet` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("name,age\nThomas,20\n", &ReaderOptions::default()).unwrap();
+    /// file.write_parquet(&"people.parquet".to_string()).unwrap();
+    /// ```
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, path: &String) -> Result<(), Error> {
+        let batch = self.to_record_batch()?;
+        let file = std::fs::File::create(path)?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)
+            .map_err(Error::other)?;
+        writer.write(&batch).map_err(Error::other)?;
+        writer.close().map_err(Error::other)?;
+        Ok(())
+    }
+}
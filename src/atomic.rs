@@ -0,0 +1,229 @@
+//! Writing a file on disk so that it's never left half-written.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::external::format_record;
+use crate::{CSVFile, CSVReader, ReaderOptions, WriterOptions};
+
+/// Distinguishes the temporary files of the writings done at the same time by the process.
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Creates the temporary file that will replace the target, in the same directory
+/// so that renaming it doesn't move it to another file system.
+/// Its name is unique, and the file is never one that already existed.
+pub(crate) fn create_temporary(target: &Path) -> Result<(PathBuf, File), Error> {
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+    let path = target.with_file_name(format!(".{}.{}.{}.tmp", name, process::id(), count));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    Ok((path, file))
+}
+
+/// Replaces the target by the temporary file, which must have been written and synced,
+/// giving it the permissions of the target if it exists.
+/// The directory is then synced too, so that the rename itself survives a crash.
+pub(crate) fn replace_with_temporary(
+    temporary: &Path,
+    file: &File,
+    target: &Path,
+) -> Result<(), Error> {
+    if let Ok(metadata) = fs::metadata(target) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    fs::rename(temporary, target)?;
+    sync_directory(target)
+}
+
+/// Syncs the directory holding a file, where its name is stored.
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> Result<(), Error> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+/// The directories can't be opened to be synced on the other platforms.
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+impl CSVFile {
+    /// Writes the CSV file to a file without ever leaving it truncated.
+    /// The file is first entirely written to a temporary file in the same directory,
+    /// which then replaces the target at once. If the program crashes or the writing fails,
+    /// the previous content of the target is left untouched. The target keeps its permissions,
+    /// and several files can be written at once by the same process.
+    ///
+    /// When `backup` is `true` and the target already exists, its previous content
+    /// is also copied to a file of the same name ending with `.bak` (e.g. `data.csv.bak`),
+    /// replacing any previous backup.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, WriterOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_atomic_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "a\n1\n").unwrap();
+    ///
+    /// let file = CSVFile::build(&vec!["a".to_string()], &vec![vec!["2".to_string()]], &',').unwrap();
+    /// file.write_atomic(&filename, &WriterOptions::default(), true).unwrap();
+    ///
+    /// assert_eq!(std::fs::read_to_string(&filename).unwrap(), "a\n2\n");
+    /// assert_eq!(std::fs::read_to_string(format!("{}.bak", filename)).unwrap(), "a\n1\n");
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// # std::fs::remove_file(format!("{}.bak", filename)).unwrap();
+    /// ```
+    pub fn write_atomic(
+        &self,
+        filename: &String,
+        options: &WriterOptions,
+        backup: bool,
+    ) -> Result<(), Error> {
+        let target = Path::new(filename);
+        let (temporary, file) = create_temporary(target)?;
+
+        let written = (|| {
+            let mut writer = BufWriter::new(&file);
+            self.write_to(&mut writer, options)?;
+            writer.flush()?;
+            drop(writer);
+            // makes sure the data is on the disk before the rename makes it visible
+            file.sync_all()?;
+            if backup && target.exists() {
+                fs::copy(target, format!("{}.bak", filename))?;
+            }
+            replace_with_temporary(&temporary, &file, target)
+        })();
+
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+        written
+    }
+
+    /// Replaces the values of a single column of a CSV file on disk, streaming the rows
+    /// one at a time instead of loading the whole file, so that updating one column
+    /// of a big file doesn't need the memory of the whole table.
+    /// The values are given in the order of the rows.
+    ///
+    /// The file is read with the delimiter, the escaping and the size limits of the options,
+    /// the other options being ignored so that every value is written back.
+    /// The header, the byte order mark and the line breaks are kept as they were,
+    /// and the rows are written again with the double quotes they need, escaped the same way.
+    /// Like [write_atomic](`CSVFile::write_atomic`), the rows are written to a temporary file
+    /// which then replaces the file at once, so the file is left untouched if anything fails.
+    ///
+    /// It may return an error if the file can't be read, parsed or written, if the column
+    /// doesn't exist, if a row doesn't have as many values as the header,
+    /// or if the number of values is different from the number of rows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, Escape, ReaderOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_update_column_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "id,city\r\n1,paris\r\n2,lyon\r\n").unwrap();
+    ///
+    /// let options = ReaderOptions { escape: Escape::DoubleQuote, ..ReaderOptions::default() };
+    /// let values = vec!["Paris".to_string(), "Lyon, \"France\"".to_string()];
+    /// CSVFile::update_column_on_disk(&filename, &"city".to_string(), &values, &options).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&filename).unwrap(),
+    ///     "id,city\r\n1,Paris\r\n2,\"Lyon, \"\"France\"\"\"\r\n"
+    /// );
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn update_column_on_disk(
+        filename: &String,
+        column_name: &String,
+        values: &Vec<String>,
+        options: &ReaderOptions,
+    ) -> Result<(), Error> {
+        let options = ReaderOptions {
+            delimiter: options.delimiter,
+            escape: options.escape,
+            max_field_size: options.max_field_size,
+            max_record_size: options.max_record_size,
+            cancellation: options.cancellation.clone(),
+            ..ReaderOptions::default()
+        };
+        let mut reader = CSVReader::open(filename, &options)?;
+        let column_idx = reader
+            .columns()
+            .iter()
+            .position(|c| c == column_name)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", column_name),
+                )
+            })?;
+        let width = reader.columns().len();
+
+        let target = Path::new(filename);
+        let (temporary, file) = create_temporary(target)?;
+        let written = (|| {
+            let mut writer = BufWriter::new(&file);
+            if reader.has_byte_order_mark() {
+                writer.write_all("\u{feff}".as_bytes())?;
+            }
+            writer.write_all(reader.raw_header().as_bytes())?;
+            writer.write_all(reader.header_line_break().as_bytes())?;
+            let mut new_values = values.iter();
+            while let Some(record) = reader.next_record() {
+                let mut record = record?;
+                if record.fields.len() != width {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The row at line {} has {} values, but the header has {}",
+                            record.position.line,
+                            record.fields.len(),
+                            width
+                        ),
+                    ));
+                }
+                // The rows without a value are still counted for the error.
+                if let Some(value) = new_values.next() {
+                    record.fields[column_idx] = value.clone();
+                    let line = format_record(&record.fields, options.delimiter, options.escape);
+                    writer.write_all(line.as_bytes())?;
+                    writer.write_all(reader.last_line_break().as_bytes())?;
+                }
+            }
+            if values.len() != reader.rows_read() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid number of fields, {} were given, but expected {}",
+                        values.len(),
+                        reader.rows_read()
+                    ),
+                ));
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+            replace_with_temporary(&temporary, &file, target)
+        })();
+
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+        written
+    }
+}
@@ -0,0 +1,416 @@
+//! A command-line interface exposing the most common operations of the crate.
+//! Run `csv-tools help` to see the available commands.
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Error, ErrorKind, Write};
+use std::process;
+
+use csv_tools::{CSVFile, CSVReader, ReaderOptions};
+
+const USAGE: &str = "Usage: csv-tools <command> [arguments] [-d <delimiter>] [-o <output>]
+An input named - is read from the standard input.
+
+Commands:
+  convert <input> <new delimiter>   Writes the file with another delimiter
+  select <input> <col1,col2,...>    Keeps only the given columns, in that order
+  merge <input> <other>             Merges the columns of two files side by side
+  join <input> <other> <key>        Keeps the rows whose key exists in both files, with the columns of both
+  validate <input>                  Reports the problems found in the file
+  stats <input>                     Prints statistics about each column
+  head <input> [n]                  Keeps the first n rows (10 by default)
+  tail <input> [n]                  Keeps the last n rows (10 by default)
+  help                              Prints this message
+
+Options:
+  -d, --delimiter <delimiter>       The delimiter of the input files (a comma by default, \"tab\" for a tabulation)
+  -o, --output <output>             Writes the result to a file instead of the standard output";
+
+/// The arguments given to a command, with the options removed.
+struct Arguments {
+    positional: Vec<String>,
+    delimiter: char,
+    output: Option<String>,
+}
+
+fn parse_delimiter(value: &str) -> Result<char, Error> {
+    match value {
+        "tab" | "\\t" => Ok('\t'),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(invalid(format!("Invalid delimiter: {}", value))),
+            }
+        }
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn parse_arguments(args: Vec<String>) -> Result<Arguments, Error> {
+    let mut arguments = Arguments {
+        positional: Vec::new(),
+        delimiter: ',',
+        output: None,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--delimiter" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| invalid(format!("Missing value after {}", arg)))?;
+                arguments.delimiter = parse_delimiter(&value)?;
+            }
+            "-o" | "--output" => {
+                arguments.output = Some(
+                    args.next()
+                        .ok_or_else(|| invalid(format!("Missing value after {}", arg)))?,
+                );
+            }
+            _ => arguments.positional.push(arg),
+        }
+    }
+    Ok(arguments)
+}
+
+impl Arguments {
+    fn get(&self, index: usize, name: &str) -> Result<&String, Error> {
+        self.positional
+            .get(index)
+            .ok_or_else(|| invalid(format!("Missing argument: {}", name)))
+    }
+
+    fn count(&self, index: usize) -> Result<usize, Error> {
+        match self.positional.get(index) {
+            Some(n) => n
+                .parse()
+                .map_err(|_| invalid(format!("Invalid number of rows: {}", n))),
+            None => Ok(10),
+        }
+    }
+
+    fn read(&self, index: usize, name: &str) -> Result<CSVFile, Error> {
+        let path = self.get(index, name)?;
+        let options = ReaderOptions::with_delimiter(&self.delimiter);
+        if path == "-" {
+            CSVFile::from_reader(io::stdin().lock(), &options)
+        } else {
+            CSVFile::new_with_options(path, &options)
+        }
+    }
+
+    /// Opens an input to read its rows one at a time.
+    fn stream(&self, index: usize, name: &str) -> Result<CSVReader<Box<dyn BufRead>>, Error> {
+        let path = self.get(index, name)?;
+        let source: Box<dyn BufRead> = if path == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(BufReader::new(File::open(path)?))
+        };
+        CSVReader::from_reader(source, &ReaderOptions::with_delimiter(&self.delimiter))
+    }
+
+    fn output(&self, file: &CSVFile, out: &mut dyn Write) -> Result<(), Error> {
+        match &self.output {
+            Some(path) => file.write(path),
+            None => out.write_all(file.to_string().as_bytes()),
+        }
+    }
+}
+
+fn column_index(file: &CSVFile, name: &String) -> Result<usize, Error> {
+    file.get_column_idx(name)
+        .ok_or_else(|| invalid(format!("The column {} doesn't exist", name)))
+}
+
+fn select(file: &CSVFile, names: &str) -> Result<CSVFile, Error> {
+    let names: Vec<String> = names.split(',').map(|n| n.to_string()).collect();
+    let indexes = names
+        .iter()
+        .map(|name| column_index(file, name))
+        .collect::<Result<Vec<usize>, Error>>()?;
+    let rows: Vec<Vec<String>> = file
+        .rows
+        .iter()
+        .map(|row| {
+            indexes
+                .iter()
+                .map(|i| row.get(*i).cloned().unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    CSVFile::build(&names, &rows, &file.delimiter)
+}
+
+fn join(file: &CSVFile, other: &CSVFile, key: &String) -> Result<CSVFile, Error> {
+    let key_idx = column_index(file, key)?;
+    let other_key_idx = column_index(other, key)?;
+    let mut index: HashMap<&String, Vec<&Vec<String>>> = HashMap::new();
+    for row in &other.rows {
+        if let Some(key) = row.get(other_key_idx) {
+            index.entry(key).or_default().push(row);
+        }
+    }
+
+    let mut columns = file.columns.clone();
+    for (i, column) in other.columns.iter().enumerate() {
+        if i != other_key_idx {
+            columns.push(column.clone());
+        }
+    }
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for row in &file.rows {
+        let matches = row.get(key_idx).and_then(|key| index.get(key));
+        for other_row in matches.into_iter().flatten() {
+            // The missing values of the short rows are empty, and the extra ones are dropped.
+            let mut joined = row.clone();
+            joined.resize(file.columns.len(), String::new());
+            for i in (0..other.columns.len()).filter(|i| *i != other_key_idx) {
+                joined.push(other_row.get(i).cloned().unwrap_or_default());
+            }
+            rows.push(joined);
+        }
+    }
+
+    CSVFile::build(&columns, &rows, &file.delimiter)
+}
+
+fn print_stats(file: &CSVFile, out: &mut dyn Write) -> Result<(), Error> {
+    writeln!(out, "{} columns, {} rows", file.len(), file.count_rows())?;
+    for column in &file.columns {
+        let stats = file.column_stats(column).expect("the column exists");
+        write!(
+            out,
+            "{}: {} values, {} empty, {} distinct",
+            column, stats.count, stats.empty, stats.distinct
+        )?;
+        if let (Some(min), Some(max), Some(mean)) = (stats.min, stats.max, stats.mean) {
+            write!(
+                out,
+                ", {} numeric (min {}, max {}, mean {})",
+                stats.numeric, min, max, mean
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Builds a file from rows read by a reader, keeping them as they were read even if they're ragged.
+fn from_streamed_rows(
+    reader: &CSVReader<Box<dyn BufRead>>,
+    rows: Vec<Vec<String>>,
+    delimiter: char,
+) -> Result<CSVFile, Error> {
+    let mut file = CSVFile::build(reader.columns(), &Vec::new(), &delimiter)?;
+    file.rows = rows;
+    Ok(file)
+}
+
+/// Reads the first rows of an input, without reading the rest.
+fn head(arguments: &Arguments) -> Result<CSVFile, Error> {
+    let n = arguments.count(1)?;
+    let mut reader = arguments.stream(0, "input")?;
+    let rows = reader
+        .by_ref()
+        .take(n)
+        .collect::<Result<Vec<Vec<String>>, Error>>()?;
+    from_streamed_rows(&reader, rows, arguments.delimiter)
+}
+
+/// Reads the last rows of an input, only holding those in memory.
+fn tail(arguments: &Arguments) -> Result<CSVFile, Error> {
+    let n = arguments.count(1)?;
+    let mut reader = arguments.stream(0, "input")?;
+    let mut rows: VecDeque<Vec<String>> = VecDeque::with_capacity(n);
+    for row in reader.by_ref() {
+        let row = row?;
+        if n == 0 {
+            continue;
+        }
+        if rows.len() == n {
+            rows.pop_front();
+        }
+        rows.push_back(row);
+    }
+    from_streamed_rows(&reader, rows.into(), arguments.delimiter)
+}
+
+fn run(command: &str, arguments: &Arguments, out: &mut dyn Write) -> Result<(), Error> {
+    match command {
+        "convert" => {
+            let mut file = arguments.read(0, "input")?;
+            file.set_delimiter(&parse_delimiter(arguments.get(1, "new delimiter")?)?);
+            arguments.output(&file, out)
+        }
+        "select" => {
+            let file = arguments.read(0, "input")?;
+            arguments.output(&select(&file, arguments.get(1, "columns")?)?, out)
+        }
+        "merge" => {
+            let mut file = arguments.read(0, "input")?;
+            file.merge(&arguments.read(1, "other")?)?;
+            arguments.output(&file, out)
+        }
+        "join" => {
+            let file = arguments.read(0, "input")?;
+            let other = arguments.read(1, "other")?;
+            arguments.output(&join(&file, &other, arguments.get(2, "key")?)?, out)
+        }
+        "validate" => {
+            let report = arguments.read(0, "input")?.check_validity();
+            writeln!(out, "{:#?}", report)?;
+            if report.is_valid() {
+                Ok(())
+            } else {
+                Err(Error::new(ErrorKind::InvalidData, "The file is invalid"))
+            }
+        }
+        "stats" => print_stats(&arguments.read(0, "input")?, out),
+        "head" => arguments.output(&head(arguments)?, out),
+        "tail" => arguments.output(&tail(arguments)?, out),
+        "help" | "--help" | "-h" => writeln!(out, "{}", USAGE),
+        _ => Err(invalid(format!("Unknown command: {}", command))),
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = match args.next() {
+        Some(command) => command,
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let result = parse_arguments(args.collect())
+        .and_then(|arguments| run(&command, &arguments, &mut io::stdout().lock()));
+    if let Err(e) = result {
+        eprintln!("csv-tools: {}", e);
+        process::exit(if e.kind() == ErrorKind::InvalidInput {
+            2
+        } else {
+            1
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn arguments(args: &[&str]) -> Arguments {
+        parse_arguments(args.iter().map(|a| a.to_string()).collect()).unwrap()
+    }
+
+    /// Runs a command on files written for the test, and returns what it printed.
+    fn run_on(command: &str, files: &[(&str, &str)], args: &[&str]) -> Result<String, Error> {
+        for (name, content) in files {
+            fs::write(name, content).unwrap();
+        }
+        let mut out: Vec<u8> = Vec::new();
+        let result = run(command, &arguments(args), &mut out);
+        for (name, _) in files {
+            fs::remove_file(name).unwrap();
+        }
+        result.map(|_| String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let parsed = arguments(&["in.csv", "-d", "tab", "a,b", "--output", "out.csv"]);
+        assert_eq!(parsed.positional, vec!["in.csv", "a,b"]);
+        assert_eq!(parsed.delimiter, '\t');
+        assert_eq!(parsed.output, Some("out.csv".to_string()));
+        assert_eq!(arguments(&["--delimiter", ";"]).delimiter, ';');
+        assert_eq!(arguments(&["in.csv"]).delimiter, ',');
+
+        let missing = parse_arguments(vec!["-o".to_string()]).err().unwrap();
+        assert_eq!(missing.kind(), ErrorKind::InvalidInput);
+        assert!(parse_arguments(vec!["-d".to_string(), ";;".to_string()]).is_err());
+        assert!(arguments(&["in.csv", "x"]).count(1).is_err());
+        assert_eq!(arguments(&["in.csv"]).count(1).unwrap(), 10);
+        assert!(arguments(&[]).get(0, "input").is_err());
+    }
+
+    #[test]
+    fn test_select_command() {
+        let files = [("test_cli_select.csv", "a,b,c\n1,2,3\n4\n")];
+        assert_eq!(
+            run_on("select", &files, &["test_cli_select.csv", "c,a"]).unwrap(),
+            "c,a\n3,1\n,4\n"
+        );
+        assert!(run_on("select", &files, &["test_cli_select.csv", "d"]).is_err());
+    }
+
+    #[test]
+    fn test_join_command() {
+        let files = [
+            ("test_cli_join_a.csv", "id,name\n1,Thomas\n2\n3,John\n"),
+            ("test_cli_join_b.csv", "age,id\n20,1\n30\n40,3,extra\n"),
+        ];
+        let args = ["test_cli_join_a.csv", "test_cli_join_b.csv", "id"];
+        assert_eq!(
+            run_on("join", &files, &args).unwrap(),
+            "id,name,age\n1,Thomas,20\n3,John,40\n"
+        );
+    }
+
+    #[test]
+    fn test_head_and_tail_commands() {
+        let files = [("test_cli_head.csv", "n\n1\n2\n3\n4\n")];
+        assert_eq!(
+            run_on("head", &files, &["test_cli_head.csv", "2"]).unwrap(),
+            "n\n1\n2\n"
+        );
+        assert_eq!(
+            run_on("tail", &files, &["test_cli_head.csv", "2"]).unwrap(),
+            "n\n3\n4\n"
+        );
+        assert_eq!(
+            run_on("tail", &files, &["test_cli_head.csv", "0"]).unwrap(),
+            "n\n"
+        );
+        assert_eq!(
+            run_on("head", &files, &["test_cli_head.csv"]).unwrap(),
+            "n\n1\n2\n3\n4\n"
+        );
+    }
+
+    #[test]
+    fn test_other_commands() {
+        let files = [
+            ("test_cli_other_a.csv", "a;b\n1;2\n"),
+            ("test_cli_other_b.csv", "c\n3\n"),
+        ];
+        assert_eq!(
+            run_on("convert", &files, &["test_cli_other_a.csv", ",", "-d", ";"]).unwrap(),
+            "a,b\n1,2\n"
+        );
+        assert_eq!(
+            run_on(
+                "merge",
+                &files,
+                &["test_cli_other_a.csv", "test_cli_other_b.csv", "-d", ";"]
+            )
+            .unwrap(),
+            "a;b;c\n1;2;3\n"
+        );
+        let stats = run_on("stats", &files, &["test_cli_other_a.csv", "-d", ";"]).unwrap();
+        assert!(stats.starts_with("2 columns, 1 rows\na: 1 values"));
+
+        let ragged = [("test_cli_validate.csv", "a,b\n1\n")];
+        assert!(run_on("validate", &ragged, &["test_cli_validate.csv"]).is_err());
+        assert!(run_on("help", &[], &[]).unwrap().starts_with("Usage:"));
+        let unknown = run_on("unknown", &[], &[]).err().unwrap();
+        assert_eq!(unknown.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,80 @@
+//! Building a row from values given by column name.
+
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+/// Builds a row of a file from values given by column name, so that the values
+/// can't end up in the wrong column when the order of the columns changes.
+/// The columns without a value get their [default value](`CSVFile::set_column_default`),
+/// or an empty value if they don't have one.
+///
+/// The columns can also be named by their [aliases](`CSVFile::set_column_aliases`).
+/// Setting the same column twice keeps the last value.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, RowBuilder};
+/// let columns = vec!["name".to_string(), "age".to_string(), "city".to_string()];
+/// let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+///
+/// let row = RowBuilder::new(&file).set("age", "19").set("name", "Thomas").build().unwrap();
+/// assert_eq!(row, vec!["Thomas", "19", ""]);
+/// file.add_row(&row).unwrap();
+///
+/// assert!(RowBuilder::new(&file).set("email", "t@x.org").build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowBuilder<'a> {
+    file: &'a CSVFile,
+    values: Vec<Option<String>>,
+    /// The first name given that isn't a column of the file.
+    unknown_column: Option<String>,
+}
+
+impl<'a> RowBuilder<'a> {
+    /// Creates a builder for a row of the given file, without any value.
+    pub fn new(file: &'a CSVFile) -> Self {
+        Self {
+            file,
+            values: vec![None; file.len()],
+            unknown_column: None,
+        }
+    }
+
+    /// Sets the value of a column. An unknown column is only reported by [build](`RowBuilder::build`).
+    pub fn set(mut self, column_name: &str, value: &str) -> Self {
+        match self.file.get_column_idx(&column_name.to_string()) {
+            Some(idx) => self.values[idx] = Some(value.to_string()),
+            None => {
+                self.unknown_column
+                    .get_or_insert_with(|| column_name.to_string());
+            }
+        }
+        self
+    }
+
+    /// Returns the values of the row, in the order of the columns.
+    /// The row can then be added with [add_row](`CSVFile::add_row`).
+    /// It may return an error if a column given to [set](`RowBuilder::set`) doesn't exist.
+    pub fn build(self) -> Result<Vec<String>, Error> {
+        if let Some(column_name) = self.unknown_column {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            ));
+        }
+
+        Ok(self
+            .values
+            .into_iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                value
+                    .or_else(|| self.file.defaults.get(&self.file.columns[idx]).cloned())
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,90 @@
+//! Cancellation of the operations that can take a long time on big files.
+
+use std::error;
+use std::fmt;
+use std::io::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token shared between the thread running a long operation and the threads
+/// that may want to abort it. Cloning the token gives another handle on the same flag.
+///
+/// An operation that gets cancelled stops as soon as possible and returns an error
+/// of kind [ErrorKind::Other](`std::io::ErrorKind::Other`) wrapping [Cancelled](`Cancelled`).
+/// The kind [ErrorKind::Interrupted](`std::io::ErrorKind::Interrupted`) isn't used,
+/// since the standard library and most callers retry the operations interrupted that way.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{Cancelled, CancellationToken, CSVFile, WriterOptions};
+/// let columns = vec!["a".to_string()];
+/// let rows = vec![vec!["1".to_string()]];
+/// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+///
+/// let token = CancellationToken::new();
+/// let options = WriterOptions {
+///     cancellation: Some(token.clone()),
+///     ..WriterOptions::default()
+/// };
+///
+/// token.cancel(); // typically called from another thread
+/// let mut output: Vec<u8> = Vec::new();
+/// let error = file.write_to(&mut output, &options).unwrap_err();
+/// assert!(Cancelled::is_cause_of(&error));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that isn't cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token from an existing flag, cancelled once the flag is set to `true`.
+    pub fn from_flag(flag: Arc<AtomicBool>) -> Self {
+        Self { cancelled: flag }
+    }
+
+    /// Asks the operations using this token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [cancel](`CancellationToken::cancel`) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error if the token has been cancelled.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::other(Cancelled))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The error wrapped in the errors returned by the operations
+/// stopped by a [CancellationToken](`CancellationToken`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Cancelled {
+    /// Returns `true` if the error was returned because an operation was cancelled.
+    pub fn is_cause_of(error: &Error) -> bool {
+        error.get_ref().is_some_and(|inner| inner.is::<Cancelled>())
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The operation was cancelled")
+    }
+}
+
+impl error::Error for Cancelled {}
@@ -0,0 +1,78 @@
+//! The checkpoints of a streaming reader, so that a long reading can resume
+//! where it stopped after a restart.
+
+use std::io::{Error, ErrorKind};
+
+use crate::json::{self, JsonValue};
+
+/// Where a [CSVReader](`crate::CSVReader`) stopped, taken by
+/// [CSVReader::checkpoint](`crate::CSVReader::checkpoint`) and given back to
+/// [CSVReader::resume](`crate::CSVReader::resume`) to continue reading from there.
+///
+/// A checkpoint is always taken between two rows, so the reader has no partial row to remember.
+/// It can be stored as JSON with [to_json](`Checkpoint::to_json`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The offset in bytes of the next row, header included.
+    pub byte: u64,
+    /// The number of the line of the next row, starting at 1 with the header.
+    pub line: usize,
+    /// The number of rows read before the checkpoint.
+    pub rows: usize,
+    /// The fingerprint of the header, to check that the reading resumes in the same file.
+    pub header_hash: u64,
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+impl Checkpoint {
+    /// Serializes the checkpoint as a JSON object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::Checkpoint;
+    /// let checkpoint = Checkpoint { byte: 120, line: 8, rows: 6, header_hash: 255 };
+    /// let text = checkpoint.to_json();
+    /// assert_eq!(text, r#"{"byte":120,"line":8,"rows":6,"header_hash":"00000000000000ff"}"#);
+    /// assert_eq!(Checkpoint::from_json(&text).unwrap(), checkpoint);
+    /// ```
+    pub fn to_json(&self) -> String {
+        JsonValue::Object(vec![
+            ("byte".to_string(), JsonValue::Number(self.byte as f64)),
+            ("line".to_string(), JsonValue::Number(self.line as f64)),
+            ("rows".to_string(), JsonValue::Number(self.rows as f64)),
+            (
+                "header_hash".to_string(),
+                JsonValue::String(format!("{:016x}", self.header_hash)),
+            ),
+        ])
+        .to_string()
+    }
+
+    /// Parses a checkpoint serialized by [to_json](`Checkpoint::to_json`).
+    /// It may return an error if the text isn't valid JSON or if a member is missing.
+    pub fn from_json(text: &str) -> Result<Checkpoint, Error> {
+        let document = json::parse(text)?;
+        let number = |key: &str| match document.get(key) {
+            Some(JsonValue::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as u64),
+            _ => Err(invalid(&format!(
+                "The checkpoint must have a positive integer {}",
+                key
+            ))),
+        };
+        let header_hash = document
+            .get("header_hash")
+            .and_then(JsonValue::as_str)
+            .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+            .ok_or_else(|| invalid("The checkpoint must have a hexadecimal header_hash"))?;
+        Ok(Checkpoint {
+            byte: number("byte")?,
+            line: number("line")? as usize,
+            rows: number("rows")? as usize,
+            header_hash,
+        })
+    }
+}
@@ -0,0 +1,106 @@
+//! Dry runs of the conversion of a column to a type.
+
+use crate::{CSVFile, ColumnType};
+
+/// The maximum number of failures listed as examples by a [CoercionReport](`CoercionReport`).
+const COERCION_EXAMPLES: usize = 5;
+
+/// A value that can't be converted, found by [can_coerce](`CSVFile::can_coerce`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoercionFailure {
+    /// The index of the row.
+    pub row: usize,
+    pub value: String,
+}
+
+/// What would happen if the values of a column were converted to a type,
+/// computed by [can_coerce](`CSVFile::can_coerce`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoercionReport {
+    pub column_type: ColumnType,
+    /// The number of values that have the type, including the ones to rewrite.
+    pub convertible: usize,
+    /// The number of values that have the type but aren't in its canonical form,
+    /// such as `"+042"` for an integer (see [ColumnType::coerce](`ColumnType::coerce`)).
+    pub changed: usize,
+    /// The number of empty values, which aren't converted.
+    pub empty: usize,
+    /// The number of values that don't have the type.
+    pub failed: usize,
+    /// The first values that don't have the type, in the order of the rows.
+    pub examples: Vec<CoercionFailure>,
+}
+
+impl CoercionReport {
+    /// Returns `true` if all the values that aren't empty have the type.
+    pub fn is_convertible(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+impl CSVFile {
+    /// Checks whether the values of a column have the given type, without modifying them,
+    /// to choose how to clean the column before converting it.
+    /// The report counts the values that would fail to convert and lists the first ones.
+    /// The blank values are counted as empty.
+    ///
+    /// It returns `None` if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ColumnType, ReaderOptions};
+    /// let file = CSVFile::from_text("age\n19\n+28\n\nunknown\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let report = file.can_coerce(&"age".to_string(), ColumnType::Integer).unwrap();
+    /// assert!(!report.is_convertible());
+    /// assert_eq!(report.convertible, 2);
+    /// assert_eq!(report.changed, 1);
+    /// assert_eq!(report.empty, 1);
+    /// assert_eq!(report.failed, 1);
+    /// assert_eq!(report.examples[0].row, 3);
+    /// assert_eq!(report.examples[0].value, "unknown");
+    /// ```
+    pub fn can_coerce(
+        &self,
+        column_name: &String,
+        column_type: ColumnType,
+    ) -> Option<CoercionReport> {
+        let idx = self.get_column_idx(column_name)?;
+        let mut report = CoercionReport {
+            column_type,
+            convertible: 0,
+            changed: 0,
+            empty: 0,
+            failed: 0,
+            examples: Vec::new(),
+        };
+        for (i, row) in self.rows.iter().enumerate() {
+            let Some(value) = row.get(idx) else {
+                continue;
+            };
+            if value.trim().is_empty() {
+                report.empty += 1;
+                continue;
+            }
+            match column_type.coerce(value) {
+                Some(coerced) => {
+                    report.convertible += 1;
+                    if &coerced != value {
+                        report.changed += 1;
+                    }
+                }
+                None => {
+                    report.failed += 1;
+                    if report.examples.len() < COERCION_EXAMPLES {
+                        report.examples.push(CoercionFailure {
+                            row: i,
+                            value: value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Some(report)
+    }
+}
@@ -0,0 +1,133 @@
+//! Rewriting a CSV file with other options, one row at a time.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+
+use crate::format::escape_formula;
+use crate::parser::{needs_quotes_with_escape, quote_field_with_escape};
+use crate::{
+    CSVFile, CSVReader, ColumnFormat, LineEnding, QuoteStyle, ReaderOptions, WriterOptions,
+};
+
+/// Rewrites a CSV file with different options (delimiter, escaping of the quotes,
+/// line endings, byte order mark...), reading and writing one row at a time,
+/// so that converting a file never holds more than a row in memory.
+/// It returns the number of rows written, the header excluded.
+///
+/// The file is read with the reader options and written with the writer options,
+/// the way [write_with_options](`CSVFile::write_with_options`) would write it.
+/// If the writer options don't specify a delimiter, the one of the reader options is kept,
+/// and if they don't specify the line endings or the byte order mark, the ones of the input are kept.
+/// Unlike the files written from a [CSVFile](`CSVFile`), the quotes of the source aren't preserved,
+/// and the output always ends with a line break.
+///
+/// It may return an error if the input can't be read or parsed, if the output can't be written,
+/// or if both are the same file.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{convert, Escape, ReaderOptions, WriterOptions};
+/// # let input = std::env::temp_dir().join("csv_tools_convert_doc_in.csv").to_string_lossy().to_string();
+/// # let output = std::env::temp_dir().join("csv_tools_convert_doc_out.csv").to_string_lossy().to_string();
+/// std::fs::write(&input, "name;bio\nThomas;\"Says \\\"hi\\\"\"\n").unwrap();
+///
+/// let from = ReaderOptions { delimiter: ';', ..ReaderOptions::default() };
+/// let to = WriterOptions { delimiter: Some(','), escape: Escape::DoubleQuote, ..WriterOptions::default() };
+/// assert_eq!(convert(&input, &output, &from, &to).unwrap(), 1);
+/// assert_eq!(std::fs::read_to_string(&output).unwrap(), "name,bio\nThomas,\"Says \"\"hi\"\"\"\n");
+/// # std::fs::remove_file(&input).unwrap();
+/// # std::fs::remove_file(&output).unwrap();
+/// ```
+pub fn convert(
+    input: &String,
+    output: &String,
+    reader_options: &ReaderOptions,
+    writer_options: &WriterOptions,
+) -> Result<usize, Error> {
+    if fs::canonicalize(output).is_ok_and(|path| fs::canonicalize(input).is_ok_and(|p| p == path)) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The input and the output of a conversion must be different files",
+        ));
+    }
+
+    let reader = CSVReader::open(input, reader_options)?;
+    let delimiter = writer_options.delimiter.unwrap_or(reader_options.delimiter);
+    let mut writer = BufWriter::new(File::create(output)?);
+
+    // The header is written like the one of a file without rows,
+    // whose end mustn't be reported as the end of the conversion.
+    let header = CSVFile::from_rows(delimiter, reader.columns().clone(), Vec::new());
+    let line_ending = writer_options
+        .line_ending
+        .or(LineEnding::from_line_break(reader.header_line_break()))
+        .unwrap_or_default();
+    let header_options = WriterOptions {
+        line_ending: Some(line_ending),
+        byte_order_mark: Some(
+            writer_options
+                .byte_order_mark
+                .unwrap_or(reader.has_byte_order_mark()),
+        ),
+        on_progress: None,
+        ..writer_options.clone()
+    };
+    let mut header_bytes: Vec<u8> = Vec::new();
+    header.write_to(&mut header_bytes, &header_options)?;
+    writer.write_all(&header_bytes)?;
+    let mut bytes_written = header_bytes.len() as u64;
+
+    let formats: Vec<Option<&ColumnFormat>> = reader
+        .columns()
+        .iter()
+        .map(|column| writer_options.column_formats.get(column))
+        .collect();
+    let always_quote = writer_options.quote_style == QuoteStyle::Always;
+    let line_ending = line_ending.as_str();
+    let mut rows_written = 0;
+    let mut line = String::new();
+    for row in reader {
+        let row = row?;
+        if let Some(token) = &writer_options.cancellation {
+            token.check()?;
+        }
+        line.clear();
+        for (j, field) in row.iter().enumerate() {
+            let format = formats.get(j).copied().flatten();
+            let mut value = match format {
+                _ if field.is_empty() => writer_options.null_value.clone(),
+                Some(format) => format.format_value(field),
+                None => field.clone(),
+            };
+            if writer_options.escape_formulas {
+                if let Some(escaped) = escape_formula(&value) {
+                    value = escaped;
+                }
+            }
+            if always_quote
+                || format.is_some_and(|f| f.always_quote)
+                || needs_quotes_with_escape(&value, delimiter, writer_options.escape)
+            {
+                line.push_str(&quote_field_with_escape(&value, writer_options.escape));
+            } else {
+                line.push_str(&value);
+            }
+            line.push(delimiter);
+        }
+        line.pop();
+        line.push_str(line_ending);
+        writer.write_all(line.as_bytes())?;
+        rows_written += 1;
+        bytes_written += line.len() as u64;
+        if let Some(progress) = &writer_options.on_progress {
+            progress.tick(rows_written, bytes_written);
+        }
+    }
+    writer.flush()?;
+
+    if let Some(progress) = &writer_options.on_progress {
+        progress.finish(rows_written, bytes_written);
+    }
+    Ok(rows_written)
+}
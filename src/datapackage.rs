@@ -0,0 +1,258 @@
+//! The [Frictionless Data](https://specs.frictionlessdata.io) descriptors of the files,
+//! a Table Schema inside a Data Package, used by open-data tools.
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::json::{self, JsonValue};
+#[cfg(feature = "fs")]
+use crate::{CSVFile, WriterOptions};
+use crate::{CSVSchema, ColumnType, SchemaColumn, SchemaPolicy};
+
+/// The name of the descriptor written by [write_with_data_package](`CSVFile::write_with_data_package`).
+#[cfg(feature = "fs")]
+const DATA_PACKAGE_FILE: &str = "datapackage.json";
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+impl ColumnType {
+    /// Returns the name of the type in a Table Schema.
+    fn table_schema_type(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "string",
+            ColumnType::Integer => "integer",
+            ColumnType::Number => "number",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+        }
+    }
+
+    /// Reads the type of a Table Schema. The types that aren't supported, such as `datetime`,
+    /// are read as text.
+    fn from_table_schema_type(name: &str) -> Self {
+        match name {
+            "integer" => ColumnType::Integer,
+            "number" => ColumnType::Number,
+            "boolean" => ColumnType::Boolean,
+            "date" => ColumnType::Date,
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+impl CSVSchema {
+    /// Returns the Table Schema object describing the columns.
+    fn table_schema(&self) -> JsonValue {
+        let fields = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut field = vec![
+                    ("name".to_string(), JsonValue::String(column.name.clone())),
+                    (
+                        "type".to_string(),
+                        JsonValue::String(column.column_type.table_schema_type().to_string()),
+                    ),
+                ];
+                if column.required {
+                    field.push((
+                        "constraints".to_string(),
+                        JsonValue::Object(vec![("required".to_string(), JsonValue::Bool(true))]),
+                    ));
+                }
+                JsonValue::Object(field)
+            })
+            .collect();
+        JsonValue::Object(vec![("fields".to_string(), JsonValue::Array(fields))])
+    }
+
+    /// Reads the columns of a Table Schema object.
+    fn from_table_schema_value(value: &JsonValue, delimiter: char) -> Result<CSVSchema, Error> {
+        let Some(JsonValue::Array(fields)) = value.get("fields") else {
+            return Err(invalid("The Table Schema must have an array of fields"));
+        };
+        let columns = fields
+            .iter()
+            .map(|field| {
+                let name = field
+                    .get("name")
+                    .and_then(JsonValue::as_str)
+                    .ok_or_else(|| invalid("Each field of the Table Schema must have a name"))?;
+                let column_type = field
+                    .get("type")
+                    .and_then(JsonValue::as_str)
+                    .map(ColumnType::from_table_schema_type)
+                    .unwrap_or_default();
+                let required = field
+                    .get("constraints")
+                    .and_then(|constraints| constraints.get("required"))
+                    == Some(&JsonValue::Bool(true));
+                Ok(SchemaColumn {
+                    name: name.to_string(),
+                    column_type,
+                    required,
+                })
+            })
+            .collect::<Result<Vec<SchemaColumn>, Error>>()?;
+
+        Ok(CSVSchema {
+            delimiter,
+            columns,
+            policy: SchemaPolicy::FailFast,
+        })
+    }
+
+    /// Generates the [Table Schema](https://specs.frictionlessdata.io/table-schema/)
+    /// of the columns, with their type and whether they are required.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{ColumnType, CSVSchema};
+    /// let schema = CSVSchema::default().with_column("id", ColumnType::Integer, true);
+    /// let json = schema.to_table_schema();
+    /// assert_eq!(CSVSchema::from_table_schema(&json).unwrap(), schema);
+    /// ```
+    pub fn to_table_schema(&self) -> String {
+        self.table_schema().to_pretty_string() + "\n"
+    }
+
+    /// Reads a Table Schema. The types that aren't supported are read as text,
+    /// and the delimiter is a comma.
+    /// It may return an error if the JSON is invalid or isn't a Table Schema.
+    pub fn from_table_schema(text: &str) -> Result<CSVSchema, Error> {
+        Self::from_table_schema_value(&json::parse(text)?, ',')
+    }
+
+    /// Generates a [Data Package](https://specs.frictionlessdata.io/data-package/)
+    /// describing a single CSV file, with its path relative to the descriptor,
+    /// its delimiter and its Table Schema.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{ColumnType, CSVSchema};
+    /// let schema = CSVSchema { delimiter: ';', ..CSVSchema::default() }
+    ///     .with_column("name", ColumnType::Text, false);
+    ///
+    /// let json = schema.to_data_package("people.csv");
+    /// let (read, path) = CSVSchema::from_data_package(&json).unwrap();
+    /// assert_eq!(read, schema);
+    /// assert_eq!(path, "people.csv");
+    /// ```
+    pub fn to_data_package(&self, path: &str) -> String {
+        let name: String = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let resource = JsonValue::Object(vec![
+            ("name".to_string(), JsonValue::String(name)),
+            ("path".to_string(), JsonValue::String(path.to_string())),
+            ("format".to_string(), JsonValue::String("csv".to_string())),
+            (
+                "dialect".to_string(),
+                JsonValue::Object(vec![(
+                    "delimiter".to_string(),
+                    JsonValue::String(self.delimiter.to_string()),
+                )]),
+            ),
+            ("schema".to_string(), self.table_schema()),
+        ]);
+        JsonValue::Object(vec![(
+            "resources".to_string(),
+            JsonValue::Array(vec![resource]),
+        )])
+        .to_pretty_string()
+            + "\n"
+    }
+
+    /// Reads the first resource of a Data Package and returns its schema,
+    /// along with the path of its file relative to the descriptor.
+    /// It may return an error if the JSON is invalid or doesn't describe a CSV file.
+    pub fn from_data_package(text: &str) -> Result<(CSVSchema, String), Error> {
+        let document = json::parse(text)?;
+        let resource = match document.get("resources") {
+            Some(JsonValue::Array(resources)) if !resources.is_empty() => &resources[0],
+            _ => return Err(invalid("The Data Package doesn't have any resource")),
+        };
+        let path = resource
+            .get("path")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| invalid("The resource of the Data Package must have a path"))?;
+
+        let delimiter = match resource
+            .get("dialect")
+            .and_then(|dialect| dialect.get("delimiter"))
+        {
+            None => ',',
+            Some(delimiter) => {
+                let mut chars = delimiter.as_str().unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err(invalid("The delimiter must be a single character")),
+                }
+            }
+        };
+        let schema = match resource.get("schema") {
+            Some(schema) => Self::from_table_schema_value(schema, delimiter)?,
+            None => {
+                return Err(invalid(
+                    "The resource of the Data Package must have a schema",
+                ))
+            }
+        };
+
+        Ok((schema, path.to_string()))
+    }
+}
+
+#[cfg(feature = "fs")]
+impl CSVFile {
+    /// Reads the CSV file described by the first resource of a Data Package,
+    /// checking it against its Table Schema like [new_with_schema](`CSVFile::new_with_schema`).
+    /// The path of the resource is relative to the directory of the descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// # std::fs::create_dir_all("data_package_example").unwrap();
+    /// let file = CSVFile::from_text("id,name\n1,Thomas\n", &ReaderOptions::default()).unwrap();
+    /// file.write_with_data_package(&"data_package_example/people.csv".to_string()).unwrap();
+    ///
+    /// let read = CSVFile::new_from_data_package(&"data_package_example/datapackage.json".to_string()).unwrap();
+    /// assert_eq!(read.rows, file.rows);
+    /// # std::fs::remove_dir_all("data_package_example").unwrap();
+    /// ```
+    pub fn new_from_data_package(descriptor: &String) -> Result<Self, Error> {
+        let (schema, path) = CSVSchema::from_data_package(&std::fs::read_to_string(descriptor)?)?;
+        let directory = Path::new(descriptor).parent().unwrap_or(Path::new(""));
+        let file_name = directory.join(path).to_string_lossy().to_string();
+        Self::new_with_schema(&file_name, &schema)
+    }
+
+    /// Writes the CSV file along with a `datapackage.json` descriptor in the same directory,
+    /// holding the schema inferred from the values (see [infer_schema](`CSVFile::infer_schema`)).
+    /// An existing descriptor is replaced.
+    pub fn write_with_data_package(&self, filename: &String) -> Result<(), Error> {
+        self.write_with_options(filename, &WriterOptions::default())?;
+        let path = Path::new(filename);
+        let resource_path = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let descriptor = self.infer_schema().to_data_package(&resource_path);
+        std::fs::write(path.with_file_name(DATA_PACKAGE_FILE), descriptor)
+    }
+}
@@ -0,0 +1,73 @@
+//! Default values of the columns, filling the values missing from the rows added by name.
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+use crate::{CSVFile, RowBuilder};
+
+impl CSVFile {
+    /// Sets the value given to a column when a row added with
+    /// [add_row_partial](`CSVFile::add_row_partial`) doesn't have one, replacing the previous default.
+    /// The default of a column is removed along with the column.
+    /// It may return an error if the column doesn't exist.
+    pub fn set_column_default(
+        &mut self,
+        column_name: &String,
+        value: &String,
+    ) -> Result<(), Error> {
+        let column_name = match self.resolve_column(column_name) {
+            Some(name) => name.clone(),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", column_name),
+                ))
+            }
+        };
+
+        self.defaults.insert(column_name, value.clone());
+
+        Ok(())
+    }
+
+    /// Gets the default value of a column.
+    pub fn column_default(&self, column_name: &String) -> Option<&String> {
+        self.defaults.get(self.resolve_column(column_name)?)
+    }
+
+    /// Removes the default value of a column and returns it.
+    pub fn remove_column_default(&mut self, column_name: &String) -> Option<String> {
+        let column_name = self.resolve_column(column_name)?.clone();
+        self.defaults.remove(&column_name)
+    }
+
+    /// Adds a row whose values are given by column name. The columns without a value
+    /// get their [default value](`CSVFile::set_column_default`), or an empty value if they don't have one.
+    /// The columns can also be named by their aliases.
+    ///
+    /// It may return an error if a column doesn't exist,
+    /// or if the row is rejected by a [Validator](`crate::Validator`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use csv_tools::CSVFile;
+    /// let columns = vec!["name".to_string(), "country".to_string(), "age".to_string()];
+    /// let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+    /// file.set_column_default(&"country".to_string(), &"France".to_string()).unwrap();
+    ///
+    /// let row = HashMap::from([("name".to_string(), "Thomas".to_string())]);
+    /// file.add_row_partial(&row).unwrap();
+    /// assert_eq!(file.rows[0], vec!["Thomas", "France", ""]);
+    /// ```
+    pub fn add_row_partial(&mut self, values: &HashMap<String, String>) -> Result<(), Error> {
+        let row = values
+            .iter()
+            .fold(RowBuilder::new(self), |builder, (column_name, value)| {
+                builder.set(column_name, value)
+            })
+            .build()?;
+        self.add_row(&row)
+    }
+}
@@ -0,0 +1,103 @@
+//! Presets bundling the conventions of the most common flavors of CSV files.
+
+use crate::{Escape, LineEnding, QuoteStyle, ReaderOptions, WriterOptions};
+
+/// A set of conventions (delimiter, quoting, escaping and line endings)
+/// used by a family of applications, selectable when reading and writing.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, Dialect};
+/// let text = "name;quote\r\nThomas;\"He said \"\"hi\"\"\"\r\n";
+/// let file = CSVFile::from_text(text, &Dialect::ExcelSemicolon.reader_options()).unwrap();
+/// assert_eq!(file.rows, vec![vec!["Thomas", "He said \"hi\""]]);
+///
+/// let written = file.to_string_with_options(&Dialect::Unix.writer_options());
+/// assert_eq!(written, "\"name\",\"quote\"\n\"Thomas\",\"He said \"\"hi\"\"\"\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Files exported by Excel with a comma: doubled quotes, CRLF line endings,
+    /// and a byte order mark so that Excel detects UTF-8 when opening them.
+    Excel,
+    /// Same as [Dialect::Excel](`Dialect::Excel`), with a semicolon as in the locales
+    /// where the comma is the decimal separator.
+    ExcelSemicolon,
+    /// Every value quoted, doubled quotes and LF line endings
+    /// (the `unix` dialect of Python's `csv` module).
+    Unix,
+    /// The format described by RFC 4180: a comma, doubled quotes,
+    /// quotes only when needed, and CRLF line endings.
+    RFC4180,
+    /// Tabs, double quotes when needed with backslash escapes, and LF line endings,
+    /// as read and written by MySQL's `SELECT ... INTO OUTFILE` and `LOAD DATA` given
+    /// `FIELDS TERMINATED BY '\t' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '\\' LINES TERMINATED BY '\n'`.
+    ///
+    /// It isn't their default format, which never encloses the values, escapes their tabs
+    /// and line breaks with a backslash instead, and writes `NULL` as `\N`.
+    /// The files of that format can't be read with this dialect, and the empty values
+    /// written with it are loaded as empty strings rather than as `NULL`.
+    MySQL,
+}
+
+impl Dialect {
+    /// Returns the character separating the values.
+    pub fn delimiter(&self) -> char {
+        match self {
+            Dialect::Excel | Dialect::Unix | Dialect::RFC4180 => ',',
+            Dialect::ExcelSemicolon => ';',
+            Dialect::MySQL => '\t',
+        }
+    }
+
+    /// Returns how the double quotes are escaped inside quoted values.
+    pub fn escape(&self) -> Escape {
+        match self {
+            Dialect::MySQL => Escape::Backslash,
+            _ => Escape::DoubleQuote,
+        }
+    }
+
+    /// Returns which values are quoted when writing.
+    pub fn quote_style(&self) -> QuoteStyle {
+        match self {
+            Dialect::Unix => QuoteStyle::Always,
+            _ => QuoteStyle::Necessary,
+        }
+    }
+
+    /// Returns the characters ending each line when writing.
+    pub fn line_ending(&self) -> LineEnding {
+        match self {
+            Dialect::Excel | Dialect::ExcelSemicolon | Dialect::RFC4180 => LineEnding::CrLf,
+            Dialect::Unix | Dialect::MySQL => LineEnding::Lf,
+        }
+    }
+
+    /// Returns `true` if the written files start with a byte order mark.
+    pub fn byte_order_mark(&self) -> bool {
+        matches!(self, Dialect::Excel | Dialect::ExcelSemicolon)
+    }
+
+    /// Returns the options to read files of this dialect.
+    pub fn reader_options(&self) -> ReaderOptions {
+        ReaderOptions {
+            delimiter: self.delimiter(),
+            escape: self.escape(),
+            ..ReaderOptions::default()
+        }
+    }
+
+    /// Returns the options to write files of this dialect.
+    pub fn writer_options(&self) -> WriterOptions {
+        WriterOptions {
+            delimiter: Some(self.delimiter()),
+            escape: self.escape(),
+            quote_style: self.quote_style(),
+            line_ending: Some(self.line_ending()),
+            byte_order_mark: Some(self.byte_order_mark()),
+            ..WriterOptions::default()
+        }
+    }
+}
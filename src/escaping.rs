@@ -0,0 +1,84 @@
+//! The quoting of single values, for the callers writing CSV lines by hand.
+
+use std::borrow::Cow;
+
+use crate::format::escape_formula;
+use crate::parser::{self, needs_quotes_with_escape, quote_field_with_escape, ParseError};
+use crate::{QuoteStyle, WriterOptions};
+
+/// Escapes a value the way [CSVFile::write_to](`crate::CSVFile::write_to`) would write it
+/// with the same options, so that lines composed by hand (by a log formatter, for example)
+/// can be read back by any CSV reader. The value is surrounded by double quotes
+/// when it needs them or when the [quote style](`WriterOptions::quote_style`) is `Always`,
+/// and the [null value](`WriterOptions::null_value`) and the
+/// [escaping of formulas](`WriterOptions::escape_formulas`) are applied.
+///
+/// The delimiter is the one of the options, or a comma if they don't specify one.
+/// The value is borrowed when it's written as is.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{escape_field, Escape, WriterOptions};
+/// let options = WriterOptions::default();
+/// assert_eq!(escape_field("Thomas", &options), "Thomas");
+/// assert_eq!(escape_field("Hello, world", &options), "\"Hello, world\"");
+///
+/// let options = WriterOptions { escape: Escape::DoubleQuote, ..WriterOptions::default() };
+/// assert_eq!(escape_field("say \"hi\"", &options), "\"say \"\"hi\"\"\"");
+/// ```
+pub fn escape_field<'a>(value: &'a str, options: &WriterOptions) -> Cow<'a, str> {
+    let delimiter = options.delimiter.unwrap_or(',');
+    let mut value = Cow::Borrowed(value);
+    if value.is_empty() && !options.null_value.is_empty() {
+        value = Cow::Owned(options.null_value.clone());
+    }
+    if options.escape_formulas {
+        if let Some(escaped) = escape_formula(&value) {
+            value = Cow::Owned(escaped);
+        }
+    }
+    if options.quote_style == QuoteStyle::Always
+        || needs_quotes_with_escape(&value, delimiter, options.escape)
+    {
+        value = Cow::Owned(quote_field_with_escape(&value, options.escape));
+    }
+    value
+}
+
+/// Reverses [escape_field](`escape_field`): removes the double quotes surrounding a value
+/// along with its escape sequences, and turns the [null value](`WriterOptions::null_value`)
+/// back into an empty value when it isn't quoted. The escaping of formulas can't be reversed,
+/// since a value may start with a single quote on purpose.
+///
+/// It may return an error if a double quote isn't closed, if an escape sequence is invalid,
+/// or if the text holds several values.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{escape_field, unescape_field, WriterOptions};
+/// let options = WriterOptions::default();
+/// assert_eq!(unescape_field("\"Hello, \\\"world\\\"\"", &options).unwrap(), "Hello, \"world\"");
+/// assert_eq!(unescape_field("Thomas", &options).unwrap(), "Thomas");
+///
+/// let escaped = escape_field("a\nb", &options);
+/// assert_eq!(unescape_field(&escaped, &options).unwrap(), "a\nb");
+/// assert!(unescape_field("\"not closed", &options).is_err());
+/// ```
+pub fn unescape_field<'a>(
+    field: &'a str,
+    options: &WriterOptions,
+) -> Result<Cow<'a, str>, ParseError> {
+    if !field.starts_with('"') {
+        if !options.null_value.is_empty() && field == options.null_value {
+            return Ok(Cow::Borrowed(""));
+        }
+        return Ok(Cow::Borrowed(field));
+    }
+    let delimiter = options.delimiter.unwrap_or(',');
+    let mut fields: Vec<String> = Vec::with_capacity(1);
+    parser::parse_line_with_escape_into(field, delimiter, options.escape, &mut fields, None)?;
+    parser::validate_fields(&fields, 1)?;
+    Ok(Cow::Owned(fields.pop().unwrap_or_default()))
+}
@@ -0,0 +1,111 @@
+//! The events emitted when a [CSVFile](`crate::CSVFile`) is modified.
+
+use std::sync::Arc;
+
+use crate::{CSVCoords, CSVFile};
+
+pub(crate) type ChangeObserver = Arc<dyn Fn(&ChangeEvent) + Send + Sync>;
+
+/// A modification of a CSV file, given to the observers registered with
+/// [on_change](`CSVFile::on_change`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEvent {
+    /// A row was appended, at the given index.
+    RowAdded { row: usize },
+    /// The row at the given index was removed.
+    RowRemoved { row: usize },
+    /// The value of a cell was replaced by a different one.
+    CellChanged {
+        coordinates: CSVCoords,
+        old_value: String,
+        new_value: String,
+    },
+    /// A column was added or inserted at the given index.
+    ColumnAdded { column: usize, name: String },
+    /// The column at the given index was removed.
+    ColumnRemoved { column: usize, name: String },
+}
+
+impl CSVFile {
+    /// Registers a callback called after each modification of the rows or of the columns
+    /// made by the methods of the file, such as [add_row](`CSVFile::add_row`),
+    /// [set_cell](`CSVFile::set_cell`), [remove_column](`CSVFile::remove_column`)
+    /// and all the methods built upon them.
+    ///
+    /// The rows moved by [reverse_rows](`CSVFile::reverse_rows`) and [shuffle_rows](`CSVFile::shuffle_rows`)
+    /// are reported as the cells whose value changed. The names of the columns aren't observed:
+    /// renaming them with [set_header_from_line](`CSVFile::set_header_from_line`) or
+    /// [sanitize](`CSVFile::sanitize`) isn't reported, and [conform_to](`CSVFile::conform_to`)
+    /// only reports the columns it adds or removes, not the ones it moves.
+    /// Direct modifications of the public fields aren't reported either.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, ChangeEvent};
+    /// # use std::sync::{Arc, Mutex};
+    /// let columns = vec!["a".to_string(), "b".to_string()];
+    /// let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+    ///
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let events_clone = Arc::clone(&events);
+    /// file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+    ///
+    /// file.add_row(&vec!["1".to_string(), "2".to_string()]).unwrap();
+    /// file.set_cell(&CSVCoords { row: 0, column: 1 }, &"3".to_string()).unwrap();
+    /// file.remove_column(0).unwrap();
+    ///
+    /// assert_eq!(*events.lock().unwrap(), vec![
+    ///     ChangeEvent::RowAdded { row: 0 },
+    ///     ChangeEvent::CellChanged {
+    ///         coordinates: CSVCoords { row: 0, column: 1 },
+    ///         old_value: "2".to_string(),
+    ///         new_value: "3".to_string(),
+    ///     },
+    ///     ChangeEvent::ColumnRemoved { column: 0, name: "a".to_string() },
+    /// ]);
+    /// ```
+    pub fn on_change<F>(&mut self, observer: F)
+    where
+        F: Fn(&ChangeEvent) + Send + Sync + 'static,
+    {
+        self.observers.push(Arc::new(observer));
+    }
+
+    /// Removes all the callbacks registered with [on_change](`CSVFile::on_change`).
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    /// Returns `true` if some callbacks are waiting for the modifications.
+    pub(crate) fn is_observed(&self) -> bool {
+        !self.observers.is_empty()
+    }
+
+    /// Notifies all the observers of a modification.
+    pub(crate) fn emit(&self, event: ChangeEvent) {
+        for observer in &self.observers {
+            observer(&event);
+        }
+    }
+
+    /// Reports each cell of a row whose value differs from the one of its previous version.
+    /// The values missing from the shorter of the two versions are reported as empty.
+    pub(crate) fn emit_row_changes(&self, row_idx: usize, old_row: &[String]) {
+        let new_row = &self.rows[row_idx];
+        for column in 0..old_row.len().max(new_row.len()) {
+            let old_value = old_row.get(column).cloned().unwrap_or_default();
+            let new_value = new_row.get(column).cloned().unwrap_or_default();
+            if old_value != new_value {
+                self.emit(ChangeEvent::CellChanged {
+                    coordinates: CSVCoords {
+                        row: row_idx,
+                        column,
+                    },
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+    }
+}
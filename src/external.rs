@@ -0,0 +1,349 @@
+//! Sorting of the files on disk that don't fit in memory,
+//! by sorting chunks of rows in temporary files and merging them.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::parser::{needs_quotes_with_escape, quote_field_with_escape, Escape};
+use crate::stats::parse_number;
+use crate::{CSVFile, CSVReader, ReaderOptions};
+
+/// Tells apart the temporary files of the sorts running at the same time in a process.
+static SORT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// The options used by [sort_file](`CSVFile::sort_file`).
+#[derive(Debug, Clone)]
+pub struct ExternalSortOptions {
+    /// The character separating the values, in the input and in the output (a comma by default).
+    pub delimiter: char,
+    /// How the double quotes are escaped inside quoted values ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+    /// The number of rows sorted in memory at once, and written to each temporary file
+    /// (100 000 by default). It bounds the memory used by the sort.
+    pub chunk_rows: usize,
+    /// Compares the keys as numbers, the values that aren't numbers coming last
+    /// in the order of their text (`false` by default, comparing the text).
+    pub numeric: bool,
+    /// Sorts from the greatest key to the smallest one (`false` by default).
+    pub descending: bool,
+    /// The directory of the temporary files (`None` by default, using the one of the system).
+    pub temp_dir: Option<PathBuf>,
+    /// The maximum number of temporary files merged at once (64 by default, at least 2).
+    /// When there are more, they're merged in several passes, so that the sort never
+    /// opens more files than the limit of the system allows.
+    pub merge_fan_in: usize,
+}
+
+impl Default for ExternalSortOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            escape: Escape::Backslash,
+            chunk_rows: 100_000,
+            numeric: false,
+            descending: false,
+            temp_dir: None,
+            merge_fan_in: 64,
+        }
+    }
+}
+
+impl ExternalSortOptions {
+    /// Compares two keys according to the options.
+    pub(crate) fn compare(&self, a: &str, b: &str) -> Ordering {
+        let ordering = if self.numeric {
+            match (parse_number(a), parse_number(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.cmp(b),
+            }
+        } else {
+            a.cmp(b)
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    pub(crate) fn reader_options(&self) -> ReaderOptions {
+        ReaderOptions {
+            delimiter: self.delimiter,
+            escape: self.escape,
+            ..ReaderOptions::default()
+        }
+    }
+}
+
+/// Writes a row as a line, quoting the values that need it.
+pub(crate) fn write_record<W: Write>(
+    writer: &mut W,
+    fields: &[String],
+    delimiter: char,
+    escape: Escape,
+) -> Result<(), Error> {
+    let mut line = format_record(fields, delimiter, escape);
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+/// Joins the values of a row, quoting the values that need it, without any line break.
+pub(crate) fn format_record(fields: &[String], delimiter: char, escape: Escape) -> String {
+    let mut line = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            line.push(delimiter);
+        }
+        if needs_quotes_with_escape(field, delimiter, escape) {
+            line.push_str(&quote_field_with_escape(field, escape));
+        } else {
+            line.push_str(field);
+        }
+    }
+    line
+}
+
+/// Temporary files, removed once they're no longer needed, even after an error.
+pub(crate) struct TempFiles {
+    directory: PathBuf,
+    prefix: String,
+    pub(crate) paths: Vec<PathBuf>,
+}
+
+impl TempFiles {
+    pub(crate) fn new(directory: Option<&PathBuf>, name: &str) -> Self {
+        Self {
+            directory: directory.cloned().unwrap_or_else(std::env::temp_dir),
+            prefix: format!(
+                "csv-tools-{}-{}-{}",
+                name,
+                std::process::id(),
+                SORT_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+            ),
+            paths: Vec::new(),
+        }
+    }
+
+    /// Creates a new temporary file, which gets removed along with the others.
+    pub(crate) fn create(&mut self) -> Result<(PathBuf, BufWriter<File>), Error> {
+        let path = self
+            .directory
+            .join(format!("{}-{}.csv", self.prefix, self.paths.len()));
+        let file = File::create(&path)?;
+        self.paths.push(path.clone());
+        Ok((path, BufWriter::new(file)))
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// The next row of a sorted chunk, ordered so that the smallest key comes first
+/// out of the heap, and the first chunk when the keys are equal.
+struct Head<'a> {
+    key: usize,
+    chunk: usize,
+    row: Vec<String>,
+    options: &'a ExternalSortOptions,
+}
+
+impl Ord for Head<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.options
+            .compare(
+                sort_key(&self.row, self.key),
+                sort_key(&other.row, other.key),
+            )
+            .then(self.chunk.cmp(&other.chunk))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+/// Returns the key of a row, which was checked to be long enough when the input was read.
+pub(crate) fn sort_key(row: &[String], key: usize) -> &str {
+    row.get(key).map_or("", String::as_str)
+}
+
+/// Merges sorted temporary files into a writer, without their header.
+fn merge_chunks<W: Write>(
+    paths: &[PathBuf],
+    writer: &mut W,
+    key: usize,
+    options: &ExternalSortOptions,
+) -> Result<(), Error> {
+    let mut chunks = paths
+        .iter()
+        .map(|path| {
+            CSVReader::open(
+                &path.to_string_lossy().to_string(),
+                &options.reader_options(),
+            )
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut heap: BinaryHeap<Head> = BinaryHeap::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter_mut().enumerate() {
+        if let Some(row) = chunk.next().transpose()? {
+            heap.push(Head {
+                key,
+                chunk: i,
+                row,
+                options,
+            });
+        }
+    }
+    while let Some(head) = heap.pop() {
+        write_record(writer, &head.row, options.delimiter, options.escape)?;
+        if let Some(row) = chunks[head.chunk].next().transpose()? {
+            heap.push(Head { row, ..head });
+        }
+    }
+    Ok(())
+}
+
+impl CSVFile {
+    /// Sorts a CSV file on disk by the values of a key column and writes the result
+    /// to another file, without holding the whole file in memory: the rows are sorted
+    /// by chunks written to temporary files, which are then merged.
+    /// The sort is stable, so the rows with the same key keep their order.
+    ///
+    /// The output may be the input file itself, since the input is entirely read
+    /// before the output is written. The temporary files are removed in every case.
+    ///
+    /// It may return an error if a file can't be read or written, if the file can't be parsed,
+    /// if the key column doesn't exist, or if a row is too short to have a key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ExternalSortOptions};
+    /// # let input = std::env::temp_dir().join("csv_tools_sort_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&input, "name,age\nThomas,19\nJohn,28\nMarie,9\nPaul,19\n").unwrap();
+    ///
+    /// let options = ExternalSortOptions {
+    ///     numeric: true,
+    ///     chunk_rows: 2,
+    ///     ..ExternalSortOptions::default()
+    /// };
+    /// CSVFile::sort_file(&input, &input, &"age".to_string(), &options).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&input).unwrap(),
+    ///     "name,age\nMarie,9\nThomas,19\nPaul,19\nJohn,28\n"
+    /// );
+    /// # std::fs::remove_file(&input).unwrap();
+    /// ```
+    pub fn sort_file(
+        input: &String,
+        output: &String,
+        key_column: &String,
+        options: &ExternalSortOptions,
+    ) -> Result<(), Error> {
+        let mut reader = CSVReader::open(input, &options.reader_options())?;
+        let columns = reader.columns().clone();
+        let key = columns
+            .iter()
+            .position(|c| c == key_column)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", key_column),
+                )
+            })?;
+        let chunk_rows = options.chunk_rows.max(1);
+
+        let mut temp_files = TempFiles::new(options.temp_dir.as_ref(), "sort");
+        let mut chunk: Vec<Vec<String>> = Vec::new();
+        loop {
+            let row = reader.next().transpose()?;
+            if let Some(row) = row {
+                if row.len() <= key {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The row {} doesn't have a value in the column {}",
+                            reader.rows_read(),
+                            key_column
+                        ),
+                    ));
+                }
+                chunk.push(row);
+                if chunk.len() < chunk_rows {
+                    continue;
+                }
+            }
+            if chunk.is_empty() {
+                break;
+            }
+            // The sort is stable, so is the whole merge.
+            chunk.sort_by(|a, b| options.compare(sort_key(a, key), sort_key(b, key)));
+            // A file small enough to be sorted in memory doesn't need temporary files.
+            if temp_files.paths.is_empty() && chunk.len() < chunk_rows {
+                break;
+            }
+            let (_, mut writer) = temp_files.create()?;
+            write_record(&mut writer, &columns, options.delimiter, options.escape)?;
+            for row in chunk.drain(..) {
+                write_record(&mut writer, &row, options.delimiter, options.escape)?;
+            }
+            writer.flush()?;
+        }
+
+        let mut writer = BufWriter::new(File::create(output)?);
+        write_record(&mut writer, &columns, options.delimiter, options.escape)?;
+        if temp_files.paths.is_empty() {
+            for row in &chunk {
+                write_record(&mut writer, row, options.delimiter, options.escape)?;
+            }
+            return writer.flush();
+        }
+
+        // The files merged together are consecutive, so the sort stays stable.
+        let fan_in = options.merge_fan_in.max(2);
+        let mut paths = temp_files.paths.clone();
+        while paths.len() > fan_in {
+            let mut merged = Vec::with_capacity(paths.len().div_ceil(fan_in));
+            for group in paths.chunks(fan_in) {
+                let (path, mut chunk_writer) = temp_files.create()?;
+                write_record(
+                    &mut chunk_writer,
+                    &columns,
+                    options.delimiter,
+                    options.escape,
+                )?;
+                merge_chunks(group, &mut chunk_writer, key, options)?;
+                chunk_writer.flush()?;
+                merged.push(path);
+            }
+            for path in &paths {
+                let _ = std::fs::remove_file(path);
+            }
+            paths = merged;
+        }
+        merge_chunks(&paths, &mut writer, key, options)?;
+        writer.flush()
+    }
+}
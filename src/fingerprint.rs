@@ -0,0 +1,194 @@
+//! Stable fingerprints of the content of a CSV file.
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+
+use crate::mask::sha256;
+use crate::CSVFile;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A 64-bit FNV-1a hasher. Contrary to the hasher of the standard library,
+/// its results never change between runs, platforms or versions of Rust,
+/// so they can be stored and compared later.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Hashes a value preceded by its length, so that the boundaries
+    /// between the values are part of the fingerprint.
+    fn write_value(&mut self, value: &str) {
+        self.write(&(value.len() as u64).to_le_bytes());
+        self.write(value.as_bytes());
+    }
+
+    fn write_values<'a>(&mut self, values: impl Iterator<Item = &'a str>) {
+        let mut count: u64 = 0;
+        for value in values {
+            self.write_value(value);
+            count += 1;
+        }
+        self.write(&count.to_le_bytes());
+    }
+}
+
+/// The hash functions of [add_hash_column](`CSVFile::add_hash_column`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// The 64-bit FNV-1a hash, written as 16 hexadecimal digits. It's fast and short,
+    /// but collisions become likely beyond a few hundred million rows.
+    #[default]
+    Fnv1a,
+    /// The SHA-256 hash, written as 64 hexadecimal digits.
+    Sha256,
+}
+
+impl CSVFile {
+    /// Returns a fingerprint of the columns and the rows of the file.
+    /// It doesn't depend on the delimiter nor on the way the values were quoted,
+    /// and it's stable across runs, so it can be stored to detect
+    /// whether the content of a file actually changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let comma = CSVFile::from_text("a,b\n1,\"2\"\n", &ReaderOptions::default()).unwrap();
+    /// let tab = CSVFile::from_text("a\tb\n1\t2\n", &ReaderOptions {
+    ///     delimiter: '\t',
+    ///     ..ReaderOptions::default()
+    /// }).unwrap();
+    /// assert_eq!(comma.content_hash(), tab.content_hash());
+    ///
+    /// let other = CSVFile::from_text("a,b\n1,3\n", &ReaderOptions::default()).unwrap();
+    /// assert_ne!(comma.content_hash(), other.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write_values(self.columns.iter().map(String::as_str));
+        for row in &self.rows {
+            hasher.write_values(row.iter().map(String::as_str));
+        }
+        hasher.0
+    }
+
+    /// Returns a fingerprint of the name and the values of a column,
+    /// or `None` if the column doesn't exist.
+    /// Like [content_hash](`CSVFile::content_hash`), it's stable across runs.
+    /// A column given by one of its aliases is hashed with its name in the header,
+    /// so the fingerprint is the same whichever name is used.
+    pub fn column_hash(&self, column_name: &String) -> Option<u64> {
+        let idx = self.get_column_idx(column_name)?;
+        let mut hasher = Fnv1a::new();
+        hasher.write_value(&self.columns[idx]);
+        hasher.write_values(
+            self.rows
+                .iter()
+                .filter_map(|row| row.get(idx))
+                .map(String::as_str),
+        );
+        Some(hasher.0)
+    }
+
+    /// Adds a column holding a hash of the given columns of each row, all of them if none
+    /// are given, to detect the rows that changed when syncing data without a natural key.
+    /// The hash is stable across runs, and the boundaries between the values are part of it,
+    /// so `"ab","c"` and `"a","bc"` get different hashes. The values missing
+    /// from the ragged rows are hashed as empty values.
+    ///
+    /// It may return an error if a column doesn't exist or if the new column already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, HashAlgorithm, ReaderOptions};
+    /// let text = "id,name,updated\n1,Thomas,2024-01-01\n2,John,2024-02-01\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// file.add_hash_column(&"hash".to_string(), HashAlgorithm::Sha256, &["id", "name"]).unwrap();
+    /// assert_eq!(file.columns, vec!["id", "name", "updated", "hash"]);
+    /// assert_eq!(file.rows[0][3].len(), 64);
+    /// assert_ne!(file.rows[0][3], file.rows[1][3]);
+    /// ```
+    pub fn add_hash_column(
+        &mut self,
+        name: &String,
+        algorithm: HashAlgorithm,
+        columns: &[&str],
+    ) -> Result<(), Error> {
+        let indexes: Vec<usize> = if columns.is_empty() {
+            (0..self.columns.len()).collect()
+        } else {
+            columns
+                .iter()
+                .map(|column| {
+                    self.get_column_idx(&column.to_string()).ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("The column {} doesn't exist", column),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<usize>, Error>>()?
+        };
+        if self.columns.contains(name) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} already exists", name),
+            ));
+        }
+
+        let hashes: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let values = indexes
+                    .iter()
+                    .map(|&idx| row.get(idx).map_or("", String::as_str));
+                match algorithm {
+                    HashAlgorithm::Fnv1a => {
+                        let mut hasher = Fnv1a::new();
+                        hasher.write_values(values);
+                        format!("{:016x}", hasher.0)
+                    }
+                    HashAlgorithm::Sha256 => {
+                        // The same encoding as the FNV-1a hash, each value preceded by its length.
+                        let mut bytes: Vec<u8> = Vec::new();
+                        let mut count: u64 = 0;
+                        for value in values {
+                            bytes.extend_from_slice(&(value.len() as u64).to_le_bytes());
+                            bytes.extend_from_slice(value.as_bytes());
+                            count += 1;
+                        }
+                        bytes.extend_from_slice(&count.to_le_bytes());
+                        let mut hex = String::with_capacity(64);
+                        for byte in sha256(&bytes) {
+                            let _ = write!(hex, "{:02x}", byte);
+                        }
+                        hex
+                    }
+                }
+            })
+            .collect();
+        self.add_column(name)?;
+        self.fill_column(name, &hashes)
+    }
+}
+
+/// Returns the FNV-1a fingerprint of raw bytes, such as the content of a file.
+pub(crate) fn bytes_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(bytes);
+    hasher.0
+}
@@ -0,0 +1,182 @@
+//! Reading and writing fixed-width text files, in which each column
+//! occupies a fixed number of characters on every line.
+//!
+//! # Example
+//!
+//! ```
+//! # use csv_tools::fixed_width::{self, FixedWidthColumn, FixedWidthSpec};
+//! # use csv_tools::Alignment;
+//! let spec = FixedWidthSpec::new(vec![
+//!     FixedWidthColumn::new("name", 8),
+//!     FixedWidthColumn { alignment: Alignment::Right, ..FixedWidthColumn::new("age", 3) },
+//! ]);
+//!
+//! let file = fixed_width::from_reader("Thomas   20\nYoshiip  99\n".as_bytes(), &spec).unwrap();
+//! assert_eq!(file.columns, vec!["name", "age"]);
+//! assert_eq!(file.rows, vec![vec!["Thomas", "20"], vec!["Yoshiip", "99"]]);
+//!
+//! assert_eq!(fixed_width::to_string(&file, &spec).unwrap(), "Thomas   20\nYoshiip  99\n");
+//! ```
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::BufWriter;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Write};
+
+use crate::{Alignment, CSVFile};
+
+/// A column of a fixed-width file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedWidthColumn {
+    pub name: String,
+    /// The number of characters the column occupies.
+    pub width: usize,
+    /// Where the value is placed within the column when writing (on the left by default).
+    pub alignment: Alignment,
+}
+
+impl FixedWidthColumn {
+    /// Creates a column aligned on the left.
+    pub fn new(name: &str, width: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            alignment: Alignment::Left,
+        }
+    }
+}
+
+/// The layout of a fixed-width file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedWidthSpec {
+    /// The columns, in the order in which they appear on each line.
+    pub columns: Vec<FixedWidthColumn>,
+    /// The character used to fill the columns (a space by default).
+    pub fill: char,
+    /// Skips the first line when reading, because it holds a header (`false` by default).
+    pub skip_first_line: bool,
+    /// The delimiter of the CSVFile created when reading (a comma by default).
+    pub delimiter: char,
+}
+
+impl FixedWidthSpec {
+    /// Creates a specification filled with spaces and without a header line.
+    pub fn new(columns: Vec<FixedWidthColumn>) -> Self {
+        Self {
+            columns,
+            fill: ' ',
+            skip_first_line: false,
+            delimiter: ',',
+        }
+    }
+
+    /// Splits a line into the values of the columns, removing the filling characters.
+    /// A line shorter than expected gives empty values for the missing columns.
+    fn split(&self, line: &str) -> Vec<String> {
+        let mut chars = line.chars();
+        self.columns
+            .iter()
+            .map(|column| {
+                let value: String = chars.by_ref().take(column.width).collect();
+                value.trim_matches(self.fill).to_string()
+            })
+            .collect()
+    }
+}
+
+/// Reads a fixed-width file into a CSVFile whose columns are the ones of the specification.
+#[cfg(feature = "fs")]
+pub fn read(file_name: &String, spec: &FixedWidthSpec) -> Result<CSVFile, Error> {
+    from_reader(File::open(file_name)?, spec)
+}
+
+/// Reads fixed-width text from any source into a CSVFile whose columns are the ones of the specification.
+/// The empty lines are ignored.
+pub fn from_reader<R: Read>(reader: R, spec: &FixedWidthSpec) -> Result<CSVFile, Error> {
+    let columns: Vec<String> = spec.columns.iter().map(|c| c.name.clone()).collect();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if (i == 0 && spec.skip_first_line) || line.is_empty() {
+            continue;
+        }
+        rows.push(spec.split(&line));
+    }
+
+    CSVFile::build(&columns, &rows, &spec.delimiter)
+}
+
+/// Writes a CSVFile as a fixed-width file.
+/// See [write_to](`write_to`) for the errors it may return.
+#[cfg(feature = "fs")]
+pub fn write(file: &CSVFile, file_name: &String, spec: &FixedWidthSpec) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(file_name)?);
+    write_to(file, &mut writer, spec)?;
+    writer.flush()
+}
+
+/// Converts a CSVFile to fixed-width text.
+/// See [write_to](`write_to`) for the errors it may return.
+pub fn to_string(file: &CSVFile, spec: &FixedWidthSpec) -> Result<String, Error> {
+    let mut output: Vec<u8> = Vec::new();
+    write_to(file, &mut output, spec)?;
+    Ok(String::from_utf8(output).expect("the values are valid UTF-8"))
+}
+
+/// Writes a CSVFile as fixed-width text, without a header line.
+/// Only the columns of the specification are written, in its order.
+///
+/// It may return an error if a column of the specification doesn't exist in the file,
+/// or if a value is too long to fit in its column.
+pub fn write_to<W: Write>(
+    file: &CSVFile,
+    writer: &mut W,
+    spec: &FixedWidthSpec,
+) -> Result<(), Error> {
+    let mut indexes: Vec<usize> = Vec::with_capacity(spec.columns.len());
+    for column in &spec.columns {
+        match file.get_column_idx(&column.name) {
+            Some(idx) => indexes.push(idx),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", column.name),
+                ))
+            }
+        }
+    }
+
+    let mut line = String::new();
+    for (i, row) in file.rows.iter().enumerate() {
+        line.clear();
+        for (column, idx) in spec.columns.iter().zip(&indexes) {
+            let value = row.get(*idx).map(|v| v.as_str()).unwrap_or_default();
+            let length = value.chars().count();
+            if length > column.width {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The value of the row of index {} in column {} is {} characters long, but the column is {} characters wide",
+                        i, column.name, length, column.width
+                    ),
+                ));
+            }
+            let fill: String = std::iter::repeat_n(spec.fill, column.width - length).collect();
+            match column.alignment {
+                Alignment::Left => {
+                    line.push_str(value);
+                    line.push_str(&fill);
+                }
+                Alignment::Right => {
+                    line.push_str(&fill);
+                    line.push_str(value);
+                }
+            }
+        }
+        line.push('\n');
+        writer.write_all(line.as_bytes())?;
+    }
+
+    Ok(())
+}
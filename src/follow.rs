@@ -0,0 +1,193 @@
+//! Following a CSV file that keeps growing, like `tail -f`.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use crate::parser::{self, ParseError};
+use crate::{CSVReader, ReaderOptions};
+
+/// Reads the rows of a CSV file as other processes append them, like `tail -f`.
+/// It's created by [CSVReader::follow](`CSVReader::follow`).
+///
+/// The follower first gives the rows already in the file, then waits for new ones.
+/// A row is only given once it's complete: a last line without its line break,
+/// or a quoted value whose closing quote wasn't written yet, is kept aside
+/// until the rest of the row arrives.
+///
+/// As an iterator, the follower never ends by itself: when there's no new row,
+/// it checks the file again after the [poll interval](`CSVFollower::with_poll_interval`).
+/// It stops at the first error, including the cancellation of the
+/// [token](`ReaderOptions::cancellation`) of the options. Use
+/// [try_next](`CSVFollower::try_next`) to read without waiting.
+///
+/// If the file gets shorter, for example because it was truncated or replaced by a log rotation,
+/// the follower starts again from the first row.
+pub struct CSVFollower {
+    file_name: String,
+    options: ReaderOptions,
+    columns: Vec<String>,
+    raw_header: String,
+    header_bytes: u64,
+    /// The offset of the first byte that wasn't turned into rows yet.
+    offset: u64,
+    /// The bytes read after `offset`, which don't make a complete row yet.
+    pending: Vec<u8>,
+    ready: VecDeque<Vec<String>>,
+    poll_interval: Duration,
+    finished: bool,
+}
+
+impl CSVReader<BufReader<File>> {
+    /// Opens a file that keeps growing, such as a log, to read its rows
+    /// as they're appended (see [CSVFollower](`CSVFollower`)).
+    /// It may return an error if the file doesn't exist, if it's empty or if the header can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::io::Write;
+    /// # use csv_tools::{CSVReader, ReaderOptions};
+    /// # let path = std::env::temp_dir().join("csv_tools_follow_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&path, "time,event\n10:00,start\n10:05,pa").unwrap();
+    ///
+    /// let mut follower = CSVReader::follow(&path, &ReaderOptions::default()).unwrap();
+    /// assert_eq!(follower.try_next().unwrap(), Some(vec!["10:00".to_string(), "start".to_string()]));
+    /// // The last row isn't complete yet.
+    /// assert_eq!(follower.try_next().unwrap(), None);
+    ///
+    /// let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    /// file.write_all(b"use\n").unwrap();
+    /// assert_eq!(follower.next().unwrap().unwrap(), vec!["10:05", "pause"]);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn follow(file_name: &String, options: &ReaderOptions) -> Result<CSVFollower, Error> {
+        let reader = CSVReader::open(file_name, options)?;
+        let header_bytes = reader.bytes_read();
+        Ok(CSVFollower {
+            file_name: file_name.clone(),
+            options: options.clone(),
+            raw_header: reader.raw_header().clone(),
+            columns: reader.into_columns(),
+            header_bytes,
+            offset: header_bytes,
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+            poll_interval: Duration::from_millis(200),
+            finished: false,
+        })
+    }
+}
+
+impl CSVFollower {
+    /// Sets how long the iterator waits before checking the file again
+    /// when there's no new row (200 milliseconds by default).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Returns the names of the columns.
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    /// Returns the next complete row, or `None` if there's none yet, without waiting.
+    /// It may return an error if the file can't be read or if a row can't be parsed.
+    pub fn try_next(&mut self) -> Result<Option<Vec<String>>, Error> {
+        if self.ready.is_empty() {
+            self.poll()?;
+        }
+        Ok(self.ready.pop_front())
+    }
+
+    /// Reads what was appended to the file since the last time,
+    /// and parses the rows that are complete.
+    fn poll(&mut self) -> Result<(), Error> {
+        let mut file = File::open(&self.file_name)?;
+        let size = file.metadata()?.len();
+        if size < self.offset + self.pending.len() as u64 {
+            self.offset = self.header_bytes;
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset + self.pending.len() as u64))?;
+        file.read_to_end(&mut self.pending)?;
+
+        let Some(last_line_break) = self.pending.iter().rposition(|b| *b == b'\n') else {
+            return Ok(());
+        };
+        let text = std::str::from_utf8(&self.pending[..=last_line_break])
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let length = self.complete_length(text);
+        if length == 0 {
+            return Ok(());
+        }
+
+        // The complete rows are read with the header, so that they get the same
+        // treatment as the rows of any other reader with these options.
+        let mut source = self.raw_header.clone();
+        source.push('\n');
+        source.push_str(&text[..length]);
+        let reader = CSVReader::from_reader(source.as_bytes(), &self.options)?;
+        for row in reader {
+            self.ready.push_back(row?);
+        }
+        self.pending.drain(..length);
+        self.offset += length as u64;
+        Ok(())
+    }
+
+    /// Returns the length of the complete rows at the beginning of the text,
+    /// which is made of whole lines. The last row is incomplete if it ends
+    /// in the middle of a quoted value.
+    fn complete_length(&self, text: &str) -> usize {
+        let mut length = 0;
+        let mut row = String::new();
+        let mut fields: Vec<String> = Vec::new();
+        for line in text.split_inclusive('\n') {
+            row.push_str(line);
+            if row.contains('"') {
+                fields.clear();
+                let content = row.trim_end_matches('\n').trim_end_matches('\r');
+                let parsed = parser::parse_line_with_escape_into(
+                    content,
+                    self.options.delimiter,
+                    self.options.escape,
+                    &mut fields,
+                    None,
+                );
+                if let Err(ParseError::UnclosedQuote) = parsed {
+                    continue;
+                }
+            }
+            length += row.len();
+            row.clear();
+        }
+        length
+    }
+}
+
+impl Iterator for CSVFollower {
+    type Item = Result<Vec<String>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            let result = match &self.options.cancellation {
+                Some(token) => token.check().and_then(|_| self.try_next()),
+                None => self.try_next(),
+            };
+            match result {
+                Ok(Some(row)) => return Some(Ok(row)),
+                Ok(None) => std::thread::sleep(self.poll_interval),
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
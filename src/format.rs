@@ -0,0 +1,211 @@
+//! Formatting rules applied to the values of a column when a CSV file is written.
+
+use crate::parser::quote_field;
+
+/// The side on which the padding characters are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// The value is kept on the left, the padding is added on the right.
+    #[default]
+    Left,
+    /// The value is pushed to the right, the padding is added on the left.
+    Right,
+}
+
+/// Pads a value until it reaches a minimum width.
+/// Values that are already longer than the width are left untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Padding {
+    pub width: usize,
+    pub alignment: Alignment,
+    pub fill: char,
+}
+
+/// Converts a date written with the `input` pattern into the `output` pattern.
+///
+/// The supported specifiers are `%Y` (year), `%m` (month), `%d` (day),
+/// `%H` (hours), `%M` (minutes), `%S` (seconds) and `%%` (a literal `%`).
+/// Any other character must match exactly.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::DateFormat;
+/// let format = DateFormat {
+///     input: "%Y-%m-%d".to_string(),
+///     output: "%d/%m/%Y".to_string(),
+/// };
+///
+/// assert_eq!(format.apply("2024-03-27"), Some("27/03/2024".to_string()));
+/// assert_eq!(format.apply("not a date"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateFormat {
+    pub input: String,
+    pub output: String,
+}
+
+/// The components of a date read by a [DateFormat](`DateFormat`).
+#[derive(Default)]
+struct DateParts {
+    year: String,
+    month: String,
+    day: String,
+    hours: String,
+    minutes: String,
+    seconds: String,
+}
+
+impl DateFormat {
+    /// Reformats the value, or returns `None` if it doesn't match the input pattern.
+    pub fn apply(&self, value: &str) -> Option<String> {
+        let parts = self.parse(value)?;
+        let mut result = String::new();
+        let mut pattern = self.output.chars();
+        while let Some(c) = pattern.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            match pattern.next() {
+                Some('Y') => result.push_str(&parts.year),
+                Some('m') => result.push_str(&parts.month),
+                Some('d') => result.push_str(&parts.day),
+                Some('H') => result.push_str(&parts.hours),
+                Some('M') => result.push_str(&parts.minutes),
+                Some('S') => result.push_str(&parts.seconds),
+                Some('%') => result.push('%'),
+                _ => return None,
+            }
+        }
+
+        Some(result)
+    }
+
+    fn parse(&self, value: &str) -> Option<DateParts> {
+        let mut parts = DateParts::default();
+        let mut value = value.chars().peekable();
+        let mut pattern = self.input.chars();
+        while let Some(c) = pattern.next() {
+            if c != '%' {
+                if value.next()? != c {
+                    return None;
+                }
+                continue;
+            }
+            let (target, width) = match pattern.next()? {
+                'Y' => (&mut parts.year, 4),
+                'm' => (&mut parts.month, 2),
+                'd' => (&mut parts.day, 2),
+                'H' => (&mut parts.hours, 2),
+                'M' => (&mut parts.minutes, 2),
+                'S' => (&mut parts.seconds, 2),
+                '%' => {
+                    if value.next()? != '%' {
+                        return None;
+                    }
+                    continue;
+                }
+                _ => return None,
+            };
+            for _ in 0..width {
+                match value.next_if(|d| d.is_ascii_digit()) {
+                    Some(d) => target.push(d),
+                    None => return None,
+                }
+            }
+        }
+
+        if value.next().is_some() {
+            return None;
+        }
+
+        Some(parts)
+    }
+}
+
+/// The formatting rules of a single column.
+/// The rules are applied in this order: date, decimals, padding, quotes.
+///
+/// A rule that can't be applied to a value (for example a fixed number
+/// of decimals on a value that isn't a number) leaves the value untouched.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{ColumnFormat, Padding, Alignment};
+/// let format = ColumnFormat {
+///     decimals: Some(2),
+///     padding: Some(Padding { width: 8, alignment: Alignment::Right, fill: ' ' }),
+///     ..ColumnFormat::default()
+/// };
+///
+/// assert_eq!(format.apply("3.14159"), "    3.14");
+/// assert_eq!(format.apply("N/A"), "     N/A");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnFormat {
+    /// Pads the values to a minimum width.
+    pub padding: Option<Padding>,
+    /// Writes the numbers with a fixed number of decimals.
+    pub decimals: Option<usize>,
+    /// Reformats the dates.
+    pub date_format: Option<DateFormat>,
+    /// Surrounds the values with double quotes, even if they don't need it.
+    pub always_quote: bool,
+}
+
+impl ColumnFormat {
+    /// Applies the rules to a value and returns the text to write in the file.
+    pub fn apply(&self, value: &str) -> String {
+        let result = self.format_value(value);
+        if self.always_quote {
+            quote_field(&result)
+        } else {
+            result
+        }
+    }
+
+    /// Applies every rule except the quotes.
+    pub(crate) fn format_value(&self, value: &str) -> String {
+        let mut result = value.to_string();
+
+        if let Some(date_format) = &self.date_format {
+            if let Some(date) = date_format.apply(&result) {
+                result = date;
+            }
+        }
+
+        if let Some(decimals) = self.decimals {
+            if let Ok(number) = result.trim().parse::<f64>() {
+                result = format!("{:.*}", decimals, number);
+            }
+        }
+
+        if let Some(padding) = &self.padding {
+            let length = result.chars().count();
+            if length < padding.width {
+                let fill: String =
+                    std::iter::repeat_n(padding.fill, padding.width - length).collect();
+                result = match padding.alignment {
+                    Alignment::Left => result + &fill,
+                    Alignment::Right => fill + &result,
+                };
+            }
+        }
+
+        result
+    }
+}
+
+/// Returns the value prefixed with a single quote if a spreadsheet application
+/// would interpret it as a formula, meaning it starts with `=`, `+`, `-` or `@`,
+/// as recommended by the OWASP guidance against CSV injection.
+/// Plain numbers such as `-12.5` are not formulas and are left untouched.
+pub(crate) fn escape_formula(value: &str) -> Option<String> {
+    if value.starts_with(['=', '+', '-', '@']) && value.parse::<f64>().is_err() {
+        Some(format!("'{}", value))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,130 @@
+//! Frequency tables counting the occurrences of the values of the columns.
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+impl CSVFile {
+    /// Finds the index of a column, failing if it doesn't exist.
+    fn frequency_column_idx(&self, column_name: &String) -> Result<usize, Error> {
+        self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })
+    }
+
+    /// Counts the occurrences of each value of a column, empty ones included,
+    /// the values missing from the ragged rows being counted as empty.
+    /// It returns a new file with two columns, the values and `count`, sorted from the
+    /// most frequent value to the least frequent one, and by first occurrence
+    /// when they're as frequent.
+    ///
+    /// It may return an error if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("city\nParis\nLyon\nParis\nNice\nLyon\nParis\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let counts = file.value_counts(&"city".to_string()).unwrap();
+    /// assert_eq!(counts.columns, vec!["city", "count"]);
+    /// assert_eq!(counts.rows, vec![
+    ///     vec!["Paris", "3"],
+    ///     vec!["Lyon", "2"],
+    ///     vec!["Nice", "1"],
+    /// ]);
+    /// ```
+    pub fn value_counts(&self, column_name: &String) -> Result<CSVFile, Error> {
+        let idx = self.frequency_column_idx(column_name)?;
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for row in &self.rows {
+            let value = row.get(idx).map_or("", String::as_str);
+            match positions.get(value) {
+                Some(position) => counts[*position].1 += 1,
+                None => {
+                    positions.insert(value, counts.len());
+                    counts.push((value, 1));
+                }
+            }
+        }
+
+        // The sort is stable, so equally frequent values stay in order of first occurrence.
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let rows = counts
+            .into_iter()
+            .map(|(value, count)| vec![value.to_string(), count.to_string()])
+            .collect();
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            vec![self.columns[idx].clone(), String::from("count")],
+            rows,
+        ))
+    }
+
+    /// Counts the occurrences of each pair of values of two columns.
+    /// It returns a new file with a row for each value of the first column
+    /// and a column for each value of the second one, both in order of first occurrence,
+    /// preceded by a column named like the first column holding its values.
+    /// The values missing from the ragged rows are counted as empty.
+    ///
+    /// It may return an error if one of the columns doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "city,product\nParis,tea\nLyon,coffee\nParis,coffee\nParis,tea\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let table = file.crosstab(&"city".to_string(), &"product".to_string()).unwrap();
+    /// assert_eq!(table.columns, vec!["city", "tea", "coffee"]);
+    /// assert_eq!(table.rows, vec![
+    ///     vec!["Paris", "2", "1"],
+    ///     vec!["Lyon", "0", "1"],
+    /// ]);
+    /// ```
+    pub fn crosstab(&self, column_a: &String, column_b: &String) -> Result<CSVFile, Error> {
+        let idx_a = self.frequency_column_idx(column_a)?;
+        let idx_b = self.frequency_column_idx(column_b)?;
+
+        let mut values_a: Vec<&str> = Vec::new();
+        let mut values_b: Vec<&str> = Vec::new();
+        let mut positions_a: HashMap<&str, usize> = HashMap::new();
+        let mut positions_b: HashMap<&str, usize> = HashMap::new();
+        let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+        for row in &self.rows {
+            let value_a = row.get(idx_a).map_or("", String::as_str);
+            let value_b = row.get(idx_b).map_or("", String::as_str);
+            let a = *positions_a.entry(value_a).or_insert_with(|| {
+                values_a.push(value_a);
+                values_a.len() - 1
+            });
+            let b = *positions_b.entry(value_b).or_insert_with(|| {
+                values_b.push(value_b);
+                values_b.len() - 1
+            });
+            *counts.entry((a, b)).or_insert(0) += 1;
+        }
+
+        let mut columns = vec![self.columns[idx_a].clone()];
+        columns.extend(values_b.iter().map(|value| value.to_string()));
+        let rows = values_a
+            .iter()
+            .enumerate()
+            .map(|(a, value)| {
+                let mut row = vec![value.to_string()];
+                row.extend(
+                    (0..values_b.len())
+                        .map(|b| counts.get(&(a, b)).copied().unwrap_or(0).to_string()),
+                );
+                row
+            })
+            .collect();
+        Ok(CSVFile::from_rows(self.delimiter, columns, rows))
+    }
+}
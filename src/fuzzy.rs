@@ -0,0 +1,83 @@
+//! Approximate search of text in the cells, tolerating typos.
+
+use crate::{CSVCoords, CSVFile};
+
+/// A cell found by [find_similar](`CSVFile::find_similar`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarCell {
+    pub coordinates: CSVCoords,
+    /// The number of characters to insert, remove or replace to turn the searched text into the value.
+    pub distance: usize,
+    /// The similarity between the searched text and the value, from 0 (nothing in common)
+    /// to 1 (identical): one minus the distance divided by the length of the longest of the two.
+    pub similarity: f64,
+}
+
+/// Computes the Levenshtein distance between two texts, in characters,
+/// keeping only two rows of the matrix.
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+impl CSVFile {
+    /// Finds the cells whose value is at most `max_distance` edits away from the text,
+    /// such as "Jonh Smith" when looking for "John Smith", to locate the typos
+    /// of manually entered data. The comparison is case-sensitive.
+    ///
+    /// The cells are sorted from the most similar to the least similar,
+    /// and by position when they are as similar.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, ReaderOptions};
+    /// let text = "name\nJohn Smith\nJonh Smith\nJane Smith\nJohn Smyth\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let found = file.find_similar("John Smith", 1);
+    /// assert_eq!(found.len(), 2);
+    /// assert_eq!(found[0].coordinates, CSVCoords { row: 0, column: 0 });
+    /// assert_eq!(found[0].similarity, 1.0);
+    /// assert_eq!(found[1].coordinates, CSVCoords { row: 3, column: 0 });
+    /// assert_eq!(found[1].distance, 1);
+    /// assert_eq!(found[1].similarity, 0.9);
+    /// ```
+    pub fn find_similar(&self, text: &str, max_distance: usize) -> Vec<SimilarCell> {
+        let searched: Vec<char> = text.chars().collect();
+        let mut found: Vec<SimilarCell> = Vec::new();
+        let mut value: Vec<char> = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                value.clear();
+                value.extend(cell.chars());
+                // The distance is at least the difference of length.
+                if value.len().abs_diff(searched.len()) > max_distance {
+                    continue;
+                }
+                let distance = levenshtein(&searched, &value);
+                if distance <= max_distance {
+                    let longest = searched.len().max(value.len()).max(1);
+                    found.push(SimilarCell {
+                        coordinates: CSVCoords { row: i, column: j },
+                        distance,
+                        similarity: 1.0 - distance as f64 / longest as f64,
+                    });
+                }
+            }
+        }
+
+        // The sort is stable, so equally similar cells stay in order.
+        found.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        found
+    }
+}
@@ -0,0 +1,103 @@
+//! Loading a CSV file published on the web, such as a spreadsheet
+//! exported as CSV or the endpoint of an open-data portal.
+//!
+//! The download is delegated to the `curl` command, which handles HTTPS,
+//! redirections and proxies without adding any dependency to the crate.
+//! It must be installed and available in the `PATH`.
+
+use std::io::{Error, ErrorKind};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::{CSVFile, ReaderOptions};
+
+/// The time given to a download by [CSVFile::from_url](`crate::CSVFile::from_url`).
+pub const DEFAULT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The time given to the connection to the server, within the timeout of the download.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl CSVFile {
+    /// Downloads a CSV file with `curl` and parses it while it is received,
+    /// the rows being read as soon as they arrive instead of once the whole body is downloaded.
+    /// Only the `http` and `https` URLs are accepted, and the redirections are followed
+    /// as long as they don't lead to another protocol, so that a server can't redirect
+    /// to a local file. The download is aborted after
+    /// [DEFAULT_DOWNLOAD_TIMEOUT](`DEFAULT_DOWNLOAD_TIMEOUT`),
+    /// see [from_url_with_timeout](`CSVFile::from_url_with_timeout`) to choose another limit.
+    ///
+    /// It may return an error if `curl` isn't installed, if the URL uses another protocol,
+    /// if the server answers with an error status or too slowly, or if the body can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_url(
+    ///     "https://docs.google.com/spreadsheets/d/e/<id>/pub?output=csv",
+    ///     &ReaderOptions::default(),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn from_url(url: &str, options: &ReaderOptions) -> Result<Self, Error> {
+        Self::from_url_with_timeout(url, options, DEFAULT_DOWNLOAD_TIMEOUT)
+    }
+
+    /// Same as [from_url](`CSVFile::from_url`), but aborts the download if it takes longer
+    /// than the given time, including the connection to the server.
+    pub fn from_url_with_timeout(
+        url: &str,
+        options: &ReaderOptions,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        let max_time = timeout.as_secs_f64().to_string();
+        let connect_timeout = timeout.min(CONNECT_TIMEOUT).as_secs_f64().to_string();
+        let mut child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--location",
+                "--proto",
+                "=http,https",
+                "--proto-redir",
+                "=http,https",
+                "--max-time",
+                &max_time,
+                "--connect-timeout",
+                &connect_timeout,
+                "--url",
+                url,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => Error::new(
+                    ErrorKind::NotFound,
+                    "The curl command is needed to download a CSV file, but it isn't installed",
+                ),
+                _ => e,
+            })?;
+
+        let body = child.stdout.take().expect("the output of curl is piped");
+        let result = CSVFile::from_reader(body, options);
+        if result.is_err() {
+            // The rest of the body won't be read, so curl would wait forever to write it.
+            let _ = child.kill();
+        }
+        let output = child.wait_with_output()?;
+        // Being killed leaves nothing on the error output,
+        // which tells a failed download apart from a failed parse.
+        let message = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && (result.is_ok() || !message.trim().is_empty()) {
+            return Err(Error::other(format!(
+                "Couldn't download {}: {}",
+                url,
+                message.trim()
+            )));
+        }
+        result
+    }
+}
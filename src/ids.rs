@@ -0,0 +1,126 @@
+//! Surrogate keys added to the files that don't have any, before importing them into a database.
+
+use std::io::Error;
+
+use crate::CSVFile;
+
+/// Generates 128 bits from the random keys of the hasher of the standard library
+/// and the current time. They aren't drawn from a proper random source,
+/// so they aren't cryptographically secure and may collide across processes.
+#[cfg(feature = "uuid")]
+fn weak_random_bits(counter: u64) -> u128 {
+    use std::hash::{BuildHasher, RandomState};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let high = RandomState::new().hash_one((counter, nanos));
+    let low = RandomState::new().hash_one((nanos, counter));
+    (u128::from(high) << 64) | u128::from(low)
+}
+
+/// Formats random bits as a UUID of version 4, 6 of the bits being replaced by the version and the variant.
+#[cfg(feature = "uuid")]
+fn format_uuid(mut bits: u128) -> String {
+    // The version (4) and the variant (RFC 4122).
+    bits = (bits & !(0xf << 76)) | (0x4 << 76);
+    bits = (bits & !(0x3 << 62)) | (0x2 << 62);
+
+    let hex = format!("{:032x}", bits);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+impl CSVFile {
+    /// Inserts a first column holding the given values,
+    /// and removes it if they are rejected by a validator.
+    fn insert_first_column(&mut self, name: &String, values: &Vec<String>) -> Result<(), Error> {
+        self.insert_column(name, 0)?;
+        let result = self.fill_column(name, values);
+        if result.is_err() {
+            self.remove_column(0)?;
+        }
+        result
+    }
+
+    /// Inserts a first column numbering the rows with sequential integers from `start`,
+    /// like an auto-incremented primary key.
+    /// It may return an error if the column already exists or if a row is rejected
+    /// by a validator, in which case the file isn't modified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("name\nThomas\nJohn\n", &ReaderOptions::default()).unwrap();
+    /// file.add_id_column(&"id".to_string(), 1).unwrap();
+    /// assert_eq!(file.columns, vec!["id", "name"]);
+    /// assert_eq!(file.rows, vec![vec!["1", "Thomas"], vec!["2", "John"]]);
+    /// ```
+    pub fn add_id_column(&mut self, name: &String, start: u64) -> Result<(), Error> {
+        let ids: Vec<String> = (0..self.rows.len() as u64)
+            .map(|i| (start + i).to_string())
+            .collect();
+        self.insert_first_column(name, &ids)
+    }
+
+    /// Inserts a first column holding a random UUID (version 4) for each row.
+    /// It may return an error if the column already exists or if a row is rejected
+    /// by a validator, in which case the file isn't modified.
+    ///
+    /// The crate has no access to a proper random source, so the UUIDs are derived
+    /// from the current time and the random keys of the hasher of the standard library.
+    /// They are practically distinct within a file, but aren't guaranteed to be unique across files
+    /// or processes, and they mustn't be used as secrets. Use
+    /// [add_uuid_column_with](`CSVFile::add_uuid_column_with`) with a real random
+    /// number generator for the keys that must be unique across several files.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("name\nThomas\nJohn\n", &ReaderOptions::default()).unwrap();
+    /// file.add_uuid_column(&"id".to_string()).unwrap();
+    /// assert_eq!(file.rows[0][0].len(), 36);
+    /// assert_ne!(file.rows[0][0], file.rows[1][0]);
+    /// ```
+    #[cfg(feature = "uuid")]
+    pub fn add_uuid_column(&mut self, name: &String) -> Result<(), Error> {
+        let mut counter = 0;
+        self.add_uuid_column_with(name, || {
+            counter += 1;
+            weak_random_bits(counter)
+        })
+    }
+
+    /// Same as [add_uuid_column](`CSVFile::add_uuid_column`), each UUID being made
+    /// of the 128 bits returned by `random`, such as those of a cryptographically secure
+    /// random number generator. The bits of the version and of the variant are overwritten.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("name\nThomas\n", &ReaderOptions::default()).unwrap();
+    /// // A real generator would be used, such as `rand::random::<u128>`.
+    /// file.add_uuid_column_with(&"id".to_string(), || u128::MAX).unwrap();
+    /// assert_eq!(file.rows[0][0], "ffffffff-ffff-4fff-bfff-ffffffffffff");
+    /// ```
+    #[cfg(feature = "uuid")]
+    pub fn add_uuid_column_with<F>(&mut self, name: &String, mut random: F) -> Result<(), Error>
+    where
+        F: FnMut() -> u128,
+    {
+        let ids: Vec<String> = (0..self.rows.len())
+            .map(|_| format_uuid(random()))
+            .collect();
+        self.insert_first_column(name, &ids)
+    }
+}
@@ -0,0 +1,306 @@
+//! A parser fed with chunks of bytes, for the sources that aren't readers,
+//! such as network streams.
+
+use std::io::{Error, ErrorKind};
+
+use crate::parser::{self, Escape, LineOptions};
+use crate::{Position, Record};
+
+/// Parses CSV records from chunks of bytes of any size, as they arrive.
+/// A chunk may end anywhere, in the middle of a line, of a quoted value
+/// or of a UTF-8 character: what can't be parsed yet is kept until the next chunk.
+///
+/// Every record is given, the header included, along with its
+/// [position](`Record::position`) in the whole stream.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::Parser;
+/// # use csv_tools::parser::LineOptions;
+/// let mut parser = Parser::new(LineOptions::default());
+///
+/// let records = parser.feed(b"name,bio\nThomas,\"Likes").unwrap();
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].fields(), &vec!["name", "bio"]);
+///
+/// let records = parser.feed(b"\nRust\"\nJohn,").unwrap();
+/// assert_eq!(records[0].fields(), &vec!["Thomas", "Likes\nRust"]);
+/// assert_eq!(records[0].position().line, 2);
+///
+/// // The last line doesn't end with a line break.
+/// let last = parser.finish().unwrap().unwrap();
+/// assert_eq!(last.fields(), &vec!["John", ""]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Parser {
+    options: LineOptions,
+    max_field_size: Option<usize>,
+    max_record_size: Option<usize>,
+    /// The bytes received after the last line break.
+    pending: Vec<u8>,
+    /// The number of bytes at the end of `pending` since the last delimiter.
+    since_delimiter: usize,
+    /// The complete lines of the record being parsed, line breaks included,
+    /// when a quoted value spans several lines.
+    record: String,
+    /// The size of the first line of `record`, without its line break.
+    first_line: usize,
+    /// Where the lines of `record` end, kept from one line to the next
+    /// so that each line is only scanned once.
+    quotes: QuoteState,
+    /// The position of the first byte that doesn't belong to a parsed record.
+    position: Position,
+}
+
+/// What the tokenizers of the [parser](`crate::parser`) module know about the quotes
+/// at the end of the text scanned so far.
+#[derive(Debug, Clone, Copy, Default)]
+struct QuoteState {
+    in_quote: bool,
+    /// With backslashes, the last character is a backslash escaping the next one.
+    escaped: bool,
+    /// With backslashes, the last character closed a quoted value, so the next one is skipped.
+    skip_next: bool,
+    /// With doubled quotes, the last character is a quote inside a quoted value,
+    /// which closes it unless another quote follows.
+    quote_pending: bool,
+    /// With doubled quotes, the current field already has characters,
+    /// so that a quote doesn't start a quoted value anymore.
+    in_field: bool,
+}
+
+impl QuoteState {
+    /// Follows the text the way the tokenizer of the escaping convention does.
+    fn scan(&mut self, text: &str, options: &LineOptions) {
+        for c in text.chars() {
+            match options.escape {
+                Escape::Backslash => {
+                    if self.skip_next {
+                        self.skip_next = false;
+                    } else if c == '\\' {
+                        self.escaped = !self.escaped;
+                    } else {
+                        if c == options.quote && !self.escaped {
+                            self.skip_next = self.in_quote;
+                            self.in_quote = !self.in_quote;
+                        }
+                        self.escaped = false;
+                    }
+                }
+                Escape::DoubleQuote => {
+                    if self.quote_pending {
+                        self.quote_pending = false;
+                        if c == options.quote {
+                            continue;
+                        }
+                        self.in_quote = false;
+                    }
+                    if self.in_quote {
+                        self.quote_pending = c == options.quote;
+                    } else if c == options.delimiter {
+                        self.in_field = false;
+                    } else {
+                        self.in_quote = c == options.quote && !self.in_field;
+                        self.in_field = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether the text scanned so far ends in the middle of a quoted value,
+    /// and needs the following lines. A pending quote closes the value at the end of a line,
+    /// and a trailing escape character is an error, so the record can be parsed.
+    fn needs_more_lines(&self) -> bool {
+        self.in_quote && !self.quote_pending && !self.escaped
+    }
+}
+
+impl Parser {
+    /// Creates a parser splitting the records with the given options.
+    pub fn new(options: LineOptions) -> Self {
+        Self {
+            options,
+            max_field_size: None,
+            max_record_size: None,
+            pending: Vec::new(),
+            since_delimiter: 0,
+            record: String::new(),
+            first_line: 0,
+            quotes: QuoteState::default(),
+            position: Position { line: 1, byte: 0 },
+        }
+    }
+
+    /// Sets the maximum size of a value, in bytes, as [ReaderOptions::max_field_size](`crate::ReaderOptions::max_field_size`)
+    /// does for the readers. Feeding a longer value is an error, raised as soon as the value
+    /// is certainly too long, without waiting for the end of its line.
+    pub fn with_max_field_size(mut self, max: usize) -> Self {
+        self.max_field_size = Some(max);
+        self
+    }
+
+    /// Sets the maximum size of a record, in bytes and without its final line break,
+    /// as [ReaderOptions::max_record_size](`crate::ReaderOptions::max_record_size`) does for the readers.
+    /// Feeding a longer record is an error, raised as soon as the bytes received exceed it.
+    pub fn with_max_record_size(mut self, max: usize) -> Self {
+        self.max_record_size = Some(max);
+        self
+    }
+
+    /// Parses a chunk of bytes, and returns the records it completes.
+    ///
+    /// It may return an error if a complete line isn't valid UTF-8, if a record can't be parsed,
+    /// or if a record or a value is longer than the maximum, in which case the parser shouldn't be fed anymore.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Record>, Error> {
+        let start = self.pending.len();
+        self.pending.extend_from_slice(chunk);
+        // Only the new bytes are searched, the previous ones don't have any line break.
+        let Some(last_line_break) = chunk.iter().rposition(|b| *b == b'\n') else {
+            self.check_pending(start)?;
+            return Ok(Vec::new());
+        };
+        // A line break can't be part of a multi-byte character, so the lines are valid on their own.
+        let lines: Vec<u8> = self.pending.drain(..=start + last_line_break).collect();
+        let lines =
+            std::str::from_utf8(&lines).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        let mut records = Vec::new();
+        for line in lines.split_inclusive('\n') {
+            if let Some(record) = self.push_line(line)? {
+                records.push(record);
+            }
+        }
+        self.since_delimiter = 0;
+        self.check_pending(0)?;
+        Ok(records)
+    }
+
+    /// Parses what remains once the source has ended, which is the last record
+    /// if it doesn't end with a line break, or `None` if there's nothing left.
+    ///
+    /// It may return an error if the remaining bytes aren't valid UTF-8
+    /// or if the last record can't be parsed, for example because a quote isn't closed.
+    pub fn finish(&mut self) -> Result<Option<Record>, Error> {
+        let rest = std::mem::take(&mut self.pending);
+        let rest = String::from_utf8(rest).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        self.record.push_str(&rest);
+        if self.record.is_empty() {
+            return Ok(None);
+        }
+        let size = self.record.trim_end_matches(['\r', '\n']).len();
+        if let Some(max) = self.max_record_size.filter(|max| size > *max) {
+            return Err(self.record_error(max));
+        }
+        self.parse_record().map(Some)
+    }
+
+    /// Adds a complete line to the record, and parses the record
+    /// unless the line ends in the middle of a quoted value.
+    fn push_line(&mut self, line: &str) -> Result<Option<Record>, Error> {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        self.quotes.scan(content, &self.options);
+        let is_first_line = self.record.is_empty();
+        self.record.push_str(line);
+        if is_first_line {
+            self.first_line = content.len();
+        }
+
+        let size = self.record.len() - (line.len() - content.len());
+        if let Some(max) = self.max_record_size.filter(|max| size > *max) {
+            return Err(self.record_error(max));
+        }
+        if !self.quotes.needs_more_lines() {
+            return self.parse_record().map(Some);
+        }
+        // The following lines all belong to the quoted value that isn't closed.
+        if let Some(max) = self
+            .max_field_size
+            .filter(|max| !is_first_line && size - self.first_line > *max)
+        {
+            return Err(self.field_error(max));
+        }
+        Ok(None)
+    }
+
+    /// Checks the size of the bytes received after the last line break,
+    /// the ones from `start` not being scanned yet.
+    fn check_pending(&mut self, start: usize) -> Result<(), Error> {
+        let pending = self.pending.strip_suffix(b"\r").unwrap_or(&self.pending);
+        if let Some(max) = self.max_record_size {
+            let content = self.record.len() + pending.len();
+            if content > max {
+                return Err(self.record_error(max));
+            }
+        }
+        if let Some(max) = self.max_field_size {
+            // Once unescaped, a value is at least half as long as in the source, without its two quotes.
+            let mut delimiter = [0; 4];
+            let delimiter = self
+                .options
+                .delimiter
+                .encode_utf8(&mut delimiter)
+                .as_bytes();
+            for end in start + 1..=self.pending.len() {
+                if self.pending[..end].ends_with(delimiter) {
+                    self.since_delimiter = 0;
+                } else {
+                    self.since_delimiter += 1;
+                }
+            }
+            let unclosed_value = if self.record.is_empty() {
+                0
+            } else {
+                self.record.len() - self.first_line + self.pending.len()
+            };
+            if self.since_delimiter > 2 * max + 2 + delimiter.len() || unclosed_value > max {
+                return Err(self.field_error(max));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the record made of the lines received so far.
+    fn parse_record(&mut self) -> Result<Record, Error> {
+        let content = self.record.strip_suffix('\n').unwrap_or(&self.record);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        let fields = parser::parse_line_with_options(content, &self.options)?;
+        if let Some(max) = self
+            .max_field_size
+            .filter(|max| fields.iter().any(|field| field.len() > *max))
+        {
+            return Err(self.field_error(max));
+        }
+        let record = Record::new(
+            fields.into_iter().map(|field| field.into_owned()).collect(),
+            self.position,
+        );
+        self.position.line += self.record.matches('\n').count();
+        self.position.byte += self.record.len() as u64;
+        self.record.clear();
+        self.quotes = QuoteState::default();
+        Ok(record)
+    }
+
+    fn record_error(&self, max: usize) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "The record at line {} is longer than the maximum of {} bytes",
+                self.position.line, max
+            ),
+        )
+    }
+
+    fn field_error(&self, max: usize) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "A value of the record at line {} is longer than the maximum of {} bytes",
+                self.position.line, max
+            ),
+        )
+    }
+}
@@ -0,0 +1,295 @@
+//! Decompression of the DEFLATE format (RFC 1951), the compression of most zip archives.
+
+use std::io::{Error, ErrorKind};
+
+/// The base lengths of the length codes 257 to 285, and their number of extra bits.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The base distances of the distance codes 0 to 29, and their number of extra bits.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order in which the lengths of the code length alphabet are given.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const MAX_BITS: usize = 15;
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Invalid compressed data: {}", message),
+    )
+}
+
+fn too_large(max_size: usize) -> Error {
+    invalid_data(&format!(
+        "the data is larger than the {} bytes expected",
+        max_size
+    ))
+}
+
+/// Reads the bits of the compressed data, starting with the least significant bit of each byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, Error> {
+        while self.count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| invalid_data("unexpected end of the data"))?;
+            self.position += 1;
+            self.buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Skips the remaining bits of the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code, given by the number of codes of each length
+/// and the symbols sorted by code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the length of the code of each symbol (0 if the symbol isn't used).
+    fn new(lengths: &[u8]) -> Result<Self, Error> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        // makes sure the lengths don't describe more codes than there are
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(invalid_data("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, Error> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for length in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid_data("invalid Huffman code"))
+    }
+}
+
+/// Decompresses raw DEFLATE data, as found in the entries of zip archives.
+/// It may return an error if the data is invalid or truncated, or as soon as it decompresses
+/// to more than `max_size` bytes, so that a tiny archive can't fill the memory.
+pub(crate) fn inflate(data: &[u8], max_size: usize) -> Result<Vec<u8>, Error> {
+    let mut reader = BitReader::new(data);
+    let mut output: Vec<u8> = Vec::with_capacity(max_size.min(data.len() * 4));
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(&mut reader, &mut output, max_size)?,
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                inflate_block(&mut reader, &mut output, max_size, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, max_size, &literals, &distances)?;
+            }
+            _ => return Err(invalid_data("invalid block type")),
+        }
+        if is_last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Copies a block that isn't compressed.
+fn inflate_stored(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    max_size: usize,
+) -> Result<(), Error> {
+    reader.align();
+    let header = reader
+        .data
+        .get(reader.position..reader.position + 4)
+        .ok_or_else(|| invalid_data("unexpected end of the data"))?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let complement = u16::from_le_bytes([header[2], header[3]]);
+    if length != !complement {
+        return Err(invalid_data("invalid length of a stored block"));
+    }
+    let start = reader.position + 4;
+    let block = reader
+        .data
+        .get(start..start + length as usize)
+        .ok_or_else(|| invalid_data("unexpected end of the data"))?;
+    if output.len() + block.len() > max_size {
+        return Err(too_large(max_size));
+    }
+    output.extend_from_slice(block);
+    reader.position = start + length as usize;
+    Ok(())
+}
+
+/// The codes of the blocks compressed with the fixed Huffman codes.
+fn fixed_codes() -> Result<(Huffman, Huffman), Error> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+/// Reads the codes of a block compressed with dynamic Huffman codes.
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), Error> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(invalid_data("too many codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths: Vec<u8> = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_lengths.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| invalid_data("repeated length without a previous length"))?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if lengths.len() + repeat > literal_count + distance_count {
+            return Err(invalid_data("too many lengths"));
+        }
+        lengths.extend(std::iter::repeat_n(value, repeat));
+    }
+    if lengths[256] == 0 {
+        return Err(invalid_data("missing end-of-block code"));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+/// Decodes the symbols of a compressed block until its end.
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    max_size: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        if symbol < 256 {
+            if output.len() == max_size {
+                return Err(too_large(max_size));
+            }
+            output.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(invalid_data("invalid length code"));
+        }
+        let length =
+            LENGTH_BASE[symbol] as usize + reader.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+        let symbol = distances.decode(reader)? as usize;
+        if symbol >= DISTANCE_BASE.len() {
+            return Err(invalid_data("invalid distance code"));
+        }
+        let distance =
+            DISTANCE_BASE[symbol] as usize + reader.bits(DISTANCE_EXTRA[symbol] as u32)? as usize;
+        if distance > output.len() {
+            return Err(invalid_data("distance too far back"));
+        }
+        if output.len() + length > max_size {
+            return Err(too_large(max_size));
+        }
+        // The copied bytes may overlap the bytes being written.
+        let start = output.len() - distance;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! An interning mode where the repeated values of a CSV file share the same storage.
+//!
+//! Columns holding a handful of distinct values (country codes, statuses...)
+//! repeated on every row take a fraction of the memory once interned.
+
+use std::collections::HashSet;
+use std::io::{BufRead, Error, ErrorKind};
+use std::sync::Arc;
+
+use crate::{CSVCoords, CSVFile, CSVReader};
+
+/// Keeps a single copy of each distinct value it is given.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    values: HashSet<Arc<str>>,
+    stats: InternStats,
+}
+
+/// Measures how much memory interning saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InternStats {
+    /// The number of values that were interned, repeated ones included.
+    pub values: usize,
+    /// The number of distinct values actually stored.
+    pub distinct: usize,
+    /// The size in bytes of all the values, as if each of them had its own storage.
+    pub total_bytes: usize,
+    /// The size in bytes of the distinct values.
+    pub stored_bytes: usize,
+}
+
+impl InternStats {
+    /// Returns the number of bytes that didn't need to be allocated.
+    pub fn saved_bytes(&self) -> usize {
+        self.total_bytes - self.stored_bytes
+    }
+
+    /// Returns how many times smaller the stored values are compared to
+    /// the values without interning (`1.0` when nothing was saved).
+    pub fn compression_ratio(&self) -> f64 {
+        if self.stored_bytes == 0 {
+            1.0
+        } else {
+            self.total_bytes as f64 / self.stored_bytes as f64
+        }
+    }
+}
+
+impl Interner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of a value, storing it first if it's the first time it's seen.
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        self.stats.values += 1;
+        self.stats.total_bytes += value.len();
+        if let Some(existing) = self.values.get(value) {
+            return Arc::clone(existing);
+        }
+        let value: Arc<str> = Arc::from(value);
+        self.stats.distinct += 1;
+        self.stats.stored_bytes += value.len();
+        self.values.insert(Arc::clone(&value));
+        value
+    }
+
+    /// Returns the savings made so far.
+    pub fn stats(&self) -> InternStats {
+        self.stats
+    }
+}
+
+/// A CSV file whose repeated values share the same storage.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVReader, InternedCSVFile, ReaderOptions};
+/// let text = "name,country\nThomas,FR\nYoshiip,FR\nAlice,FR\n";
+/// let reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+/// let file = InternedCSVFile::from_csv_reader(reader).unwrap();
+///
+/// let stats = file.intern_stats();
+/// assert_eq!(stats.values, 6);
+/// assert_eq!(stats.distinct, 4);
+/// assert_eq!(stats.saved_bytes(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct InternedCSVFile {
+    pub delimiter: char,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Arc<str>>>,
+    interner: Interner,
+}
+
+impl InternedCSVFile {
+    /// Reads the remaining rows of a reader, interning the values as they are read
+    /// so that the repeated values are never held twice in memory.
+    pub fn from_csv_reader<R: BufRead>(mut reader: CSVReader<R>) -> Result<Self, Error> {
+        let mut interner = Interner::new();
+        let mut rows = Vec::new();
+        for row in &mut reader {
+            rows.push(row?.iter().map(|value| interner.intern(value)).collect());
+        }
+        Ok(Self {
+            delimiter: reader.delimiter(),
+            columns: reader.into_columns(),
+            rows,
+            interner,
+        })
+    }
+
+    /// Interns the values of a CSV file already held in memory.
+    pub fn from_csv_file(file: &CSVFile) -> Self {
+        let mut interner = Interner::new();
+        let rows = file
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|value| interner.intern(value)).collect())
+            .collect();
+        Self {
+            delimiter: file.delimiter,
+            columns: file.columns.clone(),
+            rows,
+            interner,
+        }
+    }
+
+    /// Converts back to a regular CSV file, where each value has its own storage.
+    pub fn to_csv_file(&self) -> CSVFile {
+        CSVFile::from_rows(
+            self.delimiter,
+            self.columns.clone(),
+            self.rows
+                .iter()
+                .map(|row| row.iter().map(|value| value.to_string()).collect())
+                .collect(),
+        )
+    }
+
+    /// Returns the number of columns.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Gets the value at the given coordinates.
+    pub fn get_cell(&self, coordinates: &CSVCoords) -> Option<&str> {
+        self.rows
+            .get(coordinates.row)?
+            .get(coordinates.column)
+            .map(|value| value.as_ref())
+    }
+
+    /// Interns a new row and appends it.
+    /// It may return an error if the number of fields
+    /// in the row is different from the number of columns.
+    pub fn add_row(&mut self, data: &Vec<String>) -> Result<(), Error> {
+        if data.len() != self.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid number of fields, {} were given, but expected {}",
+                    data.len(),
+                    self.len()
+                ),
+            ));
+        }
+
+        let row = data
+            .iter()
+            .map(|value| self.interner.intern(value))
+            .collect();
+        self.rows.push(row);
+
+        Ok(())
+    }
+
+    /// Returns how much memory interning saved.
+    pub fn intern_stats(&self) -> InternStats {
+        self.interner.stats()
+    }
+}
@@ -0,0 +1,165 @@
+//! Joins of two files on disk that don't fit in memory,
+//! by sorting both of them by key and merging them.
+
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+
+use crate::external::{sort_key, write_record, TempFiles};
+use crate::{CSVFile, CSVReader, ExternalSortOptions};
+
+/// Which rows are kept by [join_files](`CSVFile::join_files`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only the rows of the left file matching rows of the right file (the default).
+    #[default]
+    Inner,
+    /// All the rows of the left file, with empty values when they don't match any row.
+    Left,
+}
+
+/// Returns the index of a column, or an error if it doesn't exist.
+fn column_idx(columns: &[String], column_name: &String) -> Result<usize, Error> {
+    columns
+        .iter()
+        .position(|c| c == column_name)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })
+}
+
+impl CSVFile {
+    /// Joins two CSV files on disk by the values of a key column and writes the result
+    /// to a third file, without holding the files in memory: both files are sorted with
+    /// [sort_file](`CSVFile::sort_file`) in temporary files, which are then read side by side.
+    ///
+    /// The output has the columns of the left file followed by the ones of the right file,
+    /// except its key column. Its rows are sorted by key, and each row of the left file is
+    /// repeated for every matching row of the right file, in their original order.
+    /// Only the rows of the right file sharing a single key are held in memory at once.
+    /// The values missing from the ragged rows of either file are written as empty values.
+    ///
+    /// The keys are compared according to the options, so with `numeric` set,
+    /// `"1"` and `"1.0"` match. The temporary files are removed in every case.
+    ///
+    /// It may return an error if a file can't be read or written, if a file can't be parsed,
+    /// if the key column is missing from one of the files, if a row is too short to have a key,
+    /// or if both files have another column with the same name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ExternalSortOptions, JoinKind};
+    /// # let directory = std::env::temp_dir();
+    /// # let path = |name: &str| directory.join(name).to_string_lossy().to_string();
+    /// # let (orders, customers, output) = (path("csv_tools_join_orders.csv"), path("csv_tools_join_customers.csv"), path("csv_tools_join_output.csv"));
+    /// std::fs::write(&orders, "order,customer\n1,c2\n2,c9\n3,c1\n4,c2\n").unwrap();
+    /// std::fs::write(&customers, "customer,name\nc1,Thomas\nc2,Yoshiip\n").unwrap();
+    ///
+    /// let key = "customer".to_string();
+    /// let options = ExternalSortOptions::default();
+    /// CSVFile::join_files(&orders, &customers, &output, &key, JoinKind::Inner, &options).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&output).unwrap(),
+    ///     "order,customer,name\n3,c1,Thomas\n1,c2,Yoshiip\n4,c2,Yoshiip\n"
+    /// );
+    ///
+    /// CSVFile::join_files(&orders, &customers, &output, &key, JoinKind::Left, &options).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&output).unwrap(),
+    ///     "order,customer,name\n3,c1,Thomas\n1,c2,Yoshiip\n4,c2,Yoshiip\n2,c9,\n"
+    /// );
+    /// # for file in [orders, customers, output] { std::fs::remove_file(file).unwrap(); }
+    /// ```
+    pub fn join_files(
+        left: &String,
+        right: &String,
+        output: &String,
+        key_column: &String,
+        kind: JoinKind,
+        options: &ExternalSortOptions,
+    ) -> Result<(), Error> {
+        let mut temp_files = TempFiles::new(options.temp_dir.as_ref(), "join");
+        let mut sorted = Vec::with_capacity(2);
+        for input in [left, right] {
+            let (path, _) = temp_files.create()?;
+            let path = path.to_string_lossy().to_string();
+            CSVFile::sort_file(input, &path, key_column, options)?;
+            sorted.push(CSVReader::open(&path, &options.reader_options())?);
+        }
+        let mut right_reader = sorted.pop().unwrap();
+        let mut left_reader = sorted.pop().unwrap();
+
+        let left_key = column_idx(left_reader.columns(), key_column)?;
+        let right_key = column_idx(right_reader.columns(), key_column)?;
+        let copied: Vec<usize> = (0..right_reader.columns().len())
+            .filter(|idx| *idx != right_key)
+            .collect();
+        let mut columns = left_reader.columns().clone();
+        for idx in &copied {
+            let column = &right_reader.columns()[*idx];
+            if columns.contains(column) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} already exists", column),
+                ));
+            }
+            columns.push(column.clone());
+        }
+
+        let mut writer = BufWriter::new(File::create(output)?);
+        write_record(&mut writer, &columns, options.delimiter, options.escape)?;
+
+        // The rows of the right file sharing the key of the last left row.
+        let mut group: Vec<Vec<String>> = Vec::new();
+        let mut group_key: Option<String> = None;
+        let mut next_right = right_reader.next().transpose()?;
+        let left_width = left_reader.columns().len();
+        while let Some(mut row) = left_reader.next().transpose()? {
+            // The short rows are completed so that the copied values land in their columns.
+            if row.len() < left_width {
+                row.resize(left_width, String::new());
+            }
+            let key = sort_key(&row, left_key);
+            let same_group = group_key
+                .as_ref()
+                .is_some_and(|group_key| options.compare(key, group_key).is_eq());
+            if !same_group {
+                group.clear();
+                while let Some(right_row) = next_right.take() {
+                    let right_row_key = sort_key(&right_row, right_key);
+                    if options.compare(right_row_key, key).is_gt() {
+                        next_right = Some(right_row);
+                        break;
+                    }
+                    if options.compare(right_row_key, key).is_eq() {
+                        group.push(right_row);
+                    }
+                    next_right = right_reader.next().transpose()?;
+                }
+                group_key = Some(key.to_string());
+            }
+
+            let width = row.len();
+            if group.is_empty() {
+                if kind == JoinKind::Left {
+                    row.resize(width + copied.len(), String::new());
+                    write_record(&mut writer, &row, options.delimiter, options.escape)?;
+                }
+                continue;
+            }
+            for right_row in &group {
+                row.truncate(width);
+                row.extend(
+                    copied
+                        .iter()
+                        .map(|idx| right_row.get(*idx).cloned().unwrap_or_default()),
+                );
+                write_record(&mut writer, &row, options.delimiter, options.escape)?;
+            }
+        }
+        writer.flush()
+    }
+}
@@ -27,34 +27,384 @@
 //!
 //! Escape characters are allowed, meaning that a string can contain `\"`.
 
-use std::collections::{HashMap, HashSet};
+// The public API consistently takes `&String` and `&Vec<_>` arguments.
+#![allow(clippy::ptr_arg)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod parser;
+
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(feature = "std")]
 use std::fmt;
+#[cfg(feature = "fs")]
 use std::fs::File;
+#[cfg(feature = "std")]
+use std::hash::{DefaultHasher, Hash, Hasher};
+#[cfg(feature = "fs")]
+use std::io::BufWriter;
+#[cfg(feature = "std")]
 use std::io::Error;
+#[cfg(feature = "std")]
 use std::io::ErrorKind;
+#[cfg(feature = "std")]
 use std::io::Write;
-use std::io::{BufRead, BufReader};
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Read};
+
+#[cfg(feature = "std")]
+mod aliases;
+#[cfg(feature = "fs")]
+mod append;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "fs")]
+mod atomic;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod cancellation;
+#[cfg(feature = "std")]
+mod checkpoint;
+#[cfg(feature = "std")]
+mod coercion;
+#[cfg(feature = "fs")]
+mod convert;
+#[cfg(feature = "std")]
+mod datapackage;
+#[cfg(feature = "std")]
+mod defaults;
+#[cfg(feature = "std")]
+mod dialect;
+#[cfg(feature = "std")]
+mod escaping;
+#[cfg(feature = "std")]
+mod events;
+#[cfg(feature = "fs")]
+mod external;
+#[cfg(feature = "std")]
+mod fingerprint;
+#[cfg(feature = "std")]
+pub mod fixed_width;
+#[cfg(feature = "fs")]
+mod follow;
+#[cfg(feature = "std")]
+mod format;
+#[cfg(feature = "std")]
+mod frequency;
+#[cfg(feature = "std")]
+mod fuzzy;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "std")]
+mod ids;
+#[cfg(feature = "std")]
+mod incremental;
+#[cfg(feature = "zip")]
+mod inflate;
+#[cfg(feature = "std")]
+mod intern;
+#[cfg(feature = "fs")]
+mod join;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "std")]
+mod lint;
+#[cfg(feature = "fs")]
+mod locking;
+#[cfg(feature = "std")]
+mod lookup;
+#[cfg(feature = "fs")]
+mod manifest;
+#[cfg(feature = "std")]
+mod mapping;
+#[cfg(feature = "std")]
+mod mask;
+#[cfg(feature = "std")]
+mod metadata;
+#[cfg(feature = "fs")]
+mod normalize;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "std")]
+mod order;
+#[cfg(feature = "std")]
+mod outliers;
+#[cfg(feature = "std")]
+mod paste;
+#[cfg(feature = "polars")]
+mod polars;
+#[cfg(feature = "fs")]
+mod probe;
+#[cfg(feature = "std")]
+mod progress;
+#[cfg(feature = "std")]
+mod query;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+mod records;
+#[cfg(feature = "std")]
+mod reference;
+#[cfg(feature = "std")]
+mod repair;
+#[cfg(feature = "std")]
+mod reshape;
+#[cfg(feature = "std")]
+mod sanitize;
+#[cfg(feature = "std")]
+mod schema;
+#[cfg(feature = "select")]
+mod select;
+#[cfg(feature = "std")]
+mod sets;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "fs")]
+mod streaming;
+#[cfg(feature = "std")]
+mod table;
+#[cfg(feature = "std")]
+mod template;
+#[cfg(feature = "std")]
+mod timeseries;
+#[cfg(feature = "std")]
+mod units;
+#[cfg(feature = "std")]
+mod updates;
+#[cfg(feature = "std")]
+mod validation;
+#[cfg(feature = "std")]
+mod validator;
+#[cfg(feature = "std")]
+mod value;
+#[cfg(feature = "std")]
+mod workbook;
+#[cfg(feature = "zip")]
+mod zip;
+
+#[cfg(feature = "std")]
+use events::ChangeObserver;
+#[cfg(feature = "std")]
+use format::escape_formula;
+#[cfg(feature = "std")]
+use parser::{needs_quotes_with_escape, quote_field_with_escape};
+
+#[cfg(feature = "std")]
+pub use builder::RowBuilder;
+#[cfg(feature = "std")]
+pub use cancellation::{CancellationToken, Cancelled};
+#[cfg(feature = "std")]
+pub use checkpoint::Checkpoint;
+#[cfg(feature = "std")]
+pub use coercion::{CoercionFailure, CoercionReport};
+#[cfg(feature = "fs")]
+pub use convert::convert;
+#[cfg(feature = "std")]
+pub use dialect::Dialect;
+#[cfg(feature = "std")]
+pub use escaping::{escape_field, unescape_field};
+#[cfg(feature = "std")]
+pub use events::ChangeEvent;
+#[cfg(feature = "fs")]
+pub use external::ExternalSortOptions;
+#[cfg(feature = "std")]
+pub use fingerprint::HashAlgorithm;
+#[cfg(feature = "fs")]
+pub use follow::CSVFollower;
+#[cfg(feature = "std")]
+pub use format::{Alignment, ColumnFormat, DateFormat, Padding};
+#[cfg(feature = "std")]
+pub use fuzzy::SimilarCell;
+#[cfg(feature = "http")]
+pub use http::DEFAULT_DOWNLOAD_TIMEOUT;
+#[cfg(feature = "std")]
+pub use incremental::Parser;
+#[cfg(feature = "std")]
+pub use intern::{InternStats, InternedCSVFile, Interner};
+#[cfg(feature = "fs")]
+pub use join::JoinKind;
+#[cfg(feature = "std")]
+pub use lint::{LintOptions, LintRule, LintWarning, Severity};
+#[cfg(feature = "fs")]
+pub use locking::FileLock;
+#[cfg(feature = "std")]
+pub use lookup::{CellDifference, ColumnComparison, DifferenceKind};
+#[cfg(feature = "fs")]
+pub use manifest::ManifestReport;
+#[cfg(feature = "std")]
+pub use mapping::{MappingReport, MissPolicy};
+#[cfg(feature = "std")]
+pub use mask::MaskKind;
+#[cfg(feature = "fs")]
+pub use normalize::{normalize_file, NormalizeOptions, NormalizeReport, SourceEncoding};
+#[cfg(feature = "std")]
+pub use options::{ColumnPredicate, LineEnding, QuoteStyle, ReaderOptions, Trim, WriterOptions};
+#[cfg(feature = "std")]
+pub use outliers::{Outlier, OutlierMethod};
+pub use parser::Escape;
+#[cfg(feature = "std")]
+pub use progress::ProgressHook;
+#[cfg(feature = "std")]
+pub use query::Query;
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+pub use reader::system_clock;
+#[cfg(feature = "std")]
+pub use reader::{CSVReader, Position, ReadStats, Record, Records};
+#[cfg(feature = "std")]
+pub use records::JsonOptions;
+#[cfg(feature = "std")]
+pub use repair::{Repair, RepairKind, RepairOptions, RepairReport};
+#[cfg(feature = "std")]
+pub use reshape::ConformPolicy;
+#[cfg(feature = "std")]
+pub use sanitize::{
+    SanitizeAction, SanitizeIssue, SanitizeIssueKind, SanitizeOptions, SanitizeReport,
+};
+#[cfg(feature = "std")]
+pub use schema::{CSVSchema, ColumnType, SchemaColumn, SchemaPolicy};
+#[cfg(feature = "std")]
+pub use snapshot::CSVSnapshot;
+#[cfg(feature = "std")]
+pub use sql::{SqlDialect, DEFAULT_SQL_BATCH_SIZE};
+#[cfg(feature = "std")]
+pub use stats::{ColumnStats, HistogramBucket};
+#[cfg(feature = "std")]
+pub use table::CSVTable;
+#[cfg(feature = "std")]
+pub use timeseries::{format_timestamp, parse_timestamp, Aggregation, Interval};
+#[cfg(feature = "std")]
+pub use validation::{DuplicateColumn, RaggedRow, ValidationReport};
+#[cfg(feature = "std")]
+pub use validator::Validator;
+#[cfg(feature = "std")]
+pub use value::CsvValue;
+#[cfg(feature = "std")]
+pub use workbook::CSVWorkbook;
+
+/// The number of rows read before estimating the total number of rows
+/// of a source to allocate them all at once.
+#[cfg(feature = "std")]
+const CAPACITY_SAMPLE_ROWS: usize = 64;
 
 /// A simple data structure for holding the raw string data of a CSV file.
+#[cfg(feature = "std")]
+#[derive(Clone)]
 pub struct CSVFile {
     pub delimiter: char,
     pub columns: Vec<String>,
     pub rows: Vec<Vec<String>>,
+    /// What is remembered about the source file to write it back the same way.
+    source: SourceDetails,
+    /// The metadata attached to the columns, indexed by column name.
+    metadata: HashMap<String, BTreeMap<String, String>>,
+    /// The rules checked when rows are added or cells are modified.
+    validators: Vec<Validator>,
+    /// The callbacks notified of the modifications.
+    observers: Vec<ChangeObserver>,
+    /// The alternative names of the columns, indexed by canonical name.
+    aliases: HashMap<String, Vec<String>>,
+    /// The values given to the columns missing from the rows added by name, indexed by column name.
+    defaults: HashMap<String, String>,
+}
+
+/// The details about the way a CSV file was written in its source,
+/// which are lost once the values are parsed.
+#[cfg(feature = "std")]
+#[derive(Default, Clone)]
+struct SourceDetails {
+    /// The cells that were surrounded by double quotes in the source file,
+    /// only filled when reading with [ReaderOptions::preserve_quotes](`ReaderOptions::preserve_quotes`).
+    quoted_cells: HashSet<CSVCoords>,
+    /// Same as `quoted_cells`, but for the header.
+    quoted_columns: HashSet<usize>,
+    /// The header line as it was read from the source.
+    header_line: Option<String>,
+    /// The header line written back as long as the columns don't change, kept when reading
+    /// with [ReaderOptions::lossless](`ReaderOptions::lossless`) or given to
+    /// [CSVFile::set_header_from_line](`CSVFile::set_header_from_line`).
+    raw_header: Option<RawLine>,
+    /// The original line of each row, aligned with the rows,
+    /// only kept when reading with [ReaderOptions::lossless](`ReaderOptions::lossless`).
+    raw_lines: Vec<Option<RawLine>>,
+    /// How the double quotes were escaped in the source.
+    escape: Escape,
+    /// The performance counters of the reading, if the file was read from a source.
+    read_stats: Option<ReadStats>,
+    /// The line break that ended the last line of the source, empty if it had none,
+    /// or `None` if the file wasn't read from a source.
+    final_line_break: Option<&'static str>,
+    /// The line break that ended the header in the source, used by default for all the lines.
+    line_ending: Option<LineEnding>,
+    /// The source started with a byte order mark, which is written back by default.
+    byte_order_mark: bool,
+}
+
+/// A line as it was written in the source file, along with
+/// a hash of the values it was parsed into. As long as the values
+/// produce the same hash, the line can be written back as-is.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+struct RawLine {
+    line: String,
+    hash: u64,
+}
+
+#[cfg(feature = "std")]
+impl RawLine {
+    fn new(line: String, fields: &Vec<String>, delimiter: &char) -> Self {
+        Self {
+            line,
+            hash: hash_fields(fields, delimiter),
+        }
+    }
+
+    /// Returns the raw line if the given values are still the ones it was parsed into.
+    fn get(&self, fields: &Vec<String>, delimiter: &char) -> Option<&String> {
+        if hash_fields(fields, delimiter) == self.hash {
+            Some(&self.line)
+        } else {
+            None
+        }
+    }
+}
+
+/// Hashes the values of a line along with the delimiter that separates them.
+#[cfg(feature = "std")]
+fn hash_fields(fields: &Vec<String>, delimiter: &char) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    delimiter.hash(&mut hasher);
+    fields.hash(&mut hasher);
+    hasher.finish()
 }
 
 /// A simple data structure for identifying the position of a cell within a CSV file.
-#[derive(PartialEq)]
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub struct CSVCoords {
     pub row: usize,
     pub column: usize,
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for CSVCoords {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {})", self.row, self.column)
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Debug for CSVCoords {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -65,29 +415,18 @@ impl fmt::Debug for CSVCoords {
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for CSVFile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut result = String::new();
-        for column in &self.columns {
-            result.push_str(column);
-            result.push(self.delimiter);
-        }
-        result.pop(); // removes the trailing delimiter
-        result.push('\n');
-
-        for row in &self.rows {
-            for field in row {
-                result.push_str(field);
-                result.push(self.delimiter);
-            }
-            result.pop();
-            result.push('\n');
-        }
-
-        write!(f, "{}", result)
+        write!(
+            f,
+            "{}",
+            self.to_string_with_options(&WriterOptions::default())
+        )
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Debug for CSVFile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -98,22 +437,169 @@ impl fmt::Debug for CSVFile {
     }
 }
 
+#[cfg(feature = "std")]
 impl CSVFile {
     /// Creates a new CSVFile from a file name and an optional delimiter (a comma by default).
     /// It reads the first line of the file to get the columns and the rest of the file to get the data.
     /// It may return an error if the file doesn't exist or if it can't be read properly.
+    #[cfg(feature = "fs")]
     pub fn new(file_name: &String, delimiter: &char) -> Result<Self, Error> {
-        let file = File::open(&file_name)?;
-        let mut lines = BufReader::new(&file).lines();
-        let first_line = lines.next().unwrap()?;
-        let columns = read_columns(&first_line, delimiter)?;
-        let rows = read_rows(&mut lines, delimiter, columns.len())?;
-
-        Ok(Self {
-            delimiter: *delimiter,
-            columns,
-            rows,
-        })
+        Self::new_with_options(file_name, &ReaderOptions::with_delimiter(delimiter))
+    }
+
+    /// Creates a new CSVFile from a file name and the options describing how to read it.
+    /// It may return an error if the file doesn't exist or if it can't be read properly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions, Trim};
+    /// # std::fs::write("trim_example.csv", "a , b\n 1,2 \n").unwrap();
+    /// let options = ReaderOptions {
+    ///     trim: Trim::All,
+    ///     ..ReaderOptions::default()
+    /// };
+    ///
+    /// let file = CSVFile::new_with_options(&"trim_example.csv".to_string(), &options).unwrap();
+    /// assert_eq!(file.columns, vec!["a", "b"]);
+    /// assert_eq!(file.rows, vec![vec!["1", "2"]]);
+    /// # std::fs::remove_file("trim_example.csv").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn new_with_options(file_name: &String, options: &ReaderOptions) -> Result<Self, Error> {
+        Self::from_csv_reader(CSVReader::open(file_name, options)?, options)
+    }
+
+    /// Creates a new CSVFile holding only some of the columns of a file, in the given order.
+    /// The values of the other columns are skipped as the file is parsed.
+    /// It may return an error if the file can't be read properly or if a column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// # std::fs::write("projection_example.csv", "a,b,c\n1,2,3\n4,5,6\n").unwrap();
+    /// let file = CSVFile::new_with_projection(&"projection_example.csv".to_string(), &["c", "a"]).unwrap();
+    /// assert_eq!(file.columns, vec!["c", "a"]);
+    /// assert_eq!(file.rows, vec![vec!["3", "1"], vec!["6", "4"]]);
+    /// # std::fs::remove_file("projection_example.csv").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn new_with_projection(file_name: &String, columns: &[&str]) -> Result<Self, Error> {
+        let options = ReaderOptions {
+            projection: Some(columns.iter().map(|c| c.to_string()).collect()),
+            ..ReaderOptions::default()
+        };
+        Self::new_with_options(file_name, &options)
+    }
+
+    /// Creates a new CSVFile from any source implementing [Read](`std::io::Read`),
+    /// such as the body of an HTTP response, parsing it as it is received.
+    /// It may return an error if the source can't be read or parsed properly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let body = "a,b\n1,2\n";
+    /// let file = CSVFile::from_reader(body.as_bytes(), &ReaderOptions::default()).unwrap();
+    /// assert_eq!(file.rows, vec![vec!["1", "2"]]);
+    /// ```
+    ///
+    /// The `http` feature adds `CSVFile::from_url`, downloading the file with `curl`.
+    /// With an HTTP client such as `reqwest`, whose blocking responses implement `Read`,
+    /// loading a published spreadsheet is a one-liner too:
+    ///
+    /// ```ignore
+    /// let response = reqwest::blocking::get("https://example.com/export.csv")?;
+    /// let file = CSVFile::from_reader(response, &ReaderOptions::default())?;
+    /// ```
+    pub fn from_reader<R: Read>(reader: R, options: &ReaderOptions) -> Result<Self, Error> {
+        Self::from_csv_reader(
+            CSVReader::from_reader(BufReader::new(reader), options)?,
+            options,
+        )
+    }
+
+    /// Creates a new CSVFile from text held in memory.
+    /// It may return an error if the text can't be parsed properly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("a;b\n1;2\n", &ReaderOptions::with_delimiter(&';')).unwrap();
+    /// assert_eq!(file.columns, vec!["a", "b"]);
+    /// assert_eq!(file.to_string(), "a;b\n1;2\n");
+    /// ```
+    pub fn from_text(text: &str, options: &ReaderOptions) -> Result<Self, Error> {
+        let reader = CSVReader::from_reader(text.as_bytes(), options)?;
+        Self::from_csv_reader(reader.with_size_hint(text.len() as u64), options)
+    }
+
+    /// Reads all the rows of a streaming reader,
+    /// keeping the details about the source the options ask for.
+    fn from_csv_reader<R: BufRead>(
+        mut reader: CSVReader<R>,
+        options: &ReaderOptions,
+    ) -> Result<Self, Error> {
+        let mut source = SourceDetails {
+            quoted_columns: reader.quoted_columns().iter().copied().collect(),
+            header_line: Some(reader.raw_header().clone()),
+            escape: options.escape,
+            line_ending: LineEnding::from_line_break(reader.header_line_break()),
+            byte_order_mark: reader.has_byte_order_mark(),
+            ..SourceDetails::default()
+        };
+        // The original lines hold the values of all the columns,
+        // so they can't be written back once some of them were skipped.
+        let lossless =
+            options.lossless && options.projection.is_none() && options.skipped_columns.is_none();
+        // A header spanning two rows is written again from the names of the columns.
+        if lossless && options.multi_header.is_none() {
+            source.raw_header = Some(RawLine::new(
+                reader.raw_header().clone(),
+                reader.columns(),
+                &options.delimiter,
+            ));
+        }
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        while let Some(record) = reader.next_record() {
+            let record = record?;
+            for column in &record.quoted {
+                source.quoted_cells.insert(CSVCoords {
+                    row: rows.len(),
+                    column: *column,
+                });
+            }
+            if lossless {
+                source.raw_lines.push(
+                    record
+                        .line
+                        .map(|line| RawLine::new(line, &record.fields, &options.delimiter)),
+                );
+            }
+            rows.push(record.fields);
+            // Once the length of the first rows is known, the rows of the whole
+            // source are allocated at once instead of growing the vectors step by step.
+            if rows.len() == CAPACITY_SAMPLE_ROWS {
+                if let Some(estimate) = reader.estimated_rows() {
+                    rows.reserve(estimate.saturating_sub(rows.len()));
+                    if lossless {
+                        source
+                            .raw_lines
+                            .reserve(estimate.saturating_sub(rows.len()));
+                    }
+                }
+            }
+        }
+        source.read_stats = Some(reader.stats());
+        source.final_line_break = Some(reader.last_line_break());
+
+        let mut file = Self::from_rows(options.delimiter, reader.into_columns(), rows);
+        file.source = source;
+        Ok(file)
     }
 
     /// Creates a new CSVFile from the columns and the rows.
@@ -148,11 +634,23 @@ impl CSVFile {
             }
         }
 
-        Ok(Self {
-            delimiter: *delimiter,
-            columns: columns.clone(),
-            rows: rows.clone(),
-        })
+        Ok(Self::from_rows(*delimiter, columns.clone(), rows.clone()))
+    }
+
+    /// Creates a new CSVFile from data that is already known to be consistent,
+    /// without any detail about a source.
+    pub(crate) fn from_rows(delimiter: char, columns: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        Self {
+            delimiter,
+            columns,
+            rows,
+            source: SourceDetails::default(),
+            metadata: HashMap::new(),
+            validators: Vec::new(),
+            observers: Vec::new(),
+            aliases: HashMap::new(),
+            defaults: HashMap::new(),
+        }
     }
 
     /// Maps the rows of the CSV file to a type `T` using a callback function `F` called on each row.
@@ -244,13 +742,187 @@ impl CSVFile {
     }
 
     /// Writes the CSV file to a file.
+    #[cfg(feature = "fs")]
     pub fn write(&self, filename: &String) -> Result<(), Error> {
-        let mut file = File::create(filename)?;
-        file.write_all(self.to_string().as_bytes())?;
+        self.write_with_options(filename, &WriterOptions::default())
+    }
+
+    /// Writes the CSV file to a file using custom options.
+    #[cfg(feature = "fs")]
+    pub fn write_with_options(
+        &self,
+        filename: &String,
+        options: &WriterOptions,
+    ) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        self.write_to(&mut writer, options)?;
+        writer.flush()
+    }
+
+    /// Converts the CSV file to a string using custom options.
+    /// The formatting rules of the columns are applied to the values of the rows, not to the header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ColumnFormat, WriterOptions};
+    /// let columns = vec!["name".to_string(), "price".to_string()];
+    /// let rows = vec![vec!["apple".to_string(), "1.5".to_string()]];
+    /// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// let mut options = WriterOptions::default();
+    /// options.set_column_format(&"name".to_string(), ColumnFormat { always_quote: true, ..ColumnFormat::default() });
+    /// options.set_column_format(&"price".to_string(), ColumnFormat { decimals: Some(2), ..ColumnFormat::default() });
+    ///
+    /// assert_eq!(file.to_string_with_options(&options), "name,price\n\"apple\",1.50\n");
+    /// assert_eq!(file.rows[0][1], "1.5");
+    /// ```
+    pub fn to_string_with_options(&self, options: &WriterOptions) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        self.write_to(&mut output, options)
+            .expect("writing to a vector cannot fail");
+        String::from_utf8(output).expect("the values are valid UTF-8")
+    }
+
+    /// Writes the CSV file, line by line, to any writer using custom options.
+    /// Unless the options say otherwise, a file read from a source is written
+    /// with the line breaks and the byte order mark of the source.
+    /// If the source didn't end with a line break, the last line is written without one too.
+    pub fn write_to<W: Write>(&self, writer: &mut W, options: &WriterOptions) -> Result<(), Error> {
+        let formats: Vec<Option<&ColumnFormat>> = self
+            .columns
+            .iter()
+            .map(|column| options.column_formats.get(column))
+            .collect();
+
+        let delimiter = options.delimiter.unwrap_or(self.delimiter);
+        let always_quote = options.quote_style == QuoteStyle::Always;
+        let line_ending = options
+            .line_ending
+            .or(self.source.line_ending)
+            .unwrap_or_default()
+            .as_str();
+        let quote = |value: &str| quote_field_with_escape(value, options.escape);
+        let needs_quotes = |value: &str| needs_quotes_with_escape(value, delimiter, options.escape);
+
+        // The raw lines can't be reused if they have to be formatted or escaped differently.
+        let lossless = !options.escape_formulas
+            && !always_quote
+            && options.null_value.is_empty()
+            && delimiter == self.delimiter
+            && options.escape == self.source.escape
+            && formats.iter().all(|f| f.is_none());
+
+        let mut line = String::new();
+        if options
+            .byte_order_mark
+            .unwrap_or(self.source.byte_order_mark)
+        {
+            line.push('\u{feff}');
+        }
+        match self
+            .source
+            .raw_header
+            .as_ref()
+            .filter(|_| lossless)
+            .and_then(|raw| raw.get(&self.columns, &self.delimiter))
+        {
+            Some(raw) if options.multi_header.is_none() => line.push_str(raw),
+            _ => {
+                let header_rows = match &options.multi_header {
+                    Some(separator) => split_header_rows(&self.columns, separator),
+                    None => vec![self.columns.clone()],
+                };
+                for (k, header_row) in header_rows.iter().enumerate() {
+                    if k > 0 {
+                        line.push_str(line_ending);
+                    }
+                    for (i, column) in header_row.iter().enumerate() {
+                        let escaped = escape_formula(column).filter(|_| options.escape_formulas);
+                        let column = escaped.as_ref().unwrap_or(column);
+                        if always_quote
+                            || (header_rows.len() == 1 && self.source.quoted_columns.contains(&i))
+                            || needs_quotes(column)
+                        {
+                            line.push_str(&quote(column));
+                        } else {
+                            line.push_str(column);
+                        }
+                        line.push(delimiter);
+                    }
+                    line.pop(); // removes the trailing delimiter
+                }
+            }
+        }
+        // The end of the source is written back the same way, without a line break if it had none.
+        let final_line_ending = match self.source.final_line_break {
+            Some("") => "",
+            Some(line_break) if options.line_ending.is_none() => line_break,
+            _ => line_ending,
+        };
+        if self.rows.is_empty() {
+            line.push_str(final_line_ending);
+        } else {
+            line.push_str(line_ending);
+        }
+        writer.write_all(line.as_bytes())?;
+        let mut bytes_written = line.len() as u64;
+
+        for (i, row) in self.rows.iter().enumerate() {
+            if let Some(token) = &options.cancellation {
+                token.check()?;
+            }
+            line.clear();
+            match self.raw_line(i, row).filter(|_| lossless) {
+                Some(raw) => line.push_str(raw),
+                None => {
+                    for (j, field) in row.iter().enumerate() {
+                        let format = formats.get(j).copied().flatten();
+                        let mut value = match format {
+                            _ if field.is_empty() => options.null_value.clone(),
+                            Some(format) => format.format_value(field),
+                            None => field.clone(),
+                        };
+                        if options.escape_formulas {
+                            if let Some(escaped) = escape_formula(&value) {
+                                value = escaped;
+                            }
+                        }
+                        if always_quote
+                            || format.is_some_and(|f| f.always_quote)
+                            || self.is_quoted(&CSVCoords { row: i, column: j })
+                            || needs_quotes(&value)
+                        {
+                            line.push_str(&quote(&value));
+                        } else {
+                            line.push_str(&value);
+                        }
+                        line.push(delimiter);
+                    }
+                    line.pop();
+                }
+            }
+            if i + 1 == self.rows.len() {
+                line.push_str(final_line_ending);
+            } else {
+                line.push_str(line_ending);
+            }
+            writer.write_all(line.as_bytes())?;
+            bytes_written += line.len() as u64;
+            if let Some(progress) = &options.on_progress {
+                progress.tick(i + 1, bytes_written);
+            }
+        }
+
+        if let Some(progress) = &options.on_progress {
+            progress.finish(self.rows.len(), bytes_written);
+        }
+
         Ok(())
     }
 
     /// Returns the number of columns in the CSV file.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.columns.len()
     }
@@ -261,9 +933,9 @@ impl CSVFile {
         self.rows.len()
     }
 
-    /// Returns `true` if the CSV file has the given column.
+    /// Returns `true` if the CSV file has the given column, given by name or by alias.
     pub fn has_column(&self, column_name: &String) -> bool {
-        self.columns.contains(column_name)
+        self.get_column_idx(column_name).is_some()
     }
 
     /// Returns `true` if the CSV file has no row.
@@ -287,9 +959,13 @@ impl CSVFile {
         self.delimiter = *new_delimiter;
     }
 
-    /// Gets the index of a column by its name.
+    /// Gets the index of a column by its name,
+    /// or by its canonical name (see [set_column_aliases](`CSVFile::set_column_aliases`)).
     pub fn get_column_idx(&self, column_name: &String) -> Option<usize> {
-        self.columns.iter().position(|c| c == column_name)
+        self.columns
+            .iter()
+            .position(|c| c == column_name)
+            .or_else(|| self.alias_idx(column_name))
     }
 
     /// Gets a cell at given coordinates.
@@ -316,6 +992,190 @@ impl CSVFile {
         self.rows.get(coordinates.row)?.get(coordinates.column)
     }
 
+    /// Sets the value of a cell.
+    /// It may return an error if the coordinates are out of range
+    /// or if the value is rejected by a [Validator](`Validator`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile};
+    /// let columns = vec!["a".to_string(), "b".to_string()];
+    /// let rows = vec![vec!["1".to_string(), "2".to_string()]];
+    /// let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// file.set_cell(&CSVCoords { row: 0, column: 1 }, &"3".to_string()).unwrap();
+    /// assert_eq!(file.rows[0], vec!["1", "3"]);
+    /// assert!(file.set_cell(&CSVCoords { row: 1, column: 0 }, &"4".to_string()).is_err());
+    /// ```
+    pub fn set_cell(&mut self, coordinates: &CSVCoords, value: &String) -> Result<(), Error> {
+        if self.get_cell(coordinates).is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The cell at row {} and column {} doesn't exist",
+                    coordinates.row, coordinates.column
+                ),
+            ));
+        }
+
+        self.validate_cell(coordinates, value)?;
+        let old_value = std::mem::replace(
+            &mut self.rows[coordinates.row][coordinates.column],
+            value.clone(),
+        );
+        if old_value != *value {
+            self.emit(ChangeEvent::CellChanged {
+                coordinates: *coordinates,
+                old_value,
+                new_value: value.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Converts the CSV file to an [InternedCSVFile](`InternedCSVFile`),
+    /// where the repeated values share the same storage.
+    pub fn intern(&self) -> InternedCSVFile {
+        InternedCSVFile::from_csv_file(self)
+    }
+
+    /// Returns the header line exactly as it was read from the source, without its line break,
+    /// or `None` if the file was built from memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("\"name\", age\nThomas,20\n", &ReaderOptions::default()).unwrap();
+    /// assert_eq!(file.columns, vec!["name", " age"]);
+    /// assert_eq!(file.raw_header(), Some(&"\"name\", age".to_string()));
+    /// ```
+    pub fn raw_header(&self) -> Option<&String> {
+        self.source.header_line.as_ref()
+    }
+
+    /// Replaces the names of the columns by the ones of a header line,
+    /// parsed with the delimiter of the file. The line is then written back byte for byte,
+    /// as long as the names of the columns aren't modified.
+    /// It may return an error if the line can't be parsed or if the number of columns differs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("a,b\n1,2\n", &ReaderOptions::default()).unwrap();
+    /// file.set_header_from_line("\"id\",  value").unwrap();
+    /// assert_eq!(file.columns, vec!["id", "  value"]);
+    /// assert_eq!(file.to_string(), "\"id\",  value\n1,2\n");
+    /// ```
+    pub fn set_header_from_line(&mut self, line: &str) -> Result<(), Error> {
+        let mut columns: Vec<String> = Vec::with_capacity(self.len());
+        let mut quoted: Vec<usize> = Vec::new();
+        parser::parse_line_with_escape_into(
+            line,
+            self.delimiter,
+            self.source.escape,
+            &mut columns,
+            Some(&mut quoted),
+        )?;
+        if columns.len() != self.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid number of columns, {} were given, but expected {}",
+                    columns.len(),
+                    self.len()
+                ),
+            ));
+        }
+
+        self.source.raw_header = Some(RawLine::new(line.to_string(), &columns, &self.delimiter));
+        self.source.quoted_columns = quoted.into_iter().collect();
+        self.columns = columns;
+
+        Ok(())
+    }
+
+    /// Returns the performance counters gathered while the file was read,
+    /// or `None` if it was built from memory.
+    pub fn read_stats(&self) -> Option<&ReadStats> {
+        self.source.read_stats.as_ref()
+    }
+
+    /// Returns `true` if the cell at the given coordinates will be surrounded by double quotes when written.
+    ///
+    /// The cells that were quoted in the source file are remembered when reading
+    /// with [ReaderOptions::preserve_quotes](`ReaderOptions::preserve_quotes`),
+    /// so that only those cells are quoted again when the file is saved.
+    pub fn is_quoted(&self, coordinates: &CSVCoords) -> bool {
+        !self.source.quoted_cells.is_empty() && self.source.quoted_cells.contains(coordinates)
+    }
+
+    /// Sets whether the cell at the given coordinates should be surrounded by double quotes when written.
+    pub fn set_quoted(&mut self, coordinates: &CSVCoords, quoted: bool) {
+        if quoted {
+            self.source.quoted_cells.insert(*coordinates);
+        } else {
+            self.source.quoted_cells.remove(coordinates);
+        }
+    }
+
+    /// Returns the line of the source file a row was read from,
+    /// as long as the row hasn't been modified since.
+    /// Only available when reading with [ReaderOptions::lossless](`ReaderOptions::lossless`).
+    fn raw_line(&self, row_idx: usize, row: &Vec<String>) -> Option<&String> {
+        self.source
+            .raw_lines
+            .get(row_idx)?
+            .as_ref()?
+            .get(row, &self.delimiter)
+    }
+
+    /// Moves what is remembered about the source file (quotes, raw lines)
+    /// after rows or columns were removed or inserted. The callbacks give
+    /// the new index of a row or a column, or `None` if it no longer exists.
+    ///
+    /// It must be called once `self.rows` has been updated.
+    fn remap_source<R, C>(&mut self, row_map: R, column_map: C)
+    where
+        R: Fn(usize) -> Option<usize>,
+        C: Fn(usize) -> Option<usize>,
+    {
+        if !self.source.raw_lines.is_empty() {
+            let mut raw_lines: Vec<Option<RawLine>> = Vec::with_capacity(self.rows.len());
+            raw_lines.resize_with(self.rows.len(), || None);
+            for (i, raw_line) in self.source.raw_lines.drain(..).enumerate() {
+                if let Some(j) = row_map(i).filter(|j| *j < raw_lines.len()) {
+                    raw_lines[j] = raw_line;
+                }
+            }
+            self.source.raw_lines = raw_lines;
+        }
+        if !self.source.quoted_cells.is_empty() {
+            self.source.quoted_cells = self
+                .source
+                .quoted_cells
+                .iter()
+                .filter_map(|coords| {
+                    Some(CSVCoords {
+                        row: row_map(coords.row)?,
+                        column: column_map(coords.column)?,
+                    })
+                })
+                .collect();
+        }
+        if !self.source.quoted_columns.is_empty() {
+            self.source.quoted_columns = self
+                .source
+                .quoted_columns
+                .iter()
+                .filter_map(|c| column_map(*c))
+                .collect();
+        }
+    }
+
     /// Finds text in the CSV file and returns the coordinates of the cells.
     pub fn find_text(&self, text: &String) -> Vec<CSVCoords> {
         let mut coords: Vec<CSVCoords> = Vec::new();
@@ -330,58 +1190,99 @@ impl CSVFile {
         coords
     }
 
-    /// Checks if the CSV file is valid.
-    /// It checks for duplicates in the columns and if the rows have the right length.
-    pub fn check_validity(&self) -> bool {
-        // Check for duplicates in the columns
-        let mut column_names: HashSet<&str> = HashSet::new();
-        for column in &self.columns {
-            if column_names.contains(column.as_str()) {
-                return false;
-            }
-            column_names.insert(column);
-        }
-
-        // Make sure the rows have the right length
-        let number_of_columns = self.len();
-        for row in &self.rows {
-            if row.len() != number_of_columns {
-                return false;
+    /// Finds the rows that appear more than once and returns the groups of identical rows,
+    /// as the indexes of the rows in each group. The groups are sorted by their first index.
+    ///
+    /// The rows are indexed by hash, so it takes a single pass over the file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// let columns = vec!["name".to_string()];
+    /// let rows = vec![
+    ///     vec!["Thomas".to_string()],
+    ///     vec!["Yoshiip".to_string()],
+    ///     vec!["Thomas".to_string()],
+    ///     vec!["Yoshiip".to_string()],
+    ///     vec!["Alice".to_string()],
+    ///     vec!["Thomas".to_string()],
+    /// ];
+    /// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// assert_eq!(file.find_duplicate_rows(), vec![vec![0, 2, 5], vec![1, 3]]);
+    /// ```
+    pub fn find_duplicate_rows(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut index: HashMap<&Vec<String>, usize> = HashMap::with_capacity(self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            match index.get(row) {
+                Some(&group) => groups[group].push(i),
+                None => {
+                    index.insert(row, groups.len());
+                    groups.push(vec![i]);
+                }
             }
         }
 
-        true
+        groups.retain(|group| group.len() > 1);
+        groups
     }
 
     /// Fills a column with the given data.
     /// It may return an error if the column doesn't exist
     /// or if the length of the data is different from the number of rows.
+    /// The ragged rows too short to have a value in the column are completed with empty values.
     pub fn fill_column(&mut self, column_name: &String, data: &Vec<String>) -> Result<(), Error> {
-        let column_idx = self.columns.iter().position(|c| c == column_name);
+        let column_idx = match self.get_column_idx(column_name) {
+            Some(idx) => idx,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", column_name),
+                ))
+            }
+        };
 
-        if column_idx.is_none() {
+        if data.len() != self.count_rows() {
             Err(Error::new(
                 ErrorKind::InvalidData,
-                format!("The column {} doesn't exist", column_name),
+                format!(
+                    "Invalid number of fields, {} were given, but expected {}",
+                    data.len(),
+                    self.count_rows()
+                ),
             ))
         } else {
-            if data.len() != self.count_rows() {
-                Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!(
-                        "Invalid number of fields, {} were given, but expected {}",
-                        data.len(),
-                        self.count_rows()
-                    ),
-                ))
-            } else {
-                let column_idx = column_idx.unwrap();
-                for (i, row) in self.rows.iter_mut().enumerate() {
+            if !self.validators.is_empty() {
+                for (i, row) in self.rows.iter().enumerate() {
+                    let mut row = row.clone();
+                    if row.len() <= column_idx {
+                        row.resize(column_idx + 1, String::new());
+                    }
                     row[column_idx] = data[i].clone();
+                    self.validate_row(&row)?;
                 }
+            }
 
-                Ok(())
+            for (i, value) in data.iter().enumerate() {
+                if self.rows[i].len() <= column_idx {
+                    self.rows[i].resize(column_idx + 1, String::new());
+                }
+                let old_value = std::mem::replace(&mut self.rows[i][column_idx], value.clone());
+                if self.is_observed() && old_value != *value {
+                    self.emit(ChangeEvent::CellChanged {
+                        coordinates: CSVCoords {
+                            row: i,
+                            column: column_idx,
+                        },
+                        old_value,
+                        new_value: value.clone(),
+                    });
+                }
             }
+
+            Ok(())
         }
     }
 
@@ -391,6 +1292,16 @@ impl CSVFile {
     ///
     /// The other CSVFile instance is supposed to be valid.
     pub fn merge(&mut self, other: &CSVFile) -> Result<(), Error> {
+        self.merge_with_progress(other, None)
+    }
+
+    /// Same as [merge](`CSVFile::merge`), but notifies the progress hook of the number of rows merged so far.
+    /// Since the merge happens in memory, the number of bytes is always `0`.
+    pub fn merge_with_progress(
+        &mut self,
+        other: &CSVFile,
+        on_progress: Option<&ProgressHook>,
+    ) -> Result<(), Error> {
         for column in &other.columns {
             if self.columns.contains(column) {
                 return Err(Error::new(
@@ -427,6 +1338,23 @@ impl CSVFile {
 
         for i in 0..other_rows {
             self.rows[i].extend(other.rows[i].iter().cloned());
+            if let Some(progress) = on_progress {
+                progress.tick(i + 1, 0);
+            }
+        }
+
+        if let Some(progress) = on_progress {
+            progress.finish(other_rows, 0);
+        }
+
+        for row in self_rows..other_rows {
+            self.emit(ChangeEvent::RowAdded { row });
+        }
+        for (i, name) in other.columns.iter().enumerate() {
+            self.emit(ChangeEvent::ColumnAdded {
+                column: initial_self_len + i,
+                name: name.clone(),
+            });
         }
 
         Ok(())
@@ -447,33 +1375,46 @@ impl CSVFile {
             ));
         }
 
+        self.validate_row(data)?;
         self.rows.push(data.clone());
+        self.emit(ChangeEvent::RowAdded {
+            row: self.rows.len() - 1,
+        });
 
         Ok(())
     }
 
     /// Adds a column to the CSV file.
     /// It may return an error if the column already exists.
-    /// It appends an empty string to each row.
+    /// It appends an empty string to each row, completing the rows that are too short.
     pub fn add_column(&mut self, name: &String) -> Result<(), Error> {
-        if self.columns.contains(&name) {
+        if self.columns.contains(name) {
             return Err(Error::new(
                 ErrorKind::InvalidData,
                 format!("The column {} already exists", name),
             ));
         }
 
+        let column_idx = self.columns.len();
         self.columns.push(name.clone());
         for row in &mut self.rows {
-            row.push(String::new());
+            // The new column goes after the last one, even in the rows that are too long.
+            if row.len() < column_idx {
+                row.resize(column_idx, String::new());
+            }
+            row.insert(column_idx, String::new());
         }
+        self.emit(ChangeEvent::ColumnAdded {
+            column: column_idx,
+            name: name.clone(),
+        });
 
         Ok(())
     }
 
     /// Inserts a column to the CSV file at a specific index.
     /// It may return an error if the column already exists or if the index is out of range.
-    /// It also inserts an empty string to each row.
+    /// It also inserts an empty string to each row, completing the rows that are too short.
     pub fn insert_column(&mut self, name: &String, column_idx: usize) -> Result<(), Error> {
         if column_idx > self.len() {
             return Err(Error::new(
@@ -482,7 +1423,7 @@ impl CSVFile {
             ));
         }
 
-        if self.columns.contains(&name) {
+        if self.columns.contains(name) {
             return Err(Error::new(
                 ErrorKind::InvalidData,
                 format!("The column {} already exists", name),
@@ -491,8 +1432,17 @@ impl CSVFile {
 
         self.columns.insert(column_idx, name.clone());
         for row in &mut self.rows {
+            // The short rows are completed so that the new column lands at its index.
+            if row.len() < column_idx {
+                row.resize(column_idx, String::new());
+            }
             row.insert(column_idx, String::new());
         }
+        self.remap_source(Some, |c| Some(if c >= column_idx { c + 1 } else { c }));
+        self.emit(ChangeEvent::ColumnAdded {
+            column: column_idx,
+            name: name.clone(),
+        });
 
         Ok(())
     }
@@ -507,10 +1457,19 @@ impl CSVFile {
             ));
         }
 
-        self.columns.remove(column_idx);
-        for row in &mut self.rows {
+        let name = self.columns.remove(column_idx);
+        self.metadata.remove(&name);
+        self.defaults.remove(&name);
+        self.validators
+            .retain(|validator| validator.column_name() != Some(&name));
+        for row in self.rows.iter_mut().filter(|row| column_idx < row.len()) {
             row.remove(column_idx);
         }
+        self.remap_source(Some, |c| shift_removed_index(c, column_idx));
+        self.emit(ChangeEvent::ColumnRemoved {
+            column: column_idx,
+            name,
+        });
 
         Ok(())
     }
@@ -526,6 +1485,8 @@ impl CSVFile {
         }
 
         self.rows.remove(row_idx);
+        self.remap_source(|r| shift_removed_index(r, row_idx), Some);
+        self.emit(ChangeEvent::RowRemoved { row: row_idx });
 
         Ok(())
     }
@@ -535,18 +1496,16 @@ impl CSVFile {
     ///
     /// If no empty row is found, then nothing happens.
     pub fn trim_end(&mut self) {
-        let mut i = self.rows.len() - 1;
-        loop {
-            if self.rows[i].iter().all(|s| s.is_empty()) {
-                self.rows.remove(i);
-                if i == 0 {
-                    break;
-                } else {
-                    i -= 1;
-                }
-            } else {
-                break;
-            }
+        let kept = self
+            .rows
+            .iter()
+            .rposition(|row| !row.iter().all(|s| s.is_empty()))
+            .map_or(0, |i| i + 1);
+        let count = self.rows.len();
+        self.rows.truncate(kept);
+        self.remap_source(|r| if r < kept { Some(r) } else { None }, Some);
+        for row in (kept..count).rev() {
+            self.emit(ChangeEvent::RowRemoved { row });
         }
     }
 
@@ -565,9 +1524,14 @@ impl CSVFile {
                 break;
             }
         }
+        let removed = to_remove.len();
         for i in to_remove.into_iter().rev() {
             self.rows.remove(i);
         }
+        self.remap_source(|r| r.checked_sub(removed), Some);
+        for row in (0..removed).rev() {
+            self.emit(ChangeEvent::RowRemoved { row });
+        }
     }
 
     /// Removes all the rows that are composed of empty strings only at the beginning and at the end.
@@ -578,7 +1542,7 @@ impl CSVFile {
 
     /// Removes all the empty lines from the CSV file.
     pub fn remove_empty_lines(&mut self) {
-        self.rows.retain(|row| !row.iter().all(|s| s.is_empty()));
+        self.delete_where(|row| row.iter().all(|s| s.is_empty()));
     }
 }
 
@@ -592,72 +1556,59 @@ impl CSVFile {
 ///
 /// The "number_of_fields" parameter is used to pre-allocate the vectors.
 /// This is useful when we know the number of fields in advance.
+#[cfg(feature = "std")]
 pub(crate) fn parse_line(
     line: &String,
     delimiter: &char,
     number_of_fields: Option<u32>,
+) -> Result<Vec<String>, Error> {
+    parse_line_with_quotes(line, delimiter, number_of_fields, None)
+}
+
+/// Same as [parse_line](`#parse_line`), but it also pushes the indexes
+/// of the fields that were surrounded by double quotes in `quoted`.
+#[cfg(feature = "std")]
+pub(crate) fn parse_line_with_quotes(
+    line: &String,
+    delimiter: &char,
+    number_of_fields: Option<u32>,
+    quoted: Option<&mut Vec<usize>>,
 ) -> Result<Vec<String>, Error> {
     let mut fields: Vec<String> = match number_of_fields {
         Some(n) => Vec::with_capacity(n as usize),
         None => Vec::new(),
     };
-
-    let mut chars = line.chars();
-    let mut current_field = String::new();
-    let mut is_in_quote = false;
-    let mut is_escaped = false;
-
-    while let Some(c) = chars.next() {
-        if c == '\\' {
-            if is_escaped {
-                current_field.push(c);
-            }
-            is_escaped = !is_escaped;
-        } else {
-            if c == '"' {
-                if !is_escaped {
-                    if is_in_quote {
-                        fields.push(current_field);
-                        current_field = String::new();
-                        // skip the next character because it should be
-                        // the delimiter (or the end of the line)
-                        chars.next();
-                    }
-                    is_in_quote = !is_in_quote;
-                } else {
-                    current_field.push(c);
-                }
-            } else {
-                if c == *delimiter && !is_in_quote {
-                    fields.push(current_field);
-                    current_field = String::new();
-                } else {
-                    current_field.push(c);
-                }
-            }
-            // If the character immediately following a blackslash
-            // isn't another backslash, then make sure to be unescaped.
-            is_escaped = false;
-        }
-    }
-
-    if is_escaped || is_in_quote {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid escape sequence",
-        ));
-    }
-
-    // Push the last field
-    fields.push(current_field);
-
+    parser::parse_line_into(line, *delimiter, &mut fields, quoted)?;
     Ok(fields)
 }
 
 /// Splits the line into a vector of strings using the delimiter.
 /// Contrary to [parse_line](`#parse_line`), this function uses the split method.
+#[cfg(feature = "std")]
 pub(crate) fn split_line(line: &String, delimiter: &char) -> Vec<String> {
-    line.split(*delimiter).map(|s| s.to_string()).collect()
+    parser::split_line(line, *delimiter)
+}
+
+/// Gives the new index of an element after the element at `removed` was removed,
+/// or `None` if it is the removed element itself.
+#[cfg(feature = "std")]
+pub(crate) fn shift_removed_index(index: usize, removed: usize) -> Option<usize> {
+    match index.cmp(&removed) {
+        std::cmp::Ordering::Less => Some(index),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(index - 1),
+    }
+}
+
+/// Removes the leading and trailing whitespace of each field, in place.
+#[cfg(feature = "std")]
+pub(crate) fn trim_fields(fields: &mut Vec<String>) {
+    for field in fields.iter_mut() {
+        let trimmed = field.trim();
+        if trimmed.len() != field.len() {
+            *field = trimmed.to_string();
+        }
+    }
 }
 
 /// Reads the columns of the CSV file.
@@ -666,6 +1617,7 @@ pub(crate) fn split_line(line: &String, delimiter: &char) -> Vec<String> {
 ///
 /// It returns a Result because it can fail if the line,
 /// contains an invalid escape sequence or an unclosed quote.
+#[cfg(feature = "std")]
 pub(crate) fn read_columns(line: &String, delimiter: &char) -> Result<Vec<String>, Error> {
     if line.contains('"') {
         parse_line(line, delimiter, None)
@@ -674,34 +1626,28 @@ pub(crate) fn read_columns(line: &String, delimiter: &char) -> Result<Vec<String
     }
 }
 
-/// Reads the data of the CSV file.
-/// It reads the lines of the file and uses the [parse_line](`#parse_line`) function if the line contains double quotes.
-/// Otherwise, it uses the [split_line](`#split_line`) function.
-///
-/// It returns a Result because it can fail if the line,
-/// contains an invalid escape sequence or an unclosed quote.
-///
-/// The "number_of_fields" parameter is used to pre-allocate the vectors.
-/// This is useful when we know the number of fields in advance.
-pub(crate) fn read_rows(
-    lines: &mut std::io::Lines<BufReader<&File>>,
-    delimiter: &char,
-    number_of_fields: usize,
-) -> Result<Vec<Vec<String>>, Error> {
-    let mut data: Vec<Vec<String>> = Vec::new();
-
-    for line in lines {
-        let line = line?;
-        let fields: Vec<String>;
-        if line.contains('"') {
-            fields = parse_line(&line, delimiter, Some(number_of_fields as u32))?;
+/// Splits the names of the columns into the two rows of a header, the groups above the columns.
+/// The name of a group is only kept above its first column.
+#[cfg(feature = "std")]
+fn split_header_rows(columns: &[String], separator: &str) -> Vec<Vec<String>> {
+    let mut groups: Vec<String> = Vec::with_capacity(columns.len());
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    let mut previous_group = "";
+    for column in columns {
+        let (group, name) = column
+            .split_once(separator)
+            .unwrap_or(("", column.as_str()));
+        groups.push(if group == previous_group {
+            String::new()
         } else {
-            fields = split_line(&line, delimiter);
-        }
-        data.push(fields);
+            group.to_string()
+        });
+        names.push(name.to_string());
+        previous_group = group;
     }
-
-    Ok(data)
+    vec![groups, names]
 }
 
+#[cfg(feature = "std")]
+#[allow(clippy::module_inception)]
 mod tests;
@@ -0,0 +1,328 @@
+//! Detection of the suspicious constructs of a CSV text, like a linter for source code.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Error;
+
+use crate::parser::{needs_quotes_with_escape, Escape};
+use crate::stats::parse_number;
+use crate::{CSVFile, CSVReader, ReaderOptions};
+
+/// The delimiters looked for by [LintRule::SuspiciousDelimiter](`LintRule::SuspiciousDelimiter`).
+const COMMON_DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+
+/// How serious a [LintWarning](`LintWarning`) is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// The checks made by [lint](`CSVFile::lint`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    /// A column whose values are quoted in some rows and not in others,
+    /// when they don't need quotes.
+    InconsistentQuoting,
+    /// Lines ending with `\r\n` and others with `\n` in the same text.
+    MixedLineEndings,
+    /// Unquoted values, or names of columns, starting or ending with whitespace.
+    TrailingWhitespace,
+    /// Unquoted values containing another common delimiter,
+    /// which often means that the text was read with the wrong delimiter.
+    SuspiciousDelimiter,
+    /// Values that aren't numbers in a column mostly made of numbers.
+    MixedTypes,
+}
+
+impl LintRule {
+    /// All the rules, in the order they are documented.
+    pub const ALL: [LintRule; 5] = [
+        LintRule::InconsistentQuoting,
+        LintRule::MixedLineEndings,
+        LintRule::TrailingWhitespace,
+        LintRule::SuspiciousDelimiter,
+        LintRule::MixedTypes,
+    ];
+
+    /// Returns the severity of the warnings of the rule when it isn't configured.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            LintRule::InconsistentQuoting => Severity::Info,
+            LintRule::MixedLineEndings => Severity::Warning,
+            LintRule::TrailingWhitespace => Severity::Info,
+            LintRule::SuspiciousDelimiter => Severity::Warning,
+            LintRule::MixedTypes => Severity::Warning,
+        }
+    }
+}
+
+/// The options used by [lint](`CSVFile::lint`).
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// The character separating the values (a comma by default).
+    pub delimiter: char,
+    /// How the double quotes are escaped inside quoted values ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+    /// The rules checked, with the severity of their warnings (all of them by default,
+    /// with their [default severity](`LintRule::default_severity`)).
+    pub rules: HashMap<LintRule, Severity>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            escape: Escape::Backslash,
+            rules: LintRule::ALL
+                .iter()
+                .map(|rule| (*rule, rule.default_severity()))
+                .collect(),
+        }
+    }
+}
+
+impl LintOptions {
+    /// Stops checking a rule.
+    pub fn disable(mut self, rule: LintRule) -> Self {
+        self.rules.remove(&rule);
+        self
+    }
+
+    /// Checks a rule, giving its warnings the given severity.
+    pub fn with_severity(mut self, rule: LintRule, severity: Severity) -> Self {
+        self.rules.insert(rule, severity);
+        self
+    }
+}
+
+/// A problem found by [lint](`CSVFile::lint`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    pub rule: LintRule,
+    pub severity: Severity,
+    /// The number of the line in the text, starting at 1 for the header.
+    pub line: usize,
+    /// The index of the column, if the problem is about a single value.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(
+                f,
+                "{:?} at line {}, column {}: {}",
+                self.severity, self.line, column, self.message
+            ),
+            None => write!(
+                f,
+                "{:?} at line {}: {}",
+                self.severity, self.line, self.message
+            ),
+        }
+    }
+}
+
+/// The warnings found so far, keeping only the ones of the enabled rules.
+struct Warnings<'a> {
+    options: &'a LintOptions,
+    found: Vec<LintWarning>,
+}
+
+impl Warnings<'_> {
+    fn enabled(&self, rule: LintRule) -> bool {
+        self.options.rules.contains_key(&rule)
+    }
+
+    fn push(&mut self, rule: LintRule, line: usize, column: Option<usize>, message: String) {
+        if let Some(severity) = self.options.rules.get(&rule) {
+            self.found.push(LintWarning {
+                rule,
+                severity: *severity,
+                line,
+                column,
+                message,
+            });
+        }
+    }
+}
+
+/// What is known about the quoting and the values of a column while going through the rows.
+#[derive(Default)]
+struct ColumnLint {
+    /// Whether the first value that didn't need quotes was quoted.
+    quoted: Option<bool>,
+    inconsistent: bool,
+    numbers: usize,
+    /// The values that aren't numbers, with their line.
+    texts: Vec<(usize, String)>,
+}
+
+impl CSVFile {
+    /// Checks a CSV text for constructs that are valid but often unintended,
+    /// such as mixed line endings or values surrounded by whitespace (see [LintRule](`LintRule`)).
+    /// The warnings are sorted by line, and the rules can be disabled or given another severity.
+    ///
+    /// It may return an error if the text can't be parsed at all, such as a value whose quotes
+    /// are never closed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, LintOptions, LintRule, Severity};
+    /// let text = "name,age\r\nThomas ,19\nJohn,unknown\nMarie,28\n";
+    /// let warnings = CSVFile::lint(text, &LintOptions::default()).unwrap();
+    ///
+    /// let rules: Vec<LintRule> = warnings.iter().map(|w| w.rule).collect();
+    /// assert_eq!(rules, vec![LintRule::MixedLineEndings, LintRule::TrailingWhitespace, LintRule::MixedTypes]);
+    /// assert_eq!(warnings[2].line, 3);
+    /// assert_eq!(warnings[2].column, Some(1));
+    ///
+    /// let options = LintOptions::default()
+    ///     .disable(LintRule::TrailingWhitespace)
+    ///     .with_severity(LintRule::MixedTypes, Severity::Error);
+    /// let warnings = CSVFile::lint(text, &options).unwrap();
+    /// assert_eq!(warnings.len(), 2);
+    /// assert_eq!(warnings[1].severity, Severity::Error);
+    /// ```
+    pub fn lint(text: &str, options: &LintOptions) -> Result<Vec<LintWarning>, Error> {
+        let mut warnings = Warnings {
+            options,
+            found: Vec::new(),
+        };
+
+        if warnings.enabled(LintRule::MixedLineEndings) {
+            let mut first_ending: Option<bool> = None;
+            for (i, line) in text.split_inclusive('\n').enumerate() {
+                if !line.ends_with('\n') {
+                    break;
+                }
+                let crlf = line.ends_with("\r\n");
+                match first_ending {
+                    None => first_ending = Some(crlf),
+                    Some(first) if first != crlf => {
+                        warnings.push(
+                            LintRule::MixedLineEndings,
+                            i + 1,
+                            None,
+                            format!(
+                                "The line ends with {}, unlike the first line",
+                                if crlf { "\\r\\n" } else { "\\n" }
+                            ),
+                        );
+                        break;
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let reader_options = ReaderOptions {
+            delimiter: options.delimiter,
+            escape: options.escape,
+            preserve_quotes: true,
+            lossless: true,
+            ..ReaderOptions::default()
+        };
+        let mut reader = CSVReader::from_reader(text.as_bytes(), &reader_options)?;
+        let columns = reader.columns().clone();
+        for (i, column) in columns.iter().enumerate() {
+            if !reader.quoted_columns().contains(&i) && column.trim() != column {
+                warnings.push(
+                    LintRule::TrailingWhitespace,
+                    1,
+                    Some(i),
+                    format!(
+                        "The name of the column \"{}\" is surrounded by whitespace",
+                        column
+                    ),
+                );
+            }
+        }
+
+        let mut lints: Vec<ColumnLint> = columns.iter().map(|_| ColumnLint::default()).collect();
+        // The line breaks inside quoted values also count.
+        let mut line = 2 + reader.raw_header().matches('\n').count();
+        while let Some(record) = reader.next_record() {
+            let record = record?;
+            for (j, value) in record.fields.iter().enumerate() {
+                let quoted = record.quoted.contains(&j);
+                if !quoted {
+                    if value.trim() != value {
+                        warnings.push(
+                            LintRule::TrailingWhitespace,
+                            line,
+                            Some(j),
+                            format!("The value \"{}\" is surrounded by whitespace", value),
+                        );
+                    }
+                    if let Some(other) = COMMON_DELIMITERS
+                        .iter()
+                        .find(|&&d| d != options.delimiter && value.contains(d))
+                    {
+                        warnings.push(
+                            LintRule::SuspiciousDelimiter,
+                            line,
+                            Some(j),
+                            format!("The value \"{}\" contains the delimiter {:?}", value, other),
+                        );
+                    }
+                }
+
+                let Some(lint) = lints.get_mut(j) else {
+                    continue;
+                };
+                if !needs_quotes_with_escape(value, options.delimiter, options.escape) {
+                    match lint.quoted {
+                        None => lint.quoted = Some(quoted),
+                        Some(first) if first != quoted && !lint.inconsistent => {
+                            lint.inconsistent = true;
+                            warnings.push(
+                                LintRule::InconsistentQuoting,
+                                line,
+                                Some(j),
+                                format!(
+                                    "The values of the column {} are quoted in some rows only",
+                                    columns[j]
+                                ),
+                            );
+                        }
+                        Some(_) => {}
+                    }
+                }
+                if !value.trim().is_empty() {
+                    if parse_number(value).is_some() {
+                        lint.numbers += 1;
+                    } else {
+                        lint.texts.push((line, value.clone()));
+                    }
+                }
+            }
+            line += 1 + record.line.map_or(0, |raw| raw.matches('\n').count());
+        }
+
+        for (j, lint) in lints.iter().enumerate() {
+            if lint.numbers > 0 && lint.numbers >= lint.texts.len() {
+                for (line, value) in &lint.texts {
+                    warnings.push(
+                        LintRule::MixedTypes,
+                        *line,
+                        Some(j),
+                        format!(
+                            "The value \"{}\" isn't a number, unlike most values of the column {}",
+                            value, columns[j]
+                        ),
+                    );
+                }
+            }
+        }
+
+        // The sort is stable, so the warnings of a line keep the order of the rules.
+        let mut found = warnings.found;
+        found.sort_by_key(|warning| warning.line);
+        Ok(found)
+    }
+}
@@ -0,0 +1,113 @@
+//! Advisory locking of the files on disk, for the processes updating the same file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, Write};
+use std::path::{Path, PathBuf};
+
+use crate::atomic::{create_temporary, replace_with_temporary};
+use crate::{CSVFile, ReaderOptions, WriterOptions};
+
+/// An exclusive lock on a file, given by [open_locked](`CSVFile::open_locked`)
+/// and held until it's dropped.
+///
+/// The lock is advisory: it only excludes the other processes asking for it,
+/// such as another program using this crate, not the ones writing to the file directly.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Releases the lock. Dropping the lock releases it too.
+    pub fn unlock(self) -> Result<(), Error> {
+        self.file.unlock()
+    }
+}
+
+/// Opens and locks a file. Since [write_locked](`CSVFile::write_locked`) replaces the file
+/// instead of writing it in place, the lock obtained after waiting may be the lock
+/// of a file that was replaced in the meantime, in which case the new file is locked instead.
+fn lock_current(path: &Path) -> Result<File, Error> {
+    loop {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        file.lock()?;
+        if is_current(&file, path)? {
+            return Ok(file);
+        }
+    }
+}
+
+/// Whether the opened file is still the one at the path.
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> Result<bool, Error> {
+    use std::os::unix::fs::MetadataExt;
+    let (opened, current) = (file.metadata()?, fs::metadata(path)?);
+    Ok(opened.dev() == current.dev() && opened.ino() == current.ino())
+}
+
+/// The other platforms don't let a file be replaced while it's open.
+#[cfg(not(unix))]
+fn is_current(_file: &File, _path: &Path) -> Result<bool, Error> {
+    Ok(true)
+}
+
+impl CSVFile {
+    /// Locks a file and reads it, waiting for the other processes holding the lock to release it.
+    /// The lock must be kept while the file is modified and given to
+    /// [write_locked](`CSVFile::write_locked`) to save the changes,
+    /// so that two processes, such as cron jobs sharing a data file,
+    /// can't overwrite the changes of each other.
+    ///
+    /// It may return an error if the file doesn't exist or if it can't be read properly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions, WriterOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_locked_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "counter\n1\n").unwrap();
+    ///
+    /// let (mut file, mut lock) = CSVFile::open_locked(&filename, &ReaderOptions::default()).unwrap();
+    /// file.rows[0][0] = "2".to_string();
+    /// file.write_locked(&mut lock, &WriterOptions::default()).unwrap();
+    /// lock.unlock().unwrap();
+    ///
+    /// assert_eq!(std::fs::read_to_string(&filename).unwrap(), "counter\n2\n");
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn open_locked(
+        file_name: &String,
+        options: &ReaderOptions,
+    ) -> Result<(CSVFile, FileLock), Error> {
+        let path = PathBuf::from(file_name);
+        let file = lock_current(&path)?;
+        let csv = CSVFile::from_reader(&file, options)?;
+        Ok((csv, FileLock { file, path }))
+    }
+
+    /// Replaces the content of a file locked by [open_locked](`CSVFile::open_locked`),
+    /// keeping the lock. Like [write_atomic](`CSVFile::write_atomic`), the file is first
+    /// written to a temporary file which then replaces it at once, so it's never left
+    /// truncated if the writing fails. The new file is locked before it replaces the previous one.
+    pub fn write_locked(&self, lock: &mut FileLock, options: &WriterOptions) -> Result<(), Error> {
+        let (temporary, file) = create_temporary(&lock.path)?;
+        let written = (|| {
+            file.lock()?;
+            let mut writer = BufWriter::new(&file);
+            self.write_to(&mut writer, options)?;
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+            replace_with_temporary(&temporary, &file, &lock.path)
+        })();
+
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary);
+            return written;
+        }
+        // releases the lock of the previous file, whose waiters then lock the new one
+        lock.file = file;
+        Ok(())
+    }
+}
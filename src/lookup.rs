@@ -0,0 +1,290 @@
+//! Operations matching the rows of two files by the value of a key column.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, ErrorKind};
+
+use crate::stats::parse_number;
+use crate::{CSVFile, ChangeEvent};
+
+/// How two values found by [compare_columns](`CSVFile::compare_columns`) differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DifferenceKind {
+    /// Both values are numbers, further apart than the tolerance.
+    /// The difference is the actual number minus the expected one.
+    Numeric { difference: f64 },
+    /// The values aren't both numbers, and their text is different.
+    Text,
+}
+
+/// A value found by [compare_columns](`CSVFile::compare_columns`) that differs between the two files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDifference {
+    /// The value of the key column of the rows.
+    pub key: String,
+    pub column: String,
+    /// The value of the compared file.
+    pub expected: String,
+    /// The value of the other file.
+    pub actual: String,
+    pub kind: DifferenceKind,
+}
+
+/// The result of [compare_columns](`CSVFile::compare_columns`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnComparison {
+    /// The values that differ, in the order of the rows and then of the columns of the compared file.
+    pub differences: Vec<CellDifference>,
+    /// The keys of the compared file that the other file doesn't have.
+    pub missing_keys: Vec<String>,
+    /// The keys of the other file that the compared file doesn't have, each listed once.
+    pub extra_keys: Vec<String>,
+    /// The columns of the compared file that the other file doesn't have.
+    pub missing_columns: Vec<String>,
+    /// The columns of the other file that the compared file doesn't have.
+    pub extra_columns: Vec<String>,
+}
+
+impl ColumnComparison {
+    /// Returns `true` if the two files have the same keys and columns, and no value differs.
+    pub fn is_match(&self) -> bool {
+        self.differences.is_empty()
+            && self.missing_keys.is_empty()
+            && self.extra_keys.is_empty()
+            && self.missing_columns.is_empty()
+            && self.extra_columns.is_empty()
+    }
+}
+
+/// Returns the index of a column, or an error if it doesn't exist.
+fn column_idx(file: &CSVFile, column_name: &String) -> Result<usize, Error> {
+    file.get_column_idx(column_name).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("The column {} doesn't exist", column_name),
+        )
+    })
+}
+
+/// Returns a value of a row, or an empty value if the row is too short to have it.
+fn cell(row: &[String], idx: usize) -> &str {
+    row.get(idx).map_or("", String::as_str)
+}
+
+impl CSVFile {
+    /// Copies columns of another file into this one, matching the rows by key
+    /// like the `VLOOKUP` function of spreadsheets: each row gets the values
+    /// of the first row of `other` whose `key_other` value equals its `key_self` value,
+    /// or empty values if there is no such row. The values missing from the ragged rows
+    /// of either file are empty too.
+    ///
+    /// It may return an error if one of the columns doesn't exist,
+    /// or if a copied column already exists in this file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut orders = CSVFile::from_text("order,customer\n1,c2\n2,c9\n", &ReaderOptions::default()).unwrap();
+    /// let customers = CSVFile::from_text("id,name,city\nc1,Thomas,Lyon\nc2,Yoshiip,Paris\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// orders.enrich_from(&customers, &"customer".to_string(), &"id".to_string(), &vec!["name".to_string()]).unwrap();
+    /// assert_eq!(orders.columns, vec!["order", "customer", "name"]);
+    /// assert_eq!(orders.rows, vec![vec!["1", "c2", "Yoshiip"], vec!["2", "c9", ""]]);
+    /// ```
+    pub fn enrich_from(
+        &mut self,
+        other: &CSVFile,
+        key_self: &String,
+        key_other: &String,
+        columns_to_copy: &Vec<String>,
+    ) -> Result<(), Error> {
+        let self_key = column_idx(self, key_self)?;
+        let other_key = column_idx(other, key_other)?;
+        let copied = columns_to_copy
+            .iter()
+            .map(|column| column_idx(other, column))
+            .collect::<Result<Vec<usize>, Error>>()?;
+        for (i, column) in columns_to_copy.iter().enumerate() {
+            if self.has_column(column) || columns_to_copy[..i].contains(column) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} already exists", column),
+                ));
+            }
+        }
+
+        let mut index: HashMap<&String, &Vec<String>> = HashMap::new();
+        for row in &other.rows {
+            if let Some(key) = row.get(other_key) {
+                index.entry(key).or_insert(row);
+            }
+        }
+
+        let width = self.columns.len();
+        for row in &mut self.rows {
+            // The short rows are completed so that the copied values land in their columns.
+            if row.len() < width {
+                row.resize(width, String::new());
+            }
+            match index.get(&row[self_key]) {
+                Some(found) => row.extend(
+                    copied
+                        .iter()
+                        .map(|&idx| found.get(idx).cloned().unwrap_or_default()),
+                ),
+                None => row.extend(vec![String::new(); copied.len()]),
+            }
+        }
+        for column in columns_to_copy {
+            self.columns.push(column.clone());
+            self.emit(ChangeEvent::ColumnAdded {
+                column: self.columns.len() - 1,
+                name: column.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rows whose value in the key column doesn't appear
+    /// in the same column of another file, for example to find the records
+    /// missing from the export of a second system.
+    /// The keys missing from the ragged rows of either file are read as empty values.
+    /// It may return an error if one of the files doesn't have the column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let crm = CSVFile::from_text("email,name\na@x.org,A\nb@x.org,B\n", &ReaderOptions::default()).unwrap();
+    /// let billing = CSVFile::from_text("email\nb@x.org\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let missing = crm.rows_not_in(&billing, &"email".to_string()).unwrap();
+    /// assert_eq!(missing.rows, vec![vec!["a@x.org", "A"]]);
+    /// ```
+    pub fn rows_not_in(&self, other: &CSVFile, key_column: &String) -> Result<CSVFile, Error> {
+        let self_key = column_idx(self, key_column)?;
+        let other_key = column_idx(other, key_column)?;
+
+        let keys: HashSet<&str> = other
+            .rows
+            .iter()
+            .map(|row| row.get(other_key).map_or("", String::as_str))
+            .collect();
+        let rows = self
+            .rows
+            .iter()
+            .filter(|row| !keys.contains(row.get(self_key).map_or("", String::as_str)))
+            .cloned()
+            .collect();
+
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            self.columns.clone(),
+            rows,
+        ))
+    }
+
+    /// Compares this file, holding the expected values, to another one, matching the rows by the value of a key column,
+    /// for example to test the output of a data pipeline against a reference file.
+    /// The values of the columns found in both files are compared: two numbers differ if they're
+    /// further apart than `numeric_tolerance`, so the slight variations of floating-point computations
+    /// aren't reported, and the other values differ if their text is different.
+    ///
+    /// Each row is compared to the first row of `other` that has the same key.
+    /// The keys and the columns found in only one of the files are reported too.
+    /// The values missing from the ragged rows of either file are compared as empty values.
+    ///
+    /// It may return an error if one of the files doesn't have the key column,
+    /// or if the tolerance is negative or not a number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, DifferenceKind, ReaderOptions};
+    /// let expected = CSVFile::from_text("id,total,status\n1,10.0,ok\n2,3.5,ok\n", &ReaderOptions::default()).unwrap();
+    /// let actual = CSVFile::from_text("id,total,status\n1,10.0000001,ok\n2,3.6,late\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let comparison = expected.compare_columns(&actual, &"id".to_string(), 1e-6).unwrap();
+    /// assert_eq!(comparison.differences.len(), 2);
+    /// assert_eq!(comparison.differences[0].column, "total");
+    /// assert!(matches!(comparison.differences[0].kind, DifferenceKind::Numeric { .. }));
+    /// assert_eq!(comparison.differences[1].kind, DifferenceKind::Text);
+    /// assert_eq!(comparison.differences[1].actual, "late");
+    /// ```
+    pub fn compare_columns(
+        &self,
+        other: &CSVFile,
+        key_column: &String,
+        numeric_tolerance: f64,
+    ) -> Result<ColumnComparison, Error> {
+        let self_key = column_idx(self, key_column)?;
+        let other_key = column_idx(other, key_column)?;
+        if numeric_tolerance.is_nan() || numeric_tolerance < 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The tolerance {} is invalid", numeric_tolerance),
+            ));
+        }
+
+        let mut comparison = ColumnComparison::default();
+        let mut compared: Vec<(&String, usize, usize)> = Vec::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            if i == self_key {
+                continue;
+            }
+            match other.get_column_idx(column) {
+                Some(j) => compared.push((column, i, j)),
+                None => comparison.missing_columns.push(column.clone()),
+            }
+        }
+        comparison.extra_columns = other
+            .columns
+            .iter()
+            .filter(|column| self.get_column_idx(column).is_none())
+            .cloned()
+            .collect();
+
+        let mut index: HashMap<&str, &Vec<String>> = HashMap::new();
+        for row in &other.rows {
+            index.entry(cell(row, other_key)).or_insert(row);
+        }
+
+        for row in &self.rows {
+            let key = cell(row, self_key);
+            let Some(found) = index.get(key) else {
+                comparison.missing_keys.push(key.to_string());
+                continue;
+            };
+            for &(column, i, j) in &compared {
+                let (expected, actual) = (cell(row, i), cell(found, j));
+                let kind = match (parse_number(expected), parse_number(actual)) {
+                    (Some(a), Some(b)) if (b - a).abs() > numeric_tolerance => {
+                        DifferenceKind::Numeric { difference: b - a }
+                    }
+                    (Some(_), Some(_)) => continue,
+                    _ if expected != actual => DifferenceKind::Text,
+                    _ => continue,
+                };
+                comparison.differences.push(CellDifference {
+                    key: key.to_string(),
+                    column: column.clone(),
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                    kind,
+                });
+            }
+        }
+
+        let keys: HashSet<&str> = self.rows.iter().map(|row| cell(row, self_key)).collect();
+        let mut seen: HashSet<&str> = HashSet::new();
+        for row in &other.rows {
+            let key = cell(row, other_key);
+            if !keys.contains(key) && seen.insert(key) {
+                comparison.extra_keys.push(key.to_string());
+            }
+        }
+
+        Ok(comparison)
+    }
+}
@@ -0,0 +1,183 @@
+//! The sidecar manifests listing the fingerprints of an exported file,
+//! so that its recipients can check that it wasn't altered.
+
+use std::io::{Error, ErrorKind};
+
+use crate::fingerprint::bytes_hash;
+use crate::json::{self, JsonValue};
+use crate::{CSVFile, ReaderOptions, WriterOptions};
+
+/// The extension added to the name of the CSV file to get the name of its manifest.
+const MANIFEST_EXTENSION: &str = ".manifest.json";
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+/// Formats a fingerprint as 16 hexadecimal digits,
+/// since JSON numbers can't hold all the 64-bit integers.
+fn format_hash(hash: u64) -> JsonValue {
+    JsonValue::String(format!("{:016x}", hash))
+}
+
+/// The differences between a CSV file and its manifest,
+/// found by [verify_manifest](`CSVFile::verify_manifest`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManifestReport {
+    /// Whether the bytes of the file are exactly the ones it had when it was written.
+    pub file_hash_matches: bool,
+    /// The number of rows listed in the manifest.
+    pub expected_rows: usize,
+    /// The number of rows of the file.
+    pub rows: usize,
+    /// The columns whose values changed.
+    pub changed_columns: Vec<String>,
+    /// The columns of the manifest that are no longer in the file.
+    pub missing_columns: Vec<String>,
+    /// The columns of the file that aren't in the manifest.
+    pub extra_columns: Vec<String>,
+}
+
+impl ManifestReport {
+    /// Returns `true` if the file wasn't modified since the manifest was written.
+    pub fn is_valid(&self) -> bool {
+        self.file_hash_matches
+            && self.expected_rows == self.rows
+            && self.changed_columns.is_empty()
+            && self.missing_columns.is_empty()
+            && self.extra_columns.is_empty()
+    }
+}
+
+impl CSVFile {
+    /// Writes the CSV file along with a manifest, in a sidecar file named like the file
+    /// followed by `.manifest.json`, listing the number of rows, the columns
+    /// with the fingerprint of their values (see [column_hash](`CSVFile::column_hash`))
+    /// and the fingerprint of the whole file, so that the recipients can check its integrity
+    /// with [verify_manifest](`CSVFile::verify_manifest`).
+    ///
+    /// The fingerprints aren't cryptographic: they detect accidental modifications,
+    /// not deliberate ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("id,name\n1,Thomas\n", &ReaderOptions::default()).unwrap();
+    /// file.write_with_manifest(&"manifest_example.csv".to_string()).unwrap();
+    ///
+    /// let report = CSVFile::verify_manifest(&"manifest_example.csv".to_string()).unwrap();
+    /// assert!(report.is_valid());
+    ///
+    /// std::fs::write("manifest_example.csv", "id,name\n1,John\n").unwrap();
+    /// let report = CSVFile::verify_manifest(&"manifest_example.csv".to_string()).unwrap();
+    /// assert!(!report.file_hash_matches);
+    /// assert_eq!(report.changed_columns, vec!["name"]);
+    /// # std::fs::remove_file("manifest_example.csv").unwrap();
+    /// # std::fs::remove_file("manifest_example.csv.manifest.json").unwrap();
+    /// ```
+    pub fn write_with_manifest(&self, filename: &String) -> Result<(), Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.write_to(&mut bytes, &WriterOptions::default())?;
+        std::fs::write(filename, &bytes)?;
+
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| {
+                JsonValue::Object(vec![
+                    ("name".to_string(), JsonValue::String(column.clone())),
+                    (
+                        "hash".to_string(),
+                        format_hash(self.column_hash(column).unwrap_or_default()),
+                    ),
+                ])
+            })
+            .collect();
+        let manifest = JsonValue::Object(vec![
+            (
+                "delimiter".to_string(),
+                JsonValue::String(self.delimiter.to_string()),
+            ),
+            (
+                "rows".to_string(),
+                JsonValue::Number(self.rows.len() as f64),
+            ),
+            ("columns".to_string(), JsonValue::Array(columns)),
+            ("file_hash".to_string(), format_hash(bytes_hash(&bytes))),
+        ]);
+        std::fs::write(
+            filename.clone() + MANIFEST_EXTENSION,
+            manifest.to_pretty_string() + "\n",
+        )
+    }
+
+    /// Checks a CSV file against the manifest written by
+    /// [write_with_manifest](`CSVFile::write_with_manifest`), and reports the differences.
+    ///
+    /// It may return an error if one of the two files can't be read,
+    /// or if the manifest is invalid.
+    pub fn verify_manifest(filename: &String) -> Result<ManifestReport, Error> {
+        let manifest = json::parse(&std::fs::read_to_string(
+            filename.clone() + MANIFEST_EXTENSION,
+        )?)?;
+        let bytes = std::fs::read(filename)?;
+
+        let mut chars = manifest
+            .get("delimiter")
+            .and_then(JsonValue::as_str)
+            .unwrap_or(",")
+            .chars();
+        let delimiter = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(invalid("The delimiter must be a single character")),
+        };
+        let expected_rows = match manifest.get("rows") {
+            Some(JsonValue::Number(rows)) => *rows as usize,
+            _ => return Err(invalid("The manifest must have a number of rows")),
+        };
+        let Some(JsonValue::Array(columns)) = manifest.get("columns") else {
+            return Err(invalid("The manifest must have an array of columns"));
+        };
+        let file_hash = manifest.get("file_hash").and_then(JsonValue::as_str);
+
+        let file = CSVFile::from_reader(
+            bytes.as_slice(),
+            &ReaderOptions {
+                delimiter,
+                ..ReaderOptions::default()
+            },
+        )?;
+        let mut report = ManifestReport {
+            file_hash_matches: file_hash == format_hash(bytes_hash(&bytes)).as_str(),
+            expected_rows,
+            rows: file.rows.len(),
+            ..ManifestReport::default()
+        };
+        let mut listed: Vec<&str> = Vec::new();
+        for column in columns {
+            let name = column
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| invalid("Each column of the manifest must have a name"))?;
+            listed.push(name);
+            match file.column_hash(&name.to_string()) {
+                None => report.missing_columns.push(name.to_string()),
+                Some(hash) => {
+                    if column.get("hash").and_then(JsonValue::as_str) != format_hash(hash).as_str()
+                    {
+                        report.changed_columns.push(name.to_string());
+                    }
+                }
+            }
+        }
+        report.extra_columns = file
+            .columns
+            .iter()
+            .filter(|column| !listed.contains(&column.as_str()))
+            .cloned()
+            .collect();
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,115 @@
+//! Recoding the values of a column with a translation table.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+/// What [map_values](`CSVFile::map_values`) does with the values missing from the table.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum MissPolicy {
+    /// The value is kept as is.
+    #[default]
+    Keep,
+    /// The value is replaced by an empty string.
+    Clear,
+    /// The value is replaced by the given one.
+    Default(String),
+    /// Nothing is modified and an error is returned.
+    Error,
+}
+
+/// The result of [map_values](`CSVFile::map_values`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MappingReport {
+    /// The number of values found in the table.
+    pub mapped: usize,
+    /// The values missing from the table, with their number of occurrences.
+    pub unmapped: BTreeMap<String, usize>,
+}
+
+impl MappingReport {
+    /// Returns `true` if all the values were found in the table.
+    pub fn is_complete(&self) -> bool {
+        self.unmapped.is_empty()
+    }
+}
+
+impl CSVFile {
+    /// Replaces the values of a column by the ones they're associated with in the table,
+    /// for example to turn country codes into country names, and reports
+    /// the values that weren't in the table.
+    /// The values missing from the ragged rows are looked up as empty values.
+    ///
+    /// It may return an error if the column doesn't exist, or if some values
+    /// aren't in the table with [MissPolicy::Error](`MissPolicy::Error`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, MissPolicy, ReaderOptions};
+    /// # use std::collections::HashMap;
+    /// let mut file = CSVFile::from_text("country\nUS\nFR\nXX\nUS\n", &ReaderOptions::default()).unwrap();
+    /// let table = HashMap::from([
+    ///     ("US".to_string(), "United States".to_string()),
+    ///     ("FR".to_string(), "France".to_string()),
+    /// ]);
+    ///
+    /// let report = file.map_values(&"country".to_string(), &table, MissPolicy::Keep).unwrap();
+    /// assert_eq!(report.mapped, 3);
+    /// assert_eq!(report.unmapped.get("XX"), Some(&1));
+    /// assert_eq!(file.rows[0][0], "United States");
+    /// assert_eq!(file.rows[2][0], "XX");
+    /// ```
+    pub fn map_values(
+        &mut self,
+        column_name: &String,
+        table: &HashMap<String, String>,
+        policy: MissPolicy,
+    ) -> Result<MappingReport, Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+
+        let mut report = MappingReport::default();
+        let values: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let value = row.get(idx).map_or("", String::as_str);
+                match table.get(value) {
+                    Some(mapped) => {
+                        report.mapped += 1;
+                        mapped.clone()
+                    }
+                    None => {
+                        *report.unmapped.entry(value.to_string()).or_insert(0) += 1;
+                        match &policy {
+                            MissPolicy::Keep | MissPolicy::Error => value.to_string(),
+                            MissPolicy::Clear => String::new(),
+                            MissPolicy::Default(default) => default.clone(),
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        if policy == MissPolicy::Error && !report.is_complete() {
+            let missing: Vec<&String> = report.unmapped.keys().collect();
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The values {:?} of the column {} aren't in the table",
+                    missing, column_name
+                ),
+            ));
+        }
+
+        self.fill_column(column_name, &values)?;
+
+        Ok(report)
+    }
+}
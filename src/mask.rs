@@ -0,0 +1,199 @@
+//! Anonymization of the columns holding personal data.
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+/// The names given by [MaskKind::FakeName](`MaskKind::FakeName`).
+const FAKE_FIRST_NAMES: [&str; 16] = [
+    "Alex",
+    "Billie",
+    "Camille",
+    "Dominique",
+    "Eden",
+    "Frankie",
+    "Gabriel",
+    "Harper",
+    "Jordan",
+    "Kim",
+    "Logan",
+    "Morgan",
+    "Noa",
+    "Robin",
+    "Sacha",
+    "Taylor",
+];
+const FAKE_LAST_NAMES: [&str; 16] = [
+    "Adams", "Bernard", "Carter", "Dubois", "Evans", "Fontaine", "Garcia", "Hughes", "Ito",
+    "Jensen", "Klein", "Lambert", "Martin", "Nguyen", "Olsen", "Petit",
+];
+
+/// How the values of a column are anonymized by [mask_column](`CSVFile::mask_column`).
+/// Empty values are always left empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskKind {
+    /// Replaces the value by the hexadecimal SHA-256 hash of the salt followed by the value.
+    /// The same value always gets the same hash, so the column can still be
+    /// used to join files masked with the same salt, while the salt, kept secret,
+    /// prevents recovering the values by hashing guesses.
+    Hash(String),
+    /// Replaces the value by `***`.
+    Redact,
+    /// Replaces all the characters but the last ones by `*`, as in `************4242`.
+    PartialKeepLast(usize),
+    /// Replaces the value by a made-up name picked from the salted hash of the value,
+    /// as for [Hash](`MaskKind::Hash`), so that a value always gets the same name
+    /// with a given salt, while the names can't be linked across files masked with different salts.
+    FakeName(String),
+}
+
+impl MaskKind {
+    /// Anonymizes a single value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::MaskKind;
+    /// assert_eq!(MaskKind::PartialKeepLast(4).apply("4970101234564242"), "************4242");
+    /// assert_eq!(MaskKind::Redact.apply("secret"), "***");
+    /// assert_eq!(MaskKind::Hash("salt".to_string()).apply("a").len(), 64);
+    /// let fake_name = MaskKind::FakeName("salt".to_string());
+    /// assert_eq!(fake_name.apply("Thomas"), fake_name.apply("Thomas"));
+    /// ```
+    pub fn apply(&self, value: &str) -> String {
+        if value.is_empty() {
+            return String::new();
+        }
+        match self {
+            MaskKind::Hash(salt) => {
+                let digest = sha256(format!("{}{}", salt, value).as_bytes());
+                let mut hex = String::with_capacity(64);
+                for byte in digest {
+                    let _ = write!(hex, "{:02x}", byte);
+                }
+                hex
+            }
+            MaskKind::Redact => "***".to_string(),
+            MaskKind::PartialKeepLast(n) => {
+                let length = value.chars().count();
+                let hidden = length.saturating_sub(*n);
+                "*".repeat(hidden) + &value.chars().skip(hidden).collect::<String>()
+            }
+            MaskKind::FakeName(salt) => {
+                let digest = sha256(format!("{}{}", salt, value).as_bytes());
+                let first = FAKE_FIRST_NAMES[digest[0] as usize % FAKE_FIRST_NAMES.len()];
+                let last = FAKE_LAST_NAMES[digest[1] as usize % FAKE_LAST_NAMES.len()];
+                format!("{} {}", first, last)
+            }
+        }
+    }
+}
+
+impl CSVFile {
+    /// Anonymizes all the values of a column, for example to share a file with analysts
+    /// once the personal data has been removed.
+    /// It may return an error if the column doesn't exist.
+    /// The values missing from the ragged rows are left empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, MaskKind, ReaderOptions};
+    /// let text = "email,card\nthomas@example.com,4970101234564242\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// file.mask_column(&"email".to_string(), &MaskKind::Hash("my secret salt".to_string())).unwrap();
+    /// file.mask_column(&"card".to_string(), &MaskKind::PartialKeepLast(4)).unwrap();
+    /// assert_ne!(file.rows[0][0], "thomas@example.com");
+    /// assert_eq!(file.rows[0][1], "************4242");
+    /// ```
+    pub fn mask_column(&mut self, column_name: &String, kind: &MaskKind) -> Result<(), Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+
+        let values: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| kind.apply(row.get(idx).map_or("", String::as_str)))
+            .collect();
+        self.fill_column(column_name, &values)
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Computes the SHA-256 digest of the data (FIPS 180-4).
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (i, s) in state.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&s.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,146 @@
+//! Metadata attached to the columns of a CSV file (description, unit, source...),
+//! which can be saved next to the file as a JSON sidecar.
+//!
+//! The sidecar has the following shape, the columns being listed in the order of the file:
+//!
+//! ```json
+//! {
+//!   "columns": {
+//!     "weight": {
+//!       "unit": "kg"
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+
+use crate::json::{self, JsonValue};
+use crate::CSVFile;
+
+impl CSVFile {
+    /// Attaches a piece of metadata to a column, replacing the previous value of the key.
+    /// It may return an error if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// let columns = vec!["weight".to_string()];
+    /// let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+    ///
+    /// file.set_column_meta(&"weight".to_string(), &"unit".to_string(), &"kg".to_string()).unwrap();
+    /// assert_eq!(file.get_column_meta(&"weight".to_string(), &"unit".to_string()), Some(&"kg".to_string()));
+    /// ```
+    pub fn set_column_meta(
+        &mut self,
+        column_name: &String,
+        key: &String,
+        value: &String,
+    ) -> Result<(), Error> {
+        let column_name = match self.resolve_column(column_name) {
+            Some(name) => name.clone(),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", column_name),
+                ))
+            }
+        };
+
+        self.metadata
+            .entry(column_name)
+            .or_default()
+            .insert(key.clone(), value.clone());
+
+        Ok(())
+    }
+
+    /// Gets a piece of metadata of a column.
+    pub fn get_column_meta(&self, column_name: &String, key: &String) -> Option<&String> {
+        self.metadata
+            .get(self.resolve_column(column_name)?)?
+            .get(key)
+    }
+
+    /// Gets all the metadata of a column, sorted by key.
+    pub fn column_meta(&self, column_name: &String) -> Option<&BTreeMap<String, String>> {
+        self.metadata.get(self.resolve_column(column_name)?)
+    }
+
+    /// Removes a piece of metadata from a column and returns its value.
+    pub fn remove_column_meta(&mut self, column_name: &String, key: &String) -> Option<String> {
+        let column_name = self.resolve_column(column_name)?.clone();
+        let metadata = self.metadata.get_mut(&column_name)?;
+        let value = metadata.remove(key);
+        if metadata.is_empty() {
+            self.metadata.remove(&column_name);
+        }
+        value
+    }
+
+    /// Serializes the metadata of the columns as JSON.
+    pub fn metadata_to_json(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .filter_map(|column| {
+                let metadata = self.metadata.get(column)?;
+                let members = metadata
+                    .iter()
+                    .map(|(key, value)| (key.clone(), JsonValue::String(value.clone())))
+                    .collect();
+                Some((column.clone(), JsonValue::Object(members)))
+            })
+            .collect();
+
+        let document = JsonValue::Object(vec![("columns".to_string(), JsonValue::Object(columns))]);
+        document.to_pretty_string() + "\n"
+    }
+
+    /// Loads the metadata of the columns from JSON, as written by
+    /// [metadata_to_json](`CSVFile::metadata_to_json`). The loaded keys are added
+    /// to the existing metadata, and the columns the file doesn't have are ignored.
+    /// It may return an error if the JSON is invalid or doesn't have the expected shape.
+    pub fn load_metadata_json(&mut self, text: &str) -> Result<(), Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidData,
+                "The metadata must be an object of columns, each holding an object of strings",
+            )
+        };
+
+        let document = json::parse(text)?;
+        let columns = match document.get("columns") {
+            Some(JsonValue::Object(columns)) => columns,
+            _ => return Err(invalid()),
+        };
+        for (column, metadata) in columns {
+            let JsonValue::Object(members) = metadata else {
+                return Err(invalid());
+            };
+            for (key, value) in members {
+                let value = value.as_str().ok_or_else(invalid)?;
+                if self.has_column(column) {
+                    self.set_column_meta(column, key, &value.to_string())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the metadata of the columns to a JSON sidecar file.
+    #[cfg(feature = "fs")]
+    pub fn write_metadata(&self, filename: &String) -> Result<(), Error> {
+        std::fs::write(filename, self.metadata_to_json())
+    }
+
+    /// Loads the metadata of the columns from a JSON sidecar file.
+    /// See [load_metadata_json](`CSVFile::load_metadata_json`).
+    #[cfg(feature = "fs")]
+    pub fn read_metadata(&mut self, filename: &String) -> Result<(), Error> {
+        self.load_metadata_json(&std::fs::read_to_string(filename)?)
+    }
+}
@@ -0,0 +1,265 @@
+//! Cleaning of the bytes of a file before parsing it: line endings, byte order mark and encoding.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Write};
+
+use crate::LineEnding;
+
+/// The characters of the bytes 0x80 to 0x9F in Windows-1252,
+/// the bytes it doesn't define being kept as the control characters of Latin-1.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// The encoding of the file given to [normalize_file](`normalize_file`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceEncoding {
+    /// UTF-8, the invalid sequences being replaced by `U+FFFD`.
+    /// A file starting with a UTF-16 byte order mark is read as UTF-16 instead.
+    #[default]
+    Utf8,
+    /// UTF-16, little-endian, the unpaired surrogates being replaced by `U+FFFD`.
+    Utf16Le,
+    /// UTF-16, big-endian, the unpaired surrogates being replaced by `U+FFFD`.
+    Utf16Be,
+    /// ISO-8859-1, where each byte is the character of the same code point.
+    Latin1,
+    /// Windows-1252, the superset of Latin-1 used by Excel on Windows in western countries.
+    Windows1252,
+}
+
+/// The options used by [normalize_file](`normalize_file`).
+#[derive(Debug, Clone)]
+pub struct NormalizeOptions {
+    /// The encoding of the input ([SourceEncoding::Utf8](`SourceEncoding::Utf8`) by default).
+    /// The output is always in UTF-8.
+    pub encoding: SourceEncoding,
+    /// The line ending that replaces every `\r\n`, `\n` and lone `\r` ([LineEnding::Lf](`LineEnding::Lf`) by default).
+    pub line_ending: LineEnding,
+    /// Removes the byte order mark at the beginning of the file (`true` by default).
+    pub strip_bom: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            encoding: SourceEncoding::Utf8,
+            line_ending: LineEnding::Lf,
+            strip_bom: true,
+        }
+    }
+}
+
+/// What [normalize_file](`normalize_file`) changed in a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizeReport {
+    /// The number of line breaks that didn't use the line ending of the options.
+    pub line_breaks_replaced: usize,
+    /// The number of invalid sequences replaced by `U+FFFD`.
+    pub invalid_sequences: usize,
+    /// Whether a byte order mark was removed.
+    pub byte_order_mark_removed: bool,
+}
+
+/// Decodes the chunks of a file, keeping the bytes of a character cut at the end of a chunk for the next one.
+struct Decoder {
+    encoding: SourceEncoding,
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    fn decode(&mut self, bytes: &[u8], text: &mut String, report: &mut NormalizeReport) {
+        match self.encoding {
+            SourceEncoding::Latin1 => text.extend(bytes.iter().map(|&b| b as char)),
+            SourceEncoding::Windows1252 => text.extend(bytes.iter().map(|&b| match b {
+                0x80..=0x9f => WINDOWS_1252[(b - 0x80) as usize],
+                _ => b as char,
+            })),
+            SourceEncoding::Utf8 => {
+                self.pending.extend_from_slice(bytes);
+                let mut start = 0;
+                loop {
+                    match std::str::from_utf8(&self.pending[start..]) {
+                        Ok(valid) => {
+                            text.push_str(valid);
+                            start = self.pending.len();
+                            break;
+                        }
+                        Err(e) => {
+                            let end = start + e.valid_up_to();
+                            // the bytes up to `valid_up_to` are always valid
+                            text.push_str(
+                                std::str::from_utf8(&self.pending[start..end]).unwrap_or_default(),
+                            );
+                            match e.error_len() {
+                                Some(length) => {
+                                    text.push('\u{fffd}');
+                                    report.invalid_sequences += 1;
+                                    start = end + length;
+                                }
+                                // the character continues in the next chunk
+                                None => {
+                                    start = end;
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                self.pending.drain(..start);
+            }
+            SourceEncoding::Utf16Le | SourceEncoding::Utf16Be => {
+                self.pending.extend_from_slice(bytes);
+                let mut units: Vec<u16> = self
+                    .pending
+                    .chunks_exact(2)
+                    .map(|pair| match self.encoding {
+                        SourceEncoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                        _ => u16::from_be_bytes([pair[0], pair[1]]),
+                    })
+                    .collect();
+                let mut consumed = units.len() * 2;
+                // a high surrogate whose low surrogate is in the next chunk
+                if units
+                    .last()
+                    .is_some_and(|unit| (0xd800..0xdc00).contains(unit))
+                {
+                    units.pop();
+                    consumed -= 2;
+                }
+                for c in char::decode_utf16(units) {
+                    text.push(c.unwrap_or_else(|_| {
+                        report.invalid_sequences += 1;
+                        '\u{fffd}'
+                    }));
+                }
+                self.pending.drain(..consumed);
+            }
+        }
+    }
+
+    /// Replaces the bytes of an incomplete character at the end of the file.
+    fn finish(&mut self, text: &mut String, report: &mut NormalizeReport) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            text.push('\u{fffd}');
+            report.invalid_sequences += 1;
+        }
+    }
+}
+
+/// Writes a cleaned copy of a file for the parsers that are strict about what they read:
+/// the file is re-encoded to UTF-8, every line break (`\r\n`, `\n` or a lone `\r`)
+/// is replaced by the line ending of the options, and the byte order mark is removed.
+/// The file is read and written in a single pass, a chunk at a time, so it doesn't have to fit in memory.
+///
+/// The line breaks inside quoted values are replaced too, since the file isn't parsed.
+///
+/// It may return an error if the input can't be read, if the output can't be written,
+/// or if both are the same file.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{normalize_file, NormalizeOptions, SourceEncoding};
+/// # let input = std::env::temp_dir().join("csv_tools_normalize_doc_in.csv").to_string_lossy().to_string();
+/// # let output = std::env::temp_dir().join("csv_tools_normalize_doc_out.csv").to_string_lossy().to_string();
+/// std::fs::write(&input, b"name,city\r\nJos\xe9,K\xf6ln\n").unwrap();
+///
+/// let options = NormalizeOptions { encoding: SourceEncoding::Windows1252, ..NormalizeOptions::default() };
+/// let report = normalize_file(&input, &output, &options).unwrap();
+/// assert_eq!(report.line_breaks_replaced, 1);
+/// assert_eq!(std::fs::read_to_string(&output).unwrap(), "name,city\nJosé,Köln\n");
+/// # std::fs::remove_file(&input).unwrap();
+/// # std::fs::remove_file(&output).unwrap();
+/// ```
+pub fn normalize_file(
+    input: &String,
+    output: &String,
+    options: &NormalizeOptions,
+) -> Result<NormalizeReport, Error> {
+    if fs::canonicalize(output).is_ok_and(|path| fs::canonicalize(input).is_ok_and(|p| p == path)) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The input and the output of a normalization must be different files",
+        ));
+    }
+
+    let mut reader = BufReader::with_capacity(64 * 1024, File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    let line_ending = options.line_ending.as_str();
+    let mut report = NormalizeReport::default();
+    let mut decoder = Decoder {
+        encoding: options.encoding,
+        pending: Vec::new(),
+    };
+    let mut at_start = true;
+    let mut pending_cr = false;
+    let mut text = String::new();
+    let mut cleaned = String::new();
+    loop {
+        let chunk = reader.fill_buf()?;
+        let is_last = chunk.is_empty();
+        if at_start && decoder.encoding == SourceEncoding::Utf8 {
+            if chunk.starts_with(&[0xff, 0xfe]) {
+                decoder.encoding = SourceEncoding::Utf16Le;
+            } else if chunk.starts_with(&[0xfe, 0xff]) {
+                decoder.encoding = SourceEncoding::Utf16Be;
+            }
+        }
+        text.clear();
+        if is_last {
+            decoder.finish(&mut text, &mut report);
+        } else {
+            decoder.decode(chunk, &mut text, &mut report);
+        }
+        let length = chunk.len();
+        reader.consume(length);
+
+        cleaned.clear();
+        for c in text.chars() {
+            if at_start {
+                at_start = false;
+                if c == '\u{feff}' && options.strip_bom {
+                    report.byte_order_mark_removed = true;
+                    continue;
+                }
+            }
+            if pending_cr {
+                pending_cr = false;
+                cleaned.push_str(line_ending);
+                if c == '\n' {
+                    if options.line_ending != LineEnding::CrLf {
+                        report.line_breaks_replaced += 1;
+                    }
+                    continue;
+                }
+                report.line_breaks_replaced += 1;
+            }
+            match c {
+                '\r' => pending_cr = true,
+                '\n' => {
+                    cleaned.push_str(line_ending);
+                    if options.line_ending != LineEnding::Lf {
+                        report.line_breaks_replaced += 1;
+                    }
+                }
+                _ => cleaned.push(c),
+            }
+        }
+        if is_last && pending_cr {
+            cleaned.push_str(line_ending);
+            report.line_breaks_replaced += 1;
+        }
+        writer.write_all(cleaned.as_bytes())?;
+        if is_last {
+            break;
+        }
+    }
+    writer.flush()?;
+
+    Ok(report)
+}
@@ -0,0 +1,262 @@
+//! Options used to customize the way CSV files are read and written.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{CancellationToken, ColumnFormat, Escape, ProgressHook};
+
+/// Controls which values get their leading and trailing whitespace removed while reading a CSV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Trim {
+    /// Keeps every value as it is written in the file.
+    #[default]
+    None,
+    /// Trims the names of the columns only.
+    Headers,
+    /// Trims the values of the rows only.
+    Fields,
+    /// Trims both the names of the columns and the values of the rows.
+    All,
+}
+
+impl Trim {
+    /// Returns `true` if the names of the columns should be trimmed.
+    pub fn headers(&self) -> bool {
+        matches!(self, Trim::Headers | Trim::All)
+    }
+
+    /// Returns `true` if the values of the rows should be trimmed.
+    pub fn fields(&self) -> bool {
+        matches!(self, Trim::Fields | Trim::All)
+    }
+}
+
+/// The options used by [CSVFile::new_with_options](`crate::CSVFile::new_with_options`)
+/// to read a CSV file.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{ReaderOptions, Trim};
+/// let options = ReaderOptions {
+///     trim: Trim::All,
+///     ..ReaderOptions::default()
+/// };
+///
+/// assert_eq!(options.delimiter, ',');
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReaderOptions {
+    /// The character separating the values (a comma by default).
+    pub delimiter: char,
+    /// How the double quotes are escaped inside quoted values ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+    /// Which values should have their surrounding whitespace removed ([Trim::None](`Trim::None`) by default).
+    pub trim: Trim,
+    /// Remembers which cells were surrounded by double quotes, so that exactly
+    /// those cells are quoted again when the file is written (`false` by default).
+    pub preserve_quotes: bool,
+    /// Keeps the original line of each row, so that the rows that weren't modified
+    /// are written back exactly as they were read, byte for byte (`false` by default).
+    /// Only the modified rows are serialized again, with the line breaks of the source.
+    pub lossless: bool,
+    /// Reads a header spanning two rows, a row of groups above the row of the columns,
+    /// as spreadsheet applications often export them (`None` by default).
+    /// Each column is named after its group and its own name, joined with the given separator,
+    /// such as `Sales.Q1`. An empty group is the continuation of the group on its left,
+    /// and the columns before the first group keep their own name.
+    pub multi_header: Option<String>,
+    /// Only keeps the given columns, in the given order (`None` by default, keeping them all).
+    /// The other values are skipped while parsing instead of being stored and removed later.
+    pub projection: Option<Vec<String>>,
+    /// Skips the columns whose name matches the condition (`None` by default),
+    /// set with [skip_columns](`ReaderOptions::skip_columns`).
+    /// Like the columns left out of the projection, their values are never stored.
+    pub skipped_columns: Option<ColumnPredicate>,
+    /// The values meaning that there is no value, such as `NA`, `NULL`, `N/A` or `-`,
+    /// which are read as empty values (none by default, only the empty values are null).
+    /// Since they become empty, the statistics, the schemas and the typed values
+    /// all treat them as missing. The comparison is case-sensitive, and happens after [trim](`ReaderOptions::trim`).
+    pub null_values: Vec<String>,
+    /// The maximum size of a value in bytes (`None` by default, without limit).
+    /// A longer value makes the reading fail, for example when an unclosed quote
+    /// swallows the rest of an untrusted file into a single value.
+    pub max_field_size: Option<usize>,
+    /// The maximum size of a record in bytes, line breaks excluded (`None` by default, without limit).
+    /// A longer record makes the reading fail before it's entirely held in memory,
+    /// so that a malformed file can't use an unbounded amount of memory.
+    /// It applies to the header too.
+    pub max_record_size: Option<usize>,
+    /// Notified of the number of rows and bytes read so far (`None` by default).
+    pub on_progress: Option<ProgressHook>,
+    /// Aborts the reading once cancelled (`None` by default).
+    pub cancellation: Option<CancellationToken>,
+    /// Returns the total number of allocations made by the program so far,
+    /// typically from a counting global allocator defined by the application (`None` by default).
+    /// When set, the allocations made while reading are reported in [ReadStats](`crate::ReadStats`).
+    pub allocation_counter: Option<fn() -> u64>,
+    /// Returns the time elapsed since a fixed point, such as [system_clock](`crate::system_clock`)
+    /// or a wrapper around `performance.now()` in a browser (`None` by default).
+    /// When set, the time spent reading is reported in [ReadStats](`crate::ReadStats`).
+    /// The reading is never timed otherwise, since `std::time::Instant` isn't available on every target.
+    pub clock: Option<fn() -> Duration>,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            escape: Escape::Backslash,
+            trim: Trim::None,
+            preserve_quotes: false,
+            lossless: false,
+            multi_header: None,
+            projection: None,
+            skipped_columns: None,
+            null_values: Vec::new(),
+            max_field_size: None,
+            max_record_size: None,
+            on_progress: None,
+            cancellation: None,
+            allocation_counter: None,
+            clock: None,
+        }
+    }
+}
+
+impl ReaderOptions {
+    /// Creates the default options with a custom delimiter.
+    pub fn with_delimiter(delimiter: &char) -> Self {
+        Self {
+            delimiter: *delimiter,
+            ..Self::default()
+        }
+    }
+
+    /// Skips the columns whose name matches the condition, so that the very wide files
+    /// only materialize the columns the application needs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "time,sensor_1,sensor_2,debug_1,debug_2\n1,10,20,x,y\n";
+    /// let options = ReaderOptions::default().skip_columns(|name| name.starts_with("debug_"));
+    ///
+    /// let file = CSVFile::from_text(text, &options).unwrap();
+    /// assert_eq!(file.columns, vec!["time", "sensor_1", "sensor_2"]);
+    /// assert_eq!(file.rows, vec![vec!["1", "10", "20"]]);
+    /// ```
+    pub fn skip_columns<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.skipped_columns = Some(ColumnPredicate(Arc::new(predicate)));
+        self
+    }
+}
+
+/// A condition on the names of the columns, given to [ReaderOptions::skip_columns](`ReaderOptions::skip_columns`).
+#[derive(Clone)]
+pub struct ColumnPredicate(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl fmt::Debug for ColumnPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ColumnPredicate")
+    }
+}
+
+impl ColumnPredicate {
+    /// Returns `true` if the column matches the condition.
+    pub(crate) fn matches(&self, column_name: &str) -> bool {
+        (self.0)(column_name)
+    }
+}
+
+/// Which values get surrounded by double quotes when a CSV file is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// Only the values that need quotes to be read back (because they contain the delimiter,
+    /// a double quote or a line break), along with the ones that were quoted in the source.
+    #[default]
+    Necessary,
+    /// Every value, the names of the columns included.
+    Always,
+}
+
+/// The characters ending each line of a written CSV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, used on Unix systems.
+    #[default]
+    Lf,
+    /// `\r\n`, used on Windows and required by RFC 4180.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the characters ending each line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Returns the line ending of a line break read from a source, `None` if it's empty.
+    pub(crate) fn from_line_break(line_break: &str) -> Option<Self> {
+        match line_break {
+            "\n" => Some(LineEnding::Lf),
+            "\r\n" => Some(LineEnding::CrLf),
+            _ => None,
+        }
+    }
+}
+
+/// The options used by [CSVFile::write_with_options](`crate::CSVFile::write_with_options`)
+/// to write a CSV file.
+#[derive(Debug, Clone, Default)]
+pub struct WriterOptions {
+    /// Overrides the delimiter of the file (`None` by default, keeping the one of the file).
+    pub delimiter: Option<char>,
+    /// How the double quotes are escaped inside quoted values ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+    /// Which values are surrounded by double quotes ([QuoteStyle::Necessary](`QuoteStyle::Necessary`) by default).
+    pub quote_style: QuoteStyle,
+    /// Overrides the characters ending each line (`None` by default, keeping the ones of the source
+    /// the file was read from, or [LineEnding::Lf](`LineEnding::Lf`) if it wasn't read from a source).
+    pub line_ending: Option<LineEnding>,
+    /// Whether the file starts with a UTF-8 byte order mark, which Excel needs
+    /// to detect the encoding of the file (`None` by default, keeping the one of the source, if any).
+    pub byte_order_mark: Option<bool>,
+    /// The formatting rules of the columns, indexed by column name.
+    /// The in-memory data is never modified by these rules.
+    pub column_formats: HashMap<String, ColumnFormat>,
+    /// Splits the names of the columns at the first occurrence of the given separator
+    /// to write the header on two rows, the groups above the columns (`None` by default),
+    /// the way [ReaderOptions::multi_header](`ReaderOptions::multi_header`) reads them.
+    /// The name of a group is only written above its first column, and the columns
+    /// without a separator are written without a group.
+    pub multi_header: Option<String>,
+    /// Prefixes the values starting with `=`, `+`, `-` or `@` with a single quote,
+    /// so that spreadsheet applications don't execute them as formulas (`false` by default).
+    /// Plain numbers such as `-12.5` are left untouched.
+    pub escape_formulas: bool,
+    /// The text written instead of the empty values, such as `NULL` for a database
+    /// (empty by default). The names of the columns aren't affected.
+    pub null_value: String,
+    /// Notified of the number of rows and bytes written so far (`None` by default).
+    pub on_progress: Option<ProgressHook>,
+    /// Aborts the writing once cancelled (`None` by default).
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl WriterOptions {
+    /// Registers the formatting rules of a column,
+    /// replacing the previous ones if there were any.
+    pub fn set_column_format(&mut self, column_name: &String, format: ColumnFormat) {
+        self.column_formats.insert(column_name.clone(), format);
+    }
+}
@@ -0,0 +1,78 @@
+//! Reordering of the rows.
+
+use crate::CSVFile;
+
+/// A SplitMix64 generator: tiny, but good enough to shuffle rows,
+/// and always giving the same numbers for the same seed, whatever the platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl CSVFile {
+    /// Reorders the rows, `order` giving the index of the previous row at each new position,
+    /// and moves what is remembered about the source along with them.
+    /// The observers are told about the cells whose value changed.
+    fn reorder_rows(&mut self, order: &[usize]) {
+        let old_rows = self.is_observed().then(|| self.rows.clone());
+        let mut new_indexes = vec![0; order.len()];
+        for (new, old) in order.iter().enumerate() {
+            new_indexes[*old] = new;
+        }
+        let mut rows: Vec<Option<Vec<String>>> = self.rows.drain(..).map(Some).collect();
+        self.rows = order.iter().filter_map(|old| rows[*old].take()).collect();
+        self.remap_source(|r| new_indexes.get(r).copied(), Some);
+        for (i, old_row) in old_rows.iter().flatten().enumerate() {
+            self.emit_row_changes(i, old_row);
+        }
+    }
+
+    /// Reverses the order of the rows, for example to put the most recent rows first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("day\n1\n2\n3\n", &ReaderOptions::default()).unwrap();
+    /// file.reverse_rows();
+    /// assert_eq!(file.rows, vec![vec!["3"], vec!["2"], vec!["1"]]);
+    /// ```
+    pub fn reverse_rows(&mut self) {
+        let order: Vec<usize> = (0..self.rows.len()).rev().collect();
+        self.reorder_rows(&order);
+    }
+
+    /// Shuffles the rows randomly, always in the same way for a given seed,
+    /// so that the train and test sets split from a dataset can be reproduced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "n\n1\n2\n3\n4\n5\n";
+    /// let mut first = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    /// let mut second = first.clone();
+    ///
+    /// first.shuffle_rows(42);
+    /// second.shuffle_rows(42);
+    /// assert_eq!(first.rows, second.rows);
+    /// assert_eq!(first.rows.len(), 5);
+    /// ```
+    pub fn shuffle_rows(&mut self, seed: u64) {
+        let mut generator = SplitMix64(seed);
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        // Fisher-Yates, the modulo bias being negligible for 64-bit numbers.
+        for i in (1..order.len()).rev() {
+            let j = (generator.next() % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        self.reorder_rows(&order);
+    }
+}
@@ -0,0 +1,88 @@
+//! Detection of the numbers of a column that are far from the others.
+
+use crate::stats::parse_number;
+use crate::CSVFile;
+
+/// How [find_outliers](`CSVFile::find_outliers`) decides that a number is an outlier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierMethod {
+    /// The numbers below the first quartile or above the third quartile by more than
+    /// `factor` times the interquartile range (Tukey's fences, usually with a factor of 1.5).
+    /// It isn't affected by the outliers themselves, so it suits skewed data.
+    Iqr { factor: f64 },
+    /// The numbers whose distance to the mean is more than `threshold` standard deviations
+    /// (usually 3). It suits data that is normally distributed.
+    ZScore { threshold: f64 },
+}
+
+impl Default for OutlierMethod {
+    fn default() -> Self {
+        OutlierMethod::Iqr { factor: 1.5 }
+    }
+}
+
+/// A number found by [find_outliers](`CSVFile::find_outliers`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier {
+    /// The index of the row.
+    pub row: usize,
+    pub value: f64,
+}
+
+impl CSVFile {
+    /// Finds the numbers of a column that are far from the others, such as a faulty
+    /// reading of a sensor, in the order of the rows. The values that aren't numbers are ignored.
+    /// It returns `None` if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, Outlier, OutlierMethod, ReaderOptions};
+    /// let text = "temperature\n21.5\n22\n21\n85\n22.5\n\n20.5\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let outliers = file.find_outliers(&"temperature".to_string(), OutlierMethod::default()).unwrap();
+    /// assert_eq!(outliers, vec![Outlier { row: 3, value: 85.0 }]);
+    /// ```
+    pub fn find_outliers(
+        &self,
+        column_name: &String,
+        method: OutlierMethod,
+    ) -> Option<Vec<Outlier>> {
+        let idx = self.get_column_idx(column_name)?;
+        let numbers: Vec<(usize, f64)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| Some((i, parse_number(row.get(idx)?)?)))
+            .collect();
+        if numbers.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let is_outlier: Box<dyn Fn(f64) -> bool> = match method {
+            OutlierMethod::Iqr { factor } => {
+                let quartiles = self.quantiles(column_name, &[0.25, 0.75])?;
+                let range = quartiles[1] - quartiles[0];
+                let low = quartiles[0] - factor * range;
+                let high = quartiles[1] + factor * range;
+                Box::new(move |n| n < low || n > high)
+            }
+            OutlierMethod::ZScore { threshold } => {
+                let count = numbers.len() as f64;
+                let mean = numbers.iter().map(|(_, n)| n).sum::<f64>() / count;
+                let variance = numbers.iter().map(|(_, n)| (n - mean).powi(2)).sum::<f64>() / count;
+                let deviation = variance.sqrt();
+                Box::new(move |n| deviation > 0.0 && (n - mean).abs() / deviation > threshold)
+            }
+        };
+
+        Some(
+            numbers
+                .into_iter()
+                .filter(|(_, n)| is_outlier(*n))
+                .map(|(row, value)| Outlier { row, value })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,423 @@
+//! The core parsing and serialization logic of the crate.
+//!
+//! This module only depends on `core` and `alloc`, so it stays available
+//! when the crate is compiled without its default `std` feature, for example
+//! on embedded data loggers that need to produce or read CSV lines without
+//! a file system:
+//!
+//! ```toml
+//! csv-tools = { version = "2", default-features = false }
+//! ```
+//!
+//! Everything else in the crate (files, readers, writers, [CSVFile](`crate::CSVFile`)...)
+//! is built on top of these functions and requires the `std` feature.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An error raised while parsing or validating a single CSV line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The line ends with a backslash that doesn't escape anything.
+    InvalidEscape,
+    /// A double quote was opened but never closed.
+    UnclosedQuote,
+    /// The line doesn't have the expected number of fields.
+    FieldCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidEscape | ParseError::UnclosedQuote => {
+                write!(f, "Invalid escape sequence")
+            }
+            ParseError::FieldCount { expected, found } => write!(
+                f,
+                "Invalid number of fields, expected {} but found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[cfg(feature = "std")]
+impl From<ParseError> for std::io::Error {
+    fn from(error: ParseError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string())
+    }
+}
+
+/// How the double quotes (and the other special characters) are escaped inside quoted values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Escape {
+    /// A backslash escapes the character that follows it, as in `"say \"hi\""`.
+    /// This is the default behavior of the crate.
+    #[default]
+    Backslash,
+    /// Double quotes are doubled inside quoted values, as in `"say ""hi"""` (RFC 4180).
+    /// Backslashes are regular characters.
+    DoubleQuote,
+}
+
+/// Parses a line of a CSV file, taking double quotes and backslash escapes into account.
+///
+/// # Example
+///
+/// ```
+/// use csv_tools::parser::parse_line;
+///
+/// let fields = parse_line("Yoshiip,\"The best, and only, Godoter\",99", ',').unwrap();
+/// assert_eq!(fields, vec!["Yoshiip", "The best, and only, Godoter", "99"]);
+/// ```
+pub fn parse_line(line: &str, delimiter: char) -> Result<Vec<String>, ParseError> {
+    let mut fields = Vec::new();
+    parse_line_into(line, delimiter, &mut fields, None)?;
+    Ok(fields)
+}
+
+/// Same as [parse_line](`parse_line`), but the fields are pushed into `fields`,
+/// which allows the caller to reuse an allocation, and the indexes of the fields
+/// that were surrounded by double quotes are pushed in `quoted`.
+pub fn parse_line_into(
+    line: &str,
+    delimiter: char,
+    fields: &mut Vec<String>,
+    quoted: Option<&mut Vec<usize>>,
+) -> Result<(), ParseError> {
+    let mut tokens = Vec::new();
+    tokenize_backslash(line, delimiter, '"', &mut tokens, quoted)?;
+    fields.extend(tokens.into_iter().map(Cow::into_owned));
+    Ok(())
+}
+
+/// Same as [parse_line_into](`parse_line_into`), with the given escaping convention.
+pub fn parse_line_with_escape_into(
+    line: &str,
+    delimiter: char,
+    escape: Escape,
+    fields: &mut Vec<String>,
+    quoted: Option<&mut Vec<usize>>,
+) -> Result<(), ParseError> {
+    let mut tokens = Vec::new();
+    match escape {
+        Escape::Backslash => tokenize_backslash(line, delimiter, '"', &mut tokens, quoted)?,
+        Escape::DoubleQuote => tokenize_doubled_quotes(line, delimiter, '"', &mut tokens, quoted)?,
+    }
+    fields.extend(tokens.into_iter().map(Cow::into_owned));
+    Ok(())
+}
+
+/// The options of [parse_line_with_options](`parse_line_with_options`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineOptions {
+    /// The character separating the values (a comma by default).
+    pub delimiter: char,
+    /// The character surrounding the values that contain special characters
+    /// (a double quote by default).
+    pub quote: char,
+    /// How the quote character is escaped inside quoted values
+    /// ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+}
+
+impl Default for LineOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            escape: Escape::Backslash,
+        }
+    }
+}
+
+/// Parses a line of a CSV file with the given delimiter, quote character and escaping convention,
+/// the same way the readers of the crate do. This is the tokenizer the whole crate is built on,
+/// usable on its own by the tools that don't need [CSVFile](`crate::CSVFile`).
+///
+/// The fields borrow the line whenever their value is written as is in it,
+/// so that parsing a line without quotes or escape sequences doesn't allocate the values.
+/// The other fields, whose quotes or escape sequences had to be removed, are owned.
+///
+/// It may return an error if a quote isn't closed or if the line ends with an escape character.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+/// use csv_tools::parser::{parse_line_with_options, LineOptions};
+/// use csv_tools::Escape;
+///
+/// let options = LineOptions { delimiter: ';', quote: '\'', escape: Escape::DoubleQuote };
+/// let fields = parse_line_with_options("Thomas;'It''s me';'Paris'", &options).unwrap();
+/// assert_eq!(fields, vec!["Thomas", "It's me", "Paris"]);
+/// assert!(matches!(fields[0], Cow::Borrowed(_)));
+/// assert!(matches!(fields[1], Cow::Owned(_)));
+/// // The quotes are removed without copying the value.
+/// assert!(matches!(fields[2], Cow::Borrowed(_)));
+/// ```
+pub fn parse_line_with_options<'a>(
+    line: &'a str,
+    options: &LineOptions,
+) -> Result<Vec<Cow<'a, str>>, ParseError> {
+    let mut fields = Vec::new();
+    match options.escape {
+        Escape::Backslash => {
+            tokenize_backslash(line, options.delimiter, options.quote, &mut fields, None)?
+        }
+        Escape::DoubleQuote => {
+            tokenize_doubled_quotes(line, options.delimiter, options.quote, &mut fields, None)?
+        }
+    }
+    Ok(fields)
+}
+
+/// The value of a field being parsed, which borrows the line as long as
+/// its characters are contiguous in it.
+enum Token {
+    Empty,
+    /// The start and the end of the value in the line.
+    Slice(usize, usize),
+    Owned(String),
+}
+
+impl Token {
+    /// Appends the character found at the given index of the line.
+    fn push(&mut self, line: &str, index: usize, c: char) {
+        let end = index + c.len_utf8();
+        *self = match core::mem::replace(self, Token::Empty) {
+            Token::Empty => Token::Slice(index, end),
+            Token::Slice(start, previous_end) if previous_end == index => Token::Slice(start, end),
+            Token::Slice(start, previous_end) => {
+                let mut value = String::from(&line[start..previous_end]);
+                value.push(c);
+                Token::Owned(value)
+            }
+            Token::Owned(mut value) => {
+                value.push(c);
+                Token::Owned(value)
+            }
+        };
+    }
+
+    fn take<'a>(&mut self, line: &'a str) -> Cow<'a, str> {
+        match core::mem::replace(self, Token::Empty) {
+            Token::Empty => Cow::Borrowed(""),
+            Token::Slice(start, end) => Cow::Borrowed(&line[start..end]),
+            Token::Owned(value) => Cow::Owned(value),
+        }
+    }
+}
+
+/// Parses a line where a backslash escapes the character that follows it.
+fn tokenize_backslash<'a>(
+    line: &'a str,
+    delimiter: char,
+    quote: char,
+    fields: &mut Vec<Cow<'a, str>>,
+    mut quoted: Option<&mut Vec<usize>>,
+) -> Result<(), ParseError> {
+    let mut chars = line.char_indices();
+    let mut current_field = Token::Empty;
+    let mut is_in_quote = false;
+    let mut is_escaped = false;
+    let mut ends_with_quote = false;
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            if is_escaped {
+                current_field.push(line, i, c);
+            }
+            is_escaped = !is_escaped;
+        } else {
+            if c == quote {
+                if !is_escaped {
+                    if is_in_quote {
+                        if let Some(quoted) = quoted.as_deref_mut() {
+                            quoted.push(fields.len());
+                        }
+                        fields.push(current_field.take(line));
+                        // skip the next character because it should be
+                        // the delimiter (or the end of the line)
+                        ends_with_quote = chars.next().is_none();
+                    }
+                    is_in_quote = !is_in_quote;
+                } else {
+                    current_field.push(line, i, c);
+                }
+            } else {
+                if c == delimiter && !is_in_quote {
+                    fields.push(current_field.take(line));
+                } else {
+                    current_field.push(line, i, c);
+                }
+            }
+            // If the character immediately following a blackslash
+            // isn't another backslash, then make sure to be unescaped.
+            is_escaped = false;
+        }
+    }
+
+    if is_escaped {
+        return Err(ParseError::InvalidEscape);
+    }
+    if is_in_quote {
+        return Err(ParseError::UnclosedQuote);
+    }
+
+    // Push the last field, unless it was a quoted field
+    // that has already been pushed when its quote got closed.
+    if !ends_with_quote {
+        fields.push(current_field.take(line));
+    }
+
+    Ok(())
+}
+
+/// Parses a line where the quotes are doubled inside quoted values.
+/// A quote only starts a quoted value at the very beginning of a field,
+/// anywhere else it's a regular character.
+fn tokenize_doubled_quotes<'a>(
+    line: &'a str,
+    delimiter: char,
+    quote: char,
+    fields: &mut Vec<Cow<'a, str>>,
+    mut quoted: Option<&mut Vec<usize>>,
+) -> Result<(), ParseError> {
+    let mut chars = line.char_indices().peekable();
+    let mut current_field = Token::Empty;
+    let mut at_field_start = true;
+    let mut is_in_quote = false;
+
+    while let Some((i, c)) = chars.next() {
+        if is_in_quote {
+            if c == quote {
+                if let Some((j, _)) = chars.next_if(|(_, next)| *next == quote) {
+                    current_field.push(line, j, quote);
+                } else {
+                    is_in_quote = false;
+                }
+            } else {
+                current_field.push(line, i, c);
+            }
+        } else if c == delimiter {
+            fields.push(current_field.take(line));
+            at_field_start = true;
+        } else if c == quote && at_field_start {
+            if let Some(quoted) = quoted.as_deref_mut() {
+                quoted.push(fields.len());
+            }
+            is_in_quote = true;
+            at_field_start = false;
+        } else {
+            current_field.push(line, i, c);
+            at_field_start = false;
+        }
+    }
+
+    if is_in_quote {
+        return Err(ParseError::UnclosedQuote);
+    }
+    fields.push(current_field.take(line));
+
+    Ok(())
+}
+
+/// Splits the line into a vector of strings using the delimiter.
+/// Contrary to [parse_line](`parse_line`), quotes and escapes are ignored.
+pub fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    line.split(delimiter).map(String::from).collect()
+}
+
+/// Makes sure that a parsed line has the expected number of fields.
+pub fn validate_fields(fields: &[String], expected: usize) -> Result<(), ParseError> {
+    if fields.len() == expected {
+        Ok(())
+    } else {
+        Err(ParseError::FieldCount {
+            expected,
+            found: fields.len(),
+        })
+    }
+}
+
+/// Surrounds a value with double quotes, escaping the double quotes
+/// and backslashes it contains so that [parse_line](`parse_line`) reads it back as is.
+pub fn quote_field(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push('"');
+    result
+}
+
+/// Same as [quote_field](`quote_field`), with the given escaping convention.
+pub fn quote_field_with_escape(value: &str, escape: Escape) -> String {
+    match escape {
+        Escape::Backslash => quote_field(value),
+        Escape::DoubleQuote => {
+            let mut result = String::with_capacity(value.len() + 2);
+            result.push('"');
+            for c in value.chars() {
+                if c == '"' {
+                    result.push('"');
+                }
+                result.push(c);
+            }
+            result.push('"');
+            result
+        }
+    }
+}
+
+/// Tells whether a value must be quoted to be read back as a single field:
+/// when it contains the delimiter, a double quote, a backslash or a line break.
+pub fn needs_quotes(value: &str, delimiter: char) -> bool {
+    value.contains([delimiter, '"', '\\', '\n', '\r'])
+}
+
+/// Same as [needs_quotes](`needs_quotes`), with the given escaping convention.
+/// Backslashes don't need quotes when the double quotes are doubled.
+pub fn needs_quotes_with_escape(value: &str, delimiter: char, escape: Escape) -> bool {
+    match escape {
+        Escape::Backslash => needs_quotes(value, delimiter),
+        Escape::DoubleQuote => value.contains([delimiter, '"', '\n', '\r']),
+    }
+}
+
+/// Serializes fields into a line (without line break), quoting only the fields that need it.
+///
+/// # Example
+///
+/// ```
+/// use csv_tools::parser::{parse_line, write_line};
+///
+/// let fields = vec!["Thomas".to_string(), "Hello, world".to_string()];
+/// let line = write_line(&fields, ',');
+/// assert_eq!(line, "Thomas,\"Hello, world\"");
+/// assert_eq!(parse_line(&line, ',').unwrap(), fields);
+/// ```
+pub fn write_line(fields: &[String], delimiter: char) -> String {
+    let mut line = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            line.push(delimiter);
+        }
+        if needs_quotes(field, delimiter) {
+            line.push_str(&quote_field(field));
+        } else {
+            line.push_str(field);
+        }
+    }
+    line
+}
@@ -0,0 +1,107 @@
+//! Parsing of the text produced by spreadsheet applications when cells are copied.
+
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind};
+
+use crate::parser::{parse_line_with_options, LineOptions, ParseError};
+use crate::{CSVFile, Escape};
+
+/// Returns the length of the line break at the start of the text, or `0` if there isn't any.
+fn line_break_len(text: &str) -> usize {
+    if text.starts_with("\r\n") {
+        2
+    } else if text.starts_with(['\n', '\r']) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Parses text where the values are quoted the way spreadsheet applications do it
+/// (and as described by RFC 4180), with [parse_line_with_options](`crate::parser::parse_line_with_options`)
+/// and [Escape::DoubleQuote](`Escape::DoubleQuote`): a value is quoted only if it starts
+/// with a double quote, a double quote is escaped by doubling it, and a quoted value
+/// may contain the delimiter and line breaks. Backslashes are regular characters.
+///
+/// The records are separated by `\n`, `\r\n` or `\r`. A line break at the very end
+/// of the text doesn't create an extra empty record.
+pub(crate) fn parse_quoted_records(
+    text: &str,
+    delimiter: &char,
+) -> Result<Vec<Vec<String>>, Error> {
+    let options = LineOptions {
+        delimiter: *delimiter,
+        quote: '"',
+        escape: Escape::DoubleQuote,
+    };
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        // A record ends at the first line break that isn't inside a quoted value,
+        // so the next line is added to the record as long as a quote is left open.
+        let mut end = 0;
+        let fields = loop {
+            let line_end = rest[end..]
+                .find(['\n', '\r'])
+                .map_or(rest.len(), |i| end + i);
+            match parse_line_with_options(&rest[..line_end], &options) {
+                Ok(fields) => {
+                    end = line_end;
+                    break fields;
+                }
+                Err(ParseError::UnclosedQuote) if line_end < rest.len() => {
+                    end = line_end + line_break_len(&rest[line_end..]);
+                }
+                Err(_) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Unclosed quote at the end of the text",
+                    ))
+                }
+            }
+        };
+        records.push(fields.into_iter().map(Cow::into_owned).collect());
+        rest = &rest[end..];
+        rest = &rest[line_break_len(rest)..];
+    }
+
+    Ok(records)
+}
+
+impl CSVFile {
+    /// Creates a new CSVFile from the text that spreadsheet applications put in the
+    /// clipboard when a range of cells is copied: tab-separated values where a cell containing
+    /// a tabulation, a line break or a double quote is surrounded by double quotes,
+    /// with its double quotes doubled. The first line is used as the header.
+    ///
+    /// It may return an error if the text is empty, if a quote isn't closed
+    /// or if the lines don't all have the same number of cells.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// let pasted = "name\tcomment\r\nThomas\t\"first line\nsecond \"\"line\"\"\"\r\n";
+    /// let file = CSVFile::from_pasted_text(pasted).unwrap();
+    ///
+    /// assert_eq!(file.columns, vec!["name", "comment"]);
+    /// assert_eq!(file.rows, vec![vec!["Thomas", "first line\nsecond \"line\""]]);
+    /// assert_eq!(file.delimiter, '\t');
+    /// ```
+    pub fn from_pasted_text(text: &str) -> Result<Self, Error> {
+        let mut records = parse_quoted_records(text, &'\t')?.into_iter();
+        let columns = match records.next() {
+            Some(columns) => columns,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The text is empty, the header is missing",
+                ))
+            }
+        };
+        let rows: Vec<Vec<String>> = records.collect();
+
+        Self::build(&columns, &rows, &'\t')
+    }
+}
@@ -0,0 +1,147 @@
+//! Converting a CSV file to and from a polars `DataFrame`, so that the data edited with this crate
+//! can be analyzed with polars, and the results brought back.
+
+use std::io::{Error, ErrorKind};
+
+use polars_core::prelude::{Column, DataFrame, DataType};
+
+use crate::stats::parse_number;
+use crate::timeseries::parse_timestamp;
+use crate::{CSVFile, CSVSchema, ColumnType};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+impl CSVFile {
+    /// Converts the file to a polars `DataFrame` with the [inferred schema](`CSVFile::infer_schema`)
+    /// of the file. See [to_polars_with_schema](`CSVFile::to_polars_with_schema`).
+    ///
+    /// This method requires the `polars` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// # use polars_core::prelude::DataType;
+    /// let file = CSVFile::from_text("name,age\nThomas,20\nJohn,\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let frame = file.to_polars().unwrap();
+    /// assert_eq!(frame.height(), 2);
+    /// assert_eq!(frame.column("age").unwrap().dtype(), &DataType::Int64);
+    /// assert_eq!(frame.column("age").unwrap().null_count(), 1);
+    ///
+    /// assert_eq!(CSVFile::from_polars(&frame).unwrap().rows, file.rows);
+    /// ```
+    pub fn to_polars(&self) -> Result<DataFrame, Error> {
+        self.to_polars_with_schema(&self.infer_schema())
+    }
+
+    /// Converts the file to a polars `DataFrame` whose columns have the types of a schema,
+    /// which must describe every column of the file. The text columns keep their values as they are,
+    /// while the empty values of the other columns are nulls.
+    /// The values missing from the ragged rows are empty values.
+    ///
+    /// It may return an error if a column of the file isn't in the schema,
+    /// or if a value doesn't have the type of its column.
+    ///
+    /// This method requires the `polars` feature.
+    pub fn to_polars_with_schema(&self, schema: &CSVSchema) -> Result<DataFrame, Error> {
+        let mut columns = Vec::with_capacity(self.columns.len());
+        for (idx, name) in self.columns.iter().enumerate() {
+            let column_type = match schema.column(name) {
+                Some(column) => column.column_type,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("The column '{}' isn't in the schema", name),
+                    ))
+                }
+            };
+            let name = name.as_str().into();
+
+            let column = if column_type == ColumnType::Text {
+                let values: Vec<&str> = self
+                    .rows
+                    .iter()
+                    .map(|row| row.get(idx).map_or("", String::as_str))
+                    .collect();
+                Column::new(name, values)
+            } else {
+                let coerced = self.coerced_column(idx, column_type)?;
+                match column_type {
+                    ColumnType::Integer => {
+                        let values: Vec<Option<i64>> = coerced
+                            .iter()
+                            .map(|v| v.as_ref().and_then(|v| v.parse().ok()))
+                            .collect();
+                        Column::new(name, values)
+                    }
+                    ColumnType::Number => {
+                        let values: Vec<Option<f64>> = coerced
+                            .iter()
+                            .map(|v| v.as_deref().and_then(parse_number))
+                            .collect();
+                        Column::new(name, values)
+                    }
+                    ColumnType::Boolean => {
+                        let values: Vec<Option<bool>> = coerced
+                            .iter()
+                            .map(|v| v.as_ref().map(|v| v == "true"))
+                            .collect();
+                        Column::new(name, values)
+                    }
+                    ColumnType::Date => {
+                        let days: Vec<Option<i32>> = coerced
+                            .iter()
+                            .map(|v| {
+                                v.as_deref()
+                                    .and_then(parse_timestamp)
+                                    .map(|seconds| seconds.div_euclid(SECONDS_PER_DAY) as i32)
+                            })
+                            .collect();
+                        Column::new(name, days)
+                            .cast(&DataType::Date)
+                            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?
+                    }
+                    ColumnType::Text => unreachable!("the text columns are handled above"),
+                }
+            };
+            columns.push(column);
+        }
+
+        DataFrame::new(self.rows.len(), columns).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Creates a new CSVFile from a polars `DataFrame`. The values are written as text
+    /// the way polars formats them, the dates as `YYYY-MM-DD`, and the nulls become empty values.
+    /// The delimiter is a comma.
+    ///
+    /// It may return an error if a column has a type that can't be written as text,
+    /// such as a list or a struct.
+    ///
+    /// This method requires the `polars` feature.
+    pub fn from_polars(frame: &DataFrame) -> Result<Self, Error> {
+        let columns: Vec<String> = frame
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let mut rows = vec![Vec::with_capacity(columns.len()); frame.height()];
+        for (name, column) in columns.iter().zip(frame.columns()) {
+            let text = column
+                .cast(&DataType::String)
+                .and_then(|text| text.str().cloned())
+                .map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("The column {} can't be written as text: {}", name, e),
+                    )
+                })?;
+            for (row, value) in rows.iter_mut().zip(text.iter()) {
+                row.push(value.unwrap_or_default().to_string());
+            }
+        }
+
+        Ok(CSVFile::from_rows(',', columns, rows))
+    }
+}
@@ -0,0 +1,224 @@
+//! Measuring a CSV file on disk, and peeking at its header, without parsing its values.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, Read, Seek, SeekFrom};
+
+use crate::parser::{Escape, ParseError};
+use crate::{CSVFile, CSVReader, ReaderOptions};
+
+/// Follows the double quotes of the bytes of a file, one byte at a time,
+/// to tell the line breaks ending a record from the ones inside quoted values.
+/// It follows the same rules as the parser for the given escaping.
+struct RecordScanner {
+    escape: Escape,
+    delimiter: [u8; 4],
+    delimiter_len: usize,
+    /// The number of bytes of the delimiter matched so far.
+    delimiter_matched: usize,
+    in_quote: bool,
+    is_escaped: bool,
+    /// With backslashes, the character following a closing quote is skipped.
+    skip_next: bool,
+    /// With doubled quotes, a quote was met inside a quoted value,
+    /// which is either its end or the first half of an escaped quote.
+    closing: bool,
+    at_field_start: bool,
+    /// Whether bytes were met since the end of the last record.
+    in_record: bool,
+}
+
+impl RecordScanner {
+    fn new(options: &ReaderOptions) -> Self {
+        let mut delimiter = [0; 4];
+        let delimiter_len = options.delimiter.encode_utf8(&mut delimiter).len();
+        Self {
+            escape: options.escape,
+            delimiter,
+            delimiter_len,
+            delimiter_matched: 0,
+            in_quote: false,
+            is_escaped: false,
+            skip_next: false,
+            closing: false,
+            at_field_start: true,
+            in_record: false,
+        }
+    }
+
+    /// Reads a byte, and returns `true` if it's the line break ending a record.
+    fn advance(&mut self, byte: u8) -> bool {
+        self.in_record = true;
+        let ends_record = match self.escape {
+            Escape::Backslash => self.advance_backslash(byte),
+            Escape::DoubleQuote => self.advance_doubled_quotes(byte),
+        };
+        if ends_record {
+            self.in_record = false;
+        }
+        ends_record
+    }
+
+    fn advance_backslash(&mut self, byte: u8) -> bool {
+        if self.skip_next {
+            self.skip_next = false;
+            if byte != b'\n' {
+                return false;
+            }
+        }
+        match byte {
+            b'\\' => self.is_escaped = !self.is_escaped,
+            b'"' if !self.is_escaped => {
+                self.skip_next = self.in_quote;
+                self.in_quote = !self.in_quote;
+            }
+            b'\n' if !self.in_quote => {
+                self.is_escaped = false;
+                return true;
+            }
+            _ => self.is_escaped = false,
+        }
+        false
+    }
+
+    fn advance_doubled_quotes(&mut self, byte: u8) -> bool {
+        if self.in_quote {
+            if self.closing {
+                self.closing = false;
+                if byte == b'"' {
+                    return false;
+                }
+                self.in_quote = false;
+            } else {
+                self.closing = byte == b'"';
+                return false;
+            }
+        }
+        if byte == b'\n' {
+            self.at_field_start = true;
+            self.delimiter_matched = 0;
+            return true;
+        }
+        if byte == self.delimiter[self.delimiter_matched] {
+            self.delimiter_matched += 1;
+        } else {
+            self.delimiter_matched = (byte == self.delimiter[0]) as usize;
+        }
+        if self.delimiter_matched == self.delimiter_len {
+            self.delimiter_matched = 0;
+            self.at_field_start = true;
+        } else {
+            self.in_quote = byte == b'"' && self.at_field_start;
+            self.at_field_start = false;
+        }
+        false
+    }
+
+    /// Whether the bytes read so far end in the middle of a quoted value.
+    fn is_in_quote(&self) -> bool {
+        self.in_quote && !self.closing
+    }
+}
+
+impl CSVFile {
+    /// Reads the names of the columns of a CSV file without reading any row,
+    /// for example to check the columns of a file before importing it.
+    /// The header is read with the given options, so the projection, the skipped columns,
+    /// the trimming and the header spanning two rows are taken into account.
+    ///
+    /// It may return an error if the file doesn't exist, if it's empty or if the header can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_read_header_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "id,\"full name\"\n1,Thomas\n").unwrap();
+    ///
+    /// let columns = CSVFile::read_header(&filename, &ReaderOptions::default()).unwrap();
+    /// assert_eq!(columns, vec!["id", "full name"]);
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn read_header(filename: &String, options: &ReaderOptions) -> Result<Vec<String>, Error> {
+        Ok(CSVReader::open(filename, options)?.into_columns())
+    }
+
+    /// Returns the number of rows and the number of columns of a CSV file,
+    /// header excluded, without parsing its values. Only the header is parsed,
+    /// with the given options, and the rows are counted by scanning the bytes of the file,
+    /// so it's a cheap way to check the size of a file before reading it entirely.
+    ///
+    /// The rows are counted like [CSVReader](`CSVReader`) would read them: an empty line
+    /// is an empty row, and a line break inside a quoted value doesn't end the row.
+    /// The number of columns takes the projection and the skipped columns of the options into account.
+    ///
+    /// It may return an error if the file can't be read, if the header can't be parsed,
+    /// or if the last quoted value is never closed. The values themselves aren't validated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_dimensions_doc.csv").to_string_lossy().to_string();
+    /// std::fs::write(&filename, "id,bio\n1,\"Likes\nRust\"\n2,\n").unwrap();
+    ///
+    /// let (rows, columns) = CSVFile::dimensions(&filename, &ReaderOptions::default()).unwrap();
+    /// assert_eq!((rows, columns), (2, 2));
+    /// assert_eq!(CSVFile::count_lines(&filename).unwrap(), 4);
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn dimensions(filename: &String, options: &ReaderOptions) -> Result<(usize, usize), Error> {
+        let reader = CSVReader::open(filename, options)?;
+        let header_bytes = reader.bytes_read();
+        let columns = reader.columns().len();
+
+        let mut file = File::open(filename)?;
+        file.seek(SeekFrom::Start(header_bytes))?;
+        let mut source = BufReader::with_capacity(64 * 1024, file);
+        let mut scanner = RecordScanner::new(options);
+        let mut rows = 0;
+        loop {
+            if let Some(token) = &options.cancellation {
+                token.check()?;
+            }
+            let chunk = source.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            rows += chunk.iter().filter(|&&b| scanner.advance(b)).count();
+            let length = chunk.len();
+            source.consume(length);
+        }
+        if scanner.is_in_quote() {
+            return Err(ParseError::UnclosedQuote.into());
+        }
+        if scanner.in_record {
+            rows += 1;
+        }
+        Ok((rows, columns))
+    }
+
+    /// Returns the number of lines of a file, header included, by counting its line breaks.
+    /// A last line that doesn't end with a line break is counted too.
+    /// Unlike [dimensions](`CSVFile::dimensions`), the line breaks inside quoted values are counted,
+    /// so it's the number of records only when no value spans several lines.
+    ///
+    /// It may return an error if the file can't be read.
+    pub fn count_lines(filename: &String) -> Result<usize, Error> {
+        let mut file = File::open(filename)?;
+        let mut buffer = vec![0; 64 * 1024];
+        let mut lines = 0;
+        let mut last_byte = None;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            lines += buffer[..read].iter().filter(|&&b| b == b'\n').count();
+            last_byte = Some(buffer[read - 1]);
+        }
+        if last_byte.is_some_and(|b| b != b'\n') {
+            lines += 1;
+        }
+        Ok(lines)
+    }
+}
@@ -0,0 +1,78 @@
+//! Progress reporting for the operations that can take a long time on big files.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A callback notified of the progress of a long operation,
+/// with the number of rows processed so far and the number of bytes read or written so far.
+///
+/// The callback is called every `interval` rows (10 000 by default),
+/// and one last time when the operation is complete.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, ProgressHook, WriterOptions};
+/// # use std::sync::{Arc, Mutex};
+/// let columns = vec!["a".to_string()];
+/// let rows = vec![vec!["1".to_string()], vec!["2".to_string()], vec!["3".to_string()]];
+/// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+///
+/// let calls = Arc::new(Mutex::new(Vec::new()));
+/// let calls_clone = Arc::clone(&calls);
+/// let options = WriterOptions {
+///     on_progress: Some(ProgressHook::new(move |rows, bytes| {
+///         calls_clone.lock().unwrap().push((rows, bytes));
+///     }).every(2)),
+///     ..WriterOptions::default()
+/// };
+///
+/// let mut output: Vec<u8> = Vec::new();
+/// file.write_to(&mut output, &options).unwrap();
+/// assert_eq!(*calls.lock().unwrap(), vec![(2, 6), (3, 8)]);
+/// ```
+#[derive(Clone)]
+pub struct ProgressHook {
+    callback: Arc<dyn Fn(usize, u64) + Send + Sync>,
+    interval: usize,
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ProgressHook {{ interval: {} }}", self.interval)
+    }
+}
+
+impl ProgressHook {
+    /// Creates a hook calling `on_progress(rows_done, bytes_done)`.
+    pub fn new<F>(on_progress: F) -> Self
+    where
+        F: Fn(usize, u64) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Arc::new(on_progress),
+            interval: 10_000,
+        }
+    }
+
+    /// Sets the number of rows between two calls of the callback.
+    pub fn every(mut self, rows: usize) -> Self {
+        self.interval = rows.max(1);
+        self
+    }
+
+    /// Calls the callback if the number of rows is a multiple of the interval.
+    pub(crate) fn tick(&self, rows_done: usize, bytes_done: u64) {
+        if rows_done.is_multiple_of(self.interval) {
+            (self.callback)(rows_done, bytes_done);
+        }
+    }
+
+    /// Calls the callback for the end of the operation,
+    /// unless it has just been called for the same number of rows.
+    pub(crate) fn finish(&self, rows_done: usize, bytes_done: u64) {
+        if rows_done == 0 || !rows_done.is_multiple_of(self.interval) {
+            (self.callback)(rows_done, bytes_done);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! A small expression language to filter the rows of a file.
+
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind};
+
+use crate::stats::parse_number;
+use crate::CSVFile;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Identifier(String),
+    /// A name between backquotes, which is never a keyword.
+    QuotedIdentifier(String),
+    Text(String),
+    Number(f64),
+    Operator(Comparison),
+    And,
+    Or,
+    Not,
+    OpenParen,
+    CloseParen,
+    Comma,
+    Star,
+}
+
+impl Token {
+    /// Returns `true` if the token is the given keyword, whatever its case.
+    #[cfg(feature = "select")]
+    pub(crate) fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Identifier(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// A side of a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operand {
+    /// A column, with its index once the query is bound to a file.
+    Column(String, usize),
+    Text(String),
+    Number(f64),
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Comparison, Operand),
+}
+
+impl Expr {
+    /// Replaces the names of the columns by their indexes in the file.
+    pub(crate) fn bind(&mut self, file: &CSVFile) -> Result<(), Error> {
+        match self {
+            Expr::Or(left, right) | Expr::And(left, right) => {
+                left.bind(file)?;
+                right.bind(file)
+            }
+            Expr::Not(expr) => expr.bind(file),
+            Expr::Compare(left, _, right) => {
+                for operand in [left, right] {
+                    if let Operand::Column(name, idx) = operand {
+                        *idx = file.get_column_idx(name).ok_or_else(|| {
+                            Error::new(
+                                ErrorKind::InvalidData,
+                                format!("The column {} doesn't exist", name),
+                            )
+                        })?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Evaluates the expression on a row, once bound to the columns of the file.
+    pub(crate) fn matches(&self, row: &[String]) -> bool {
+        match self {
+            Expr::Or(left, right) => left.matches(row) || right.matches(row),
+            Expr::And(left, right) => left.matches(row) && right.matches(row),
+            Expr::Not(expr) => !expr.matches(row),
+            Expr::Compare(left, comparison, right) => compare(left, *comparison, right, row),
+        }
+    }
+}
+
+/// Compares two operands. A number literal requires a numeric comparison, which is
+/// only true with `!=` if the other side isn't a number. Otherwise, values are compared
+/// as numbers when both of them are numbers, and as text when they aren't.
+fn compare(left: &Operand, comparison: Comparison, right: &Operand, row: &[String]) -> bool {
+    let text = |operand: &Operand| -> String {
+        match operand {
+            Operand::Column(_, idx) => row.get(*idx).cloned().unwrap_or_default(),
+            Operand::Text(text) => text.clone(),
+            Operand::Number(number) => number.to_string(),
+        }
+    };
+    let (left_text, right_text) = (text(left), text(right));
+    let numeric = matches!(left, Operand::Number(_)) || matches!(right, Operand::Number(_));
+
+    match (parse_number(&left_text), parse_number(&right_text)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).is_some_and(|o| comparison.accepts(o)),
+        _ if numeric => comparison == Comparison::NotEqual,
+        _ => comparison.accepts(left_text.cmp(&right_text)),
+    }
+}
+
+/// A filter on the rows of a file, parsed from an expression such as
+/// `age > 30 && country == 'FR'`.
+///
+/// An expression compares columns, texts in single quotes (a quote is doubled inside a text),
+/// and numbers with the operators `==` (or `=`), `!=` (or `<>`), `<`, `<=`, `>` and `>=`.
+/// The comparisons are combined with `&&` (or `and`), `||` (or `or`), `!` (or `not`)
+/// and parentheses. A column whose name isn't made of letters, digits, `_` and `.`
+/// can be written between backquotes, as in `` `first name` == 'Thomas' ``.
+///
+/// Values are compared as numbers when both of them are numbers, and as text otherwise.
+/// Comparing a value that isn't a number to a number is always false, except with `!=`.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, Query, ReaderOptions};
+/// let text = "name,age,country\nThomas,19,FR\nYoshiip,35,FR\nJohn,42,US\n";
+/// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+///
+/// assert_eq!(file.query("age > 30 && country == 'FR'").unwrap(), vec![1]);
+///
+/// let query = Query::parse("not (name = 'John' or age < 20)").unwrap();
+/// assert_eq!(file.filter(&query).unwrap().rows, vec![vec!["Yoshiip", "35", "FR"]]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    /// Parses an expression. It may return an error if the syntax is invalid.
+    pub fn parse(text: &str) -> Result<Query, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let expr = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.error("unexpected token after the end of the expression"));
+        }
+        Ok(Query { expr })
+    }
+
+    #[cfg(feature = "select")]
+    pub(crate) fn from_expr(expr: Expr) -> Query {
+        Query { expr }
+    }
+
+    /// Resolves the columns of the query in the header of the file.
+    /// It may return an error if a column doesn't exist.
+    pub(crate) fn bind(&self, file: &CSVFile) -> Result<Expr, Error> {
+        let mut expr = self.expr.clone();
+        expr.bind(file)?;
+        Ok(expr)
+    }
+}
+
+fn syntax_error(message: String) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Invalid query: {}", message),
+    )
+}
+
+/// Splits an expression into tokens.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::OpenParen, 1),
+            (',', _) => (Token::Comma, 1),
+            ('*', _) => (Token::Star, 1),
+            (')', _) => (Token::CloseParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Operator(Comparison::Equal), 2),
+            ('=', _) => (Token::Operator(Comparison::Equal), 1),
+            ('!', Some('=')) | ('<', Some('>')) => (Token::Operator(Comparison::NotEqual), 2),
+            ('!', _) => (Token::Not, 1),
+            ('<', Some('=')) => (Token::Operator(Comparison::LessOrEqual), 2),
+            ('<', _) => (Token::Operator(Comparison::Less), 1),
+            ('>', Some('=')) => (Token::Operator(Comparison::GreaterOrEqual), 2),
+            ('>', _) => (Token::Operator(Comparison::Greater), 1),
+            ('\'', _) | ('`', _) => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        Some(&d) if d == c && chars.get(j + 1) == Some(&c) => {
+                            value.push(c);
+                            j += 2;
+                        }
+                        Some(&d) if d == c => break,
+                        Some(&d) => {
+                            value.push(d);
+                            j += 1;
+                        }
+                        None => {
+                            return Err(syntax_error(format!("unclosed {} at character {}", c, i)))
+                        }
+                    }
+                }
+                let token = if c == '`' {
+                    Token::QuotedIdentifier(value)
+                } else {
+                    Token::Text(value)
+                };
+                (token, j + 1 - i)
+            }
+            (c, _)
+                if c.is_ascii_digit()
+                    || (c == '-' && next.is_some_and(|d| d.is_ascii_digit() || d == '.'))
+                    || c == '.' =>
+            {
+                let mut j = i + 1;
+                while j < chars.len()
+                    && (chars[j].is_ascii_digit()
+                        || chars[j] == '.'
+                        || matches!(chars[j], 'e' | 'E')
+                        || (matches!(chars[j], '+' | '-') && matches!(chars[j - 1], 'e' | 'E')))
+                {
+                    j += 1;
+                }
+                let literal: String = chars[i..j].iter().collect();
+                let number = literal
+                    .parse::<f64>()
+                    .map_err(|_| syntax_error(format!("invalid number {}", literal)))?;
+                (Token::Number(number), j - i)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let mut j = i + 1;
+                while j < chars.len()
+                    && (chars[j].is_alphanumeric() || matches!(chars[j], '_' | '.'))
+                {
+                    j += 1;
+                }
+                let word: String = chars[i..j].iter().collect();
+                let token = match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Identifier(word),
+                };
+                (token, j - i)
+            }
+            (c, _) => {
+                return Err(syntax_error(format!(
+                    "unexpected character {} at character {}",
+                    c, i
+                )))
+            }
+        };
+        tokens.push(token);
+        i += length;
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser, from the lowest to the highest precedence:
+/// `||`, `&&`, `!`, then comparisons and parentheses.
+pub(crate) struct Parser {
+    pub(crate) tokens: Vec<Token>,
+    pub(crate) position: usize,
+}
+
+impl Parser {
+    pub(crate) fn error(&self, message: &str) -> Error {
+        syntax_error(format!("{} (token {})", message, self.position))
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    pub(crate) fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    pub(crate) fn or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, Error> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        if self.peek() == Some(&Token::OpenParen) {
+            self.position += 1;
+            let expr = self.or()?;
+            if self.next() != Some(Token::CloseParen) {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(expr);
+        }
+        let left = self.operand()?;
+        let comparison = match self.next() {
+            Some(Token::Operator(comparison)) => comparison,
+            _ => return Err(self.error("expected a comparison operator")),
+        };
+        let right = self.operand()?;
+        Ok(Expr::Compare(left, comparison, right))
+    }
+
+    fn operand(&mut self) -> Result<Operand, Error> {
+        match self.next() {
+            Some(Token::Identifier(name)) | Some(Token::QuotedIdentifier(name)) => {
+                Ok(Operand::Column(name, usize::MAX))
+            }
+            Some(Token::Text(text)) => Ok(Operand::Text(text)),
+            Some(Token::Number(number)) => Ok(Operand::Number(number)),
+            _ => Err(self.error("expected a column, a text or a number")),
+        }
+    }
+}
+
+impl CSVFile {
+    /// Returns the indexes of the rows matching an expression (see [Query](`Query`)).
+    /// It may return an error if the expression is invalid or uses a column that doesn't exist.
+    pub fn query(&self, expression: &str) -> Result<Vec<usize>, Error> {
+        let expr = Query::parse(expression)?.bind(self)?;
+        Ok(self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| expr.matches(row))
+            .map(|(i, _)| i)
+            .collect())
+    }
+
+    /// Returns a new file with only the rows matching the query.
+    /// It may return an error if the query uses a column that doesn't exist.
+    pub fn filter(&self, query: &Query) -> Result<CSVFile, Error> {
+        let expr = query.bind(self)?;
+        let rows = self
+            .rows
+            .iter()
+            .filter(|row| expr.matches(row))
+            .cloned()
+            .collect();
+
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            self.columns.clone(),
+            rows,
+        ))
+    }
+}
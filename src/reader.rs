@@ -0,0 +1,922 @@
+//! A reader going through the rows of a CSV file one by one,
+//! without holding the whole file in memory.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::OnceLock;
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+use crate::fingerprint::bytes_hash;
+use crate::parser::{self, ParseError};
+use crate::{read_columns, trim_fields};
+use crate::{Checkpoint, ReaderOptions};
+
+/// A row as it was read from the source, before it gets stored.
+pub(crate) struct RawRecord {
+    /// The line the row was parsed from,
+    /// only kept when reading with [ReaderOptions::lossless](`ReaderOptions::lossless`).
+    pub(crate) line: Option<String>,
+    pub(crate) fields: Vec<String>,
+    /// The indexes of the fields that were surrounded by double quotes,
+    /// only filled when reading with [ReaderOptions::preserve_quotes](`ReaderOptions::preserve_quotes`).
+    pub(crate) quoted: Vec<usize>,
+    pub(crate) position: Position,
+}
+
+/// Where a row starts in the source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// The number of the first line of the row, starting at 1 with the header.
+    /// It may differ from the index of the row, since quoted values may span several lines.
+    pub line: usize,
+    /// The offset in bytes of the beginning of the row in the source, header included.
+    pub byte: u64,
+}
+
+/// A row read by [CSVReader::records](`CSVReader::records`), along with its position in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    fields: Vec<String>,
+    position: Position,
+}
+
+impl Record {
+    pub(crate) fn new(fields: Vec<String>, position: Position) -> Self {
+        Self { fields, position }
+    }
+
+    /// Returns the values of the row.
+    pub fn fields(&self) -> &Vec<String> {
+        &self.fields
+    }
+
+    /// Consumes the record and returns the values of the row.
+    pub fn into_fields(self) -> Vec<String> {
+        self.fields
+    }
+
+    /// Returns where the row starts in the source.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+/// An iterator over the rows of a [CSVReader](`CSVReader`) along with their positions,
+/// created by [CSVReader::records](`CSVReader::records`).
+pub struct Records<'a, R: BufRead> {
+    reader: &'a mut CSVReader<R>,
+}
+
+impl<R: BufRead> Iterator for Records<'_, R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.reader
+                .next_record()?
+                .map(|record| Record::new(record.fields, record.position)),
+        )
+    }
+}
+
+/// Performance counters gathered while reading a CSV file.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, ReaderOptions};
+/// let file = CSVFile::from_text("a,b\n1,2\n3,4\n", &ReaderOptions::default()).unwrap();
+/// let stats = file.read_stats().unwrap();
+/// assert_eq!(stats.rows, 2);
+/// assert_eq!(stats.bytes, 12);
+/// assert_eq!(stats.elapsed, None);
+/// assert_eq!(stats.allocations, None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadStats {
+    /// The number of rows read, the header excluded.
+    pub rows: usize,
+    /// The number of bytes read, the header included.
+    pub bytes: u64,
+    /// The time spent reading, from the creation of the reader to the end of the source,
+    /// only measured when [ReaderOptions::clock](`ReaderOptions::clock`) is set.
+    pub elapsed: Option<Duration>,
+    /// The number of allocations made while reading, only counted when
+    /// [ReaderOptions::allocation_counter](`ReaderOptions::allocation_counter`) is set.
+    pub allocations: Option<u64>,
+}
+
+impl ReadStats {
+    /// Returns the number of rows read per second, or `None` if the reading wasn't timed.
+    pub fn rows_per_second(&self) -> Option<f64> {
+        self.elapsed
+            .map(|elapsed| self.rows as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE))
+    }
+
+    /// Returns the number of bytes read per second, or `None` if the reading wasn't timed.
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        self.elapsed
+            .map(|elapsed| self.bytes as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE))
+    }
+}
+
+/// Returns the time elapsed since the first call, measured with `std::time::Instant`.
+/// It's meant to be given to [ReaderOptions::clock](`ReaderOptions::clock`),
+/// and isn't available on `wasm32-unknown-unknown` where `Instant` panics.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{system_clock, CSVFile, ReaderOptions};
+/// let options = ReaderOptions {
+///     clock: Some(system_clock),
+///     ..ReaderOptions::default()
+/// };
+/// let file = CSVFile::from_text("a,b\n1,2\n", &options).unwrap();
+/// assert!(file.read_stats().unwrap().elapsed.is_some());
+/// ```
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn system_clock() -> Duration {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed()
+}
+
+/// Reads the rows of a CSV file one at a time. The header is read as soon as the reader is created.
+///
+/// The reader is an iterator over the rows, and the iteration stops at the first error.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVReader, ReaderOptions};
+/// let text = "a,b\n1,2\n3,4\n";
+/// let mut reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+/// assert_eq!(reader.columns(), &vec!["a".to_string(), "b".to_string()]);
+///
+/// let rows: Vec<Vec<String>> = reader.collect::<Result<_, _>>().unwrap();
+/// assert_eq!(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+/// ```
+pub struct CSVReader<R: BufRead> {
+    source: R,
+    /// The line being parsed, reused from one row to the next
+    /// so that reading a row doesn't allocate a new line.
+    buffer: String,
+    options: ReaderOptions,
+    columns: Vec<String>,
+    /// The indexes, in the source, of the columns that are kept when reading with
+    /// [ReaderOptions::projection](`ReaderOptions::projection`) or skipping columns.
+    projection: Option<Vec<usize>>,
+    quoted_columns: Vec<usize>,
+    raw_header: String,
+    /// The line break that ended the header (`\n` or `\r\n`), empty if the source has no rows.
+    header_line_break: &'static str,
+    /// Whether the source started with a byte order mark, which isn't part of the header.
+    byte_order_mark: bool,
+    rows_read: usize,
+    bytes_read: u64,
+    /// The number of lines read so far, header included.
+    lines_read: usize,
+    /// The position of the last row read, or of the row that couldn't be read.
+    last_position: Option<Position>,
+    /// The line break that ended the last line read (`\n` or `\r\n`), empty if it had none,
+    /// so that the end of the source can be written back the same way.
+    last_line_break: &'static str,
+    header_bytes: u64,
+    /// The total size of the source in bytes, when it is known in advance.
+    size_hint: Option<u64>,
+    finished: bool,
+    /// The time given by the clock when the reader was created, if the reading is timed.
+    started: Option<Duration>,
+    /// The time it took to read the whole source, set once the end is reached.
+    elapsed: Option<Duration>,
+    allocations_at_start: Option<u64>,
+}
+
+#[cfg(feature = "fs")]
+impl CSVReader<BufReader<File>> {
+    /// Opens a file and reads its header.
+    /// It may return an error if the file doesn't exist, if it's empty or if the header can't be parsed.
+    pub fn open(file_name: &String, options: &ReaderOptions) -> Result<Self, Error> {
+        let file = File::open(file_name)?;
+        let size = file.metadata()?.len();
+        Ok(Self::from_reader(BufReader::new(file), options)?.with_size_hint(size))
+    }
+}
+
+impl<R: BufRead> CSVReader<R> {
+    /// Creates a reader from any buffered source and reads the header.
+    /// It may return an error if the source is empty or if the header can't be parsed.
+    pub fn from_reader(reader: R, options: &ReaderOptions) -> Result<Self, Error> {
+        let started = options.clock.map(|clock| clock());
+        let allocations_at_start = options.allocation_counter.map(|count| count());
+        let mut source = reader;
+        let mut raw_header = String::new();
+        let limits = Limits::new(options, 1);
+        let (mut header_bytes, line_break) =
+            match read_line(&mut source, &mut raw_header, &limits, 0)? {
+                Some(read) => read,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "The file is empty, the header is missing",
+                    ))
+                }
+            };
+
+        // The byte order mark some applications (e.g. Excel) put at the beginning of the file
+        let byte_order_mark = raw_header.starts_with('\u{feff}');
+        if byte_order_mark {
+            raw_header.remove(0);
+        }
+
+        let mut quoted_columns: Vec<usize> = Vec::new();
+        let mut header_line_break = line_break;
+        let mut columns = if raw_header.contains('"') {
+            let (columns, extra_bytes, line_break) = parse_quoted_line(
+                &mut source,
+                &mut raw_header,
+                line_break,
+                options,
+                0,
+                Some(&mut quoted_columns).filter(|_| options.preserve_quotes),
+                &limits,
+            )?;
+            header_bytes += extra_bytes;
+            header_line_break = line_break;
+            columns
+        } else {
+            read_columns(&raw_header, &options.delimiter)?
+        };
+        limits.check_fields(&columns)?;
+        if options.trim.headers() {
+            trim_fields(&mut columns);
+        }
+
+        if let Some(separator) = &options.multi_header {
+            let mut second_row = String::new();
+            let limits = Limits::new(options, raw_header.matches('\n').count() + 2);
+            let line_break = match read_line(&mut source, &mut second_row, &limits, 0)? {
+                Some((bytes, line_break)) => {
+                    header_bytes += bytes;
+                    line_break
+                }
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "The file ends before the second row of the header",
+                    ))
+                }
+            };
+            let first_line_break = header_line_break;
+            header_line_break = line_break;
+            let mut names = if second_row.contains('"') {
+                let (names, extra_bytes, line_break) = parse_quoted_line(
+                    &mut source,
+                    &mut second_row,
+                    line_break,
+                    options,
+                    columns.len(),
+                    None,
+                    &limits,
+                )?;
+                header_bytes += extra_bytes;
+                header_line_break = line_break;
+                names
+            } else {
+                read_columns(&second_row, &options.delimiter)?
+            };
+            limits.check_fields(&names)?;
+            if options.trim.headers() {
+                trim_fields(&mut names);
+            }
+            columns = combine_header_rows(&columns, &names, separator);
+            // The quotes of the two rows can't be attributed to the combined names.
+            quoted_columns.clear();
+            raw_header.push_str(first_line_break);
+            raw_header.push_str(&second_row);
+        }
+
+        let mut projection = match &options.projection {
+            Some(names) => Some(project_columns(&columns, names)?),
+            None => None,
+        };
+        if let Some(skipped) = &options.skipped_columns {
+            let kept = projection.unwrap_or_else(|| (0..columns.len()).collect());
+            projection = Some(
+                kept.into_iter()
+                    .filter(|&i| !skipped.matches(&columns[i]))
+                    .collect(),
+            );
+        }
+        if let Some(indexes) = &projection {
+            columns = indexes.iter().map(|&i| columns[i].clone()).collect();
+            quoted_columns = project_quoted(&quoted_columns, indexes);
+        }
+
+        Ok(Self {
+            source,
+            buffer: String::new(),
+            options: options.clone(),
+            bytes_read: header_bytes as u64,
+            lines_read: raw_header.matches('\n').count() + 1,
+            last_position: None,
+            header_bytes: header_bytes as u64,
+            size_hint: None,
+            columns,
+            projection,
+            quoted_columns,
+            raw_header,
+            header_line_break,
+            byte_order_mark,
+            rows_read: 0,
+            last_line_break: header_line_break,
+            finished: false,
+            started,
+            elapsed: None,
+            allocations_at_start,
+        })
+    }
+
+    /// Tells the reader the total size of its source in bytes, header included,
+    /// so that it can estimate the number of rows. [CSVReader::open](`CSVReader::open`)
+    /// already does it using the metadata of the file.
+    pub fn with_size_hint(mut self, total_bytes: u64) -> Self {
+        self.size_hint = Some(total_bytes);
+        self
+    }
+
+    /// Estimates the total number of rows of the source from the average length
+    /// of the rows read so far and the size of the source.
+    /// It returns `None` if no row was read yet or if the size of the source is unknown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVReader, ReaderOptions};
+    /// let text = "a,b\n1,2\n3,4\n5,6\n";
+    /// let mut reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default())
+    ///     .unwrap()
+    ///     .with_size_hint(text.len() as u64);
+    /// assert_eq!(reader.estimated_rows(), None);
+    ///
+    /// reader.next();
+    /// assert_eq!(reader.estimated_rows(), Some(3));
+    /// ```
+    pub fn estimated_rows(&self) -> Option<usize> {
+        let total_bytes = self.size_hint?;
+        if self.rows_read == 0 {
+            return None;
+        }
+        let average = (self.bytes_read - self.header_bytes) / self.rows_read as u64;
+        let remaining = total_bytes.saturating_sub(self.bytes_read);
+        Some(self.rows_read + (remaining / average.max(1)) as usize)
+    }
+
+    /// Returns the names of the columns.
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    /// Returns the number of rows read so far.
+    pub fn rows_read(&self) -> usize {
+        self.rows_read
+    }
+
+    /// Returns the number of bytes read so far, header included.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the position of the last row read, or of the row that couldn't be read
+    /// after an error, so that the error can tell where the problem is.
+    /// It returns `None` if no row was read yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVReader, ReaderOptions};
+    /// let text = "a,b\n1,2\n\"3\n4\",5\n6,\"7\n";
+    /// let mut reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+    /// while let Some(Ok(_)) = reader.next() {}
+    ///
+    /// let position = reader.position().unwrap();
+    /// assert_eq!((position.line, position.byte), (5, 16));
+    /// ```
+    pub fn position(&self) -> Option<Position> {
+        self.last_position
+    }
+
+    /// Returns an iterator over the rows along with their positions in the source,
+    /// for example to go back to a row later by seeking its offset.
+    /// The iteration stops at the first error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVReader, ReaderOptions};
+    /// let text = "name,bio\nThomas,\"Likes\nRust\"\nJohn,Nothing\n";
+    /// let mut reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+    ///
+    /// let positions: Vec<(usize, u64)> = reader
+    ///     .records()
+    ///     .map(|record| record.unwrap().position())
+    ///     .map(|position| (position.line, position.byte))
+    ///     .collect();
+    /// assert_eq!(positions, vec![(2, 9), (4, 29)]);
+    /// assert!(text[29..].starts_with("John"));
+    /// ```
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records { reader: self }
+    }
+
+    /// Returns where the reader stands, between the last row read and the next one,
+    /// so that the reading can [resume](`CSVReader::resume`) from there later,
+    /// for example after a restart of the program.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            byte: self.bytes_read,
+            line: self.lines_read + 1,
+            rows: self.rows_read,
+            header_hash: bytes_hash(self.raw_header.as_bytes()),
+        }
+    }
+
+    /// Returns the performance counters of the reading so far,
+    /// or of the whole reading once the end of the source is reached.
+    pub fn stats(&self) -> ReadStats {
+        ReadStats {
+            rows: self.rows_read,
+            bytes: self.bytes_read,
+            elapsed: self.elapsed.or_else(|| self.time_since_start()),
+            allocations: self
+                .options
+                .allocation_counter
+                .zip(self.allocations_at_start)
+                .map(|(count, start)| count().saturating_sub(start)),
+        }
+    }
+
+    /// Returns the time elapsed since the reader was created, if the reading is timed.
+    fn time_since_start(&self) -> Option<Duration> {
+        self.options
+            .clock
+            .zip(self.started)
+            .map(|(clock, start)| clock().saturating_sub(start))
+    }
+
+    /// Returns the character separating the values.
+    pub(crate) fn delimiter(&self) -> char {
+        self.options.delimiter
+    }
+
+    /// Consumes the reader and returns the names of the columns.
+    pub(crate) fn into_columns(self) -> Vec<String> {
+        self.columns
+    }
+
+    /// Returns the header line as it was written in the source.
+    pub(crate) fn raw_header(&self) -> &String {
+        &self.raw_header
+    }
+
+    /// Returns the line break that ended the header, empty if the source has no rows.
+    pub(crate) fn header_line_break(&self) -> &'static str {
+        self.header_line_break
+    }
+
+    /// Returns `true` if the source started with a byte order mark.
+    pub(crate) fn has_byte_order_mark(&self) -> bool {
+        self.byte_order_mark
+    }
+
+    /// Returns the line break that ended the last line read so far, empty if it had none.
+    pub(crate) fn last_line_break(&self) -> &'static str {
+        self.last_line_break
+    }
+
+    /// Returns the indexes of the columns whose name was surrounded by double quotes.
+    pub(crate) fn quoted_columns(&self) -> &Vec<usize> {
+        &self.quoted_columns
+    }
+
+    /// Reads the next row, keeping the details about its source line.
+    /// It returns `None` once the end of the file is reached or after an error.
+    pub(crate) fn next_record(&mut self) -> Option<Result<RawRecord, Error>> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.read_record();
+        match &result {
+            Some(Ok(_)) => {
+                if let Some(progress) = &self.options.on_progress {
+                    progress.tick(self.rows_read, self.bytes_read);
+                }
+            }
+            Some(Err(_)) => self.finished = true,
+            None => {
+                self.finished = true;
+                self.elapsed = self.time_since_start();
+                if let Some(progress) = &self.options.on_progress {
+                    progress.finish(self.rows_read, self.bytes_read);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn read_record(&mut self) -> Option<Result<RawRecord, Error>> {
+        if let Some(token) = &self.options.cancellation {
+            if let Err(e) = token.check() {
+                return Some(Err(e));
+            }
+        }
+
+        let position = Position {
+            line: self.lines_read + 1,
+            byte: self.bytes_read,
+        };
+        let limits = Limits::new(&self.options, position.line);
+        let line_break = match read_line(&mut self.source, &mut self.buffer, &limits, 0) {
+            Ok(Some((bytes, line_break))) => {
+                self.bytes_read += bytes as u64;
+                self.last_position = Some(position);
+                line_break
+            }
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let delimiter = self.options.delimiter;
+        let mut quoted: Vec<usize> = Vec::new();
+        let has_quotes = self.buffer.contains('"');
+        let fields = if has_quotes {
+            parse_quoted_line(
+                &mut self.source,
+                &mut self.buffer,
+                line_break,
+                &self.options,
+                self.columns.len(),
+                Some(&mut quoted).filter(|_| self.options.preserve_quotes),
+                &limits,
+            )
+            .map(|(fields, extra_bytes, line_break)| {
+                self.bytes_read += extra_bytes as u64;
+                // The following lines were appended along with their line breaks.
+                self.lines_read += self.buffer.matches('\n').count();
+                self.last_line_break = line_break;
+                fields
+            })
+        } else if let Some(projection) = &self.projection {
+            self.last_line_break = line_break;
+            // Only the kept values get allocated.
+            let mut fields = vec![String::new(); projection.len()];
+            for (i, value) in self.buffer.split(delimiter).enumerate() {
+                if let Some(position) = projection.iter().position(|&p| p == i) {
+                    fields[position] = value.to_string();
+                }
+            }
+            Ok(fields)
+        } else {
+            self.last_line_break = line_break;
+            // The capacity is known in advance, so the row is allocated only once.
+            let mut fields = Vec::with_capacity(self.columns.len());
+            fields.extend(self.buffer.split(delimiter).map(String::from));
+            Ok(fields)
+        };
+        self.lines_read += 1;
+        let mut fields =
+            match fields.and_then(|fields| limits.check_fields(&fields).map(|_| fields)) {
+                Ok(fields) => fields,
+                Err(e) => return Some(Err(e)),
+            };
+        if let (Some(projection), true) = (&self.projection, has_quotes) {
+            fields = projection
+                .iter()
+                .map(|&i| fields.get_mut(i).map(std::mem::take).unwrap_or_default())
+                .collect();
+            quoted = project_quoted(&quoted, projection);
+        }
+        if self.options.trim.fields() {
+            trim_fields(&mut fields);
+        }
+        if !self.options.null_values.is_empty() {
+            for field in fields.iter_mut() {
+                if self.options.null_values.contains(field) {
+                    field.clear();
+                }
+            }
+        }
+
+        self.rows_read += 1;
+        Some(Ok(RawRecord {
+            line: self.options.lossless.then(|| self.buffer.clone()),
+            fields,
+            quoted,
+            position,
+        }))
+    }
+}
+
+impl<R: BufRead + Seek> CSVReader<R> {
+    /// Moves the reader to a [checkpoint](`CSVReader::checkpoint`) taken earlier on the same source,
+    /// so that the next row read is the one that followed the checkpoint,
+    /// without reading again the rows before it. The counters of rows,
+    /// bytes and lines continue from the ones of the checkpoint.
+    ///
+    /// It may return an error if the header of the source isn't the one the checkpoint
+    /// was taken with, or if the source can't be seeked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::io::Cursor;
+    /// # use csv_tools::{Checkpoint, CSVReader, ReaderOptions};
+    /// let text = "id,name\n1,Thomas\n2,John\n3,Marie\n";
+    /// let mut reader = CSVReader::from_reader(Cursor::new(text), &ReaderOptions::default()).unwrap();
+    /// reader.next();
+    /// let saved = reader.checkpoint().to_json();
+    ///
+    /// // Later, after a restart
+    /// let mut reader = CSVReader::from_reader(Cursor::new(text), &ReaderOptions::default()).unwrap();
+    /// reader.resume(&Checkpoint::from_json(&saved).unwrap()).unwrap();
+    /// assert_eq!(reader.next().unwrap().unwrap(), vec!["2", "John"]);
+    /// assert_eq!(reader.rows_read(), 2);
+    /// ```
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> Result<(), Error> {
+        if checkpoint.header_hash != bytes_hash(self.raw_header.as_bytes()) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The checkpoint was taken on a source with a different header",
+            ));
+        }
+        if checkpoint.byte < self.header_bytes || checkpoint.line < 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The checkpoint is before the end of the header",
+            ));
+        }
+        self.source.seek(SeekFrom::Start(checkpoint.byte))?;
+        self.bytes_read = checkpoint.byte;
+        self.lines_read = checkpoint.line - 1;
+        self.rows_read = checkpoint.rows;
+        self.last_position = None;
+        self.finished = false;
+        self.elapsed = None;
+        Ok(())
+    }
+}
+
+/// The maximum sizes set in the options, along with the line
+/// of the record being read, for the errors.
+struct Limits {
+    field: Option<usize>,
+    record: Option<usize>,
+    line: usize,
+    /// Ends the values, to bound the lines read with only a maximum field size.
+    delimiter: char,
+}
+
+impl Limits {
+    fn new(options: &ReaderOptions, line: usize) -> Self {
+        Self {
+            field: options.max_field_size,
+            record: options.max_record_size,
+            line,
+            delimiter: options.delimiter,
+        }
+    }
+
+    fn field_error(&self, max: usize) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "A value of the record at line {} is longer than the maximum of {} bytes",
+                self.line, max
+            ),
+        )
+    }
+
+    fn check_fields(&self, fields: &[String]) -> Result<(), Error> {
+        match self.field {
+            Some(max) if fields.iter().any(|field| field.len() > max) => Err(self.field_error(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads the next line into `buffer`, without its line break (`\n` or `\r\n`).
+/// It returns the number of bytes read along with the line break that was removed,
+/// or `None` at the end of the source.
+///
+/// With a maximum record size, `used` being the size of the beginning of the record
+/// already read, it never reads much more than the maximum, and fails if the line
+/// makes the record longer than that. With only a maximum field size, it reads the line
+/// by pieces of the same budget, and fails as soon as a value is too long,
+/// so that a line without any delimiter isn't read entirely.
+fn read_line<R: BufRead>(
+    source: &mut R,
+    buffer: &mut String,
+    limits: &Limits,
+    used: usize,
+) -> Result<Option<(usize, &'static str)>, Error> {
+    buffer.clear();
+    let bytes = match (limits.record, limits.field) {
+        (None, None) => source.read_line(buffer)?,
+        (Some(max), _) => {
+            let mut line = std::mem::take(buffer).into_bytes();
+            // Two more bytes for the line break
+            let budget = max.saturating_sub(used) as u64 + 2;
+            let bytes = Read::take(&mut *source, budget).read_until(b'\n', &mut line)?;
+            let content = line.strip_suffix(b"\n").unwrap_or(&line);
+            let content = content.strip_suffix(b"\r").unwrap_or(content);
+            if used + content.len() > max {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The record at line {} is longer than the maximum of {} bytes",
+                        limits.line, max
+                    ),
+                ));
+            }
+            *buffer = String::from_utf8(line).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            bytes
+        }
+        (None, Some(max)) => {
+            let mut line = std::mem::take(buffer).into_bytes();
+            let bytes = read_line_by_field(source, &mut line, limits, max)?;
+            *buffer = String::from_utf8(line).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            bytes
+        }
+    };
+    if bytes == 0 {
+        return Ok(None);
+    }
+    let mut line_break = "";
+    if buffer.ends_with('\n') {
+        buffer.pop();
+        line_break = "\n";
+        if buffer.ends_with('\r') {
+            buffer.pop();
+            line_break = "\r\n";
+        }
+    }
+    Ok(Some((bytes, line_break)))
+}
+
+/// Reads a line whose values can't be longer than `max` bytes, by pieces,
+/// failing as soon as the bytes since the last delimiter are certainly too many for a value:
+/// once unescaped, a value is at least half as long as in the source, without its two quotes.
+/// The exact sizes are checked once the line is parsed.
+fn read_line_by_field<R: BufRead>(
+    source: &mut R,
+    line: &mut Vec<u8>,
+    limits: &Limits,
+    max: usize,
+) -> Result<usize, Error> {
+    let mut delimiter = [0; 4];
+    let delimiter = limits.delimiter.encode_utf8(&mut delimiter).as_bytes();
+    // Two more bytes for the quotes, and two for the line break
+    let budget = 2 * max + 4;
+    let mut since_delimiter = 0;
+    let mut bytes = 0;
+    loop {
+        let start = line.len();
+        let read = Read::take(&mut *source, budget as u64).read_until(b'\n', line)?;
+        bytes += read;
+        for end in start + 1..=line.len() {
+            if line[..end].ends_with(delimiter) {
+                since_delimiter = 0;
+            } else {
+                since_delimiter += 1;
+            }
+        }
+        // The first bytes of a delimiter longer than a byte are counted before it's recognized.
+        if since_delimiter > budget + delimiter.len() {
+            return Err(limits.field_error(max));
+        }
+        if read == 0 || line.ends_with(b"\n") {
+            return Ok(bytes);
+        }
+    }
+}
+
+/// Parses the line held in `buffer`. Since quoted values may contain line breaks,
+/// the following lines of the source are appended to it, line break included,
+/// as long as it ends in the middle of a quoted value.
+/// It returns the fields along with the number of bytes read from the following lines
+/// and the line break that ended the last of them.
+fn parse_quoted_line<R: BufRead>(
+    source: &mut R,
+    buffer: &mut String,
+    mut line_break: &'static str,
+    options: &ReaderOptions,
+    number_of_fields: usize,
+    mut quoted: Option<&mut Vec<usize>>,
+    limits: &Limits,
+) -> Result<(Vec<String>, usize, &'static str), Error> {
+    let mut extra_bytes = 0;
+    // The following lines all belong to the quoted value that isn't closed.
+    let mut unclosed_value_size = 0;
+    let mut next_line = String::new();
+    loop {
+        let mut fields = Vec::with_capacity(number_of_fields);
+        if let Some(quoted) = quoted.as_deref_mut() {
+            quoted.clear();
+        }
+        match parser::parse_line_with_escape_into(
+            buffer,
+            options.delimiter,
+            options.escape,
+            &mut fields,
+            quoted.as_deref_mut(),
+        ) {
+            Ok(()) => return Ok((fields, extra_bytes, line_break)),
+            Err(ParseError::UnclosedQuote) if !line_break.is_empty() => {
+                let used = buffer.len() + line_break.len();
+                match read_line(source, &mut next_line, limits, used)? {
+                    Some((bytes, next_line_break)) => {
+                        unclosed_value_size += line_break.len() + next_line.len();
+                        if let Some(max) = limits.field.filter(|max| unclosed_value_size > *max) {
+                            return Err(limits.field_error(max));
+                        }
+                        buffer.push_str(line_break);
+                        buffer.push_str(&next_line);
+                        extra_bytes += bytes;
+                        line_break = next_line_break;
+                    }
+                    None => return Err(ParseError::UnclosedQuote.into()),
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Names the columns of a two-row header after their group and their own name.
+/// The empty groups continue the group on their left.
+fn combine_header_rows(groups: &[String], names: &[String], separator: &str) -> Vec<String> {
+    let mut group = "";
+    (0..groups.len().max(names.len()))
+        .map(|i| {
+            if let Some(g) = groups.get(i).filter(|g| !g.is_empty()) {
+                group = g;
+            }
+            let name = names.get(i).map(String::as_str).unwrap_or_default();
+            match (group.is_empty(), name.is_empty()) {
+                (true, _) => name.to_string(),
+                (false, true) => group.to_string(),
+                (false, false) => format!("{}{}{}", group, separator, name),
+            }
+        })
+        .collect()
+}
+
+/// Finds the indexes of the projected columns, in the order they were given.
+fn project_columns(columns: &Vec<String>, names: &Vec<String>) -> Result<Vec<usize>, Error> {
+    let mut indexes = Vec::with_capacity(names.len());
+    for name in names {
+        let idx = match columns.iter().position(|c| c == name) {
+            Some(idx) => idx,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", name),
+                ))
+            }
+        };
+        if indexes.contains(&idx) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} is projected more than once", name),
+            ));
+        }
+        indexes.push(idx);
+    }
+    Ok(indexes)
+}
+
+/// Converts the indexes of quoted fields in the source to their indexes once projected.
+fn project_quoted(quoted: &Vec<usize>, projection: &Vec<usize>) -> Vec<usize> {
+    projection
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| quoted.contains(i))
+        .map(|(position, _)| position)
+        .collect()
+}
+
+impl<R: BufRead> Iterator for CSVReader<R> {
+    type Item = Result<Vec<String>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_record()?.map(|record| record.fields))
+    }
+}
@@ -0,0 +1,209 @@
+//! Conversions between CSV files and JSON arrays of records.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, ErrorKind};
+
+use crate::json::{self, JsonValue};
+use crate::CSVFile;
+
+/// The options of [to_json](`CSVFile::to_json`) and [from_json](`CSVFile::from_json`).
+#[derive(Debug, Clone)]
+pub struct JsonOptions {
+    /// Treats the separator in the column names as a path into nested objects,
+    /// so that `address.city` is exported as `{"address": {"city": ...}}`,
+    /// and flattens the nested objects and arrays on import (`true` by default).
+    pub nested: bool,
+    /// The character separating the levels of nesting in the column names (`.` by default).
+    pub separator: char,
+    /// Indents the exported JSON (`false` by default).
+    pub pretty: bool,
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self {
+            nested: true,
+            separator: '.',
+            pretty: false,
+        }
+    }
+}
+
+/// Inserts a value in an object, following the path of keys.
+fn insert_nested(members: &mut Vec<(String, JsonValue)>, path: &[&str], value: JsonValue) {
+    let key = path[0];
+    if path.len() == 1 {
+        members.push((key.to_string(), value));
+        return;
+    }
+    let idx = match members.iter().position(|(k, _)| k == key) {
+        Some(idx) => idx,
+        None => {
+            members.push((key.to_string(), JsonValue::Object(Vec::new())));
+            members.len() - 1
+        }
+    };
+    if let JsonValue::Object(children) = &mut members[idx].1 {
+        insert_nested(children, &path[1..], value);
+    }
+}
+
+/// Flattens a value into the cells of a row, naming the nested values
+/// by the path of keys (or indexes of arrays) leading to them.
+fn flatten(
+    prefix: String,
+    value: &JsonValue,
+    options: &JsonOptions,
+    cells: &mut Vec<(String, String)>,
+) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}{}{}", prefix, options.separator, key)
+        }
+    };
+    match value {
+        JsonValue::Object(members) if options.nested && !members.is_empty() => {
+            for (key, value) in members {
+                flatten(join(key), value, options, cells);
+            }
+        }
+        JsonValue::Array(values) if options.nested && !values.is_empty() => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(join(&i.to_string()), value, options, cells);
+            }
+        }
+        JsonValue::Null => cells.push((prefix, String::new())),
+        JsonValue::String(s) => cells.push((prefix, s.clone())),
+        other => cells.push((prefix, other.to_string())),
+    }
+}
+
+impl CSVFile {
+    /// Exports the rows as a JSON array of objects, whose keys are the columns.
+    /// All the values are exported as strings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, JsonOptions, ReaderOptions};
+    /// let text = "name,address.city,address.zip\nThomas,Lyon,69001\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     file.to_json(&JsonOptions::default()),
+    ///     r#"[{"name":"Thomas","address":{"city":"Lyon","zip":"69001"}}]"#
+    /// );
+    /// ```
+    pub fn to_json(&self, options: &JsonOptions) -> String {
+        // a column is only nested if none of its prefixes is also a column
+        let names: HashSet<&str> = self.columns.iter().map(|c| c.as_str()).collect();
+        let paths: Vec<Vec<&str>> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let path: Vec<&str> = column.split(options.separator).collect();
+                let has_prefix_column = options.nested
+                    && column
+                        .match_indices(options.separator)
+                        .any(|(i, _)| names.contains(&column[..i]));
+                if !options.nested || has_prefix_column || path.iter().any(|k| k.is_empty()) {
+                    vec![column.as_str()]
+                } else {
+                    path
+                }
+            })
+            .collect();
+
+        let records = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut members = Vec::new();
+                for (path, value) in paths.iter().zip(row.iter()) {
+                    insert_nested(&mut members, path, JsonValue::String(value.clone()));
+                }
+                JsonValue::Object(members)
+            })
+            .collect();
+
+        let document = JsonValue::Array(records);
+        if options.pretty {
+            document.to_pretty_string()
+        } else {
+            document.to_string()
+        }
+    }
+
+    /// Imports a JSON array of objects, as written by [to_json](`CSVFile::to_json`).
+    /// The columns are the keys of the objects in the order they first appear,
+    /// and the missing keys get empty values. The numbers and booleans are written
+    /// as in JSON, and `null` becomes an empty value.
+    ///
+    /// With the `nested` option, the nested objects and arrays are flattened
+    /// into columns named after their path (`address.city`, `tags.0`...),
+    /// otherwise they're kept as JSON text.
+    ///
+    /// It may return an error if the JSON is invalid or isn't an array of objects.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, JsonOptions};
+    /// let json = r#"[{"id": 1, "user": {"name": "Thomas", "tags": ["a", "b"]}}, {"id": 2, "active": true}]"#;
+    /// let file = CSVFile::from_json(json, &',', &JsonOptions::default()).unwrap();
+    ///
+    /// assert_eq!(file.columns, vec!["id", "user.name", "user.tags.0", "user.tags.1", "active"]);
+    /// assert_eq!(file.rows[1], vec!["2", "", "", "", "true"]);
+    /// ```
+    pub fn from_json(
+        text: &str,
+        delimiter: &char,
+        options: &JsonOptions,
+    ) -> Result<CSVFile, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidData,
+                "The JSON must be an array of objects",
+            )
+        };
+
+        let JsonValue::Array(records) = json::parse(text)? else {
+            return Err(invalid());
+        };
+
+        let mut columns: Vec<String> = Vec::new();
+        let mut indexes: HashMap<String, usize> = HashMap::new();
+        let mut records_cells: Vec<Vec<(String, String)>> = Vec::with_capacity(records.len());
+        for record in &records {
+            let JsonValue::Object(members) = record else {
+                return Err(invalid());
+            };
+            let mut cells = Vec::new();
+            for (key, value) in members {
+                flatten(key.clone(), value, options, &mut cells);
+            }
+            for (column, _) in &cells {
+                if !indexes.contains_key(column) {
+                    indexes.insert(column.clone(), columns.len());
+                    columns.push(column.clone());
+                }
+            }
+            records_cells.push(cells);
+        }
+
+        let rows = records_cells
+            .into_iter()
+            .map(|cells| {
+                let mut row = vec![String::new(); columns.len()];
+                for (column, value) in cells {
+                    row[indexes[&column]] = value;
+                }
+                row
+            })
+            .collect();
+
+        Ok(CSVFile::from_rows(*delimiter, columns, rows))
+    }
+}
@@ -0,0 +1,210 @@
+//! Spreadsheet-style references to the cells, such as `A1` or `C12`, and to blocks of cells.
+
+use std::io::{Error, ErrorKind};
+
+use crate::{CSVCoords, CSVFile};
+
+fn invalid_reference(reference: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("The cell reference {} is invalid", reference),
+    )
+}
+
+impl CSVCoords {
+    /// Parses a reference to a cell in the notation of spreadsheets, made of the letters
+    /// of the column followed by the number of the row, such as `C12`.
+    /// The columns go from `A` to `Z`, then from `AA` to `AZ`, and so on,
+    /// and the rows are numbered from 1. The header isn't counted, so `A1` is the first value of the first row.
+    ///
+    /// The letters may be lowercase, and the dollar signs of absolute references (`$C$12`) are ignored.
+    /// It may return an error if the reference is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVCoords;
+    /// let coords = CSVCoords::from_a1("C12").unwrap();
+    /// assert_eq!((coords.row, coords.column), (11, 2));
+    /// assert_eq!(CSVCoords::from_a1("$AA$1").unwrap().column, 26);
+    /// assert!(CSVCoords::from_a1("A0").is_err());
+    /// ```
+    pub fn from_a1(reference: &str) -> Result<Self, Error> {
+        let trimmed = reference.trim();
+        let rest = trimmed.strip_prefix('$').unwrap_or(trimmed);
+        let letters_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (letters, digits) = rest.split_at(letters_end);
+        let digits = digits.strip_prefix('$').unwrap_or(digits);
+        if letters.is_empty() {
+            return Err(invalid_reference(reference));
+        }
+
+        let mut column: usize = 0;
+        for c in letters.chars() {
+            let value = (c.to_ascii_uppercase() as u8 - b'A') as usize + 1;
+            column = column
+                .checked_mul(26)
+                .and_then(|column| column.checked_add(value))
+                .ok_or_else(|| invalid_reference(reference))?;
+        }
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid_reference(reference));
+        }
+        let row: usize = digits
+            .parse()
+            .ok()
+            .filter(|row| *row > 0)
+            .ok_or_else(|| invalid_reference(reference))?;
+
+        Ok(Self {
+            row: row - 1,
+            column: column - 1,
+        })
+    }
+
+    /// Formats the coordinates in the notation of spreadsheets, such as `C12`.
+    /// See [from_a1](`CSVCoords::from_a1`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVCoords;
+    /// assert_eq!(CSVCoords { row: 11, column: 2 }.to_a1(), "C12");
+    /// assert_eq!(CSVCoords { row: 0, column: 27 }.to_a1(), "AB1");
+    /// ```
+    pub fn to_a1(&self) -> String {
+        let mut letters: Vec<u8> = Vec::new();
+        let mut column = self.column + 1;
+        while column > 0 {
+            column -= 1;
+            letters.push(b'A' + (column % 26) as u8);
+            column /= 26;
+        }
+        letters.reverse();
+        format!("{}{}", String::from_utf8(letters).unwrap(), self.row + 1)
+    }
+}
+
+impl CSVFile {
+    /// Gets the value of a cell from a reference in the notation of spreadsheets,
+    /// such as `B2` (see [CSVCoords::from_a1](`CSVCoords::from_a1`)).
+    /// It returns `None` if the reference is invalid or if the cell doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("name,age\nThomas,19\nYoshiip,20\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// assert_eq!(file.get_cell_a1("B2"), Some(&"20".to_string()));
+    /// assert_eq!(file.get_cell_a1("C1"), None);
+    /// ```
+    pub fn get_cell_a1(&self, reference: &str) -> Option<&String> {
+        self.get_cell(&CSVCoords::from_a1(reference).ok()?)
+    }
+
+    /// Copies a rectangular block of cells into a new file, from the top left corner
+    /// to the bottom right corner, both included. The new file has the columns of the block.
+    ///
+    /// It may return an error if a corner is out of range,
+    /// or if the bottom right corner is above or to the left of the top left corner.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("a,b,c\n1,2,3\n4,5,6\n7,8,9\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let block = file.get_range(&CSVCoords::from_a1("B2").unwrap(), &CSVCoords::from_a1("C3").unwrap()).unwrap();
+    /// assert_eq!(block.columns, vec!["b", "c"]);
+    /// assert_eq!(block.rows, vec![vec!["5", "6"], vec!["8", "9"]]);
+    /// ```
+    pub fn get_range(
+        &self,
+        top_left: &CSVCoords,
+        bottom_right: &CSVCoords,
+    ) -> Result<CSVFile, Error> {
+        for corner in [top_left, bottom_right] {
+            if self.get_cell(corner).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The cell at row {} and column {} doesn't exist",
+                        corner.row, corner.column
+                    ),
+                ));
+            }
+        }
+        if bottom_right.row < top_left.row || bottom_right.column < top_left.column {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The range from {} to {} is inverted",
+                    top_left.to_a1(),
+                    bottom_right.to_a1()
+                ),
+            ));
+        }
+
+        let columns = top_left.column..=bottom_right.column;
+        let rows = self.rows[top_left.row..=bottom_right.row]
+            .iter()
+            .map(|row| row[columns.clone()].to_vec())
+            .collect();
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            self.columns[columns].to_vec(),
+            rows,
+        ))
+    }
+
+    /// Pastes the rows of another file as a block of cells whose top left corner is at the given coordinates,
+    /// replacing the values of the cells it covers. The header of the other file is ignored.
+    ///
+    /// The modified rows are checked by the [validators](`crate::Validator`) before any of them
+    /// is replaced, so the file is left untouched if one of them is rejected,
+    /// and each modified cell is reported to the [observers](`CSVFile::on_change`).
+    ///
+    /// It may return an error if the block doesn't fit in the file, or if a row is rejected by a validator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("a,b,c\n1,2,3\n4,5,6\n", &ReaderOptions::default()).unwrap();
+    /// let block = CSVFile::from_text("x,y\n0,0\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// file.set_range(&CSVCoords::from_a1("B2").unwrap(), &block).unwrap();
+    /// assert_eq!(file.rows, vec![vec!["1", "2", "3"], vec!["4", "0", "0"]]);
+    /// ```
+    pub fn set_range(&mut self, top_left: &CSVCoords, block: &CSVFile) -> Result<(), Error> {
+        let fits = top_left.row + block.count_rows() <= self.count_rows()
+            && top_left.column + block.len() <= self.len();
+        if !fits {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "A block of {} rows and {} columns doesn't fit at {}",
+                    block.count_rows(),
+                    block.len(),
+                    top_left.to_a1()
+                ),
+            ));
+        }
+
+        let mut new_rows: Vec<Vec<String>> = Vec::with_capacity(block.count_rows());
+        for (i, values) in block.rows.iter().enumerate() {
+            let mut row = self.rows[top_left.row + i].clone();
+            row[top_left.column..top_left.column + block.len()].clone_from_slice(values);
+            self.validate_row(&row)?;
+            new_rows.push(row);
+        }
+        for (i, row) in new_rows.into_iter().enumerate() {
+            self.replace_row(top_left.row + i, row);
+        }
+
+        Ok(())
+    }
+}
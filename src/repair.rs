@@ -0,0 +1,313 @@
+//! Recovery of the CSV texts damaged by hand edits or by naive exports,
+//! whose rows can't be read as they are.
+
+use std::io::{Error, ErrorKind};
+
+use crate::parser::{parse_line_with_escape_into, split_line, Escape, ParseError};
+use crate::stats::parse_number;
+use crate::CSVFile;
+
+/// A change made by [CSVFile::repair](`crate::CSVFile::repair`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairKind {
+    /// A double quote was never closed, so it got closed at the end of the line.
+    ClosedQuote,
+    /// The row was broken by stray line breaks, so the given number of lines were joined.
+    JoinedLines { lines: usize },
+    /// The row was too short, so the given number of empty values were added at the end.
+    PaddedRow { missing: usize },
+    /// The row was too long, so the given number of fields were joined with the delimiter
+    /// into the value of the column of the given index, as if the delimiter was part of the value.
+    MergedFields { column: usize, fields: usize },
+    /// An empty line was removed.
+    RemovedBlankLine,
+}
+
+/// A change made by [CSVFile::repair](`crate::CSVFile::repair`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+    /// The number of the first line concerned in the text, starting at 1 for the header.
+    pub line: usize,
+    pub kind: RepairKind,
+}
+
+/// The options of [CSVFile::repair](`crate::CSVFile::repair`).
+#[derive(Debug, Clone)]
+pub struct RepairOptions {
+    /// The character separating the values (a comma by default).
+    pub delimiter: char,
+    /// How the double quotes are escaped inside quoted values ([Escape::Backslash](`Escape::Backslash`) by default).
+    pub escape: Escape,
+    /// The text put between the parts of a value broken by a stray line break (a space by default).
+    pub line_joiner: String,
+    /// The maximum number of lines a quoted value may span before its quote
+    /// is considered unbalanced (10 by default).
+    pub max_quoted_lines: usize,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            escape: Escape::Backslash,
+            line_joiner: String::from(" "),
+            max_quoted_lines: 10,
+        }
+    }
+}
+
+/// The changes made by [CSVFile::repair](`crate::CSVFile::repair`), in the order of the lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepairReport {
+    pub repairs: Vec<Repair>,
+}
+
+impl RepairReport {
+    /// Returns `true` if nothing had to be changed.
+    pub fn is_clean(&self) -> bool {
+        self.repairs.is_empty()
+    }
+}
+
+/// Parses a line, failing if its quotes or its escapes are unbalanced.
+fn parse(line: &str, options: &RepairOptions) -> Result<Vec<String>, ParseError> {
+    let mut fields = Vec::new();
+    parse_line_with_escape_into(line, options.delimiter, options.escape, &mut fields, None)?;
+    Ok(fields)
+}
+
+/// Goes through the lines of the text, assembling the records.
+struct Lines<'a> {
+    lines: Vec<&'a str>,
+    next: usize,
+    options: &'a RepairOptions,
+    report: RepairReport,
+}
+
+impl Lines<'_> {
+    /// Reads the next record, along with the index of its first line.
+    /// If the number of fields is known, a quote still open at the end of the line
+    /// may be closed on the following lines only if it gives the right number of fields.
+    fn next_record(&mut self, expected: Option<usize>) -> Option<(usize, Vec<String>)> {
+        while self.lines.get(self.next)?.is_empty() {
+            self.report.repairs.push(Repair {
+                line: self.next + 1,
+                kind: RepairKind::RemovedBlankLine,
+            });
+            self.next += 1;
+        }
+
+        let first = self.next;
+        let line = self.lines[first];
+        self.next += 1;
+        match parse(line, self.options) {
+            Ok(fields) => return Some((first, fields)),
+            Err(ParseError::UnclosedQuote) if expected.is_some() => {
+                let mut joined = line.to_string();
+                let last = (first + self.options.max_quoted_lines).min(self.lines.len());
+                for end in first + 1..last {
+                    joined.push('\n');
+                    joined.push_str(self.lines[end]);
+                    match parse(&joined, self.options) {
+                        Ok(fields) if Some(fields.len()) == expected => {
+                            self.next = end + 1;
+                            return Some((first, fields));
+                        }
+                        Ok(_) => break,
+                        Err(_) => {}
+                    }
+                }
+            }
+            Err(_) => {}
+        }
+
+        self.report.repairs.push(Repair {
+            line: first + 1,
+            kind: RepairKind::ClosedQuote,
+        });
+        let fields = parse(&format!("{}\"", line), self.options)
+            .unwrap_or_else(|_| split_line(line, self.options.delimiter));
+        Some((first, fields))
+    }
+
+    /// Adds the following lines to a record that is too short, as long as they're also too short,
+    /// joining the last value of a line with the first value of the next one.
+    fn join_broken_lines(&mut self, fields: &mut Vec<String>, expected: usize) -> usize {
+        let mut joined = 1;
+        while fields.len() < expected {
+            let Some(line) = self.lines.get(self.next) else {
+                break;
+            };
+            let Ok(mut next) = parse(line, self.options) else {
+                break;
+            };
+            if line.is_empty() || fields.len() + next.len() - 1 > expected {
+                break;
+            }
+            let end = fields.pop().unwrap_or_default();
+            let start = next.remove(0);
+            fields.push(if end.is_empty() {
+                start
+            } else if start.is_empty() {
+                end
+            } else {
+                end + &self.options.line_joiner + &start
+            });
+            fields.append(&mut next);
+            self.next += 1;
+            joined += 1;
+        }
+        joined
+    }
+}
+
+/// Merges the extra fields of a row into a single column, choosing the one that keeps
+/// the most numbers in the numeric columns (the last one if it doesn't matter).
+fn merge_extra_fields(
+    fields: &[String],
+    numeric: &[bool],
+    delimiter: char,
+) -> (usize, Vec<String>) {
+    let expected = numeric.len();
+    let extra = fields.len() - expected;
+    let separator = delimiter.to_string();
+    let mut best: Option<(usize, usize, Vec<String>)> = None;
+    for column in 0..expected {
+        let mut row: Vec<String> = fields[..column].to_vec();
+        row.push(fields[column..=column + extra].join(&separator));
+        row.extend_from_slice(&fields[column + extra + 1..]);
+        let score = row
+            .iter()
+            .zip(numeric)
+            .filter(|(value, &numeric)| {
+                !numeric || value.trim().is_empty() || parse_number(value).is_some()
+            })
+            .count();
+        if best
+            .as_ref()
+            .is_none_or(|(best_score, _, _)| score >= *best_score)
+        {
+            best = Some((score, column, row));
+        }
+    }
+    let (_, column, row) = best.unwrap_or_default();
+    (column, row)
+}
+
+impl CSVFile {
+    /// Reads a damaged CSV text, fixing the most common corruptions instead of failing,
+    /// and reports every change that was made:
+    ///
+    /// - a quote left open at the end of a line is closed, unless closing it on one of the
+    ///   following lines gives a row with the right number of fields (a value with line breaks),
+    /// - a row broken by stray line breaks is joined back, as long as the lines are too short,
+    /// - a row that remains too short is padded with empty values,
+    /// - the extra fields of a row that is too long are joined back into a single value,
+    ///   as if its delimiter was part of the value, in the column that keeps the numeric columns
+    ///   numeric (or in the last column when it doesn't matter),
+    /// - empty lines are removed.
+    ///
+    /// It may return an error if the text is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, RepairKind, RepairOptions};
+    /// let text = "name,age,city\nThomas,19,\"Paris\nJohn\nSmith,25,London\nMarie,28,Lyon,France\n";
+    /// let (file, report) = CSVFile::repair(text, &RepairOptions::default()).unwrap();
+    ///
+    /// assert_eq!(file.rows[0], vec!["Thomas", "19", "Paris"]);
+    /// assert_eq!(file.rows[1], vec!["John Smith", "25", "London"]);
+    /// assert_eq!(file.rows[2], vec!["Marie", "28", "Lyon,France"]);
+    ///
+    /// let kinds: Vec<RepairKind> = report.repairs.iter().map(|r| r.kind).collect();
+    /// assert_eq!(kinds, vec![
+    ///     RepairKind::ClosedQuote,
+    ///     RepairKind::JoinedLines { lines: 2 },
+    ///     RepairKind::MergedFields { column: 2, fields: 2 },
+    /// ]);
+    /// ```
+    pub fn repair(text: &str, options: &RepairOptions) -> Result<(CSVFile, RepairReport), Error> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let mut lines: Vec<&str> = text
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
+        if text.ends_with('\n') {
+            lines.pop();
+        }
+        let mut lines = Lines {
+            lines,
+            next: 0,
+            options,
+            report: RepairReport::default(),
+        };
+
+        let Some((_, columns)) = lines.next_record(None) else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The file is empty, the header is missing",
+            ));
+        };
+        let expected = columns.len();
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        // The rows that are too long, with the index of their repair, are fixed last,
+        // once the numeric columns are known.
+        let mut long_rows: Vec<(usize, usize)> = Vec::new();
+        while let Some((line, mut fields)) = lines.next_record(Some(expected)) {
+            if fields.len() < expected {
+                let joined = lines.join_broken_lines(&mut fields, expected);
+                if joined > 1 {
+                    lines.report.repairs.push(Repair {
+                        line: line + 1,
+                        kind: RepairKind::JoinedLines { lines: joined },
+                    });
+                }
+            }
+            if fields.len() < expected {
+                lines.report.repairs.push(Repair {
+                    line: line + 1,
+                    kind: RepairKind::PaddedRow {
+                        missing: expected - fields.len(),
+                    },
+                });
+                fields.resize(expected, String::new());
+            } else if fields.len() > expected {
+                long_rows.push((rows.len(), lines.report.repairs.len()));
+                lines.report.repairs.push(Repair {
+                    line: line + 1,
+                    kind: RepairKind::MergedFields {
+                        column: 0,
+                        fields: 0,
+                    },
+                });
+            }
+            rows.push(fields);
+        }
+
+        let numeric: Vec<bool> = (0..expected)
+            .map(|j| {
+                let mut values = rows
+                    .iter()
+                    .filter(|row| row.len() == expected)
+                    .map(|row| row[j].trim())
+                    .filter(|value| !value.is_empty())
+                    .peekable();
+                values.peek().is_some() && values.all(|value| parse_number(value).is_some())
+            })
+            .collect();
+        let mut report = lines.report;
+        for (row, repair) in long_rows {
+            let extra = rows[row].len() - expected;
+            let (column, merged) = merge_extra_fields(&rows[row], &numeric, options.delimiter);
+            rows[row] = merged;
+            report.repairs[repair].kind = RepairKind::MergedFields {
+                column,
+                fields: extra + 1,
+            };
+        }
+
+        Ok((CSVFile::from_rows(options.delimiter, columns, rows), report))
+    }
+}
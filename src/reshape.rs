@@ -0,0 +1,308 @@
+//! Operations turning some columns into other columns.
+
+use std::io::{Error, ErrorKind};
+
+use crate::{CSVFile, ChangeEvent};
+
+/// What [conform_to](`CSVFile::conform_to`) does with the columns that aren't in the target list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConformPolicy {
+    /// The extra columns are kept after the target columns, in their original order.
+    #[default]
+    KeepExtras,
+    /// The extra columns are removed.
+    DropExtras,
+    /// Nothing is modified and an error is returned if there are extra columns.
+    RejectExtras,
+}
+
+impl CSVFile {
+    /// Replaces a column by several new columns, splitting each of its values
+    /// around the separator. A value is split into at most as many parts as there are
+    /// new columns (the last part keeps the remaining separators), and the missing parts are empty.
+    /// The rows too short to have a value are completed with empty values.
+    ///
+    /// It may return an error if the column doesn't exist, if the separator is empty or no new column is given,
+    /// or if a new column already exists (apart from the one being split).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "id,name\n1,\"Doe, John\"\n2,Cher\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// file.split_column(&"name".to_string(), ", ", &vec!["last".to_string(), "first".to_string()]).unwrap();
+    /// assert_eq!(file.columns, vec!["id", "last", "first"]);
+    /// assert_eq!(file.rows, vec![vec!["1", "Doe", "John"], vec!["2", "Cher", ""]]);
+    /// ```
+    pub fn split_column(
+        &mut self,
+        column_name: &String,
+        separator: &str,
+        new_names: &Vec<String>,
+    ) -> Result<(), Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+        if new_names.is_empty() || separator.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "A column must be split around a separator into at least one column",
+            ));
+        }
+        for (i, name) in new_names.iter().enumerate() {
+            if (name != column_name && self.has_column(name)) || new_names[..i].contains(name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} already exists", name),
+                ));
+            }
+        }
+
+        let mut parts: Vec<Vec<String>> =
+            vec![Vec::with_capacity(self.count_rows()); new_names.len()];
+        for row in &self.rows {
+            let value = row.get(idx).map_or("", String::as_str);
+            let mut split = value.splitn(new_names.len(), separator);
+            for column in parts.iter_mut() {
+                column.push(split.next().unwrap_or_default().to_string());
+            }
+        }
+
+        self.remove_column(idx)?;
+        for (i, (name, values)) in new_names.iter().zip(parts.iter()).enumerate() {
+            self.insert_column(name, idx + i)?;
+            self.fill_column(name, values)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a column joining the values of several columns with a separator,
+    /// which is the inverse of [split_column](`CSVFile::split_column`).
+    /// If `drop_sources` is `true`, the combined columns are removed
+    /// and the new column takes the place of the first of them,
+    /// otherwise it's added after all the other columns.
+    /// The values missing from the ragged rows are joined as empty values.
+    ///
+    /// It may return an error if one of the columns doesn't exist,
+    /// or if the new column already exists (apart from a dropped source).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "id,first,last\n1,John,Doe\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// file.combine_columns(&["first", "last"], " ", &"full_name".to_string(), true).unwrap();
+    /// assert_eq!(file.columns, vec!["id", "full_name"]);
+    /// assert_eq!(file.rows, vec![vec!["1", "John Doe"]]);
+    /// ```
+    pub fn combine_columns(
+        &mut self,
+        column_names: &[&str],
+        separator: &str,
+        new_name: &String,
+        drop_sources: bool,
+    ) -> Result<(), Error> {
+        let indexes = column_names
+            .iter()
+            .map(|name| {
+                self.get_column_idx(&name.to_string()).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("The column {} doesn't exist", name),
+                    )
+                })
+            })
+            .collect::<Result<Vec<usize>, Error>>()?;
+        if self.has_column(new_name) && !(drop_sources && column_names.contains(&new_name.as_str()))
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} already exists", new_name),
+            ));
+        }
+
+        let values: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                indexes
+                    .iter()
+                    .map(|&idx| row.get(idx).map_or("", String::as_str))
+                    .collect::<Vec<&str>>()
+                    .join(separator)
+            })
+            .collect();
+
+        let position = if drop_sources {
+            let mut removed = indexes.clone();
+            removed.sort_unstable();
+            removed.dedup();
+            for idx in removed.iter().rev() {
+                self.remove_column(*idx)?;
+            }
+            removed.first().copied().unwrap_or(self.len())
+        } else {
+            self.len()
+        };
+        self.insert_column(new_name, position)?;
+        self.fill_column(new_name, &values)
+    }
+
+    /// Reorders the columns to follow a target list, creating the missing columns
+    /// with empty values, so that files with heterogeneous headers can be concatenated.
+    /// The policy tells what happens to the columns that aren't in the list.
+    /// The ragged rows are completed with empty values, and their values
+    /// beyond the last column are dropped.
+    ///
+    /// It may return an error if the target list has duplicates,
+    /// or if there are extra columns with [ConformPolicy::RejectExtras](`ConformPolicy::RejectExtras`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ConformPolicy, ReaderOptions};
+    /// let mut file = CSVFile::from_text("email,name,age\nt@x.org,Thomas,19\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// file.conform_to(&["name", "email", "phone"], ConformPolicy::DropExtras).unwrap();
+    /// assert_eq!(file.columns, vec!["name", "email", "phone"]);
+    /// assert_eq!(file.rows, vec![vec!["Thomas", "t@x.org", ""]]);
+    /// ```
+    pub fn conform_to(&mut self, target: &[&str], policy: ConformPolicy) -> Result<(), Error> {
+        for (i, name) in target.iter().enumerate() {
+            if target[..i].contains(name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} is listed more than once", name),
+                ));
+            }
+        }
+        let extras: Vec<usize> = (0..self.len())
+            .filter(|&idx| !target.contains(&self.columns[idx].as_str()))
+            .collect();
+        if policy == ConformPolicy::RejectExtras && !extras.is_empty() {
+            let names: Vec<&String> = extras.iter().map(|&idx| &self.columns[idx]).collect();
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The columns {:?} aren't in the target list", names),
+            ));
+        }
+
+        // the original index of each column of the result, `None` for the created ones
+        let mut order: Vec<Option<usize>> = target
+            .iter()
+            .map(|name| self.get_column_idx(&name.to_string()))
+            .collect();
+        if policy == ConformPolicy::KeepExtras {
+            order.extend(extras.iter().map(|&idx| Some(idx)));
+        }
+        let mut new_indexes: Vec<Option<usize>> = vec![None; self.len()];
+        for (new_idx, old_idx) in order.iter().enumerate() {
+            if let Some(old_idx) = old_idx {
+                new_indexes[*old_idx] = Some(new_idx);
+            }
+        }
+
+        let removed: Vec<(usize, String)> = extras
+            .iter()
+            .filter(|&&idx| new_indexes[idx].is_none())
+            .map(|&idx| (idx, self.columns[idx].clone()))
+            .collect();
+        self.columns = order
+            .iter()
+            .enumerate()
+            .map(|(new_idx, old_idx)| match old_idx {
+                Some(idx) => self.columns[*idx].clone(),
+                None => target[new_idx].to_string(),
+            })
+            .collect();
+        for row in &mut self.rows {
+            *row = order
+                .iter()
+                .map(|old_idx| match old_idx {
+                    Some(idx) => row.get_mut(*idx).map(std::mem::take).unwrap_or_default(),
+                    None => String::new(),
+                })
+                .collect();
+        }
+        self.remap_source(Some, |c| new_indexes.get(c).copied().flatten());
+
+        for (idx, name) in removed.into_iter().rev() {
+            self.metadata.remove(&name);
+            self.defaults.remove(&name);
+            self.validators
+                .retain(|validator| validator.column_name() != Some(&name));
+            self.emit(ChangeEvent::ColumnRemoved { column: idx, name });
+        }
+        for (new_idx, old_idx) in order.iter().enumerate() {
+            if old_idx.is_none() {
+                self.emit(ChangeEvent::ColumnAdded {
+                    column: new_idx,
+                    name: self.columns[new_idx].clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a new file with only the given columns, in the given order and renamed,
+    /// each pair being the name of a column followed by its name in the new file,
+    /// so that the output matches the columns expected by another program in a single step.
+    /// A column may be kept several times under different names,
+    /// and the values missing from the ragged rows are empty.
+    ///
+    /// It may return an error if a column doesn't exist, or if a new name is given more than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("id,name,age\n1,Thomas,19\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let projected = file.project(&[("name", "full_name"), ("id", "user_id")]).unwrap();
+    /// assert_eq!(projected.columns, vec!["full_name", "user_id"]);
+    /// assert_eq!(projected.rows, vec![vec!["Thomas", "1"]]);
+    /// ```
+    pub fn project(&self, columns: &[(&str, &str)]) -> Result<CSVFile, Error> {
+        let mut indexes: Vec<usize> = Vec::with_capacity(columns.len());
+        for (i, (old_name, new_name)) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|(_, name)| name == new_name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} is listed more than once", new_name),
+                ));
+            }
+            let idx = self.get_column_idx(&old_name.to_string()).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", old_name),
+                )
+            })?;
+            indexes.push(idx);
+        }
+
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                indexes
+                    .iter()
+                    .map(|&idx| row.get(idx).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            columns.iter().map(|(_, name)| name.to_string()).collect(),
+            rows,
+        ))
+    }
+}
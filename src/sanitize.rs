@@ -0,0 +1,171 @@
+//! Detection and removal of the characters that strict systems refuse to ingest.
+
+use crate::{CSVCoords, CSVFile, ChangeEvent};
+
+/// What [CSVFile::sanitize](`crate::CSVFile::sanitize`) does with the problematic characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SanitizeAction {
+    /// Removes the characters from the values.
+    #[default]
+    Strip,
+    /// Only reports the characters, without modifying the values.
+    Report,
+}
+
+/// The kind of problematic character found in a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanitizeIssueKind {
+    /// A NULL byte (`\0`).
+    NullByte,
+    /// Any other control character (`\x01` to `\x1F`, `\x7F` and the C1 controls).
+    ControlCharacter,
+    /// The replacement character (`U+FFFD`), left behind by a lossy conversion
+    /// of invalid UTF-8 or of an unpaired surrogate. A Rust string can't hold
+    /// a surrogate itself, so this character is the only trace such an artifact leaves.
+    ReplacementCharacter,
+    /// A noncharacter (such as `U+FFFE` or `U+FFFF`) or a byte order mark in the middle of a value.
+    Noncharacter,
+}
+
+/// A problematic character found in a value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizeIssue {
+    /// The index of the row, or `None` if the value is the name of a column.
+    pub row: Option<usize>,
+    pub column: usize,
+    /// The position of the character in the value, counted in characters.
+    pub position: usize,
+    pub character: char,
+    pub kind: SanitizeIssueKind,
+}
+
+/// The options of [CSVFile::sanitize](`crate::CSVFile::sanitize`).
+#[derive(Debug, Clone)]
+pub struct SanitizeOptions {
+    pub action: SanitizeAction,
+    /// The control characters that are considered harmless (by default a tabulation
+    /// and the line breaks, which legitimately appear in the quoted values spanning several lines).
+    pub allowed: Vec<char>,
+    /// Also sanitizes the names of the columns (`true` by default).
+    pub include_header: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            action: SanitizeAction::Strip,
+            allowed: vec!['\t', '\n', '\r'],
+            include_header: true,
+        }
+    }
+}
+
+/// The problematic characters found by [CSVFile::sanitize](`crate::CSVFile::sanitize`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SanitizeReport {
+    pub issues: Vec<SanitizeIssue>,
+}
+
+impl SanitizeReport {
+    /// Returns `true` if no problematic character was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Returns the kind of issue a character represents, if any.
+fn classify(c: char, allowed: &[char]) -> Option<SanitizeIssueKind> {
+    if allowed.contains(&c) {
+        return None;
+    }
+    match c {
+        '\0' => Some(SanitizeIssueKind::NullByte),
+        '\u{FFFD}' => Some(SanitizeIssueKind::ReplacementCharacter),
+        '\u{FEFF}' | '\u{FFFE}' | '\u{FFFF}' | '\u{FDD0}'..='\u{FDEF}' => {
+            Some(SanitizeIssueKind::Noncharacter)
+        }
+        c if c.is_control() => Some(SanitizeIssueKind::ControlCharacter),
+        _ => None,
+    }
+}
+
+/// Reports the problematic characters of a value, and removes them if asked to.
+/// It returns `true` if the value was modified.
+fn sanitize_value(
+    value: &mut String,
+    row: Option<usize>,
+    column: usize,
+    options: &SanitizeOptions,
+    report: &mut SanitizeReport,
+) -> bool {
+    let mut found = false;
+    for (position, c) in value.chars().enumerate() {
+        if let Some(kind) = classify(c, &options.allowed) {
+            found = true;
+            report.issues.push(SanitizeIssue {
+                row,
+                column,
+                position,
+                character: c,
+                kind,
+            });
+        }
+    }
+    if found && options.action == SanitizeAction::Strip {
+        value.retain(|c| classify(c, &options.allowed).is_none());
+        return true;
+    }
+    false
+}
+
+impl CSVFile {
+    /// Finds the control characters, NULL bytes and invalid characters left
+    /// by lossy conversions in the values, and strips them or reports them
+    /// depending on the options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, SanitizeOptions, SanitizeIssueKind};
+    /// let columns = vec!["name".to_string()];
+    /// let rows = vec![vec!["Tho\0mas\u{FFFD}".to_string()]];
+    /// let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// let report = file.sanitize(&SanitizeOptions::default());
+    /// assert_eq!(report.issues.len(), 2);
+    /// assert_eq!(report.issues[0].kind, SanitizeIssueKind::NullByte);
+    /// assert_eq!(report.issues[0].row, Some(0));
+    /// assert_eq!(file.rows[0][0], "Thomas");
+    /// ```
+    pub fn sanitize(&mut self, options: &SanitizeOptions) -> SanitizeReport {
+        let mut report = SanitizeReport::default();
+
+        if options.include_header {
+            for (j, column) in self.columns.iter_mut().enumerate() {
+                sanitize_value(column, None, j, options, &mut report);
+            }
+        }
+
+        let observed = self.is_observed();
+        let mut changes: Vec<ChangeEvent> = Vec::new();
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                let old_value = observed.then(|| cell.clone());
+                if sanitize_value(cell, Some(i), j, options, &mut report) {
+                    if let Some(old_value) = old_value {
+                        changes.push(ChangeEvent::CellChanged {
+                            coordinates: CSVCoords { row: i, column: j },
+                            old_value,
+                            new_value: cell.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for event in changes {
+            self.emit(event);
+        }
+
+        report
+    }
+}
@@ -0,0 +1,501 @@
+//! The expected structure of a file, checked while it is read.
+
+use std::collections::HashSet;
+use std::io::{BufRead, Error, ErrorKind};
+
+use crate::json::JsonValue;
+use crate::stats::parse_number;
+use crate::timeseries::{format_timestamp, parse_timestamp};
+use crate::{CSVFile, CSVReader, ReaderOptions, SqlDialect};
+
+/// The type of the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnType {
+    /// Any value.
+    #[default]
+    Text,
+    /// A whole number, such as `-42`.
+    Integer,
+    /// Any number, such as `3.14` or `1e-3`.
+    Number,
+    /// `true` or `false`, also accepting `yes`/`no` and `1`/`0` in any case.
+    Boolean,
+    /// A date written as `YYYY-MM-DD`.
+    Date,
+}
+
+impl ColumnType {
+    /// Converts a value to its canonical form for the type:
+    /// the surrounding whitespace is removed, the integers lose their `+` sign and leading zeros,
+    /// and the booleans become `true` or `false`. The text is kept as it is.
+    /// It returns `None` if the value doesn't have the type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::ColumnType;
+    /// assert_eq!(ColumnType::Integer.coerce(" +042 "), Some("42".to_string()));
+    /// assert_eq!(ColumnType::Boolean.coerce("Yes"), Some("true".to_string()));
+    /// assert_eq!(ColumnType::Date.coerce("2024-02-30"), None);
+    /// ```
+    pub fn coerce(&self, value: &str) -> Option<String> {
+        let trimmed = value.trim();
+        match self {
+            ColumnType::Text => Some(value.to_string()),
+            ColumnType::Integer => trimmed.parse::<i64>().ok().map(|n| n.to_string()),
+            ColumnType::Number => parse_number(trimmed).map(|_| trimmed.to_string()),
+            ColumnType::Boolean => match trimmed.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Some("true".to_string()),
+                "false" | "no" | "0" => Some("false".to_string()),
+                _ => None,
+            },
+            // The date is formatted back to reject the days that don't exist, such as February 30.
+            ColumnType::Date => parse_timestamp(trimmed)
+                .filter(|_| trimmed.len() == 10)
+                .map(|seconds| format_timestamp(seconds, true))
+                .filter(|date| date == trimmed),
+        }
+    }
+
+    /// The types tried by [infer_schema](`CSVFile::infer_schema`), from the most specific one.
+    const INFERRED: [ColumnType; 4] = [
+        ColumnType::Integer,
+        ColumnType::Number,
+        ColumnType::Date,
+        ColumnType::Boolean,
+    ];
+
+    /// Returns the name of the type in JSON Schema, along with its format.
+    fn json_type(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            ColumnType::Text => ("string", None),
+            ColumnType::Integer => ("integer", None),
+            ColumnType::Number => ("number", None),
+            ColumnType::Boolean => ("boolean", None),
+            ColumnType::Date => ("string", Some("date")),
+        }
+    }
+
+    /// Returns the name of the type of a column in a database.
+    fn sql_type(&self, dialect: SqlDialect) -> &'static str {
+        match (self, dialect) {
+            (ColumnType::Text, _) => "TEXT",
+            (ColumnType::Integer, SqlDialect::SQLite) => "INTEGER",
+            (ColumnType::Integer, _) => "BIGINT",
+            (ColumnType::Number, SqlDialect::Postgres) => "DOUBLE PRECISION",
+            (ColumnType::Number, SqlDialect::MySQL) => "DOUBLE",
+            (ColumnType::Number, SqlDialect::SQLite) => "REAL",
+            (ColumnType::Boolean, _) => "BOOLEAN",
+            (ColumnType::Date, SqlDialect::SQLite) => "TEXT",
+            (ColumnType::Date, _) => "DATE",
+        }
+    }
+
+    /// Describes the type in the error messages.
+    fn description(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "a text",
+            ColumnType::Integer => "an integer",
+            ColumnType::Number => "a number",
+            ColumnType::Boolean => "a boolean",
+            ColumnType::Date => "a date",
+        }
+    }
+}
+
+/// A column described by a [CSVSchema](`CSVSchema`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaColumn {
+    pub name: String,
+    pub column_type: ColumnType,
+    /// Rejects the empty values. When the column isn't required, the empty values
+    /// are accepted whatever the type, and the blank ones are emptied.
+    pub required: bool,
+}
+
+/// What happens when a file doesn't match its schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaPolicy {
+    /// Stops reading at the first problem, which is returned as an error.
+    #[default]
+    FailFast,
+    /// Reads the whole file and returns a single error listing all the problems.
+    CollectErrors,
+}
+
+/// The columns a file must have, in any order, and the type of their values.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{ColumnType, CSVSchema};
+/// let schema = CSVSchema::default()
+///     .with_column("id", ColumnType::Integer, true)
+///     .with_column("name", ColumnType::Text, false);
+/// assert_eq!(schema.columns.len(), 2);
+/// assert_eq!(schema.delimiter, ',');
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CSVSchema {
+    /// The character separating the values (a comma by default).
+    pub delimiter: char,
+    pub columns: Vec<SchemaColumn>,
+    /// What happens when the file doesn't match ([SchemaPolicy::FailFast](`SchemaPolicy::FailFast`) by default).
+    pub policy: SchemaPolicy,
+}
+
+impl Default for CSVSchema {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            columns: Vec::new(),
+            policy: SchemaPolicy::FailFast,
+        }
+    }
+}
+
+impl CSVSchema {
+    /// Adds a column to the schema.
+    pub fn with_column(mut self, name: &str, column_type: ColumnType, required: bool) -> Self {
+        self.columns.push(SchemaColumn {
+            name: name.to_string(),
+            column_type,
+            required,
+        });
+        self
+    }
+
+    /// Sets what happens when the file doesn't match.
+    pub fn with_policy(mut self, policy: SchemaPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the description of the column of the schema having the given name.
+    pub fn column(&self, name: &str) -> Option<&SchemaColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Generates the [JSON Schema](https://json-schema.org) of the rows, as objects
+    /// whose members are the columns and whose values have the type of their column,
+    /// so that the rows exported to JSON can be validated by other tools.
+    /// The columns that aren't required may also be `null`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{ColumnType, CSVSchema};
+    /// let schema = CSVSchema::default()
+    ///     .with_column("id", ColumnType::Integer, true)
+    ///     .with_column("since", ColumnType::Date, false);
+    ///
+    /// let json = schema.to_json_schema();
+    /// assert!(json.contains("\"required\": [\n    \"id\"\n  ]"));
+    /// assert!(json.contains("\"format\": \"date\""));
+    /// ```
+    pub fn to_json_schema(&self) -> String {
+        let properties = self
+            .columns
+            .iter()
+            .map(|column| {
+                let (name, format) = column.column_type.json_type();
+                let mut property = vec![(
+                    "type".to_string(),
+                    if column.required {
+                        JsonValue::String(name.to_string())
+                    } else {
+                        JsonValue::Array(vec![
+                            JsonValue::String(name.to_string()),
+                            JsonValue::String("null".to_string()),
+                        ])
+                    },
+                )];
+                if let Some(format) = format {
+                    property.push(("format".to_string(), JsonValue::String(format.to_string())));
+                }
+                (column.name.clone(), JsonValue::Object(property))
+            })
+            .collect();
+        let required = self
+            .columns
+            .iter()
+            .filter(|column| column.required)
+            .map(|column| JsonValue::String(column.name.clone()))
+            .collect();
+
+        JsonValue::Object(vec![
+            (
+                "$schema".to_string(),
+                JsonValue::String("https://json-schema.org/draft/2020-12/schema".to_string()),
+            ),
+            ("type".to_string(), JsonValue::String("object".to_string())),
+            ("properties".to_string(), JsonValue::Object(properties)),
+            ("required".to_string(), JsonValue::Array(required)),
+            ("additionalProperties".to_string(), JsonValue::Bool(false)),
+        ])
+        .to_pretty_string()
+    }
+
+    /// Generates the `CREATE TABLE` statement of a table having the columns of the schema,
+    /// to be used before the statements of [to_sql_inserts](`CSVFile::to_sql_inserts`).
+    /// The required columns are `NOT NULL`, and SQLite stores the dates as text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{ColumnType, CSVSchema, SqlDialect};
+    /// let schema = CSVSchema::default()
+    ///     .with_column("id", ColumnType::Integer, true)
+    ///     .with_column("price", ColumnType::Number, false);
+    ///
+    /// assert_eq!(
+    ///     schema.to_sql_ddl(&"products".to_string(), SqlDialect::Postgres),
+    ///     "CREATE TABLE \"products\" (\n  \"id\" BIGINT NOT NULL,\n  \"price\" DOUBLE PRECISION\n);\n"
+    /// );
+    /// ```
+    pub fn to_sql_ddl(&self, table: &String, dialect: SqlDialect) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "  {} {}{}",
+                    dialect.quote_identifier(&column.name),
+                    column.column_type.sql_type(dialect),
+                    if column.required { " NOT NULL" } else { "" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",\n");
+        format!(
+            "CREATE TABLE {} (\n{}\n);\n",
+            dialect.quote_identifier(table),
+            columns
+        )
+    }
+}
+
+/// The problems found so far, either returned at once or gathered until the end.
+struct SchemaErrors {
+    policy: SchemaPolicy,
+    messages: Vec<String>,
+}
+
+impl SchemaErrors {
+    fn report(&mut self, message: String) -> Result<(), Error> {
+        match self.policy {
+            SchemaPolicy::FailFast => Err(Error::new(ErrorKind::InvalidData, message)),
+            SchemaPolicy::CollectErrors => {
+                self.messages.push(message);
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        if self.messages.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The file doesn't match the schema:\n{}",
+                    self.messages.join("\n")
+                ),
+            ))
+        }
+    }
+}
+
+impl CSVFile {
+    /// Infers the schema of the file from its values. Each column gets the most specific type
+    /// accepted by all its non-empty values, trying an integer, a number, a date and a boolean
+    /// before falling back to text, and is required if none of its values is empty.
+    /// A column without any value is an optional text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ColumnType, ReaderOptions};
+    /// let text = "id,price,since,name\n1,2.5,2024-01-01,Thomas\n2,3,,John\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let schema = file.infer_schema();
+    /// let types: Vec<ColumnType> = schema.columns.iter().map(|c| c.column_type).collect();
+    /// assert_eq!(types, vec![ColumnType::Integer, ColumnType::Number, ColumnType::Date, ColumnType::Text]);
+    /// assert!(schema.columns[0].required);
+    /// assert!(!schema.columns[2].required);
+    /// ```
+    pub fn infer_schema(&self) -> CSVSchema {
+        let columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let values: Vec<&str> = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(idx))
+                    .map(|value| value.as_str())
+                    .filter(|value| !value.trim().is_empty())
+                    .collect();
+                let column_type = ColumnType::INFERRED
+                    .into_iter()
+                    .find(|t| !values.is_empty() && values.iter().all(|v| t.coerce(v).is_some()))
+                    .unwrap_or(ColumnType::Text);
+                SchemaColumn {
+                    name: name.clone(),
+                    column_type,
+                    required: !self.rows.is_empty() && values.len() == self.rows.len(),
+                }
+            })
+            .collect();
+
+        CSVSchema {
+            delimiter: self.delimiter,
+            columns,
+            policy: SchemaPolicy::FailFast,
+        }
+    }
+
+    /// Returns the values of a column in the canonical form of their type, for the typed exports,
+    /// `None` standing for the empty values, including the ones missing from the ragged rows.
+    /// It returns an error if a value doesn't have the type.
+    #[cfg(any(feature = "arrow", feature = "polars"))]
+    pub(crate) fn coerced_column(
+        &self,
+        idx: usize,
+        column_type: ColumnType,
+    ) -> Result<Vec<Option<String>>, Error> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let value = row.get(idx).map_or("", String::as_str);
+                if value.trim().is_empty() {
+                    return Ok(None);
+                }
+                column_type.coerce(value).map(Some).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The value \"{}\" of the column {} in the row of index {} isn't {}",
+                            value,
+                            self.columns[idx],
+                            i,
+                            column_type.description()
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Creates a new CSVFile from a file that must match a schema.
+    /// The header must have all the columns of the schema, in any order, and no other column.
+    /// Each row is checked as soon as it is parsed, and its values are converted
+    /// to the canonical form of their type (see [ColumnType::coerce](`ColumnType::coerce`)).
+    ///
+    /// It returns an error if the file can't be read or doesn't match the schema,
+    /// either at the first problem or listing all of them, depending on the policy of the schema.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ColumnType, CSVSchema};
+    /// # std::fs::write("schema_example.csv", "name,age\nThomas, 19\nJohn,\n").unwrap();
+    /// let schema = CSVSchema::default()
+    ///     .with_column("age", ColumnType::Integer, false)
+    ///     .with_column("name", ColumnType::Text, true);
+    ///
+    /// let file = CSVFile::new_with_schema(&"schema_example.csv".to_string(), &schema).unwrap();
+    /// assert_eq!(file.rows, vec![vec!["Thomas", "19"], vec!["John", ""]]);
+    /// # std::fs::write("schema_example.csv", "name,age\nThomas,nineteen\n").unwrap();
+    /// assert!(CSVFile::new_with_schema(&"schema_example.csv".to_string(), &schema).is_err());
+    /// # std::fs::remove_file("schema_example.csv").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn new_with_schema(file_name: &String, schema: &CSVSchema) -> Result<Self, Error> {
+        let options = ReaderOptions::with_delimiter(&schema.delimiter);
+        Self::from_reader_with_schema(CSVReader::open(file_name, &options)?, schema)
+    }
+
+    /// Creates a new CSVFile from text held in memory that must match a schema,
+    /// the same way as [new_with_schema](`CSVFile::new_with_schema`).
+    pub fn from_text_with_schema(text: &str, schema: &CSVSchema) -> Result<Self, Error> {
+        let options = ReaderOptions::with_delimiter(&schema.delimiter);
+        Self::from_reader_with_schema(CSVReader::from_reader(text.as_bytes(), &options)?, schema)
+    }
+
+    fn from_reader_with_schema<R: BufRead>(
+        mut reader: CSVReader<R>,
+        schema: &CSVSchema,
+    ) -> Result<Self, Error> {
+        let mut errors = SchemaErrors {
+            policy: schema.policy,
+            messages: Vec::new(),
+        };
+
+        let columns = reader.columns().clone();
+        let mut seen: HashSet<&String> = HashSet::new();
+        let mut types: Vec<Option<&SchemaColumn>> = Vec::with_capacity(columns.len());
+        for column in &columns {
+            if !seen.insert(column) {
+                errors.report(format!("The column {} is duplicated", column))?;
+            }
+            let expected = schema.column(column);
+            if expected.is_none() {
+                errors.report(format!("The column {} isn't in the schema", column))?;
+            }
+            types.push(expected);
+        }
+        for expected in &schema.columns {
+            if !columns.contains(&expected.name) {
+                errors.report(format!("The column {} doesn't exist", expected.name))?;
+            }
+        }
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for row in &mut reader {
+            let mut row = row?;
+            let index = rows.len();
+            if row.len() != columns.len() {
+                errors.report(format!(
+                    "Invalid number of fields for row of index {}, {} were given, but expected {}",
+                    index,
+                    row.len(),
+                    columns.len()
+                ))?;
+            }
+            for (value, expected) in row.iter_mut().zip(&types) {
+                let Some(expected) = expected else { continue };
+                if value.trim().is_empty() {
+                    if expected.required {
+                        errors.report(format!(
+                            "The column {} is required, but the row of index {} has no value",
+                            expected.name, index
+                        ))?;
+                    }
+                    value.clear();
+                    continue;
+                }
+                match expected.column_type.coerce(value) {
+                    Some(coerced) => *value = coerced,
+                    None => errors.report(format!(
+                        "The value \"{}\" of the column {} in the row of index {} isn't {}",
+                        value,
+                        expected.name,
+                        index,
+                        expected.column_type.description()
+                    ))?,
+                }
+            }
+            rows.push(row);
+        }
+        errors.finish()?;
+
+        let mut file = Self::from_rows(schema.delimiter, columns, rows);
+        file.source.read_stats = Some(reader.stats());
+        Ok(file)
+    }
+}
@@ -0,0 +1,257 @@
+//! A subset of the SQL `SELECT` statement, evaluated on the rows of a file.
+
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind};
+
+use crate::query::{tokenize, Expr, Parser, Query, Token};
+use crate::stats::parse_number;
+use crate::CSVFile;
+
+/// A column of the result, with its name in the result.
+struct Projection {
+    column: String,
+    alias: String,
+}
+
+/// A parsed `SELECT` statement.
+struct Statement {
+    /// `None` for `SELECT *`.
+    projections: Option<Vec<Projection>>,
+    filter: Option<Query>,
+    /// The columns to sort by, `true` for a descending order.
+    order: Vec<(String, bool)>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+/// Orders two values as numbers if they both are numbers, and as text otherwise.
+/// Numbers come before texts so that the order is total.
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (parse_number(a), parse_number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+impl Parser {
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.peek().is_some_and(|t| t.is_keyword(keyword)) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {}", keyword)))
+        }
+    }
+
+    fn accept_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn column_name(&mut self) -> Result<String, Error> {
+        match self.next() {
+            Some(Token::Identifier(name)) | Some(Token::QuotedIdentifier(name)) => Ok(name),
+            _ => Err(self.error("expected a column")),
+        }
+    }
+
+    fn count(&mut self) -> Result<usize, Error> {
+        match self.next() {
+            Some(Token::Number(n)) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            _ => Err(self.error("expected a positive integer")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, Error> {
+        self.expect_keyword("select")?;
+        let projections = if self.peek() == Some(&Token::Star) {
+            self.position += 1;
+            None
+        } else {
+            let mut projections = Vec::new();
+            loop {
+                let column = self.column_name()?;
+                let alias = if self.accept_keyword("as") {
+                    self.column_name()?
+                } else {
+                    column.clone()
+                };
+                projections.push(Projection { column, alias });
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.position += 1;
+            }
+            Some(projections)
+        };
+
+        self.expect_keyword("from")?;
+        self.expect_keyword("this")?;
+
+        let filter = if self.accept_keyword("where") {
+            Some(Query::from_expr(self.or()?))
+        } else {
+            None
+        };
+
+        let mut order = Vec::new();
+        if self.accept_keyword("order") {
+            self.expect_keyword("by")?;
+            loop {
+                let column = self.column_name()?;
+                let descending = if self.accept_keyword("desc") {
+                    true
+                } else {
+                    self.accept_keyword("asc");
+                    false
+                };
+                order.push((column, descending));
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.position += 1;
+            }
+        }
+
+        let limit = if self.accept_keyword("limit") {
+            Some(self.count()?)
+        } else {
+            None
+        };
+        let offset = if self.accept_keyword("offset") {
+            self.count()?
+        } else {
+            0
+        };
+
+        if self.position < self.tokens.len() {
+            return Err(self.error("unexpected token after the end of the statement"));
+        }
+
+        Ok(Statement {
+            projections,
+            filter,
+            order,
+            limit,
+            offset,
+        })
+    }
+}
+
+impl CSVFile {
+    /// Evaluates a basic SQL `SELECT` statement on the rows of the file,
+    /// and returns the result as a new file. The table is always named `this`.
+    ///
+    /// The supported syntax is:
+    ///
+    /// ```sql
+    /// SELECT * | column [AS alias], ...
+    /// FROM this
+    /// [WHERE condition]
+    /// [ORDER BY column [ASC | DESC], ...]
+    /// [LIMIT count [OFFSET count]]
+    /// ```
+    ///
+    /// The keywords are case-insensitive, and the condition uses the syntax
+    /// of [Query](`crate::Query`), with `AND`, `OR` and `NOT`. The sorting compares
+    /// numbers as numbers, puts them before the other values, and keeps the original
+    /// order of equal rows. The values missing from the ragged rows are read as empty values.
+    /// It may return an error if the statement is invalid or uses a column that doesn't exist.
+    ///
+    /// This method requires the `select` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "name,age,country\nThomas,19,FR\nYoshiip,35,FR\nJohn,42,US\nMarie,28,FR\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let result = file
+    ///     .select("SELECT name, age AS years FROM this WHERE country = 'FR' ORDER BY age DESC LIMIT 2")
+    ///     .unwrap();
+    /// assert_eq!(result.columns, vec!["name", "years"]);
+    /// assert_eq!(result.rows, vec![vec!["Yoshiip", "35"], vec!["Marie", "28"]]);
+    /// ```
+    pub fn select(&self, statement: &str) -> Result<CSVFile, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(statement)?,
+            position: 0,
+        };
+        let statement = parser.statement()?;
+
+        let column_idx = |name: &String| {
+            self.get_column_idx(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} doesn't exist", name),
+                )
+            })
+        };
+        let (columns, indexes): (Vec<String>, Vec<usize>) = match &statement.projections {
+            None => (self.columns.clone(), (0..self.len()).collect()),
+            Some(projections) => {
+                let mut columns = Vec::with_capacity(projections.len());
+                let mut indexes = Vec::with_capacity(projections.len());
+                for projection in projections {
+                    indexes.push(column_idx(&projection.column)?);
+                    columns.push(projection.alias.clone());
+                }
+                (columns, indexes)
+            }
+        };
+        let order = statement
+            .order
+            .iter()
+            .map(|(name, descending)| Ok((column_idx(name)?, *descending)))
+            .collect::<Result<Vec<(usize, bool)>, Error>>()?;
+        let filter: Option<Expr> = match &statement.filter {
+            Some(query) => Some(query.bind(self)?),
+            None => None,
+        };
+
+        let mut rows: Vec<&Vec<String>> = self
+            .rows
+            .iter()
+            .filter(|row| filter.as_ref().is_none_or(|expr| expr.matches(row)))
+            .collect();
+        if !order.is_empty() {
+            rows.sort_by(|a, b| {
+                order
+                    .iter()
+                    .map(|&(idx, descending)| {
+                        let ordering = compare_values(
+                            a.get(idx).map_or("", String::as_str),
+                            b.get(idx).map_or("", String::as_str),
+                        );
+                        if descending {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    })
+                    .find(|ordering| *ordering != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            });
+        }
+
+        let rows = rows
+            .into_iter()
+            .skip(statement.offset)
+            .take(statement.limit.unwrap_or(usize::MAX))
+            .map(|row| {
+                indexes
+                    .iter()
+                    .map(|&idx| row.get(idx).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        Ok(CSVFile::from_rows(self.delimiter, columns, rows))
+    }
+}
@@ -0,0 +1,107 @@
+//! Set operations treating the rows of two files as tuples.
+
+use std::collections::HashSet;
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+impl CSVFile {
+    /// Returns the rows of `other` with their values in the order of the columns of this file.
+    /// It returns an error if the two files don't have the same columns.
+    fn aligned_rows(&self, other: &CSVFile) -> Result<Vec<Vec<String>>, Error> {
+        let mut own_columns = self.columns.clone();
+        let mut other_columns = other.columns.clone();
+        own_columns.sort();
+        other_columns.sort();
+        if own_columns != other_columns {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The columns don't match, expected {:?} but found {:?}",
+                    self.columns, other.columns
+                ),
+            ));
+        }
+
+        if self.columns == other.columns {
+            return Ok(other.rows.clone());
+        }
+        let order: Vec<usize> = self
+            .columns
+            .iter()
+            .filter_map(|column| other.get_column_idx(column))
+            .collect();
+        Ok(other
+            .rows
+            .iter()
+            .map(|row| order.iter().map(|&idx| row[idx].clone()).collect())
+            .collect())
+    }
+
+    /// Returns the distinct rows that are in this file or in the other one.
+    /// The rows of this file come first, in their original order.
+    ///
+    /// Like the other set operations, it may return an error if the files
+    /// don't have the same columns. The columns can be in a different order,
+    /// in which case the result keeps the order of this file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let a = CSVFile::from_text("id,name\n1,A\n2,B\n", &ReaderOptions::default()).unwrap();
+    /// let b = CSVFile::from_text("name,id\nB,2\nC,3\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// assert_eq!(a.union(&b).unwrap().rows, vec![vec!["1", "A"], vec!["2", "B"], vec!["3", "C"]]);
+    /// assert_eq!(a.intersection(&b).unwrap().rows, vec![vec!["2", "B"]]);
+    /// assert_eq!(a.difference(&b).unwrap().rows, vec![vec!["1", "A"]]);
+    /// ```
+    pub fn union(&self, other: &CSVFile) -> Result<CSVFile, Error> {
+        let other_rows = self.aligned_rows(other)?;
+        let mut seen: HashSet<&Vec<String>> = HashSet::new();
+        let rows = self
+            .rows
+            .iter()
+            .chain(other_rows.iter())
+            .filter(|row| seen.insert(row))
+            .cloned()
+            .collect();
+
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            self.columns.clone(),
+            rows,
+        ))
+    }
+
+    /// Returns the distinct rows of this file that are also in the other one.
+    /// See [union](`CSVFile::union`).
+    pub fn intersection(&self, other: &CSVFile) -> Result<CSVFile, Error> {
+        let other_rows = self.aligned_rows(other)?;
+        let other_rows: HashSet<&Vec<String>> = other_rows.iter().collect();
+        Ok(self.distinct_rows_where(|row| other_rows.contains(row)))
+    }
+
+    /// Returns the distinct rows of this file that aren't in the other one.
+    /// See [union](`CSVFile::union`).
+    pub fn difference(&self, other: &CSVFile) -> Result<CSVFile, Error> {
+        let other_rows = self.aligned_rows(other)?;
+        let other_rows: HashSet<&Vec<String>> = other_rows.iter().collect();
+        Ok(self.distinct_rows_where(|row| !other_rows.contains(row)))
+    }
+
+    fn distinct_rows_where<F>(&self, predicate: F) -> CSVFile
+    where
+        F: Fn(&Vec<String>) -> bool,
+    {
+        let mut seen: HashSet<&Vec<String>> = HashSet::new();
+        let rows = self
+            .rows
+            .iter()
+            .filter(|row| predicate(row) && seen.insert(row))
+            .cloned()
+            .collect();
+
+        CSVFile::from_rows(self.delimiter, self.columns.clone(), rows)
+    }
+}
@@ -0,0 +1,82 @@
+//! An immutable view of a CSV file that can be shared without copying it.
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::CSVFile;
+
+/// An immutable snapshot of a CSV file, created by [CSVFile::freeze](`CSVFile::freeze`).
+///
+/// Cloning a snapshot is cheap since the data is shared, not copied,
+/// which makes it suitable for sharing a file across threads or requests.
+/// All the read-only methods of [CSVFile](`CSVFile`) are available on the snapshot.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::CSVFile;
+/// let columns = vec!["name".to_string()];
+/// let rows = vec![vec!["Thomas".to_string()]];
+/// let snapshot = CSVFile::build(&columns, &rows, &',').unwrap().freeze();
+///
+/// let shared = snapshot.clone();
+/// let handle = std::thread::spawn(move || shared.count_rows());
+/// assert_eq!(handle.join().unwrap(), 1);
+///
+/// let mut file = snapshot.thaw();
+/// file.add_row(&vec!["Yoshiip".to_string()]).unwrap();
+/// assert_eq!(file.count_rows(), 2);
+/// ```
+#[derive(Clone)]
+pub struct CSVSnapshot {
+    file: Arc<CSVFile>,
+}
+
+impl CSVSnapshot {
+    /// Returns a mutable copy of the file. The data is only copied
+    /// if other clones of the snapshot still exist.
+    /// The copy doesn't have any [observer](`CSVFile::on_change`).
+    pub fn thaw(self) -> CSVFile {
+        let mut file = Arc::try_unwrap(self.file).unwrap_or_else(|file| (*file).clone());
+        file.clear_observers();
+        file
+    }
+
+    /// Returns `true` if both snapshots share the same data.
+    pub fn ptr_eq(&self, other: &CSVSnapshot) -> bool {
+        Arc::ptr_eq(&self.file, &other.file)
+    }
+}
+
+impl Deref for CSVSnapshot {
+    type Target = CSVFile;
+
+    fn deref(&self) -> &CSVFile {
+        &self.file
+    }
+}
+
+impl fmt::Display for CSVSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.file, f)
+    }
+}
+
+impl fmt::Debug for CSVSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CSVSnapshot {{ file: {:?} }}", self.file)
+    }
+}
+
+impl CSVFile {
+    /// Turns the file into an immutable snapshot that can be cloned and shared cheaply.
+    /// The data isn't copied, but the [observers](`CSVFile::on_change`) are removed
+    /// since the snapshot can't be modified.
+    pub fn freeze(mut self) -> CSVSnapshot {
+        self.clear_observers();
+        CSVSnapshot {
+            file: Arc::new(self),
+        }
+    }
+}
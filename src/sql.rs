@@ -0,0 +1,111 @@
+//! Generation of SQL statements from a CSV file.
+
+use std::io::{Error, Write};
+
+use crate::CSVFile;
+
+/// The number of rows per INSERT statement used by [CSVFile::to_sql_inserts](`crate::CSVFile::to_sql_inserts`).
+pub const DEFAULT_SQL_BATCH_SIZE: usize = 1000;
+
+/// The database systems whose quoting rules are supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Postgres,
+    MySQL,
+    SQLite,
+}
+
+impl SqlDialect {
+    /// Quotes the name of a table or a column.
+    pub fn quote_identifier(&self, name: &str) -> String {
+        match self {
+            SqlDialect::MySQL => format!("`{}`", name.replace('`', "``")),
+            SqlDialect::Postgres | SqlDialect::SQLite => {
+                format!("\"{}\"", name.replace('"', "\"\""))
+            }
+        }
+    }
+
+    /// Quotes a value as a string literal.
+    /// MySQL also treats backslashes as escape characters, so they are doubled.
+    pub fn quote_string(&self, value: &str) -> String {
+        let mut result = String::with_capacity(value.len() + 2);
+        result.push('\'');
+        for c in value.chars() {
+            match c {
+                '\'' => result.push_str("''"),
+                '\\' if *self == SqlDialect::MySQL => result.push_str("\\\\"),
+                _ => result.push(c),
+            }
+        }
+        result.push('\'');
+        result
+    }
+}
+
+impl CSVFile {
+    /// Generates the INSERT statements adding the rows to a table,
+    /// with up to [DEFAULT_SQL_BATCH_SIZE](`DEFAULT_SQL_BATCH_SIZE`) rows per statement.
+    /// Every value is inserted as a string literal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, SqlDialect};
+    /// let columns = vec!["name".to_string(), "age".to_string()];
+    /// let rows = vec![
+    ///     vec!["Thomas".to_string(), "20".to_string()],
+    ///     vec!["O'Brien".to_string(), "30".to_string()],
+    /// ];
+    /// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// assert_eq!(
+    ///     file.to_sql_inserts(&"people".to_string(), SqlDialect::Postgres),
+    ///     "INSERT INTO \"people\" (\"name\", \"age\") VALUES\n('Thomas', '20'),\n('O''Brien', '30');\n"
+    /// );
+    /// ```
+    pub fn to_sql_inserts(&self, table: &String, dialect: SqlDialect) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        self.write_sql_inserts(&mut output, table, dialect, DEFAULT_SQL_BATCH_SIZE)
+            .expect("writing to a vector cannot fail");
+        String::from_utf8(output).expect("the values are valid UTF-8")
+    }
+
+    /// Writes the INSERT statements adding the rows to a table,
+    /// with up to `batch_size` rows per statement.
+    /// Nothing is written if there are no rows.
+    pub fn write_sql_inserts<W: Write>(
+        &self,
+        writer: &mut W,
+        table: &String,
+        dialect: SqlDialect,
+        batch_size: usize,
+    ) -> Result<(), Error> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| dialect.quote_identifier(c))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let header = format!(
+            "INSERT INTO {} ({}) VALUES\n",
+            dialect.quote_identifier(table),
+            columns
+        );
+
+        for batch in self.rows.chunks(batch_size.max(1)) {
+            writer.write_all(header.as_bytes())?;
+            for (i, row) in batch.iter().enumerate() {
+                let values = row
+                    .iter()
+                    .map(|v| dialect.quote_string(v))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let end = if i + 1 == batch.len() { ";" } else { "," };
+                writeln!(writer, "({}){}", values, end)?;
+            }
+        }
+
+        Ok(())
+    }
+}
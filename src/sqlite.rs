@@ -0,0 +1,189 @@
+//! Exporting a CSV file to a table of a SQLite database, and importing the result of a query.
+//!
+//! The database is accessed with the `sqlite3` command-line shell, which must be installed
+//! and available in the `PATH`, so that the crate doesn't depend on the SQLite library.
+
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::process::{Child, Command, Stdio};
+
+use crate::{CSVFile, CSVSchema, ColumnType, Escape, ReaderOptions, SqlDialect};
+
+/// Starts the `sqlite3` shell on a database, reading the statements from its standard input.
+fn spawn_sqlite(path: &String, arguments: &[&str], stdout: Stdio) -> Result<Child, Error> {
+    Command::new("sqlite3")
+        .args(arguments)
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => Error::new(
+                ErrorKind::NotFound,
+                "The sqlite3 command is needed to access a SQLite database, but it isn't installed",
+            ),
+            _ => e,
+        })
+}
+
+/// Converts a value to the literal inserted in a column of the given type.
+/// The empty values of the typed columns are `NULL`, and the booleans are stored as `1` or `0`,
+/// the way SQLite represents them. The values that don't have the type are inserted as text.
+fn sqlite_literal(value: &str, column_type: ColumnType) -> String {
+    if column_type == ColumnType::Text {
+        return SqlDialect::SQLite.quote_string(value);
+    }
+    if value.trim().is_empty() {
+        return "NULL".to_string();
+    }
+    match (column_type, column_type.coerce(value)) {
+        (ColumnType::Boolean, Some(boolean)) => {
+            if boolean == "true" { "1" } else { "0" }.to_string()
+        }
+        (_, Some(coerced)) => SqlDialect::SQLite.quote_string(&coerced),
+        (_, None) => SqlDialect::SQLite.quote_string(value),
+    }
+}
+
+impl CSVFile {
+    /// Creates a table in a SQLite database with the [inferred schema](`CSVFile::infer_schema`)
+    /// of the file, and inserts all the rows in it.
+    /// The database is created if it doesn't exist.
+    ///
+    /// It may return an error if `sqlite3` isn't installed, if the table already exists,
+    /// or if the database can't be written. Nothing is inserted when an error occurs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("name,age\nThomas,20\n", &ReaderOptions::default()).unwrap();
+    /// file.to_sqlite(&"people.db".to_string(), &"people".to_string()).unwrap();
+    ///
+    /// let adults = CSVFile::from_sqlite(&"people.db".to_string(), "SELECT name FROM people WHERE age >= 18").unwrap();
+    /// assert_eq!(adults.rows, vec![vec!["Thomas"]]);
+    /// ```
+    pub fn to_sqlite(&self, path: &String, table: &String) -> Result<(), Error> {
+        self.to_sqlite_with_schema(path, table, &self.infer_schema())
+    }
+
+    /// Creates a table in a SQLite database with the columns of a schema,
+    /// which must describe every column of the file, and inserts all the rows in it.
+    /// The database is created if it doesn't exist.
+    ///
+    /// It may return an error if a column of the file isn't in the schema, if `sqlite3` isn't installed,
+    /// if the table already exists, or if the database can't be written.
+    /// Nothing is inserted when an error occurs.
+    pub fn to_sqlite_with_schema(
+        &self,
+        path: &String,
+        table: &String,
+        schema: &CSVSchema,
+    ) -> Result<(), Error> {
+        let mut types = Vec::with_capacity(self.columns.len());
+        for name in &self.columns {
+            match schema.column(name) {
+                Some(column) => types.push(column.column_type),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("The column '{}' isn't in the schema", name),
+                    ))
+                }
+            }
+        }
+
+        let mut child = spawn_sqlite(path, &["-bail"], Stdio::null())?;
+        let stdin = child.stdin.take().expect("the input of sqlite3 is piped");
+        let written = self.write_sqlite_script(BufWriter::new(stdin), table, schema, &types);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::other(format!(
+                "Couldn't write the table {} in {}: {}",
+                table,
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        written
+    }
+
+    /// Writes the statements creating and filling the table, in a single transaction
+    /// so that `sqlite3` rolls everything back if one of them fails.
+    fn write_sqlite_script<W: Write>(
+        &self,
+        mut writer: W,
+        table: &String,
+        schema: &CSVSchema,
+        types: &[ColumnType],
+    ) -> Result<(), Error> {
+        let dialect = SqlDialect::SQLite;
+        writer.write_all(b"BEGIN;\n")?;
+        writer.write_all(schema.to_sql_ddl(table, dialect).as_bytes())?;
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| dialect.quote_identifier(c))
+            .collect::<Vec<String>>()
+            .join(", ");
+        for row in &self.rows {
+            let values = types
+                .iter()
+                .enumerate()
+                .map(|(idx, column_type)| {
+                    sqlite_literal(row.get(idx).map_or("", |v| v.as_str()), *column_type)
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            writeln!(
+                writer,
+                "INSERT INTO {} ({}) VALUES ({});",
+                dialect.quote_identifier(table),
+                columns,
+                values
+            )?;
+        }
+        writer.write_all(b"COMMIT;\n")?;
+        writer.flush()
+    }
+
+    /// Runs a query on a SQLite database and creates a CSVFile from its result,
+    /// whose columns are the columns of the result. The `NULL` values become empty values.
+    /// A query without any row gives a file without any column, since `sqlite3` doesn't
+    /// tell the columns of an empty result.
+    ///
+    /// The database is opened in read-only mode. The query is run by the `sqlite3` shell,
+    /// which also understands its own commands starting with a dot, some of them running
+    /// programs, so it must never come from an untrusted source.
+    ///
+    /// It may return an error if `sqlite3` isn't installed, if the database can't be opened,
+    /// or if the query fails.
+    pub fn from_sqlite(path: &String, query: &str) -> Result<Self, Error> {
+        let mut child = spawn_sqlite(
+            path,
+            &["-readonly", "-bail", "-csv", "-header"],
+            Stdio::piped(),
+        )?;
+        let mut stdin = child.stdin.take().expect("the input of sqlite3 is piped");
+        stdin.write_all(query.as_bytes())?;
+        stdin.write_all(b";\n")?;
+        drop(stdin);
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::other(format!(
+                "Couldn't run the query on {}: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        if output.stdout.is_empty() {
+            return Ok(Self::from_rows(',', Vec::new(), Vec::new()));
+        }
+        let options = ReaderOptions {
+            escape: Escape::DoubleQuote,
+            ..ReaderOptions::default()
+        };
+        Self::from_reader(output.stdout.as_slice(), &options)
+    }
+}
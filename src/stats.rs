@@ -0,0 +1,323 @@
+//! Descriptive statistics computed on the values of a column.
+
+use std::collections::HashMap;
+
+use crate::{CSVCoords, CSVFile};
+
+/// The statistics of a single column.
+///
+/// The numeric statistics only take into account the values that can be parsed as numbers,
+/// and are `None` if there are no such values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// The number of values, empty ones included.
+    pub count: usize,
+    /// The number of empty values.
+    pub empty: usize,
+    /// The number of distinct values.
+    pub distinct: usize,
+    /// The number of values that can be parsed as numbers.
+    pub numeric: usize,
+    pub sum: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+/// A range of values of a [histogram](`CSVFile::histogram`), with the number of values in it.
+/// The start is included, the end is excluded, except for the last bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBucket {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+/// Parses a value as a number, ignoring the surrounding whitespace.
+pub(crate) fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Computes the Pearson correlation coefficient of pairs of numbers,
+/// or `None` if there are fewer than two pairs or if one of the series is constant.
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let count = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+    // Rounding errors could put it slightly outside of its range.
+    Some((covariance / (variance_x * variance_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+impl CSVFile {
+    /// Returns the numbers of a column, skipping the values that can't be parsed as numbers.
+    /// It returns `None` if the column doesn't exist.
+    pub fn numeric_values(&self, column_name: &String) -> Option<Vec<f64>> {
+        let idx = self.get_column_idx(column_name)?;
+        Some(
+            self.rows
+                .iter()
+                .filter_map(|row| parse_number(row.get(idx)?))
+                .collect(),
+        )
+    }
+
+    /// Computes the statistics of a column.
+    /// It returns `None` if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// let columns = vec!["score".to_string()];
+    /// let rows = vec![
+    ///     vec!["10".to_string()],
+    ///     vec!["".to_string()],
+    ///     vec!["20".to_string()],
+    /// ];
+    /// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+    ///
+    /// let stats = file.column_stats(&"score".to_string()).unwrap();
+    /// assert_eq!(stats.count, 3);
+    /// assert_eq!(stats.empty, 1);
+    /// assert_eq!(stats.numeric, 2);
+    /// assert_eq!(stats.mean, Some(15.0));
+    /// ```
+    pub fn column_stats(&self, column_name: &String) -> Option<ColumnStats> {
+        let idx = self.get_column_idx(column_name)?;
+        Some(ColumnStats::compute(
+            self.rows.iter().filter_map(|row| row.get(idx)).collect(),
+        ))
+    }
+
+    /// Computes the quantiles of the numbers of a column, interpolating linearly between
+    /// the two closest numbers (the same method as the default one of spreadsheets and NumPy).
+    /// The probabilities go from 0 (the minimum) to 1 (the maximum), and are clamped to that range.
+    /// It returns `None` if the column doesn't exist or doesn't have any numbers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("score\n4\n1\n\n3\n2\nabsent\n", &ReaderOptions::default()).unwrap();
+    /// let quantiles = file.quantiles(&"score".to_string(), &[0.25, 0.5, 0.75]).unwrap();
+    /// assert_eq!(quantiles, vec![1.75, 2.5, 3.25]);
+    /// ```
+    pub fn quantiles(&self, column_name: &String, probabilities: &[f64]) -> Option<Vec<f64>> {
+        let mut numbers = self.numeric_values(column_name)?;
+        if numbers.is_empty() {
+            return None;
+        }
+        numbers.sort_by(f64::total_cmp);
+
+        let last = (numbers.len() - 1) as f64;
+        Some(
+            probabilities
+                .iter()
+                .map(|p| {
+                    let position = p.clamp(0.0, 1.0) * last;
+                    let below = numbers[position.floor() as usize];
+                    let above = numbers[position.ceil() as usize];
+                    below + (above - below) * position.fract()
+                })
+                .collect(),
+        )
+    }
+
+    /// Counts the numbers of a column falling in each of the given number of buckets
+    /// of equal width, between the minimum and the maximum.
+    /// It returns `None` if the column doesn't exist, doesn't have any numbers,
+    /// or if there are no buckets.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, HistogramBucket, ReaderOptions};
+    /// let file = CSVFile::from_text("age\n10\n12\n15\n20\n30\n", &ReaderOptions::default()).unwrap();
+    /// let buckets = file.histogram(&"age".to_string(), 2).unwrap();
+    /// assert_eq!(buckets, vec![
+    ///     HistogramBucket { start: 10.0, end: 20.0, count: 3 },
+    ///     HistogramBucket { start: 20.0, end: 30.0, count: 2 },
+    /// ]);
+    /// ```
+    pub fn histogram(&self, column_name: &String, buckets: usize) -> Option<Vec<HistogramBucket>> {
+        let numbers = self.numeric_values(column_name)?;
+        if buckets == 0 {
+            return None;
+        }
+        let min = numbers.iter().copied().reduce(f64::min)?;
+        let max = numbers.iter().copied().reduce(f64::max)?;
+        let width = (max - min) / buckets as f64;
+
+        let mut histogram: Vec<HistogramBucket> = (0..buckets)
+            .map(|i| HistogramBucket {
+                start: min + width * i as f64,
+                end: if i + 1 == buckets {
+                    max
+                } else {
+                    min + width * (i + 1) as f64
+                },
+                count: 0,
+            })
+            .collect();
+        for number in numbers {
+            let i = if width > 0.0 {
+                (((number - min) / width) as usize).min(buckets - 1)
+            } else {
+                0
+            };
+            histogram[i].count += 1;
+        }
+        Some(histogram)
+    }
+
+    /// Computes the Pearson correlation between each pair of numeric columns,
+    /// the columns whose non-empty values are all numbers. Each pair only takes into account
+    /// the rows where both values are numbers.
+    ///
+    /// It returns the matrix as a new file, with a first column named `column` holding the names
+    /// of the numeric columns, followed by a column for each of them.
+    /// The correlation is left empty when it can't be computed,
+    /// such as with a column whose numbers are all the same.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "name,height,weight,age\nThomas,180,80,19\nJohn,160,60,40\nMarie,170,70,28\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let matrix = file.correlations();
+    /// assert_eq!(matrix.columns, vec!["column", "height", "weight", "age"]);
+    /// assert_eq!(matrix.rows[0][..3], ["height", "1", "1"]);
+    /// ```
+    pub fn correlations(&self) -> CSVFile {
+        let numeric: Vec<usize> = (0..self.columns.len())
+            .filter(|&j| {
+                let mut values = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(j))
+                    .filter(|value| !value.trim().is_empty())
+                    .peekable();
+                values.peek().is_some() && values.all(|value| parse_number(value).is_some())
+            })
+            .collect();
+        let values: Vec<Vec<Option<f64>>> = numeric
+            .iter()
+            .map(|&j| {
+                self.rows
+                    .iter()
+                    .map(|row| parse_number(row.get(j)?))
+                    .collect()
+            })
+            .collect();
+
+        let mut columns = vec![String::from("column")];
+        columns.extend(numeric.iter().map(|&j| self.columns[j].clone()));
+        let rows = (0..numeric.len())
+            .map(|a| {
+                let mut row = vec![self.columns[numeric[a]].clone()];
+                row.extend((0..numeric.len()).map(|b| {
+                    let pairs: Vec<(f64, f64)> = values[a]
+                        .iter()
+                        .zip(&values[b])
+                        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                        .collect();
+                    pearson(&pairs).map(|r| r.to_string()).unwrap_or_default()
+                }));
+                row
+            })
+            .collect();
+        CSVFile::from_rows(self.delimiter, columns, rows)
+    }
+
+    /// Returns the length of the longest value of each column, in characters,
+    /// for example to choose the size of the `VARCHAR` columns of a database.
+    /// The names of the columns aren't taken into account, and the length is 0 if there are no rows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("name,city\nThomas,Paris\nJo,Zürich\n", &ReaderOptions::default()).unwrap();
+    /// let lengths = file.max_cell_len_per_column();
+    /// assert_eq!(lengths["name"], 6);
+    /// assert_eq!(lengths["city"], 6);
+    /// ```
+    pub fn max_cell_len_per_column(&self) -> HashMap<String, usize> {
+        let mut lengths = vec![0; self.columns.len()];
+        for row in &self.rows {
+            for (length, cell) in lengths.iter_mut().zip(row) {
+                *length = (*length).max(cell.chars().count());
+            }
+        }
+        self.columns.iter().cloned().zip(lengths).collect()
+    }
+
+    /// Returns the coordinates of the cells longer than the given number of characters,
+    /// to find the values that wouldn't fit in a `VARCHAR(n)` column before loading them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("code,label\nFR,France\nGBR,United Kingdom\n", &ReaderOptions::default()).unwrap();
+    /// assert_eq!(file.cells_exceeding(2), vec![
+    ///     CSVCoords { row: 0, column: 1 },
+    ///     CSVCoords { row: 1, column: 0 },
+    ///     CSVCoords { row: 1, column: 1 },
+    /// ]);
+    /// assert!(file.cells_exceeding(14).is_empty());
+    /// ```
+    pub fn cells_exceeding(&self, max_length: usize) -> Vec<CSVCoords> {
+        let mut coords: Vec<CSVCoords> = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if cell.chars().count() > max_length {
+                    coords.push(CSVCoords { row: i, column: j });
+                }
+            }
+        }
+
+        coords
+    }
+}
+
+impl ColumnStats {
+    /// Computes the statistics of the given values.
+    pub(crate) fn compute(values: Vec<&String>) -> Self {
+        let numbers: Vec<f64> = values.iter().filter_map(|v| parse_number(v)).collect();
+        let mut distinct: Vec<&String> = values.clone();
+        distinct.sort();
+        distinct.dedup();
+
+        let sum: Option<f64> = if numbers.is_empty() {
+            None
+        } else {
+            Some(numbers.iter().sum())
+        };
+
+        ColumnStats {
+            count: values.len(),
+            empty: values.iter().filter(|v| v.is_empty()).count(),
+            distinct: distinct.len(),
+            numeric: numbers.len(),
+            sum,
+            min: numbers.iter().copied().reduce(f64::min),
+            max: numbers.iter().copied().reduce(f64::max),
+            mean: sum.map(|s| s / numbers.len() as f64),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Computations over the files on disk that don't fit in memory,
+//! reading their rows one at a time.
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+use crate::stats::parse_number;
+use crate::{Aggregation, CSVFile, CSVReader, ReaderOptions};
+
+/// What is needed of the numbers of a group to aggregate them without keeping them.
+#[derive(Clone, Copy)]
+struct Accumulator {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+    first: f64,
+    last: f64,
+}
+
+impl Accumulator {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            first: 0.0,
+            last: 0.0,
+        }
+    }
+
+    fn push(&mut self, number: f64) {
+        if self.count == 0 {
+            self.first = number;
+        }
+        self.count += 1;
+        self.sum += number;
+        self.min = self.min.min(number);
+        self.max = self.max.max(number);
+        self.last = number;
+    }
+
+    /// Same as [Aggregation::apply](`Aggregation::apply`) with the numbers that were pushed.
+    fn apply(&self, aggregation: Aggregation) -> Option<f64> {
+        if self.count == 0 {
+            return if aggregation == Aggregation::Count {
+                Some(0.0)
+            } else {
+                None
+            };
+        }
+        Some(match aggregation {
+            Aggregation::Sum => self.sum,
+            Aggregation::Mean => self.sum / self.count as f64,
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Count => self.count as f64,
+            Aggregation::First => self.first,
+            Aggregation::Last => self.last,
+        })
+    }
+}
+
+/// The suffix of the name of a column holding an aggregation.
+fn aggregation_name(aggregation: Aggregation) -> &'static str {
+    match aggregation {
+        Aggregation::Sum => "sum",
+        Aggregation::Mean => "mean",
+        Aggregation::Min => "min",
+        Aggregation::Max => "max",
+        Aggregation::Count => "count",
+        Aggregation::First => "first",
+        Aggregation::Last => "last",
+    }
+}
+
+impl CSVFile {
+    /// Groups the rows of a CSV file on disk by the values of a key column and aggregates
+    /// the numbers of other columns, in a single pass over the file, without holding it
+    /// in memory: only a few numbers per distinct key are kept.
+    ///
+    /// It returns a new file with a row for each distinct key, in order of first occurrence,
+    /// and the columns: the key column, `count` (the number of rows of the group),
+    /// then a column named `{column}_{aggregation}` for each aggregation, like `amount_sum`.
+    /// The values that aren't numbers are ignored, and an aggregation of no numbers
+    /// gives an empty value (except `Count`, which gives `0`).
+    ///
+    /// It may return an error if the file can't be read or parsed, if a column doesn't exist,
+    /// if a row is too short to have a value in one of the columns, or if two columns
+    /// of the result would have the same name, such as a key column named `count`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{Aggregation, CSVFile, ReaderOptions};
+    /// # let path = std::env::temp_dir().join("csv_tools_stream_group_by.csv").to_string_lossy().to_string();
+    /// std::fs::write(&path, "city,amount\nParis,10\nLyon,4\nParis,5.5\nLyon,n/a\n").unwrap();
+    ///
+    /// let summary = CSVFile::stream_group_by(
+    ///     &path,
+    ///     &"city".to_string(),
+    ///     &[("amount", Aggregation::Sum), ("amount", Aggregation::Max)],
+    ///     &ReaderOptions::default(),
+    /// ).unwrap();
+    /// assert_eq!(summary.columns, vec!["city", "count", "amount_sum", "amount_max"]);
+    /// assert_eq!(summary.rows, vec![
+    ///     vec!["Paris", "2", "15.5", "10"],
+    ///     vec!["Lyon", "2", "4", "4"],
+    /// ]);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn stream_group_by(
+        path: &String,
+        key_column: &String,
+        aggregations: &[(&str, Aggregation)],
+        options: &ReaderOptions,
+    ) -> Result<CSVFile, Error> {
+        let mut reader = CSVReader::open(path, options)?;
+        let column_idx = |column_name: &str| {
+            reader
+                .columns()
+                .iter()
+                .position(|c| c == column_name)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("The column {} doesn't exist", column_name),
+                    )
+                })
+        };
+        let key = column_idx(key_column)?;
+        let mut columns = vec![reader.columns()[key].clone()];
+        let mut aggregated: Vec<(usize, Aggregation)> = Vec::with_capacity(aggregations.len());
+        let mut names = vec![String::from("count")];
+        for (column_name, aggregation) in aggregations {
+            aggregated.push((column_idx(column_name)?, *aggregation));
+            names.push(format!(
+                "{}_{}",
+                column_name,
+                aggregation_name(*aggregation)
+            ));
+        }
+        for name in names {
+            if columns.contains(&name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The column {} already exists", name),
+                ));
+            }
+            columns.push(name);
+        }
+        let width = aggregated
+            .iter()
+            .map(|(idx, _)| *idx)
+            .chain([key])
+            .max()
+            .unwrap_or(key)
+            + 1;
+
+        let mut groups: Vec<(String, usize, Vec<Accumulator>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        while let Some(row) = reader.next().transpose()? {
+            if row.len() < width {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The row {} has {} values, but the columns used need {}",
+                        reader.rows_read(),
+                        row.len(),
+                        width
+                    ),
+                ));
+            }
+            let position = match positions.get(&row[key]) {
+                Some(position) => *position,
+                None => {
+                    positions.insert(row[key].clone(), groups.len());
+                    groups.push((
+                        row[key].clone(),
+                        0,
+                        vec![Accumulator::new(); aggregated.len()],
+                    ));
+                    groups.len() - 1
+                }
+            };
+            let (_, count, accumulators) = &mut groups[position];
+            *count += 1;
+            for ((idx, _), accumulator) in aggregated.iter().zip(accumulators.iter_mut()) {
+                if let Some(number) = parse_number(&row[*idx]) {
+                    accumulator.push(number);
+                }
+            }
+        }
+
+        let rows = groups
+            .into_iter()
+            .map(|(value, count, accumulators)| {
+                let mut row = vec![value, count.to_string()];
+                row.extend(aggregated.iter().zip(&accumulators).map(
+                    |((_, aggregation), accumulator)| {
+                        accumulator
+                            .apply(*aggregation)
+                            .map(|n| n.to_string())
+                            .unwrap_or_default()
+                    },
+                ));
+                row
+            })
+            .collect();
+        Ok(CSVFile::from_rows(options.delimiter, columns, rows))
+    }
+}
@@ -0,0 +1,252 @@
+//! A columnar representation of a CSV file, where the values are stored column by column.
+//!
+//! Scanning a column of a [CSVFile](`crate::CSVFile`) jumps from one row to the next,
+//! whereas the values of a column of a [CSVTable](`CSVTable`) are contiguous in memory,
+//! which makes column scans, statistics and typed extractions much faster on wide files.
+
+use std::io::{Error, ErrorKind};
+
+use crate::stats::parse_number;
+use crate::{CSVCoords, CSVFile, ColumnStats};
+
+/// A CSV file stored column-wise: one vector of values per column.
+///
+/// All the columns always have the same number of values.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::CSVFile;
+/// let columns = vec!["name".to_string(), "age".to_string()];
+/// let rows = vec![
+///     vec!["Thomas".to_string(), "20".to_string()],
+///     vec!["Yoshiip".to_string(), "99".to_string()],
+/// ];
+/// let file = CSVFile::build(&columns, &rows, &',').unwrap();
+///
+/// let table = file.to_table();
+/// assert_eq!(table.column(&"age".to_string()), Some(&vec!["20".to_string(), "99".to_string()]));
+/// assert_eq!(table.to_csv_file().rows, rows);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CSVTable {
+    pub delimiter: char,
+    columns: Vec<String>,
+    data: Vec<Vec<String>>,
+    row_count: usize,
+}
+
+impl CSVTable {
+    /// Creates a table from the names of the columns and their values.
+    /// It may return an error if the number of columns and of vectors of values differ,
+    /// or if the columns don't all have the same number of values.
+    pub fn build(
+        columns: &Vec<String>,
+        data: &Vec<Vec<String>>,
+        delimiter: &char,
+    ) -> Result<Self, Error> {
+        if columns.len() != data.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid number of columns, {} names were given for {} columns of values",
+                    columns.len(),
+                    data.len()
+                ),
+            ));
+        }
+
+        let row_count = data.first().map_or(0, |values| values.len());
+        for (index, values) in data.iter().enumerate() {
+            if values.len() != row_count {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid number of values for column of index {}, {} were given, but expected {}",
+                        index,
+                        values.len(),
+                        row_count
+                    ),
+                ));
+            }
+        }
+
+        Ok(Self {
+            delimiter: *delimiter,
+            columns: columns.clone(),
+            data: data.clone(),
+            row_count,
+        })
+    }
+
+    /// Converts a CSV file to a table.
+    /// The missing values of the rows that are shorter than the header are filled with empty strings,
+    /// and the extra values of the rows that are longer are dropped.
+    pub fn from_csv_file(file: &CSVFile) -> Self {
+        let data = (0..file.columns.len())
+            .map(|idx| {
+                file.rows
+                    .iter()
+                    .map(|row| row.get(idx).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            delimiter: file.delimiter,
+            columns: file.columns.clone(),
+            data,
+            row_count: file.rows.len(),
+        }
+    }
+
+    /// Converts the table back to a CSV file stored row by row.
+    pub fn to_csv_file(&self) -> CSVFile {
+        let rows = (0..self.row_count)
+            .map(|row| self.data.iter().map(|values| values[row].clone()).collect())
+            .collect();
+
+        CSVFile::from_rows(self.delimiter, self.columns.clone(), rows)
+    }
+
+    /// Returns the names of the columns.
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    /// Returns the number of columns in the table.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the number of rows in the table.
+    pub fn count_rows(&self) -> usize {
+        self.row_count
+    }
+
+    /// Checks if the table has a column.
+    pub fn has_column(&self, column_name: &String) -> bool {
+        self.columns.contains(column_name)
+    }
+
+    /// Gets the index of a column by its name.
+    pub fn get_column_idx(&self, column_name: &String) -> Option<usize> {
+        self.columns.iter().position(|c| c == column_name)
+    }
+
+    /// Gets all the values of a column.
+    pub fn column(&self, column_name: &String) -> Option<&Vec<String>> {
+        self.data.get(self.get_column_idx(column_name)?)
+    }
+
+    /// Gets all the values of a column, so that they can be modified in place.
+    pub fn column_mut(&mut self, column_name: &String) -> Option<&mut [String]> {
+        let idx = self.get_column_idx(column_name)?;
+        Some(self.data[idx].as_mut_slice())
+    }
+
+    /// Gets the value at the given coordinates.
+    pub fn get_cell(&self, coordinates: &CSVCoords) -> Option<&String> {
+        self.data.get(coordinates.column)?.get(coordinates.row)
+    }
+
+    /// Gathers the values of a row.
+    pub fn get_row(&self, row_idx: usize) -> Option<Vec<&String>> {
+        if row_idx >= self.row_count {
+            return None;
+        }
+        Some(self.data.iter().map(|values| &values[row_idx]).collect())
+    }
+
+    /// Adds a row at the end of the table.
+    /// It may return an error if the number of fields
+    /// in the row is different from the number of columns.
+    pub fn add_row(&mut self, data: &Vec<String>) -> Result<(), Error> {
+        if data.len() != self.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid number of fields, {} were given, but expected {}",
+                    data.len(),
+                    self.len()
+                ),
+            ));
+        }
+
+        for (values, value) in self.data.iter_mut().zip(data) {
+            values.push(value.clone());
+        }
+        self.row_count += 1;
+
+        Ok(())
+    }
+
+    /// Adds a column at the end of the table along with its values.
+    /// It may return an error if the column already exists
+    /// or if the number of values is different from the number of rows.
+    pub fn add_column(&mut self, name: &String, values: Vec<String>) -> Result<(), Error> {
+        if self.has_column(name) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} already exists", name),
+            ));
+        }
+        if values.len() != self.row_count && !self.columns.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid number of values, {} were given, but expected {}",
+                    values.len(),
+                    self.row_count
+                ),
+            ));
+        }
+
+        self.row_count = values.len();
+        self.columns.push(name.clone());
+        self.data.push(values);
+
+        Ok(())
+    }
+
+    /// Removes a column and returns its values.
+    /// It may return an error if the column index is out of range.
+    pub fn remove_column(&mut self, column_idx: usize) -> Result<Vec<String>, Error> {
+        if column_idx >= self.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The column index {} is out of range", column_idx),
+            ));
+        }
+
+        self.columns.remove(column_idx);
+        Ok(self.data.remove(column_idx))
+    }
+
+    /// Returns the numbers of a column, skipping the values that can't be parsed as numbers.
+    /// It returns `None` if the column doesn't exist.
+    pub fn numeric_values(&self, column_name: &String) -> Option<Vec<f64>> {
+        Some(
+            self.column(column_name)?
+                .iter()
+                .filter_map(|value| parse_number(value))
+                .collect(),
+        )
+    }
+
+    /// Computes the statistics of a column.
+    /// It returns `None` if the column doesn't exist.
+    pub fn column_stats(&self, column_name: &String) -> Option<ColumnStats> {
+        Some(ColumnStats::compute(
+            self.column(column_name)?.iter().collect(),
+        ))
+    }
+}
+
+impl CSVFile {
+    /// Converts the CSV file to a [CSVTable](`CSVTable`), which stores the values column by column.
+    pub fn to_table(&self) -> CSVTable {
+        CSVTable::from_csv_file(self)
+    }
+}
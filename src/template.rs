@@ -0,0 +1,102 @@
+//! Rendering of text templates with the values of each row.
+
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+/// A piece of a parsed template.
+enum Part {
+    Text(String),
+    /// The index of the column whose value replaces the placeholder.
+    Column(usize),
+}
+
+impl CSVFile {
+    /// Parses a template into pieces of text and placeholders,
+    /// checking that the columns exist.
+    fn parse_template(&self, template: &str) -> Result<Vec<Part>, Error> {
+        let mut parts: Vec<Part> = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|(_, c)| *c == '{').is_some() => text.push('{'),
+                '}' if chars.next_if(|(_, c)| *c == '}').is_some() => text.push('}'),
+                '{' => {
+                    let mut column_name = String::new();
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        column_name.push(c);
+                    }
+                    if !closed {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("The placeholder at position {} isn't closed", position),
+                        ));
+                    }
+                    let idx = self.get_column_idx(&column_name).ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("The column {} doesn't exist", column_name),
+                        )
+                    })?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Column(idx));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Renders a template for each row, replacing the names of the columns
+    /// between braces by their value, to generate emails, file names or reports.
+    /// Double braces (`{{` and `}}`) are rendered as literal braces,
+    /// and the values missing from the ragged rows are rendered as empty values.
+    ///
+    /// It may return an error if a column doesn't exist or if a brace isn't closed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let file = CSVFile::from_text("name,score\nThomas,19\nJohn,15\n", &ReaderOptions::default()).unwrap();
+    ///
+    /// let lines = file.render_rows("Hello {name}, your score is {score}").unwrap();
+    /// assert_eq!(lines, vec![
+    ///     "Hello Thomas, your score is 19",
+    ///     "Hello John, your score is 15",
+    /// ]);
+    ///
+    /// assert_eq!(file.render_rows("{{{name}}}").unwrap()[0], "{Thomas}");
+    /// assert!(file.render_rows("{age}").is_err());
+    /// ```
+    pub fn render_rows(&self, template: &str) -> Result<Vec<String>, Error> {
+        let parts = self.parse_template(template)?;
+        Ok(self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut rendered = String::with_capacity(template.len());
+                for part in &parts {
+                    match part {
+                        Part::Text(text) => rendered.push_str(text),
+                        Part::Column(idx) => {
+                            rendered.push_str(row.get(*idx).map_or("", String::as_str))
+                        }
+                    }
+                }
+                rendered
+            })
+            .collect())
+    }
+}
@@ -1,8 +1,11 @@
 #[cfg(test)]
 mod tests {
     use crate::*;
+    #[cfg(feature = "fs")]
     use std::fs;
+    #[cfg(feature = "fs")]
     use std::fs::File;
+    #[cfg(feature = "fs")]
     use std::io::Read;
 
     fn get_fake_columns() -> Vec<String> {
@@ -188,7 +191,7 @@ mod tests {
         let columns = get_fake_columns();
         let data = get_fake_rows();
         let csv_file = CSVFile::build(&columns, &data, &',').unwrap();
-        assert!(csv_file.check_validity());
+        assert!(csv_file.check_validity().is_valid());
     }
 
     #[test]
@@ -197,7 +200,7 @@ mod tests {
         let data = get_fake_rows();
         let mut csv_file = CSVFile::build(&columns, &data, &',').unwrap();
         csv_file.columns.remove(1);
-        assert!(!csv_file.check_validity());
+        assert!(!csv_file.check_validity().is_valid());
     }
 
     #[test]
@@ -206,7 +209,7 @@ mod tests {
         let data = get_fake_rows();
         let mut csv_file = CSVFile::build(&columns, &data, &',').unwrap();
         csv_file.columns[0] = "b".to_string();
-        assert!(!csv_file.check_validity());
+        assert!(!csv_file.check_validity().is_valid());
     }
 
     #[test]
@@ -215,7 +218,7 @@ mod tests {
         let data = get_fake_rows();
         let mut csv_file = CSVFile::build(&columns, &data, &',').unwrap();
         csv_file.rows[0].remove(1);
-        assert!(!csv_file.check_validity());
+        assert!(!csv_file.check_validity().is_valid());
     }
 
     #[test]
@@ -319,6 +322,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "fs")]
     fn test_write() {
         let columns = get_fake_columns();
         let data = get_fake_rows();
@@ -544,7 +548,7 @@ mod tests {
         assert_eq!(csv_file1.len(), 5);
         assert_eq!(csv_file1.count_rows(), 3);
         assert_eq!(csv_file1.rows[2].len(), 5);
-        assert!(csv_file1.check_validity());
+        assert!(csv_file1.is_valid());
     }
 
     #[test]
@@ -572,7 +576,7 @@ mod tests {
         assert_eq!(csv_file1.rows[2][2], "9".to_string());
         assert_eq!(csv_file1.rows[2][3], empty_string());
         assert_eq!(csv_file1.rows[2][4], empty_string());
-        assert!(csv_file1.check_validity());
+        assert!(csv_file1.is_valid());
     }
 
     #[test]
@@ -636,6 +640,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "fs")]
     fn test_readme() {
         let filename = String::from("./test_langs.csv");
         let file = CSVFile::new(&filename, &',').unwrap();
@@ -676,12 +681,10 @@ mod tests {
         }
 
         // mapped_rows is a vector of Language.
-        let mapped_rows = file.map_rows(|row: &Vec<String>| {
-            Language {
-                name: row[0].clone(),
-                level_of_fun: row[1].parse().unwrap(),
-                level_of_difficulty: row[2].parse().unwrap(),
-            }
+        let mapped_rows = file.map_rows(|row: &Vec<String>| Language {
+            name: row[0].clone(),
+            level_of_fun: row[1].parse().unwrap(),
+            level_of_difficulty: row[2].parse().unwrap(),
         });
 
         assert_eq!(mapped_rows.len(), 8);
@@ -694,4 +697,4259 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_trim_fields() {
+        let mut fields = vec![" a".to_string(), "b ".to_string(), " c c ".to_string()];
+        trim_fields(&mut fields);
+        assert_eq!(fields, vec!["a", "b", "c c"]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_read_with_trim_options() {
+        let filename = String::from("test_trim.csv");
+        fs::write(&filename, " a , b , c\n 1 ,\" 2 \", 3\n").unwrap();
+
+        let options = ReaderOptions {
+            trim: Trim::Headers,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::new_with_options(&filename, &options).unwrap();
+        assert_eq!(file.columns, vec!["a", "b", "c"]);
+        assert_eq!(file.rows, vec![vec![" 1 ", " 2 ", " 3"]]);
+
+        let options = ReaderOptions {
+            trim: Trim::Fields,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::new_with_options(&filename, &options).unwrap();
+        assert_eq!(file.columns, vec![" a ", " b ", " c"]);
+        assert_eq!(file.rows, vec![vec!["1", "2", "3"]]);
+
+        let file = CSVFile::new(&filename, &',').unwrap();
+        assert_eq!(file.columns, vec![" a ", " b ", " c"]);
+
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_write_with_column_formats() {
+        let columns = vec!["name".to_string(), "price".to_string(), "date".to_string()];
+        let rows = vec![
+            vec![
+                "apple".to_string(),
+                "1.5".to_string(),
+                "2024-03-27".to_string(),
+            ],
+            vec![
+                "pear \"green\"".to_string(),
+                "?".to_string(),
+                "unknown".to_string(),
+            ],
+        ];
+        let csv_file = CSVFile::build(&columns, &rows, &',').unwrap();
+
+        let mut options = WriterOptions::default();
+        options.set_column_format(
+            &"name".to_string(),
+            ColumnFormat {
+                always_quote: true,
+                ..ColumnFormat::default()
+            },
+        );
+        options.set_column_format(
+            &"price".to_string(),
+            ColumnFormat {
+                decimals: Some(2),
+                padding: Some(Padding {
+                    width: 5,
+                    alignment: Alignment::Right,
+                    fill: '0',
+                }),
+                ..ColumnFormat::default()
+            },
+        );
+        options.set_column_format(
+            &"date".to_string(),
+            ColumnFormat {
+                date_format: Some(DateFormat {
+                    input: "%Y-%m-%d".to_string(),
+                    output: "%d/%m/%Y".to_string(),
+                }),
+                ..ColumnFormat::default()
+            },
+        );
+
+        let target_filename = String::from("test_formats.csv");
+        csv_file
+            .write_with_options(&target_filename, &options)
+            .unwrap();
+        let contents = fs::read_to_string(&target_filename).unwrap();
+        assert_eq!(
+            contents,
+            "name,price,date\n\"apple\",01.50,27/03/2024\n\"pear \\\"green\\\"\",0000?,unknown\n"
+        );
+        assert_eq!(csv_file.rows, rows); // the in-memory data is untouched
+
+        let read_back = CSVFile::new(&target_filename, &',').unwrap();
+        assert_eq!(read_back.rows[1][0], "pear \"green\"");
+        fs::remove_file(&target_filename).unwrap();
+    }
+
+    #[test]
+    fn test_date_format() {
+        let format = DateFormat {
+            input: "%d/%m/%Y %H:%M".to_string(),
+            output: "%Y-%m-%dT%H:%M:00".to_string(),
+        };
+        assert_eq!(
+            format.apply("27/03/2024 14:05"),
+            Some("2024-03-27T14:05:00".to_string())
+        );
+        assert_eq!(format.apply("27/03/24 14:05"), None);
+        assert_eq!(format.apply("27/03/2024 14:05 extra"), None);
+    }
+
+    #[test]
+    fn test_parse_line_ending_with_quoted_field() {
+        let line = r#"a,"b""#.to_string();
+        let result = parse_line(&line, &',', None).unwrap();
+        assert_eq!(result, vec!["a", "b"]);
+
+        let line = r#""a","#.to_string();
+        let result = parse_line(&line, &',', None).unwrap();
+        assert_eq!(result, vec!["a", ""]);
+
+        let mut quoted = Vec::new();
+        let line = r#""a",b,"c""#.to_string();
+        let result = parse_line_with_quotes(&line, &',', None, Some(&mut quoted)).unwrap();
+        assert_eq!(result, vec!["a", "b", "c"]);
+        assert_eq!(quoted, vec![0, 2]);
+    }
+
+    /// A tiny xorshift generator, so that the property tests are reproducible.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+
+        fn value(&mut self) -> String {
+            const CHARS: [char; 13] = [
+                'a', 'Z', '0', ' ', ',', ';', '\t', '"', '\\', '\n', '\r', 'é', '=',
+            ];
+            let len = self.next(7);
+            (0..len).map(|_| CHARS[self.next(CHARS.len())]).collect()
+        }
+    }
+
+    #[test]
+    fn test_round_trip_arbitrary_values() {
+        let mut random = Random(0x2545F4914F6CDD1D);
+        for _ in 0..2000 {
+            let delimiter = [',', ';', '\t'][random.next(3)];
+            let width = random.next(4) + 1;
+            let mut columns: Vec<String> = (0..width).map(|_| random.value()).collect();
+            columns.dedup();
+            let rows: Vec<Vec<String>> = (0..random.next(6))
+                .map(|_| (0..columns.len()).map(|_| random.value()).collect())
+                .collect();
+
+            let line = parser::write_line(&columns, delimiter);
+            assert_eq!(parser::parse_line(&line, delimiter).unwrap(), columns);
+
+            let file = CSVFile::build(&columns, &rows, &delimiter).unwrap();
+            for escape in [Escape::Backslash, Escape::DoubleQuote] {
+                let writer_options = WriterOptions {
+                    escape,
+                    ..WriterOptions::default()
+                };
+                let text = file.to_string_with_options(&writer_options);
+                for options in [
+                    ReaderOptions {
+                        escape,
+                        ..ReaderOptions::with_delimiter(&delimiter)
+                    },
+                    ReaderOptions {
+                        escape,
+                        preserve_quotes: true,
+                        lossless: true,
+                        ..ReaderOptions::with_delimiter(&delimiter)
+                    },
+                ] {
+                    let read = CSVFile::from_text(&text, &options)
+                        .unwrap_or_else(|e| panic!("{:?} can't be read: {}", text, e));
+                    assert_eq!(read.columns, columns, "{:?}", text);
+                    assert_eq!(read.rows, rows, "{:?}", text);
+                    assert_eq!(read.to_string_with_options(&writer_options), text);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dialects() {
+        let columns = vec!["name".to_string(), "note".to_string()];
+        let rows = vec![vec!["Thomas".to_string(), "a \"b\"; c\\d".to_string()]];
+        let file = CSVFile::build(&columns, &rows, &',').unwrap();
+
+        let excel = file.to_string_with_options(&Dialect::ExcelSemicolon.writer_options());
+        assert_eq!(
+            excel,
+            "\u{feff}name;note\r\nThomas;\"a \"\"b\"\"; c\\d\"\r\n"
+        );
+        let mysql = file.to_string_with_options(&Dialect::MySQL.writer_options());
+        assert_eq!(mysql, "name\tnote\nThomas\t\"a \\\"b\\\"; c\\\\d\"\n");
+
+        for dialect in [
+            Dialect::Excel,
+            Dialect::ExcelSemicolon,
+            Dialect::Unix,
+            Dialect::RFC4180,
+            Dialect::MySQL,
+        ] {
+            let text = file.to_string_with_options(&dialect.writer_options());
+            let read = CSVFile::from_text(&text, &dialect.reader_options()).unwrap();
+            assert_eq!(read.columns, columns, "{:?}", dialect);
+            assert_eq!(read.rows, rows, "{:?}", dialect);
+        }
+    }
+
+    #[test]
+    fn test_raw_header() {
+        let text = "\u{feff}\"id\";  name ;\"multi\nline\"\n1;Thomas;x\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::with_delimiter(&';')).unwrap();
+        assert_eq!(file.columns, vec!["id", "  name ", "multi\nline"]);
+        assert_eq!(
+            file.raw_header(),
+            Some(&"\"id\";  name ;\"multi\nline\"".to_string())
+        );
+
+        assert!(file.set_header_from_line("a;b").is_err());
+        assert!(file.set_header_from_line("a;\"b;c").is_err());
+        file.set_header_from_line("a ;\"b\";  c").unwrap();
+        assert_eq!(file.columns, vec!["a ", "b", "  c"]);
+        // The byte order mark of the source is written back.
+        assert!(file.to_string().starts_with("\u{feff}a ;\"b\";  c\n"));
+
+        file.columns[0] = "z".to_string();
+        assert!(file.to_string().starts_with("\u{feff}z;\"b\";  c\n"));
+        assert_eq!(
+            file.raw_header(),
+            Some(&"\"id\";  name ;\"multi\nline\"".to_string())
+        );
+
+        let file = CSVFile::build(&vec!["a".to_string()], &vec![], &',').unwrap();
+        assert_eq!(file.raw_header(), None);
+    }
+
+    #[test]
+    fn test_column_metadata() {
+        let columns = vec!["name".to_string(), "weight".to_string()];
+        let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+        let weight = "weight".to_string();
+        file.set_column_meta(&weight, &"unit".to_string(), &"kg".to_string())
+            .unwrap();
+        file.set_column_meta(
+            &weight,
+            &"description".to_string(),
+            &"The \"net\" weight".to_string(),
+        )
+        .unwrap();
+        assert!(file
+            .set_column_meta(&"age".to_string(), &"unit".to_string(), &"y".to_string())
+            .is_err());
+
+        let json = file.metadata_to_json();
+        assert_eq!(
+            json,
+            "{\n  \"columns\": {\n    \"weight\": {\n      \"description\": \"The \\\"net\\\" weight\",\n      \"unit\": \"kg\"\n    }\n  }\n}\n"
+        );
+
+        let mut other = CSVFile::build(&columns, &vec![], &',').unwrap();
+        other.load_metadata_json(&json).unwrap();
+        assert_eq!(other.column_meta(&weight), file.column_meta(&weight));
+        assert!(other
+            .load_metadata_json("{\"columns\": {\"name\": 1}}")
+            .is_err());
+        assert!(other.load_metadata_json("{\"columns\": ").is_err());
+
+        assert_eq!(
+            other.remove_column_meta(&weight, &"unit".to_string()),
+            Some("kg".to_string())
+        );
+        other.remove_column(1).unwrap();
+        assert_eq!(other.column_meta(&weight), None);
+    }
+
+    #[test]
+    fn test_freeze_and_thaw() {
+        let file = CSVFile::from_text("a,b\n1,2\n", &ReaderOptions::default()).unwrap();
+        let snapshot = file.freeze();
+        let shared = snapshot.clone();
+        assert!(shared.ptr_eq(&snapshot));
+        assert_eq!(
+            shared.get_cell(&CSVCoords { row: 0, column: 1 }),
+            Some(&"2".to_string())
+        );
+        assert_eq!(shared.to_string(), "a,b\n1,2\n");
+
+        let mut copy = shared.thaw();
+        copy.rows[0][1] = "3".to_string();
+        assert_eq!(snapshot.rows[0][1], "2");
+
+        let file = snapshot.thaw();
+        assert_eq!(file.raw_header(), Some(&"a,b".to_string()));
+    }
+
+    #[test]
+    fn test_thawed_copy_has_no_observers() {
+        use std::sync::{Arc, Mutex};
+        let mut file = CSVFile::from_text("a,b\n1,2\n", &ReaderOptions::default()).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = Arc::clone(&events);
+        file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+
+        let snapshot = file.freeze();
+        let mut shared = snapshot.clone().thaw();
+        shared
+            .add_row(&vec!["3".to_string(), "4".to_string()])
+            .unwrap();
+        let mut copy = snapshot.thaw();
+        copy.remove_row(0).unwrap();
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validators() {
+        let columns = vec!["name".to_string(), "age".to_string(), "email".to_string()];
+        let rows = vec![vec![
+            "Thomas".to_string(),
+            "19".to_string(),
+            "thomas@example.com".to_string(),
+        ]];
+        let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+        file.add_validator(Validator::column(
+            &"age".to_string(),
+            "age must parse as u8",
+            |value| value.parse::<u8>().is_ok(),
+        ))
+        .unwrap();
+        file.add_validator(Validator::row("a name is required", |row| {
+            !row[0].is_empty()
+        }))
+        .unwrap();
+        assert!(file
+            .add_validator(Validator::column(&"phone".to_string(), "", |_| true))
+            .is_err());
+
+        let error = file
+            .add_row(&vec![
+                "Yoshiip".to_string(),
+                "300".to_string(),
+                String::new(),
+            ])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value \"300\" of the column age was rejected: age must parse as u8"
+        );
+        assert!(file
+            .add_row(&vec![String::new(), "20".to_string(), String::new()])
+            .is_err());
+        assert_eq!(file.count_rows(), 1);
+
+        let coords = CSVCoords { row: 0, column: 1 };
+        assert!(file.set_cell(&coords, &"old".to_string()).is_err());
+        assert!(file
+            .fill_column(&"name".to_string(), &vec![String::new()])
+            .is_err());
+        assert_eq!(file.rows[0][0], "Thomas");
+        file.set_cell(&coords, &"20".to_string()).unwrap();
+        assert_eq!(file.rows[0][1], "20");
+
+        file.remove_column(1).unwrap();
+        assert_eq!(file.validators().len(), 1);
+        file.clear_validators();
+        file.fill_column(&"name".to_string(), &vec![String::new()])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_change_events() {
+        use std::sync::{Arc, Mutex};
+
+        let columns = vec!["a".to_string(), "b".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "2".to_string()],
+            vec!["3".to_string(), "4".to_string()],
+        ];
+        let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = Arc::clone(&events);
+        file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+
+        file.fill_column(&"a".to_string(), &vec!["1".to_string(), "5".to_string()])
+            .unwrap();
+        file.insert_column(&"c".to_string(), 0).unwrap();
+        file.remove_row(0).unwrap();
+        assert!(file.add_row(&vec!["6".to_string()]).is_err());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::CellChanged {
+                    coordinates: CSVCoords { row: 1, column: 0 },
+                    old_value: "3".to_string(),
+                    new_value: "5".to_string(),
+                },
+                ChangeEvent::ColumnAdded {
+                    column: 0,
+                    name: "c".to_string(),
+                },
+                ChangeEvent::RowRemoved { row: 0 },
+            ]
+        );
+
+        file.clear_observers();
+        file.add_column(&"d".to_string()).unwrap();
+        assert_eq!(events.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_change_events_of_other_mutators() {
+        use std::sync::{Arc, Mutex};
+
+        let text = "a,b\n1,x\n,\n2,y\n,\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = Arc::clone(&events);
+        file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+
+        file.remove_empty_lines();
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                ChangeEvent::RowRemoved { row: 3 },
+                ChangeEvent::RowRemoved { row: 1 },
+            ]
+        );
+
+        // The moved rows are reported as the cells whose value changed.
+        file.reverse_rows();
+        assert_eq!(file.rows, vec![vec!["2", "y"], vec!["1", "x"]]);
+        let cell = |row, column, old_value: &str, new_value: &str| ChangeEvent::CellChanged {
+            coordinates: CSVCoords { row, column },
+            old_value: old_value.to_string(),
+            new_value: new_value.to_string(),
+        };
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                cell(0, 0, "1", "2"),
+                cell(0, 1, "x", "y"),
+                cell(1, 0, "2", "1"),
+                cell(1, 1, "y", "x"),
+            ]
+        );
+
+        let other = CSVFile::from_text("c\n3\n4\n5\n", &ReaderOptions::default()).unwrap();
+        file.merge(&other).unwrap();
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                ChangeEvent::RowAdded { row: 2 },
+                ChangeEvent::ColumnAdded {
+                    column: 2,
+                    name: "c".to_string(),
+                },
+            ]
+        );
+
+        file.set_cell(&CSVCoords { row: 0, column: 1 }, &"y\0".to_string())
+            .unwrap();
+        events.lock().unwrap().clear();
+        file.sanitize(&SanitizeOptions::default());
+        assert_eq!(*events.lock().unwrap(), vec![cell(0, 1, "y\0", "y")]);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let columns = vec!["a".to_string(), "b".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "2".to_string()],
+            vec!["3".to_string(), "4".to_string()],
+        ];
+        let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+        let hash = file.content_hash();
+        let column_hash = file.column_hash(&"a".to_string()).unwrap();
+
+        // the values must not be concatenated without their boundaries
+        let shifted = CSVFile::build(
+            &columns,
+            &vec![
+                vec!["12".to_string(), String::new()],
+                vec!["3".to_string(), "4".to_string()],
+            ],
+            &',',
+        )
+        .unwrap();
+        assert_ne!(shifted.content_hash(), hash);
+
+        file.set_delimiter(&';');
+        assert_eq!(file.content_hash(), hash);
+        file.set_cell(&CSVCoords { row: 1, column: 1 }, &"5".to_string())
+            .unwrap();
+        assert_ne!(file.content_hash(), hash);
+        assert_eq!(file.column_hash(&"a".to_string()), Some(column_hash));
+        assert_ne!(file.column_hash(&"b".to_string()), Some(column_hash));
+        assert_eq!(file.column_hash(&"c".to_string()), None);
+
+        // a column given by an alias has the same fingerprint as the column in the header
+        let mut aliases = HashMap::new();
+        aliases.insert("alpha".to_string(), vec!["a".to_string()]);
+        file.set_column_aliases(&aliases);
+        assert_eq!(file.column_hash(&"alpha".to_string()), Some(column_hash));
+    }
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(parse_timestamp("1970-01-02"), Some(86_400));
+        assert_eq!(parse_timestamp("2000-03-01T00:00:00Z"), Some(951_868_800));
+        assert_eq!(
+            parse_timestamp("2000-03-01T02:00:00.250+02:00"),
+            Some(951_868_800)
+        );
+        assert_eq!(parse_timestamp("1700000000"), Some(1_700_000_000));
+        assert_eq!(parse_timestamp("2000-13-01"), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+        assert_eq!(
+            format_timestamp(951_868_800 + 3661, false),
+            "2000-03-01 01:01:01"
+        );
+        assert_eq!(format_timestamp(-1, true), "1969-12-31");
+
+        let text = "sensor,time,temperature\n\
+            a,2024-02-28 23:59,10\n\
+            a,2024-02-29 08:00,14\n\
+            b,2024-02-29 20:00,\n\
+            a,2024-02-28 00:00,6\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let time = "time".to_string();
+
+        let daily = file
+            .resample(&time, Interval::Days(1), Aggregation::Mean)
+            .unwrap();
+        assert_eq!(daily.columns, file.columns);
+        assert_eq!(
+            daily.rows,
+            vec![vec!["", "2024-02-28", "8"], vec!["", "2024-02-29", "14"]]
+        );
+        let counts = file
+            .resample(&time, Interval::Hours(12), Aggregation::Count)
+            .unwrap();
+        assert_eq!(
+            counts.rows,
+            vec![
+                vec!["0", "2024-02-28 00:00:00", "1"],
+                vec!["0", "2024-02-28 12:00:00", "1"],
+                vec!["0", "2024-02-29 00:00:00", "1"],
+                vec!["0", "2024-02-29 12:00:00", "0"],
+            ]
+        );
+
+        assert!(file
+            .resample(&"date".to_string(), Interval::Days(1), Aggregation::Sum)
+            .is_err());
+        let mut invalid = file.clone();
+        invalid.rows[0][1] = "soon".to_string();
+        assert!(invalid
+            .resample(&time, Interval::Days(1), Aggregation::Sum)
+            .is_err());
+
+        let text = "time,value
+2024-01-01,1
+2024-01-01
+2024-01-01,2,9
+";
+        let ragged = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let sums = ragged
+            .resample(&time, Interval::Days(1), Aggregation::Sum)
+            .unwrap();
+        assert_eq!(sums.rows, vec![vec!["2024-01-01", "3"]]);
+        let ragged = CSVFile::from_text(
+            "value,time
+1
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let error = ragged
+            .resample(&time, Interval::Days(1), Aggregation::Sum)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_rolling() {
+        let text = "time,value\n1,4\n2,n/a\n3,8\n4,2\n5,6\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let value = "value".to_string();
+
+        file.rolling(&value, 3, Aggregation::Max, &"max".to_string())
+            .unwrap();
+        file.rolling(&value, 1, Aggregation::Sum, &"sum".to_string())
+            .unwrap();
+        assert_eq!(file.columns, vec!["time", "value", "max", "sum"]);
+        let max: Vec<&str> = file.rows.iter().map(|row| row[2].as_str()).collect();
+        assert_eq!(max, vec!["", "", "8", "8", "8"]);
+        let sum: Vec<&str> = file.rows.iter().map(|row| row[3].as_str()).collect();
+        assert_eq!(sum, vec!["4", "", "8", "2", "6"]);
+
+        assert!(file
+            .rolling(&value, 0, Aggregation::Mean, &"mean".to_string())
+            .is_err());
+        assert!(file
+            .rolling(&value, 2, Aggregation::Mean, &"max".to_string())
+            .is_err());
+        assert!(file
+            .rolling(
+                &"other".to_string(),
+                2,
+                Aggregation::Mean,
+                &"mean".to_string()
+            )
+            .is_err());
+        assert_eq!(file.len(), 4);
+
+        let mut ragged = CSVFile::from_text(
+            "time,value
+1,4
+2
+3,8
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        ragged
+            .rolling(&value, 2, Aggregation::Sum, &"sum".to_string())
+            .unwrap();
+        let sum: Vec<&str> = ragged.rows.iter().map(|row| row[2].as_str()).collect();
+        assert_eq!(sum, vec!["", "4", "8"]);
+    }
+
+    #[test]
+    fn test_enrich_from() {
+        let mut orders = CSVFile::from_text(
+            "order,customer\n1,c2\n2,c9\n3,c1\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let customers = CSVFile::from_text(
+            "id,name,city\nc1,Thomas,Lyon\nc2,Yoshiip,Paris\nc1,Duplicate,Nice\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let customer = "customer".to_string();
+        let id = "id".to_string();
+
+        assert!(orders
+            .enrich_from(&customers, &customer, &id, &vec!["country".to_string()])
+            .is_err());
+        assert!(orders
+            .enrich_from(&customers, &customer, &id, &vec!["order".to_string()])
+            .is_err());
+        assert_eq!(orders.len(), 2);
+
+        orders
+            .enrich_from(
+                &customers,
+                &customer,
+                &id,
+                &vec!["city".to_string(), "name".to_string()],
+            )
+            .unwrap();
+        assert_eq!(orders.columns, vec!["order", "customer", "city", "name"]);
+        assert_eq!(
+            orders.rows,
+            vec![
+                vec!["1", "c2", "Paris", "Yoshiip"],
+                vec!["2", "c9", "", ""],
+                vec!["3", "c1", "Lyon", "Thomas"],
+            ]
+        );
+
+        let mut ragged = CSVFile::from_text("k,v\n1\n2,b\n", &ReaderOptions::default()).unwrap();
+        let other = CSVFile::from_text("v,w,x\nb\n,y,z\n", &ReaderOptions::default()).unwrap();
+        let v = "v".to_string();
+        ragged
+            .enrich_from(&other, &v, &v, &vec!["w".to_string(), "x".to_string()])
+            .unwrap();
+        assert_eq!(
+            ragged.rows,
+            vec![vec!["1", "", "y", "z"], vec!["2", "b", "", ""]]
+        );
+    }
+
+    #[test]
+    fn test_rows_not_in() {
+        let crm = CSVFile::from_text(
+            "id,name\n1,A\n2,B\n3,C\n2,B again\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let billing =
+            CSVFile::from_text("amount,id\n10,2\n5,4\n", &ReaderOptions::default()).unwrap();
+
+        let missing = crm.rows_not_in(&billing, &"id".to_string()).unwrap();
+        assert_eq!(missing.columns, crm.columns);
+        assert_eq!(missing.rows, vec![vec!["1", "A"], vec!["3", "C"]]);
+        assert!(crm.rows_not_in(&billing, &"name".to_string()).is_err());
+
+        let ragged = CSVFile::from_text("k,v,w\n1\n2,b,c\n", &ReaderOptions::default()).unwrap();
+        let other = CSVFile::from_text("v\nb\n", &ReaderOptions::default()).unwrap();
+        let missing = ragged.rows_not_in(&other, &"v".to_string()).unwrap();
+        assert_eq!(missing.rows, vec![vec!["1"]]);
+        let other = CSVFile::from_text("k,v\nb\n,c\n", &ReaderOptions::default()).unwrap();
+        let missing = ragged.rows_not_in(&other, &"v".to_string()).unwrap();
+        assert_eq!(missing.rows, vec![vec!["2", "b", "c"]]);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = CSVFile::from_text("x,y\n1,a\n2,b\n1,a\n3,c\n", &ReaderOptions::default()).unwrap();
+        let b = CSVFile::from_text("y,x\nc,3\nd,4\nc,3\n", &ReaderOptions::default()).unwrap();
+
+        let union = a.union(&b).unwrap();
+        assert_eq!(union.columns, vec!["x", "y"]);
+        assert_eq!(
+            union.rows,
+            vec![
+                vec!["1", "a"],
+                vec!["2", "b"],
+                vec!["3", "c"],
+                vec!["4", "d"]
+            ]
+        );
+        assert_eq!(a.intersection(&b).unwrap().rows, vec![vec!["3", "c"]]);
+        assert_eq!(
+            a.difference(&b).unwrap().rows,
+            vec![vec!["1", "a"], vec!["2", "b"]]
+        );
+        assert_eq!(b.difference(&a).unwrap().rows, vec![vec!["d", "4"]]);
+
+        let other = CSVFile::from_text("x,z\n1,a\n", &ReaderOptions::default()).unwrap();
+        assert!(a.union(&other).is_err());
+        assert!(a.intersection(&other).is_err());
+        assert!(a.difference(&other).is_err());
+    }
+
+    #[test]
+    fn test_split_column() {
+        let text = "id,date,note\n1,2024-03-01,a\n2,2024-03,b\n3,,c\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let names = vec!["year".to_string(), "rest".to_string()];
+
+        file.split_column(&"date".to_string(), "-", &names).unwrap();
+        assert_eq!(file.columns, vec!["id", "year", "rest", "note"]);
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["1", "2024", "03-01", "a"],
+                vec!["2", "2024", "03", "b"],
+                vec!["3", "", "", "c"],
+            ]
+        );
+
+        // the split column can keep its name
+        let names = vec!["rest".to_string(), "day".to_string()];
+        file.split_column(&"rest".to_string(), "-", &names).unwrap();
+        assert_eq!(file.columns, vec!["id", "year", "rest", "day", "note"]);
+        assert_eq!(file.rows[0], vec!["1", "2024", "03", "01", "a"]);
+
+        assert!(file
+            .split_column(&"year".to_string(), "-", &vec!["note".to_string()])
+            .is_err());
+        assert!(file
+            .split_column(&"year".to_string(), "-", &vec![])
+            .is_err());
+        assert!(file
+            .split_column(&"other".to_string(), "-", &vec!["a".to_string()])
+            .is_err());
+        assert_eq!(file.len(), 5);
+
+        let mut ragged =
+            CSVFile::from_text("id,date,note\n1\n2,2024-03\n", &ReaderOptions::default()).unwrap();
+        ragged
+            .split_column(
+                &"date".to_string(),
+                "-",
+                &vec!["year".to_string(), "month".to_string()],
+            )
+            .unwrap();
+        assert_eq!(ragged.columns, vec!["id", "year", "month", "note"]);
+        assert_eq!(
+            ragged.rows,
+            vec![vec!["1", "", ""], vec!["2", "2024", "03"]]
+        );
+    }
+
+    #[test]
+    fn test_combine_columns() {
+        let text = "first,id,last\nJohn,1,Doe\nCher,2,\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let sources = ["first", "last"];
+
+        file.combine_columns(&sources, " ", &"full".to_string(), false)
+            .unwrap();
+        assert_eq!(file.columns, vec!["first", "id", "last", "full"]);
+        assert_eq!(file.rows[0][3], "John Doe");
+        assert_eq!(file.rows[1][3], "Cher ");
+
+        assert!(file
+            .combine_columns(&sources, " ", &"id".to_string(), true)
+            .is_err());
+        assert!(file
+            .combine_columns(&["other"], " ", &"x".to_string(), true)
+            .is_err());
+
+        file.combine_columns(&["last", "first"], ", ", &"first".to_string(), true)
+            .unwrap();
+        assert_eq!(file.columns, vec!["first", "id", "full"]);
+        assert_eq!(file.rows[0], vec!["Doe, John", "1", "John Doe"]);
+
+        // splitting is the inverse operation
+        let names = vec!["last".to_string(), "first".to_string()];
+        file.split_column(&"first".to_string(), ", ", &names)
+            .unwrap();
+        assert_eq!(file.rows[0], vec!["Doe", "John", "1", "John Doe"]);
+
+        let mut ragged =
+            CSVFile::from_text("id,first,last\n1\n2,Cher\n", &ReaderOptions::default()).unwrap();
+        ragged
+            .combine_columns(&["first", "last"], " ", &"full".to_string(), true)
+            .unwrap();
+        assert_eq!(ragged.columns, vec!["id", "full"]);
+        assert_eq!(ragged.rows, vec![vec!["1", " "], vec!["2", "Cher "]]);
+    }
+
+    #[test]
+    fn test_json_records() {
+        let text = "id,address.city,address.geo.lat,address,meta..x\n1,Lyon,45.7,home,a\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        // a column can't be nested below another column, nor have an empty key
+        let nested = file.to_json(&JsonOptions::default());
+        assert_eq!(
+            nested,
+            r#"[{"id":"1","address.city":"Lyon","address.geo.lat":"45.7","address":"home","meta..x":"a"}]"#
+        );
+        let mut file = file.clone();
+        file.remove_column(3).unwrap();
+        let nested = file.to_json(&JsonOptions::default());
+        assert_eq!(
+            nested,
+            r#"[{"id":"1","address":{"city":"Lyon","geo":{"lat":"45.7"}},"meta..x":"a"}]"#
+        );
+        let flat = file.to_json(&JsonOptions {
+            nested: false,
+            pretty: true,
+            ..JsonOptions::default()
+        });
+        assert!(flat.starts_with("[\n  {\n    \"id\": \"1\",\n    \"address.city\""));
+
+        let imported = CSVFile::from_json(&nested, &',', &JsonOptions::default()).unwrap();
+        assert_eq!(imported.columns, file.columns);
+        assert_eq!(imported.rows, file.rows);
+
+        let json = r#"[{"a": {"b": [1, null]}, "c": "x"}, {"c": "y", "d": {}}]"#;
+        let raw = CSVFile::from_json(
+            json,
+            &';',
+            &JsonOptions {
+                nested: false,
+                ..JsonOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(raw.columns, vec!["a", "c", "d"]);
+        assert_eq!(raw.rows[0], vec![r#"{"b":[1,null]}"#, "x", ""]);
+        assert_eq!(raw.rows[1], vec!["", "y", "{}"]);
+
+        assert!(CSVFile::from_json(r#"{"a": 1}"#, &',', &JsonOptions::default()).is_err());
+        assert!(CSVFile::from_json("[1]", &',', &JsonOptions::default()).is_err());
+        assert!(CSVFile::from_json("[", &',', &JsonOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_map_values() {
+        let file = CSVFile::from_text(
+            "id,status\n1,A\n2,B\n3,?\n4,\n5,?\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let status = "status".to_string();
+        let table = HashMap::from([
+            ("A".to_string(), "active".to_string()),
+            ("B".to_string(), "blocked".to_string()),
+        ]);
+
+        let mut strict = file.clone();
+        let error = strict
+            .map_values(&status, &table, MissPolicy::Error)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The values [\"\", \"?\"] of the column status aren't in the table"
+        );
+        assert_eq!(strict.rows, file.rows);
+
+        let mut cleared = file.clone();
+        let report = cleared
+            .map_values(&status, &table, MissPolicy::Clear)
+            .unwrap();
+        assert_eq!(report.mapped, 2);
+        assert_eq!(
+            report.unmapped,
+            BTreeMap::from([(String::new(), 1), ("?".to_string(), 2)])
+        );
+        assert!(!report.is_complete());
+        let values: Vec<&str> = cleared.rows.iter().map(|row| row[1].as_str()).collect();
+        assert_eq!(values, vec!["active", "blocked", "", "", ""]);
+
+        let mut defaulted = file.clone();
+        defaulted
+            .map_values(&status, &table, MissPolicy::Default("unknown".to_string()))
+            .unwrap();
+        assert_eq!(defaulted.rows[4][1], "unknown");
+        assert!(defaulted
+            .map_values(&"other".to_string(), &table, MissPolicy::Keep)
+            .is_err());
+
+        let mut ragged = CSVFile::from_text(
+            "id,status
+1
+2,A
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let report = ragged
+            .map_values(&status, &table, MissPolicy::Default("unknown".to_string()))
+            .unwrap();
+        assert_eq!(report.unmapped.get(""), Some(&1));
+        assert_eq!(ragged.rows, vec![vec!["1", "unknown"], vec!["2", "active"]]);
+    }
+
+    #[test]
+    fn test_mask_column() {
+        let hash = MaskKind::Hash(String::new());
+        assert_eq!(
+            hash.apply("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // a message spanning two blocks
+        assert_eq!(
+            hash.apply(&"a".repeat(100)),
+            "2816597888e4a0d3a36b82b83316ab32680eb8f00f8cd3b904d681246d285a0e"
+        );
+        assert_ne!(
+            MaskKind::Hash("salt".to_string()).apply("abc"),
+            hash.apply("abc")
+        );
+        assert_eq!(MaskKind::PartialKeepLast(3).apply("été"), "été");
+        assert_eq!(MaskKind::PartialKeepLast(0).apply("abc"), "***");
+
+        let text = "name,email\nThomas,t@x.org\n,\nThomas,y@x.org\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let fake_name = MaskKind::FakeName("salt".to_string());
+        file.mask_column(&"name".to_string(), &fake_name).unwrap();
+        file.mask_column(&"email".to_string(), &MaskKind::Redact)
+            .unwrap();
+        assert_ne!(file.rows[0][0], "Thomas");
+        assert_eq!(file.rows[0][0], file.rows[2][0]);
+        assert_eq!(file.rows[1], vec!["", ""]);
+        assert_eq!(file.rows[2][1], "***");
+        assert!(file
+            .mask_column(&"phone".to_string(), &MaskKind::Redact)
+            .is_err());
+
+        // the fake names depend on the salt
+        let names: Vec<String> = (0..8)
+            .map(|i| MaskKind::FakeName(i.to_string()).apply("Thomas"))
+            .collect();
+        assert!(names.iter().any(|name| *name != names[0]));
+
+        let mut ragged = CSVFile::from_text(
+            "name,email
+Thomas
+John,j@x.org
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        ragged
+            .mask_column(&"email".to_string(), &MaskKind::Redact)
+            .unwrap();
+        assert_eq!(ragged.rows[0], vec!["Thomas", ""]);
+        assert_eq!(ragged.rows[1][1], "***");
+    }
+
+    #[test]
+    fn test_query() {
+        let text =
+            "name,age,first name,score\nThomas,19,T,-1.5\nYoshiip,35,Y,n/a\nO'Neil,9,O,2e3\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        assert_eq!(file.query("age >= 19").unwrap(), vec![0, 1]);
+        // compared as text when one of the values isn't a number
+        assert_eq!(file.query("name < 'U'").unwrap(), vec![0, 2]);
+        assert_eq!(file.query("name == 'O''Neil'").unwrap(), vec![2]);
+        assert_eq!(file.query("`first name` = 'Y'").unwrap(), vec![1]);
+        assert_eq!(file.query("score < 0").unwrap(), vec![0]);
+        assert_eq!(file.query("score != 0").unwrap(), vec![0, 1, 2]);
+        assert_eq!(file.query("score > -2").unwrap(), vec![0, 2]);
+        assert_eq!(file.query("score = 2000").unwrap(), vec![2]);
+        assert_eq!(file.query("age = '35.0'").unwrap(), vec![1]);
+        assert_eq!(
+            file.query("age < 10 || age > 30 && name <> 'Yoshiip'")
+                .unwrap(),
+            vec![2]
+        );
+        assert_eq!(
+            file.query("(age < 10 OR age > 30) AND NOT name = 'Yoshiip'")
+                .unwrap(),
+            vec![2]
+        );
+        assert_eq!(file.query("!!(age == 19)").unwrap(), vec![0]);
+
+        assert!(file.query("height > 1").is_err());
+        for invalid in [
+            "age >",
+            "age",
+            "(age > 1",
+            "age > 1 )",
+            "name == 'x",
+            "age # 1",
+            "",
+        ] {
+            assert!(Query::parse(invalid).is_err(), "{}", invalid);
+        }
+
+        let query = Query::parse("age > 18").unwrap();
+        let adults = file.filter(&query).unwrap();
+        assert_eq!(adults.columns, file.columns);
+        assert_eq!(adults.count_rows(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "select")]
+    fn test_select() {
+        let text = "name,age,country,order\nThomas,19,FR,3\nYoshiip,35,FR,1\nJohn,n/a,US,2\nMarie,35,DE,4\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let all = file.select("select * from THIS").unwrap();
+        assert_eq!(all.columns, file.columns);
+        assert_eq!(all.rows, file.rows);
+
+        // ties keep the original order, and numbers come before the other values
+        let sorted = file
+            .select("SELECT name FROM this ORDER BY age DESC, `order` ASC")
+            .unwrap();
+        let names: Vec<&str> = sorted.rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(names, vec!["John", "Yoshiip", "Marie", "Thomas"]);
+        let sorted = file.select("SELECT name FROM this ORDER BY age").unwrap();
+        let names: Vec<&str> = sorted.rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(names, vec!["Thomas", "Yoshiip", "Marie", "John"]);
+
+        let page = file
+            .select("SELECT `order` AS rank, name FROM this WHERE age >= 19 AND NOT country = 'US' ORDER BY `order` LIMIT 2 OFFSET 1")
+            .unwrap();
+        assert_eq!(page.columns, vec!["rank", "name"]);
+        assert_eq!(page.rows, vec![vec!["3", "Thomas"], vec!["4", "Marie"]]);
+
+        for invalid in [
+            "SELECT name",
+            "SELECT name FROM other",
+            "SELECT height FROM this",
+            "SELECT name FROM this WHERE height > 1",
+            "SELECT name FROM this ORDER age",
+            "SELECT name FROM this LIMIT -1",
+            "SELECT name FROM this LIMIT 1.5",
+            "SELECT name, FROM this",
+            "SELECT name FROM this WHERE age > 1 extra",
+        ] {
+            assert!(file.select(invalid).is_err(), "{}", invalid);
+        }
+
+        let ragged = CSVFile::from_text(
+            "name,age
+Thomas
+John,42
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let result = ragged
+            .select("SELECT age, name FROM this ORDER BY age")
+            .unwrap();
+        assert_eq!(result.rows, vec![vec!["42", "John"], vec!["", "Thomas"]]);
+    }
+
+    #[test]
+    fn test_conform_to() {
+        let options = ReaderOptions {
+            preserve_quotes: true,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::from_text("b,extra,a\n\"1\",x,2\n3,y,4\n", &options).unwrap();
+        let target = ["a", "b", "c"];
+
+        let mut kept = file.clone();
+        kept.conform_to(&target, ConformPolicy::KeepExtras).unwrap();
+        assert_eq!(kept.columns, vec!["a", "b", "c", "extra"]);
+        assert_eq!(kept.rows[0], vec!["2", "1", "", "x"]);
+        assert!(kept.is_quoted(&CSVCoords { row: 0, column: 1 }));
+        assert_eq!(kept.to_string(), "a,b,c,extra\n2,\"1\",,x\n4,3,,y\n");
+
+        let mut dropped = file.clone();
+        dropped
+            .set_column_meta(&"extra".to_string(), &"unit".to_string(), &"m".to_string())
+            .unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = std::sync::Arc::clone(&events);
+        dropped.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+        dropped
+            .conform_to(&target, ConformPolicy::DropExtras)
+            .unwrap();
+        assert_eq!(dropped.columns, vec!["a", "b", "c"]);
+        assert_eq!(dropped.rows[1], vec!["4", "3", ""]);
+        assert!(dropped.column_meta(&"extra".to_string()).is_none());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::ColumnRemoved {
+                    column: 1,
+                    name: "extra".to_string()
+                },
+                ChangeEvent::ColumnAdded {
+                    column: 2,
+                    name: "c".to_string()
+                },
+            ]
+        );
+
+        let mut rejected = file.clone();
+        assert!(rejected
+            .conform_to(&target, ConformPolicy::RejectExtras)
+            .is_err());
+        assert!(rejected
+            .conform_to(&["a", "a", "b", "extra"], ConformPolicy::KeepExtras)
+            .is_err());
+        assert_eq!(rejected.columns, file.columns);
+
+        let mut ragged = CSVFile::from_text("b,a\n1\n2,3,4\n", &ReaderOptions::default()).unwrap();
+        ragged
+            .conform_to(&target, ConformPolicy::KeepExtras)
+            .unwrap();
+        assert_eq!(ragged.rows, vec![vec!["", "1", ""], vec!["3", "2", ""]]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_append_rows_to_file() {
+        let filename = String::from("test_append.csv");
+        fs::write(&filename, "\u{feff}time;\"level\"\r\n10:00;info").unwrap();
+        let options = WriterOptions {
+            delimiter: Some(';'),
+            line_ending: Some(LineEnding::CrLf),
+            byte_order_mark: Some(true),
+            ..WriterOptions::default()
+        };
+
+        let rows = vec![
+            vec!["10:05".to_string(), "warning; disk".to_string()],
+            vec!["10:06".to_string(), "error".to_string()],
+        ];
+        let columns = vec!["time".to_string(), "level".to_string()];
+        CSVFile::append_rows_to_file(&filename, &columns, &rows, &options).unwrap();
+        assert!(CSVFile::append_rows_to_file(
+            &filename,
+            &columns,
+            &vec![vec!["x".to_string()]],
+            &options
+        )
+        .is_err());
+        // the rows of another file with as many columns are refused
+        let swapped = vec!["level".to_string(), "time".to_string()];
+        assert!(CSVFile::append_rows_to_file(&filename, &swapped, &rows, &options).is_err());
+        CSVFile::append_rows_to_file(&filename, &columns, &vec![], &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&filename).unwrap(),
+            "\u{feff}time;\"level\"\r\n10:00;info\r\n10:05;\"warning; disk\"\r\n10:06;error\r\n"
+        );
+
+        fs::remove_file(&filename).unwrap();
+        assert!(CSVFile::append_rows_to_file(&filename, &columns, &rows, &options).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_write_atomic() {
+        let filename = String::from("test_atomic.csv");
+        let backup = String::from("test_atomic.csv.bak");
+        let columns = vec!["a".to_string(), "b".to_string()];
+        let file = CSVFile::build(
+            &columns,
+            &vec![vec!["1".to_string(), "2".to_string()]],
+            &',',
+        )
+        .unwrap();
+
+        // no backup when there is nothing to back up
+        file.write_atomic(&filename, &WriterOptions::default(), true)
+            .unwrap();
+        assert_eq!(fs::read_to_string(&filename).unwrap(), "a,b\n1,2\n");
+        assert!(!std::path::Path::new(&backup).exists());
+
+        let mut updated = file.clone();
+        updated.rows[0][1] = "3".to_string();
+        updated
+            .write_atomic(&filename, &WriterOptions::default(), true)
+            .unwrap();
+        assert_eq!(fs::read_to_string(&filename).unwrap(), "a,b\n1,3\n");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "a,b\n1,2\n");
+
+        // a cancelled writing leaves the target untouched
+        let token = CancellationToken::new();
+        token.cancel();
+        let options = WriterOptions {
+            cancellation: Some(token),
+            ..WriterOptions::default()
+        };
+        assert!(file.write_atomic(&filename, &options, false).is_err());
+        assert_eq!(fs::read_to_string(&filename).unwrap(), "a,b\n1,3\n");
+        assert_eq!(
+            fs::read_dir(".")
+                .unwrap()
+                .filter(|e| e
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(".tmp"))
+                .count(),
+            0
+        );
+
+        // the threads writing the same file don't share their temporary file
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let filename = filename.clone();
+                let mut file = file.clone();
+                file.rows[0][1] = i.to_string();
+                std::thread::spawn(move || {
+                    file.write_atomic(&filename, &WriterOptions::default(), false)
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+        assert!(fs::read_to_string(&filename)
+            .unwrap()
+            .starts_with("a,b\n1,"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&filename, fs::Permissions::from_mode(0o640)).unwrap();
+            file.write_atomic(&filename, &WriterOptions::default(), false)
+                .unwrap();
+            let mode = fs::metadata(&filename).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+
+        fs::remove_file(&filename).unwrap();
+        fs::remove_file(&backup).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_file_locking() {
+        let filename = String::from("test_locking.csv");
+        fs::write(&filename, "counter\n1\n").unwrap();
+
+        let (mut file, mut lock) =
+            CSVFile::open_locked(&filename, &ReaderOptions::default()).unwrap();
+        let other = fs::File::open(&filename).unwrap();
+        assert!(other.try_lock().is_err());
+
+        // a process waiting for the lock reads the file written by the holder of the lock
+        let waiting = {
+            let filename = filename.clone();
+            std::thread::spawn(move || {
+                let (file, lock) =
+                    CSVFile::open_locked(&filename, &ReaderOptions::default()).unwrap();
+                lock.unlock().unwrap();
+                file.rows[0][0].clone()
+            })
+        };
+
+        file.rows[0][0] = "10".to_string();
+        file.write_locked(&mut lock, &WriterOptions::default())
+            .unwrap();
+        file.rows[0][0] = "2".to_string();
+        file.write_locked(&mut lock, &WriterOptions::default())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&filename).unwrap(), "counter\n2\n");
+        let other = fs::File::open(&filename).unwrap();
+        assert!(other.try_lock().is_err());
+
+        lock.unlock().unwrap();
+        assert_eq!(waiting.join().unwrap(), "2");
+        assert!(other.try_lock().is_ok());
+        other.unlock().unwrap();
+
+        fs::remove_file(&filename).unwrap();
+        assert!(CSVFile::open_locked(&filename, &ReaderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_final_line_break_round_trip() {
+        let options = ReaderOptions::default();
+        for text in [
+            "a,b,c\n1,,\n2,3,",
+            "a,b,c\n1,2,3\n",
+            "a,b,c\n\"x\ny\",2,",
+            "a,b",
+            "a,b\n",
+            "a,b,c\r\n1,,\r\n2,3,\r\n",
+            "a,b\r\n1,2",
+            "a,b\r\n1,2\n",
+            "\u{feff}a,b\r\n\"x\r\ny\",2\r\n",
+        ] {
+            let file = CSVFile::from_text(text, &options).unwrap();
+            assert_eq!(file.to_string(), text);
+        }
+
+        let mut file = CSVFile::from_text("a,b\n1,2", &options).unwrap();
+        file.add_row(&vec!["3".to_string(), "".to_string()])
+            .unwrap();
+        assert_eq!(file.to_string(), "a,b\n1,2\n3,");
+
+        let mut file = CSVFile::from_text("a,b\r\n1,2\r\n", &options).unwrap();
+        file.add_row(&vec!["3".to_string(), "".to_string()])
+            .unwrap();
+        assert_eq!(file.to_string(), "a,b\r\n1,2\r\n3,\r\n");
+
+        let file = CSVFile::build(&vec!["a".to_string()], &vec![], &',').unwrap();
+        assert_eq!(file.to_string(), "a\n");
+    }
+
+    #[test]
+    fn test_cell_lengths() {
+        let text = "id,comment\n1,\n22,été\n333,ok\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let lengths = file.max_cell_len_per_column();
+        assert_eq!(lengths.len(), 2);
+        assert_eq!(lengths["id"], 3);
+        assert_eq!(lengths["comment"], 3);
+        assert_eq!(
+            file.cells_exceeding(2),
+            vec![
+                CSVCoords { row: 1, column: 1 },
+                CSVCoords { row: 2, column: 0 }
+            ]
+        );
+
+        let empty = CSVFile::from_text("a\n", &ReaderOptions::default()).unwrap();
+        assert_eq!(empty.max_cell_len_per_column()["a"], 0);
+    }
+
+    #[test]
+    fn test_find_similar() {
+        let text = "first,last\nJohn,Smith\nJonh,Smiht\nJon,Smith\n,\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let found: Vec<(usize, usize, usize)> = file
+            .find_similar("John", 2)
+            .iter()
+            .map(|cell| (cell.coordinates.row, cell.coordinates.column, cell.distance))
+            .collect();
+        assert_eq!(found, vec![(0, 0, 0), (2, 0, 1), (1, 0, 2)]);
+
+        let found = file.find_similar("Smith", 0);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|cell| cell.similarity == 1.0));
+
+        // the empty cells are as far from the text as it is long
+        assert_eq!(file.find_similar("ab", 2).len(), 2);
+        assert_eq!(
+            file.find_similar("", 0)[0].coordinates,
+            CSVCoords { row: 3, column: 0 }
+        );
+    }
+
+    #[test]
+    fn test_column_aliases() {
+        let text = "CustID,customer id,total\n1,a,10\n2,b,20\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let customer_id = "customer_id".to_string();
+        assert!(!file.has_column(&customer_id));
+
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            customer_id.clone(),
+            vec![
+                "cust_id".to_string(),
+                "customer id".to_string(),
+                "CustID".to_string(),
+            ],
+        );
+        file.set_column_aliases(&aliases);
+        assert_eq!(file.column_aliases(), &aliases);
+
+        // the first variant found in the header wins
+        assert_eq!(file.get_column_idx(&customer_id), Some(1));
+        assert_eq!(
+            file.resolve_column(&customer_id),
+            Some(&"customer id".to_string())
+        );
+        assert_eq!(file.query("customer_id = 'b'").unwrap(), vec![1]);
+
+        file.fill_column(&customer_id, &vec!["x".to_string(), "y".to_string()])
+            .unwrap();
+        assert_eq!(file.rows[1], vec!["2", "y", "20"]);
+
+        file.set_column_meta(&customer_id, &"type".to_string(), &"id".to_string())
+            .unwrap();
+        assert_eq!(
+            file.get_column_meta(&"customer id".to_string(), &"type".to_string()),
+            Some(&"id".to_string())
+        );
+
+        file.add_validator(Validator::column(&customer_id, "not empty", |v| {
+            !v.is_empty()
+        }))
+        .unwrap();
+        assert_eq!(
+            file.validators()[0].column_name(),
+            Some(&"customer id".to_string())
+        );
+        assert!(file
+            .add_row(&vec!["3".to_string(), "".to_string(), "30".to_string()])
+            .is_err());
+
+        // a column actually named like the canonical name is preferred
+        file.add_column(&"cust".to_string()).unwrap();
+        aliases.insert("CustID".to_string(), vec!["cust".to_string()]);
+        file.set_column_aliases(&aliases);
+        assert_eq!(file.get_column_idx(&"CustID".to_string()), Some(0));
+        assert!(file.query("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn test_multi_header() {
+        let text = ",Sales,,\"Costs\",\nid,Q1,Q2,Q1,\"Q2\"\n1,10,20,5,6\n";
+        let options = ReaderOptions {
+            multi_header: Some(".".to_string()),
+            lossless: true,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert_eq!(
+            file.columns,
+            vec!["id", "Sales.Q1", "Sales.Q2", "Costs.Q1", "Costs.Q2"]
+        );
+        assert_eq!(file.rows, vec![vec!["1", "10", "20", "5", "6"]]);
+        assert_eq!(file.read_stats().unwrap().bytes, text.len() as u64);
+
+        let writer_options = WriterOptions {
+            multi_header: Some(".".to_string()),
+            ..WriterOptions::default()
+        };
+        assert_eq!(
+            file.to_string_with_options(&writer_options),
+            ",Sales,,Costs,\nid,Q1,Q2,Q1,Q2\n1,10,20,5,6\n"
+        );
+        assert_eq!(
+            file.to_string(),
+            "id,Sales.Q1,Sales.Q2,Costs.Q1,Costs.Q2\n1,10,20,5,6\n"
+        );
+
+        // the second row of the header is missing
+        assert!(CSVFile::from_text(",Sales\n", &options).is_err());
+    }
+
+    #[test]
+    fn test_skip_columns() {
+        let text = "time,t_1,\"t,2\",debug\n1,a,\"b,c\",x\n2,d,e,y\n";
+        let options = ReaderOptions::default().skip_columns(|name| name == "debug");
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert_eq!(file.columns, vec!["time", "t_1", "t,2"]);
+        assert_eq!(file.rows, vec![vec!["1", "a", "b,c"], vec!["2", "d", "e"]]);
+
+        // combined with a projection, keeping its order
+        let options = ReaderOptions {
+            projection: Some(vec![
+                "debug".to_string(),
+                "t_1".to_string(),
+                "time".to_string(),
+            ]),
+            ..ReaderOptions::default().skip_columns(|name| name.starts_with("t_"))
+        };
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert_eq!(file.columns, vec!["debug", "time"]);
+        assert_eq!(file.rows, vec![vec!["x", "1"], vec!["y", "2"]]);
+
+        let options = ReaderOptions::default().skip_columns(|_| true);
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert!(file.has_no_columns());
+    }
+
+    #[test]
+    fn test_schema_on_read() {
+        let schema = CSVSchema::default()
+            .with_column("id", ColumnType::Integer, true)
+            .with_column("price", ColumnType::Number, false)
+            .with_column("active", ColumnType::Boolean, false)
+            .with_column("since", ColumnType::Date, false);
+
+        let text = "since,id,price,active\n2024-02-29,+7,1.5,YES\n, 8 ,  ,0\n";
+        let file = CSVFile::from_text_with_schema(text, &schema).unwrap();
+        assert_eq!(file.columns, vec!["since", "id", "price", "active"]);
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["2024-02-29", "7", "1.5", "true"],
+                vec!["", "8", "", "false"]
+            ]
+        );
+
+        let text = "id,price,active,since\nx,1,true,2023-02-29\n,1,2,2024-01-01\n1,2\n";
+        let error = CSVFile::from_text_with_schema(text, &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The value \"x\" of the column id in the row of index 0 isn't an integer"
+        );
+
+        let schema = schema.with_policy(SchemaPolicy::CollectErrors);
+        let error = CSVFile::from_text_with_schema(text, &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The file doesn't match the schema:\n\
+            The value \"x\" of the column id in the row of index 0 isn't an integer\n\
+            The value \"2023-02-29\" of the column since in the row of index 0 isn't a date\n\
+            The column id is required, but the row of index 1 has no value\n\
+            The value \"2\" of the column active in the row of index 1 isn't a boolean\n\
+            Invalid number of fields for row of index 2, 2 were given, but expected 4"
+        );
+
+        let error = CSVFile::from_text_with_schema("id,extra,id\n", &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The file doesn't match the schema:\n\
+            The column extra isn't in the schema\n\
+            The column id is duplicated\n\
+            The column price doesn't exist\n\
+            The column active doesn't exist\n\
+            The column since doesn't exist"
+        );
+    }
+
+    #[test]
+    fn test_schema_artifacts() {
+        let text = "id,score,active,note\n1,0.5,yes,\n2,1,no,\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let schema = file.infer_schema();
+        assert_eq!(
+            schema,
+            CSVSchema::default()
+                .with_column("id", ColumnType::Integer, true)
+                .with_column("score", ColumnType::Number, true)
+                .with_column("active", ColumnType::Boolean, true)
+                .with_column("note", ColumnType::Text, false)
+        );
+
+        assert_eq!(
+            schema.to_json_schema(),
+            r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "id": {
+      "type": "integer"
+    },
+    "score": {
+      "type": "number"
+    },
+    "active": {
+      "type": "boolean"
+    },
+    "note": {
+      "type": [
+        "string",
+        "null"
+      ]
+    }
+  },
+  "required": [
+    "id",
+    "score",
+    "active"
+  ],
+  "additionalProperties": false
+}"#
+        );
+
+        let schema = schema.with_column("since", ColumnType::Date, false);
+        let table = "scores".to_string();
+        assert_eq!(
+            schema.to_sql_ddl(&table, SqlDialect::MySQL),
+            "CREATE TABLE `scores` (\n  `id` BIGINT NOT NULL,\n  `score` DOUBLE NOT NULL,\n  \
+            `active` BOOLEAN NOT NULL,\n  `note` TEXT,\n  `since` DATE\n);\n"
+        );
+        assert_eq!(
+            schema.to_sql_ddl(&table, SqlDialect::SQLite),
+            "CREATE TABLE \"scores\" (\n  \"id\" INTEGER NOT NULL,\n  \"score\" REAL NOT NULL,\n  \
+            \"active\" BOOLEAN NOT NULL,\n  \"note\" TEXT,\n  \"since\" TEXT\n);\n"
+        );
+    }
+
+    #[test]
+    fn test_table_schema() {
+        let text = r#"{
+            "fields": [
+                {"name": "id", "type": "integer", "constraints": {"required": true}},
+                {"name": "at", "type": "datetime"},
+                {"name": "ok", "type": "boolean", "constraints": {"required": false}}
+            ]
+        }"#;
+        let schema = CSVSchema::from_table_schema(text).unwrap();
+        assert_eq!(
+            schema,
+            CSVSchema::default()
+                .with_column("id", ColumnType::Integer, true)
+                .with_column("at", ColumnType::Text, false)
+                .with_column("ok", ColumnType::Boolean, false)
+        );
+        assert_eq!(
+            CSVSchema::from_table_schema(&schema.to_table_schema()).unwrap(),
+            schema
+        );
+        assert!(CSVSchema::from_table_schema("{\"fields\": [{}]}").is_err());
+        assert!(CSVSchema::from_data_package("{\"resources\": []}").is_err());
+
+        let package = r#"{"resources": [{"path": "a.csv", "dialect": {"delimiter": ";;"}, "schema": {"fields": []}}]}"#;
+        assert!(CSVSchema::from_data_package(package).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_data_package() {
+        let directory = "test_data_package";
+        fs::create_dir_all(directory).unwrap();
+        let text = "id;Full Name;born\n1;Thomas;2005-01-01\n2;;2001-12-31\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::with_delimiter(&';')).unwrap();
+        file.write_with_data_package(&format!("{}/My People.csv", directory))
+            .unwrap();
+
+        let descriptor = format!("{}/datapackage.json", directory);
+        let (schema, path) =
+            CSVSchema::from_data_package(&fs::read_to_string(&descriptor).unwrap()).unwrap();
+        assert_eq!(path, "My People.csv");
+        assert_eq!(schema, file.infer_schema());
+        assert!(fs::read_to_string(&descriptor)
+            .unwrap()
+            .contains("\"name\": \"my-people\""));
+
+        let read = CSVFile::new_from_data_package(&descriptor).unwrap();
+        assert_eq!(read.columns, file.columns);
+        assert_eq!(read.rows, file.rows);
+
+        fs::write(
+            format!("{}/My People.csv", directory),
+            "id;Full Name;born\nx;;\n",
+        )
+        .unwrap();
+        assert!(CSVFile::new_from_data_package(&descriptor).is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_lint() {
+        let text = " id,\"name\",note\n1,\"Thomas\",\"a\nb\"\n2,John,x;y\n3,\"Marie\",\"x;y\"\nfour,Paul, \n";
+        let warnings = CSVFile::lint(text, &LintOptions::default()).unwrap();
+        let found: Vec<(LintRule, usize, Option<usize>)> = warnings
+            .iter()
+            .map(|w| (w.rule, w.line, w.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (LintRule::TrailingWhitespace, 1, Some(0)),
+                (LintRule::InconsistentQuoting, 4, Some(1)),
+                (LintRule::SuspiciousDelimiter, 4, Some(2)),
+                (LintRule::InconsistentQuoting, 5, Some(2)),
+                (LintRule::TrailingWhitespace, 6, Some(2)),
+                (LintRule::MixedTypes, 6, Some(0)),
+            ]
+        );
+        assert_eq!(
+            warnings[5].to_string(),
+            "Warning at line 6, column 0: The value \"four\" isn't a number, unlike most values of the column  id"
+        );
+
+        let options = LintOptions {
+            delimiter: ';',
+            ..LintOptions::default()
+        };
+        let warnings = CSVFile::lint("a;b\n1,5;2\r\n", &options).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].rule, LintRule::MixedLineEndings);
+        assert_eq!(warnings[1].rule, LintRule::SuspiciousDelimiter);
+
+        assert!(CSVFile::lint("a\n\"b\n", &LintOptions::default()).is_err());
+        assert!(CSVFile::lint("a,b\n1,2\n", &LintOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_repair() {
+        let text = "id;comment;total\r\n1;\"fine\";10\r\n\r\n2;\"two\nlines\";20\r\n3;broken\nin;30\n4;a;b;40\n5\n";
+        let options = RepairOptions {
+            delimiter: ';',
+            ..RepairOptions::default()
+        };
+        let (file, report) = CSVFile::repair(text, &options).unwrap();
+        assert_eq!(file.delimiter, ';');
+        assert_eq!(file.columns, vec!["id", "comment", "total"]);
+        assert_eq!(file.rows.len(), 5);
+        assert_eq!(file.rows[1], vec!["2", "two\nlines", "20"]);
+        assert_eq!(file.rows[2], vec!["3", "broken in", "30"]);
+        assert_eq!(file.rows[3], vec!["4", "a;b", "40"]);
+        assert_eq!(file.rows[4], vec!["5", "", ""]);
+        assert_eq!(
+            report.repairs,
+            vec![
+                Repair {
+                    line: 3,
+                    kind: RepairKind::RemovedBlankLine
+                },
+                Repair {
+                    line: 6,
+                    kind: RepairKind::JoinedLines { lines: 2 }
+                },
+                Repair {
+                    line: 8,
+                    kind: RepairKind::MergedFields {
+                        column: 1,
+                        fields: 2
+                    }
+                },
+                Repair {
+                    line: 9,
+                    kind: RepairKind::PaddedRow { missing: 2 }
+                },
+            ]
+        );
+
+        let (_, report) = CSVFile::repair("a,b\n1,2\n", &RepairOptions::default()).unwrap();
+        assert!(report.is_clean());
+        assert!(CSVFile::repair("", &RepairOptions::default()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_write_with_manifest() {
+        let filename = String::from("test_manifest.csv");
+        let file = CSVFile::from_text(
+            "id;name;age\n1;Thomas;19\n2;John;28\n",
+            &ReaderOptions {
+                delimiter: ';',
+                ..ReaderOptions::default()
+            },
+        )
+        .unwrap();
+        file.write_with_manifest(&filename).unwrap();
+        let manifest = fs::read_to_string("test_manifest.csv.manifest.json").unwrap();
+        assert!(manifest.contains("\"rows\": 2"));
+        assert!(CSVFile::verify_manifest(&filename).unwrap().is_valid());
+
+        fs::write(
+            &filename,
+            "id;name;city\n1;Thomas;19\n2;John;28\n3;Marie;22\n",
+        )
+        .unwrap();
+        let report = CSVFile::verify_manifest(&filename).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.expected_rows, 2);
+        assert_eq!(report.rows, 3);
+        assert_eq!(report.changed_columns, vec!["id", "name"]);
+        assert_eq!(report.missing_columns, vec!["age"]);
+        assert_eq!(report.extra_columns, vec!["city"]);
+
+        fs::remove_file("test_manifest.csv.manifest.json").unwrap();
+        assert!(CSVFile::verify_manifest(&filename).is_err());
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_render_rows() {
+        let mut file = CSVFile::from_text(
+            "first name,email\nThomas,thomas@example.com\nJohn,\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            file.render_rows("To: {email} ({first name})").unwrap(),
+            vec!["To: thomas@example.com (Thomas)", "To:  (John)"]
+        );
+        assert_eq!(
+            file.render_rows("no placeholder").unwrap()[1],
+            "no placeholder"
+        );
+
+        let mut aliases = HashMap::new();
+        aliases.insert("name".to_string(), vec!["first name".to_string()]);
+        file.set_column_aliases(&aliases);
+        assert_eq!(file.render_rows("{name}.txt").unwrap()[0], "Thomas.txt");
+
+        assert!(file.render_rows("{name").is_err());
+        assert!(file.render_rows("{age}").is_err());
+
+        let ragged = CSVFile::from_text("name,email\nThomas\n", &ReaderOptions::default()).unwrap();
+        assert_eq!(
+            ragged.render_rows("{name} <{email}>").unwrap(),
+            vec!["Thomas <>"]
+        );
+    }
+
+    #[test]
+    fn test_value_counts_and_crosstab() {
+        let text =
+            "name,team,level\nThomas,red,A\nJohn,blue,B\nMarie,red,\nPaul,red,A\nLuc,blue,A\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let counts = file.value_counts(&"level".to_string()).unwrap();
+        assert_eq!(counts.delimiter, ',');
+        assert_eq!(
+            counts.rows,
+            vec![vec!["A", "3"], vec!["B", "1"], vec!["", "1"]]
+        );
+        assert!(file.value_counts(&"age".to_string()).is_err());
+
+        let table = file
+            .crosstab(&"team".to_string(), &"level".to_string())
+            .unwrap();
+        assert_eq!(table.columns, vec!["team", "A", "B", ""]);
+        assert_eq!(
+            table.rows,
+            vec![vec!["red", "2", "0", "1"], vec!["blue", "1", "1", "0"]]
+        );
+        assert!(file
+            .crosstab(&"team".to_string(), &"age".to_string())
+            .is_err());
+
+        let empty = CSVFile::from_text("a,b\n", &ReaderOptions::default()).unwrap();
+        let table = empty.crosstab(&"a".to_string(), &"b".to_string()).unwrap();
+        assert_eq!(table.columns, vec!["a"]);
+        assert!(table.rows.is_empty());
+
+        let ragged = CSVFile::from_text("a,b\nx\nx,y\n", &ReaderOptions::default()).unwrap();
+        let counts = ragged.value_counts(&"b".to_string()).unwrap();
+        assert_eq!(counts.rows, vec![vec!["", "1"], vec!["y", "1"]]);
+        let table = ragged.crosstab(&"a".to_string(), &"b".to_string()).unwrap();
+        assert_eq!(table.columns, vec!["a", "", "y"]);
+        assert_eq!(table.rows, vec![vec!["x", "1", "1"]]);
+    }
+
+    #[test]
+    fn test_quantiles_and_histogram() {
+        let text = "value,label\n5,a\n-1,b\n,c\n2.5,d\n10,e\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let value = "value".to_string();
+
+        assert_eq!(
+            file.quantiles(&value, &[0.0, 0.5, 1.0, 2.0]).unwrap(),
+            vec![-1.0, 3.75, 10.0, 10.0]
+        );
+        assert!(file.quantiles(&"label".to_string(), &[0.5]).is_none());
+        assert!(file.quantiles(&"missing".to_string(), &[0.5]).is_none());
+
+        let buckets = file.histogram(&value, 3).unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].start, -1.0);
+        assert_eq!(buckets[2].end, 10.0);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 1, 1]);
+        assert!(file.histogram(&value, 0).is_none());
+        assert!(file.histogram(&"label".to_string(), 3).is_none());
+
+        let constant = CSVFile::from_text("n\n7\n7\n", &ReaderOptions::default()).unwrap();
+        let buckets = constant.histogram(&"n".to_string(), 2).unwrap();
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[1].count, 0);
+    }
+
+    #[test]
+    fn test_find_outliers() {
+        let text = "reading\n10\n11\n9\n10\n12\n-40\nerror\n10\n11\n9\n10\n250\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let reading = "reading".to_string();
+
+        let outliers = file
+            .find_outliers(&reading, OutlierMethod::Iqr { factor: 1.5 })
+            .unwrap();
+        let rows: Vec<usize> = outliers.iter().map(|o| o.row).collect();
+        assert_eq!(rows, vec![5, 11]);
+        assert_eq!(outliers[0].value, -40.0);
+
+        let outliers = file
+            .find_outliers(&reading, OutlierMethod::ZScore { threshold: 2.0 })
+            .unwrap();
+        assert_eq!(
+            outliers,
+            vec![Outlier {
+                row: 11,
+                value: 250.0
+            }]
+        );
+
+        let constant = CSVFile::from_text("n\n1\n1\n", &ReaderOptions::default()).unwrap();
+        assert!(constant
+            .find_outliers(&"n".to_string(), OutlierMethod::ZScore { threshold: 3.0 })
+            .unwrap()
+            .is_empty());
+        assert!(file
+            .find_outliers(&"missing".to_string(), OutlierMethod::default())
+            .is_none());
+    }
+
+    #[test]
+    fn test_correlations() {
+        let text = "x,label,down,flat,partial\n1,a,10,5,\n2,b,8,5,3\n3,c,6,5,1\n4,d,4,5,\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let matrix = file.correlations();
+        assert_eq!(
+            matrix.columns,
+            vec!["column", "x", "down", "flat", "partial"]
+        );
+        assert_eq!(matrix.rows.len(), 4);
+        assert_eq!(matrix.rows[0], vec!["x", "1", "-1", "", "-1"]);
+        assert_eq!(matrix.rows[2], vec!["flat", "", "", "", ""]);
+        assert_eq!(matrix.rows[3][4], "1");
+
+        let text_only = CSVFile::from_text("a\nb\n", &ReaderOptions::default()).unwrap();
+        assert_eq!(text_only.correlations().columns, vec!["column"]);
+    }
+
+    #[test]
+    fn test_can_coerce() {
+        let text = "value\n1\nYES\n0\nmaybe\n \nno\n2\n3\n4\n5\n6\n7\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let value = "value".to_string();
+
+        let report = file.can_coerce(&value, ColumnType::Boolean).unwrap();
+        assert_eq!(report.column_type, ColumnType::Boolean);
+        assert_eq!(report.convertible, 4);
+        assert_eq!(report.changed, 4);
+        assert_eq!(report.empty, 1);
+        assert_eq!(report.failed, 7);
+        assert_eq!(report.examples.len(), 5);
+        assert_eq!(
+            report.examples[0],
+            CoercionFailure {
+                row: 3,
+                value: "maybe".to_string()
+            }
+        );
+        // Nothing was modified.
+        assert_eq!(file.rows[1][0], "YES");
+
+        let report = file.can_coerce(&value, ColumnType::Text).unwrap();
+        assert!(report.is_convertible());
+        assert_eq!(report.changed, 0);
+        assert!(file
+            .can_coerce(&"age".to_string(), ColumnType::Text)
+            .is_none());
+    }
+
+    #[test]
+    fn test_typed_column() {
+        let text = "value\n-7\n1e3\nFalse\n2024-02-29\n2024-02-30\n\n abc \n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let values = file.typed_column(&"value".to_string()).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                CsvValue::Int(-7),
+                CsvValue::Float(1000.0),
+                CsvValue::Bool(false),
+                CsvValue::Date(parse_timestamp("2024-02-29").unwrap()),
+                CsvValue::Text("2024-02-30".to_string()),
+                CsvValue::Null,
+                CsvValue::Text(" abc ".to_string()),
+            ]
+        );
+        let formatted: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            formatted,
+            vec![
+                "-7",
+                "1000",
+                "false",
+                "2024-02-29",
+                "2024-02-30",
+                "",
+                " abc "
+            ]
+        );
+        assert_eq!(values[0].as_f64(), Some(-7.0));
+        assert_eq!(values[1].as_i64(), None);
+        assert_eq!(values[3].column_type(), Some(ColumnType::Date));
+        assert!(values[5].is_null());
+        assert!(file.typed_column(&"missing".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_null_values() {
+        let options = ReaderOptions {
+            null_values: vec!["NA".to_string(), "N/A".to_string(), "-".to_string()],
+            trim: Trim::Fields,
+            ..ReaderOptions::default()
+        };
+        let text = "name,age\nThomas, NA\nJohn,-\nNA,28\nMarie,na\n";
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert_eq!(file.rows[0], vec!["Thomas", ""]);
+        assert_eq!(file.rows[1], vec!["John", ""]);
+        assert_eq!(file.rows[2], vec!["", "28"]);
+        assert_eq!(file.rows[3], vec!["Marie", "na"]);
+
+        let age = "age".to_string();
+        assert_eq!(file.column_stats(&age).unwrap().empty, 2);
+        assert_eq!(file.typed_column(&age).unwrap()[1], CsvValue::Null);
+
+        let writer_options = WriterOptions {
+            null_value: "NULL".to_string(),
+            ..WriterOptions::default()
+        };
+        let mut written: Vec<u8> = Vec::new();
+        file.write_to(&mut written, &writer_options).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "name,age\nThomas,NULL\nJohn,NULL\nNULL,28\nMarie,na\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_units_and_parse_currency() {
+        let text = "amount,distance\n\"\u{a3}1,000,000\",12 km\n1'234.5 CHF,km 3\n-.5,4 m\n\"12\u{a0}345,67\",\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        file.strip_units(&"distance".to_string(), &["km"]).unwrap();
+        let distances: Vec<&String> = file.rows.iter().map(|row| &row[1]).collect();
+        assert_eq!(distances, vec!["12", "3", "4 m", ""]);
+
+        file.parse_currency(&"amount".to_string()).unwrap();
+        let amounts: Vec<&String> = file.rows.iter().map(|row| &row[0]).collect();
+        assert_eq!(amounts, vec!["1000000", "1234.5", "-0.5", "12345.67"]);
+
+        file.rows[0][1] = "12 x 3".to_string();
+        assert!(file.parse_currency(&"distance".to_string()).is_err());
+        assert_eq!(file.rows[0][1], "12 x 3");
+        assert!(file.strip_units(&"missing".to_string(), &["$"]).is_err());
+
+        let mut ragged = CSVFile::from_text(
+            "amount,distance
+$3
+$4,5 km
+",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        ragged
+            .strip_units(&"distance".to_string(), &["km"])
+            .unwrap();
+        ragged.parse_currency(&"distance".to_string()).unwrap();
+        assert_eq!(ragged.rows, vec![vec!["$3", ""], vec!["$4", "5"]]);
+    }
+
+    #[test]
+    fn test_add_hash_column() {
+        let text = "a,b,c\nab,c,x\na,bc,x\nab,c,y\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let key = "key".to_string();
+
+        file.add_hash_column(&key, HashAlgorithm::Fnv1a, &["a", "b"])
+            .unwrap();
+        assert_eq!(file.rows[0][3].len(), 16);
+        assert_ne!(file.rows[0][3], file.rows[1][3]);
+        assert_eq!(file.rows[0][3], file.rows[2][3]);
+        assert!(file
+            .add_hash_column(&key, HashAlgorithm::Fnv1a, &["a"])
+            .is_err());
+
+        let all = "all".to_string();
+        file.add_hash_column(&all, HashAlgorithm::Sha256, &[])
+            .unwrap();
+        assert_ne!(file.rows[0][4], file.rows[2][4]);
+        assert!(file
+            .add_hash_column(&"other".to_string(), HashAlgorithm::Sha256, &["d"])
+            .is_err());
+        assert_eq!(file.columns.len(), 5);
+
+        // a missing value is hashed as an empty one, and the extra values are kept
+        let mut ragged =
+            CSVFile::from_text("a,b\nx\nx,\nx,y,z\n", &ReaderOptions::default()).unwrap();
+        ragged
+            .add_hash_column(&key, HashAlgorithm::Fnv1a, &[])
+            .unwrap();
+        assert_eq!(ragged.rows[0][2], ragged.rows[1][2]);
+        assert_eq!(ragged.rows[2].len(), 4);
+        assert_eq!(ragged.rows[2][3], "z");
+    }
+
+    #[test]
+    fn test_add_id_column() {
+        let mut file = CSVFile::from_text("id,name\n,Thomas\n", &ReaderOptions::default()).unwrap();
+        assert!(file.add_id_column(&"id".to_string(), 1).is_err());
+        file.add_id_column(&"row".to_string(), 0).unwrap();
+        assert_eq!(file.columns, vec!["row", "id", "name"]);
+        assert_eq!(file.rows[0], vec!["0", "", "Thomas"]);
+
+        let mut empty = CSVFile::from_text("name\n", &ReaderOptions::default()).unwrap();
+        empty.add_id_column(&"id".to_string(), 100).unwrap();
+        assert_eq!(empty.columns, vec!["id", "name"]);
+        assert!(empty.rows.is_empty());
+
+        // The column is removed when a validator rejects the ids.
+        let mut validated =
+            CSVFile::from_text("name\nThomas\n", &ReaderOptions::default()).unwrap();
+        validated
+            .add_validator(Validator::row("no row starts with 1", |row| row[0] != "1"))
+            .unwrap();
+        assert!(validated.add_id_column(&"id".to_string(), 1).is_err());
+        assert_eq!(validated.columns, vec!["name"]);
+        assert_eq!(validated.rows, vec![vec!["Thomas"]]);
+        validated.add_id_column(&"id".to_string(), 2).unwrap();
+        assert_eq!(validated.rows, vec![vec!["2", "Thomas"]]);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn test_add_uuid_column() {
+        let mut file = CSVFile::from_text("name\na\nb\nc\n", &ReaderOptions::default()).unwrap();
+        file.add_uuid_column(&"uuid".to_string()).unwrap();
+        let mut ids: Vec<&String> = file.rows.iter().map(|row| &row[0]).collect();
+        for id in &ids {
+            let groups: Vec<usize> = id.split('-').map(str::len).collect();
+            assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+            assert_eq!(&id[14..15], "4");
+            assert!("89ab".contains(&id[19..20]));
+        }
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+
+        let mut counter = 0u128;
+        file.add_uuid_column_with(&"key".to_string(), || {
+            counter += 1;
+            counter
+        })
+        .unwrap();
+        assert_eq!(file.rows[0][0], "00000000-0000-4000-8000-000000000001");
+        assert_eq!(file.rows[2][0], "00000000-0000-4000-8000-000000000003");
+    }
+
+    #[test]
+    fn test_reverse_and_shuffle_rows() {
+        let options = ReaderOptions {
+            preserve_quotes: true,
+            lossless: true,
+            ..ReaderOptions::default()
+        };
+        let text = "n,label\n1,\"a\"\n2, b\n3,c\n";
+        let mut file = CSVFile::from_text(text, &options).unwrap();
+        file.reverse_rows();
+        assert_eq!(file.rows[0], vec!["3", "c"]);
+        assert!(file.is_quoted(&CSVCoords { row: 2, column: 1 }));
+        assert_eq!(
+            file.to_string_with_options(&WriterOptions::default()),
+            "n,label\n3,c\n2, b\n1,\"a\"\n"
+        );
+
+        let mut text = String::from("n\n");
+        for i in 0..100 {
+            text.push_str(&format!("{}\n", i));
+        }
+        let original = CSVFile::from_text(&text, &ReaderOptions::default()).unwrap();
+        let mut shuffled = original.clone();
+        shuffled.shuffle_rows(7);
+        assert_ne!(shuffled.rows, original.rows);
+        let mut other = original.clone();
+        other.shuffle_rows(8);
+        assert_ne!(shuffled.rows, other.rows);
+        shuffled
+            .rows
+            .sort_by_key(|row| row[0].parse::<usize>().unwrap());
+        assert_eq!(shuffled.rows, original.rows);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_sort_file() {
+        let input = String::from("test_sort_input.csv");
+        let output = String::from("test_sort_output.csv");
+        let mut text = String::from("id;key;label\n");
+        for i in 0..1000 {
+            text.push_str(&format!("{};{};\"value; {}\"\n", i, (i * 7919) % 100, i));
+        }
+        fs::write(&input, &text).unwrap();
+        let options = ExternalSortOptions {
+            delimiter: ';',
+            chunk_rows: 64,
+            numeric: true,
+            descending: true,
+            ..ExternalSortOptions::default()
+        };
+        CSVFile::sort_file(&input, &output, &"key".to_string(), &options).unwrap();
+
+        let mut expected = CSVFile::new(&input, &';').unwrap().rows;
+        expected.sort_by_key(|row| std::cmp::Reverse(row[1].parse::<u32>().unwrap()));
+        let sorted = CSVFile::new(&output, &';').unwrap();
+        assert_eq!(sorted.columns, vec!["id", "key", "label"]);
+        assert_eq!(sorted.rows, expected);
+
+        // No temporary file is left behind.
+        let leftovers = fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                let name = name.to_string_lossy();
+                name.starts_with(&format!("csv-tools-sort-{}-", std::process::id()))
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+
+        // The chunks are merged a few at a time, and the sort stays stable.
+        let merged_by_three = ExternalSortOptions {
+            merge_fan_in: 3,
+            ..options.clone()
+        };
+        CSVFile::sort_file(&input, &output, &"key".to_string(), &merged_by_three).unwrap();
+        assert_eq!(CSVFile::new(&output, &';').unwrap().rows, expected);
+
+        assert!(CSVFile::sort_file(&input, &output, &"missing".to_string(), &options).is_err());
+
+        // A row without a key is an error, not a panic.
+        fs::write(&input, "id;key\n1;b\n2\n3;a\n").unwrap();
+        let error = CSVFile::sort_file(&input, &output, &"key".to_string(), &options).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_join_files() {
+        let left = String::from("test_join_left.csv");
+        let right = String::from("test_join_right.csv");
+        let output = String::from("test_join_output.csv");
+        fs::write(&left, "id,key\n1,b\n2,a\n3,c\n4,b\n5,a\n").unwrap();
+        fs::write(&right, "key,value\nb,x\na,y\nb,z\nd,w\n").unwrap();
+        let options = ExternalSortOptions {
+            chunk_rows: 2,
+            ..ExternalSortOptions::default()
+        };
+        let key = String::from("key");
+
+        CSVFile::join_files(&left, &right, &output, &key, JoinKind::Inner, &options).unwrap();
+        let joined = CSVFile::new(&output, &',').unwrap();
+        assert_eq!(joined.columns, vec!["id", "key", "value"]);
+        assert_eq!(
+            joined.rows,
+            vec![
+                vec!["2", "a", "y"],
+                vec!["5", "a", "y"],
+                vec!["1", "b", "x"],
+                vec!["1", "b", "z"],
+                vec!["4", "b", "x"],
+                vec!["4", "b", "z"],
+            ]
+        );
+
+        CSVFile::join_files(&left, &right, &output, &key, JoinKind::Left, &options).unwrap();
+        let joined = CSVFile::new(&output, &',').unwrap();
+        assert_eq!(joined.rows.len(), 7);
+        assert_eq!(joined.rows[6], vec!["3", "c", ""]);
+
+        // Both files have an `id` column.
+        fs::write(&right, "key,id\na,1\n").unwrap();
+        assert!(
+            CSVFile::join_files(&left, &right, &output, &key, JoinKind::Inner, &options).is_err()
+        );
+        assert!(CSVFile::join_files(
+            &left,
+            &right,
+            &output,
+            &"missing".to_string(),
+            JoinKind::Inner,
+            &options
+        )
+        .is_err());
+
+        // The ragged rows are completed, but a row without a key is an error.
+        fs::write(&left, "id,key,note\n1,a\n2,b,x\n").unwrap();
+        fs::write(&right, "key,label,size\na,A\nb\n").unwrap();
+        CSVFile::join_files(&left, &right, &output, &key, JoinKind::Inner, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "id,key,note,label,size\n1,a,,A,\n2,b,x,,\n"
+        );
+        fs::write(&right, "label,key\nA,a\nB\n").unwrap();
+        let error = CSVFile::join_files(&left, &right, &output, &key, JoinKind::Inner, &options)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        fs::remove_file(&left).unwrap();
+        fs::remove_file(&right).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_stream_group_by() {
+        let path = String::from("test_stream_group_by.csv");
+        fs::write(&path, "shop;amount\na;1\nb;2\na;\na;7\nb;-3\nc;x\n").unwrap();
+        let options = ReaderOptions {
+            delimiter: ';',
+            ..ReaderOptions::default()
+        };
+        let aggregations = [
+            ("amount", Aggregation::Count),
+            ("amount", Aggregation::Mean),
+            ("amount", Aggregation::Min),
+            ("amount", Aggregation::First),
+            ("amount", Aggregation::Last),
+        ];
+        let summary =
+            CSVFile::stream_group_by(&path, &"shop".to_string(), &aggregations, &options).unwrap();
+
+        // Same results as the aggregations of the whole file in memory.
+        let file = CSVFile::new(&path, &';').unwrap();
+        for (i, (_, aggregation)) in aggregations.iter().enumerate() {
+            for row in &summary.rows {
+                let numbers: Vec<f64> = file
+                    .rows
+                    .iter()
+                    .filter(|r| r[0] == row[0])
+                    .filter_map(|r| r[1].parse().ok())
+                    .collect();
+                let expected = aggregation
+                    .apply(&numbers)
+                    .map(|n| n.to_string())
+                    .unwrap_or_default();
+                assert_eq!(row[i + 2], expected);
+            }
+        }
+        let counts: Vec<&String> = summary.rows.iter().map(|row| &row[1]).collect();
+        assert_eq!(counts, vec!["3", "2", "1"]);
+        assert_eq!(summary.rows[2], vec!["c", "1", "0", "", "", "", ""]);
+
+        let key = "shop".to_string();
+        let duplicated = [("amount", Aggregation::Sum), ("amount", Aggregation::Sum)];
+        assert!(CSVFile::stream_group_by(&path, &key, &duplicated, &options).is_err());
+        let missing = [("price", Aggregation::Sum)];
+        assert!(CSVFile::stream_group_by(&path, &key, &missing, &options).is_err());
+
+        // A key column named `count` would give two columns of the same name.
+        fs::write(&path, "count;amount\n1;2\n").unwrap();
+        let sum = [("amount", Aggregation::Sum)];
+        assert!(CSVFile::stream_group_by(&path, &"count".to_string(), &sum, &options).is_err());
+
+        // A row too short to have a used value is an error, not a panic.
+        fs::write(&path, "shop;amount;note\na;1\nb\n").unwrap();
+        let summary = CSVFile::stream_group_by(&path, &key, &[], &options).unwrap();
+        assert_eq!(summary.rows, vec![vec!["a", "1"], vec!["b", "1"]]);
+        let error = CSVFile::stream_group_by(&path, &key, &sum, &options).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_positions() {
+        let text = "\u{feff}id;note\r\n1;\"a\r\nb\"\r\n2;c\r\n\r\n3;d";
+        let mut reader = CSVReader::from_reader(
+            text.as_bytes(),
+            &ReaderOptions {
+                delimiter: ';',
+                ..ReaderOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(reader.position(), None);
+
+        let records: Vec<Record> = reader.records().collect::<Result<_, _>>().unwrap();
+        let positions: Vec<Position> = records.iter().map(Record::position).collect();
+        assert_eq!(
+            positions.iter().map(|p| p.line).collect::<Vec<_>>(),
+            vec![2, 4, 5, 6]
+        );
+        // Seeking the offset of a row gives back its line.
+        for (record, position) in records.iter().zip(&positions) {
+            let rest = &text.as_bytes()[position.byte as usize..];
+            let first_value = record.fields()[0].as_bytes();
+            assert!(rest.starts_with(first_value));
+        }
+        assert_eq!(reader.position(), positions.last().copied());
+        assert_eq!(records[0].clone().into_fields(), vec!["1", "a\r\nb"]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_checkpoint_resume() {
+        let path = String::from("test_checkpoint.csv");
+        fs::write(&path, "id,note\n1,a\n2,\"b\nc\"\n3,d\n4,e\n").unwrap();
+        let options = ReaderOptions::default();
+
+        let mut reader = CSVReader::open(&path, &options).unwrap();
+        reader.next();
+        reader.next();
+        let checkpoint = reader.checkpoint();
+        assert_eq!((checkpoint.rows, checkpoint.line), (2, 5));
+        let expected: Vec<Vec<String>> = reader.collect::<Result<_, _>>().unwrap();
+
+        let saved = checkpoint.to_json();
+        let mut reader = CSVReader::open(&path, &options).unwrap();
+        reader
+            .resume(&Checkpoint::from_json(&saved).unwrap())
+            .unwrap();
+        let position = reader.records().next().unwrap().unwrap().position();
+        assert_eq!((position.line, position.byte), (5, checkpoint.byte));
+
+        let mut reader = CSVReader::open(&path, &options).unwrap();
+        reader.resume(&checkpoint).unwrap();
+        let rows: Vec<Vec<String>> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows, expected);
+        assert_eq!(reader.rows_read(), 4);
+
+        // The header of the file changed since the checkpoint.
+        fs::write(&path, "id,text\n1,a\n").unwrap();
+        let mut reader = CSVReader::open(&path, &options).unwrap();
+        assert!(reader.resume(&checkpoint).is_err());
+        assert!(Checkpoint::from_json("{\"byte\":-1}").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_follow() {
+        use std::io::Write;
+
+        let path = String::from("test_follow.csv");
+        fs::write(&path, "id;note\n1;a\n").unwrap();
+        let options = ReaderOptions {
+            delimiter: ';',
+            ..ReaderOptions::default()
+        };
+        let mut follower = CSVReader::follow(&path, &options)
+            .unwrap()
+            .with_poll_interval(std::time::Duration::from_millis(1));
+        assert_eq!(follower.columns(), &vec!["id", "note"]);
+        assert_eq!(follower.next().unwrap().unwrap(), vec!["1", "a"]);
+        assert_eq!(follower.try_next().unwrap(), None);
+
+        // A row is given once its quoted value and its line are complete.
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all("2;\"multi\nline".as_bytes()).unwrap();
+        assert_eq!(follower.try_next().unwrap(), None);
+        file.write_all("\"\n3;".as_bytes()).unwrap();
+        assert_eq!(follower.next().unwrap().unwrap(), vec!["2", "multi\nline"]);
+        assert_eq!(follower.try_next().unwrap(), None);
+        file.write_all("é\n".as_bytes()).unwrap();
+        assert_eq!(follower.next().unwrap().unwrap(), vec!["3", "é"]);
+
+        // The file got replaced by a shorter one.
+        fs::write(&path, "id;note\n4;d\n").unwrap();
+        assert_eq!(follower.next().unwrap().unwrap(), vec!["4", "d"]);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let options = ReaderOptions {
+            cancellation: Some(token),
+            ..options
+        };
+        let mut follower = CSVReader::follow(&path, &options).unwrap();
+        assert!(follower.next().unwrap().is_err());
+        assert!(follower.next().is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_update_column_on_disk() {
+        let filename = String::from("test_update_column_on_disk.csv");
+        let original = "\"id\";name;note\n1;Thomas;\"a;b\"\n2;John;\"multi\nline\"\n";
+        fs::write(&filename, original).unwrap();
+        let column = String::from("name");
+        let options = ReaderOptions::with_delimiter(&';');
+
+        let values = vec!["THOMAS".to_string(), "Jo\"hn".to_string()];
+        CSVFile::update_column_on_disk(&filename, &column, &values, &options).unwrap();
+        let file = CSVFile::new(&filename, &';').unwrap();
+        assert_eq!(file.columns, vec!["id", "name", "note"]);
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["1", "THOMAS", "a;b"],
+                vec!["2", "Jo\"hn", "multi\nline"]
+            ]
+        );
+        assert!(fs::read_to_string(&filename)
+            .unwrap()
+            .starts_with("\"id\";name;note\n"));
+
+        // Nothing changes when the update fails.
+        let content = fs::read_to_string(&filename).unwrap();
+        let too_few = vec!["A".to_string()];
+        assert!(CSVFile::update_column_on_disk(&filename, &column, &too_few, &options).is_err());
+        let missing = String::from("age");
+        assert!(CSVFile::update_column_on_disk(&filename, &missing, &values, &options).is_err());
+        assert_eq!(fs::read_to_string(&filename).unwrap(), content);
+        assert_eq!(
+            fs::read_dir(".")
+                .unwrap()
+                .filter(|entry| entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".test_update_column_on_disk"))
+                .count(),
+            0
+        );
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_update_column_on_disk_keeps_the_source_format() {
+        let filename = String::from("test_update_column_on_disk_format.csv");
+        let options = ReaderOptions {
+            escape: Escape::DoubleQuote,
+            ..ReaderOptions::default()
+        };
+        fs::write(
+            &filename,
+            "\u{feff}id,name,city\r\n1,\"Smith, \"\"J\"\"\",paris\r\n2,x,lyon",
+        )
+        .unwrap();
+        let values = vec!["Paris".to_string(), "Lyon".to_string()];
+        CSVFile::update_column_on_disk(&filename, &"city".to_string(), &values, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&filename).unwrap(),
+            "\u{feff}id,name,city\r\n1,\"Smith, \"\"J\"\"\",Paris\r\n2,x,Lyon"
+        );
+
+        // A row that doesn't have as many values as the header is an error.
+        let content = "id,name,city\n1,Thomas\n2,x,lyon\n";
+        fs::write(&filename, content).unwrap();
+        let error =
+            CSVFile::update_column_on_disk(&filename, &"city".to_string(), &values, &options)
+                .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(fs::read_to_string(&filename).unwrap(), content);
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_escape_field() {
+        let values = [
+            "plain",
+            "",
+            "a;b",
+            "say \"hi\"",
+            "back\\slash",
+            "multi\r\nline",
+            "=SUM(A1)",
+        ];
+        let columns = vec!["value".to_string()];
+        for escape in [Escape::Backslash, Escape::DoubleQuote] {
+            for quote_style in [QuoteStyle::Necessary, QuoteStyle::Always] {
+                let options = WriterOptions {
+                    delimiter: Some(';'),
+                    escape,
+                    quote_style,
+                    ..WriterOptions::default()
+                };
+                for value in values {
+                    // Same as the values written by the whole file.
+                    let rows = vec![vec![value.to_string()]];
+                    let file = CSVFile::build(&columns, &rows, &';').unwrap();
+                    let written = file.to_string_with_options(&options);
+                    let escaped = escape_field(value, &options);
+                    assert!(written.ends_with(&format!("\n{}\n", escaped)));
+                    assert_eq!(unescape_field(&escaped, &options).unwrap(), value);
+                }
+            }
+        }
+
+        let options = WriterOptions {
+            null_value: "NULL".to_string(),
+            escape_formulas: true,
+            ..WriterOptions::default()
+        };
+        assert_eq!(escape_field("", &options), "NULL");
+        assert_eq!(unescape_field("NULL", &options).unwrap(), "");
+        assert_eq!(unescape_field("\"NULL\"", &options).unwrap(), "NULL");
+        assert_eq!(escape_field("=1+1", &options), "'=1+1");
+        assert!(matches!(
+            escape_field("plain", &options),
+            std::borrow::Cow::Borrowed(_)
+        ));
+        assert!(unescape_field("\"a\",\"b\"", &options).is_err());
+    }
+
+    #[test]
+    fn test_incremental_parser() {
+        let cases = [
+            (
+                "id;note\r\n1;\"a\r\nb;\\\"c\\\"\"\r\n2;été\n\n3;\"\n\"\n4;last",
+                Escape::Backslash,
+            ),
+            (
+                "id;note\r\n1;\"a\r\nb;\"\"c\"\"\"\r\n2;é\"t\"é\n\n3;\"\n\"\"\n\"\n4;\"\"\n5;last",
+                Escape::DoubleQuote,
+            ),
+        ];
+        for (text, escape) in cases {
+            let options = ReaderOptions {
+                delimiter: ';',
+                escape,
+                ..ReaderOptions::default()
+            };
+            let mut reader = CSVReader::from_reader(text.as_bytes(), &options).unwrap();
+            let expected: Vec<Record> = reader.records().collect::<Result<_, _>>().unwrap();
+
+            // Whatever the size of the chunks, the records are the ones of the reader.
+            for size in 1..text.len() {
+                let mut parser = Parser::new(parser::LineOptions {
+                    delimiter: ';',
+                    escape,
+                    ..parser::LineOptions::default()
+                });
+                let mut records = Vec::new();
+                for chunk in text.as_bytes().chunks(size) {
+                    records.extend(parser.feed(chunk).unwrap());
+                }
+                records.extend(parser.finish().unwrap());
+                assert_eq!(records[0].fields(), &vec!["id", "note"]);
+                assert_eq!(records[1..], expected[..]);
+                assert!(parser.finish().unwrap().is_none());
+            }
+        }
+
+        // The limits of the readers
+        let mut parser = Parser::new(parser::LineOptions::default()).with_max_record_size(8);
+        assert_eq!(parser.feed(b"id,name\n1,To").unwrap().len(), 1);
+        assert_eq!(parser.feed(b"m\n2,Yoshi").unwrap().len(), 1);
+        let error = parser.feed(b"ip").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The record at line 3 is longer than the maximum of 8 bytes"
+        );
+        let mut parser = Parser::new(parser::LineOptions::default()).with_max_field_size(4);
+        assert_eq!(parser.feed(b"id,name\n1,\"Tom\n").unwrap().len(), 1);
+        assert!(parser.feed(b"Cat\n").is_err());
+        let mut parser = Parser::new(parser::LineOptions::default()).with_max_field_size(4);
+        assert!(parser.feed(b"id,Thomas\n").is_err());
+        // A line that never ends is rejected as it arrives.
+        let mut parser = Parser::new(parser::LineOptions::default()).with_max_field_size(4);
+        assert!(parser.feed(b"id,na").unwrap().is_empty());
+        assert!((0..10).any(|_| parser.feed(b"aaaa").is_err()));
+
+        let mut parser = Parser::new(parser::LineOptions::default());
+        assert!(parser.feed(b"a,\"b\nc").unwrap().is_empty());
+        assert!(parser.finish().is_err());
+        let mut parser = Parser::new(parser::LineOptions::default());
+        assert!(parser.feed(b"a,\xff\n").is_err());
+    }
+
+    #[test]
+    fn test_max_field_and_record_size() {
+        let options = ReaderOptions {
+            max_record_size: Some(8),
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::from_text("id,name\r\n1,\"Thom\"\r\n", &options).unwrap();
+        assert_eq!(file.rows, vec![vec!["1", "Thom"]]);
+        let error = CSVFile::from_text("id,name\n1,Tom\n2,Yoshiip\n", &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The record at line 3 is longer than the maximum of 8 bytes"
+        );
+        // The limit may fall in the middle of a character.
+        let error = CSVFile::from_text("id,name\n1,ééééé\n", &options).unwrap_err();
+        assert!(error.to_string().starts_with("The record at line 2"));
+        assert!(CSVFile::from_text("identifier,name\n", &options).is_err());
+
+        // An unclosed quote swallowing the rest of the file.
+        let mut text = String::from("id,name\n1,\"Thomas\n");
+        for i in 0..10_000 {
+            text.push_str(&format!("{},row\n", i));
+        }
+        let options = ReaderOptions {
+            max_field_size: Some(64),
+            ..ReaderOptions::default()
+        };
+        let mut reader = CSVReader::from_reader(text.as_bytes(), &options).unwrap();
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A value of the record at line 2 is longer than the maximum of 64 bytes"
+        );
+        assert!(reader.bytes_read() < 200);
+        assert!(CSVFile::from_text(
+            "id,name\n1,Thomas\n",
+            &ReaderOptions {
+                max_field_size: Some(5),
+                ..ReaderOptions::default()
+            }
+        )
+        .is_err());
+
+        // A line that never ends, without any delimiter, isn't read entirely.
+        let options = ReaderOptions {
+            max_field_size: Some(4),
+            delimiter: '§',
+            escape: Escape::DoubleQuote,
+            ..ReaderOptions::default()
+        };
+        let source = "id§name\n".as_bytes().chain(std::io::repeat(b'a'));
+        let error = CSVFile::from_reader(source, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A value of the record at line 2 is longer than the maximum of 4 bytes"
+        );
+        // The escaped quotes take more room in the source than in the values.
+        let line = vec!["\"\"\"\"\"\"\"\"\"\""; 1000].join("§");
+        let file = CSVFile::from_text(&format!("x\n{}\n", line), &options).unwrap();
+        assert_eq!(file.rows[0].len(), 1000);
+        assert_eq!(file.rows[0][999], "\"\"\"\"");
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_dimensions() {
+        let filename = String::from("test_dimensions.csv");
+        let cases = [
+            ("a,b\n1,2\n3,4\n", Escape::Backslash, (2, 2), 3),
+            ("a,b\n1,2\n3,4", Escape::Backslash, (2, 2), 3),
+            ("a,b\r\n1,\"x\r\ny\"\r\n\r\n", Escape::Backslash, (2, 2), 4),
+            (
+                "a,b\n1,\"say \\\"hi\n\\\"\"\n",
+                Escape::Backslash,
+                (1, 2),
+                3,
+            ),
+            (
+                "a,b\n1,\"say \"\"hi\n\"\"\"\n2,x\"\n",
+                Escape::DoubleQuote,
+                (2, 2),
+                4,
+            ),
+            ("a\n", Escape::Backslash, (0, 1), 1),
+        ];
+        for (text, escape, dimensions, lines) in cases {
+            fs::write(&filename, text).unwrap();
+            let options = ReaderOptions {
+                escape,
+                ..ReaderOptions::default()
+            };
+            let rows = CSVReader::open(&filename, &options).unwrap().count();
+            assert_eq!(rows, dimensions.0, "{:?}", text);
+            assert_eq!(
+                CSVFile::dimensions(&filename, &options).unwrap(),
+                dimensions
+            );
+            assert_eq!(CSVFile::count_lines(&filename).unwrap(), lines);
+        }
+
+        fs::write(&filename, "a,b\n1,\"not closed\n").unwrap();
+        assert!(CSVFile::dimensions(&filename, &ReaderOptions::default()).is_err());
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_read_header() {
+        let filename = String::from("test_read_header.csv");
+        fs::write(&filename, " id , name \n1,\"not closed\n").unwrap();
+        let options = ReaderOptions {
+            trim: Trim::All,
+            ..ReaderOptions::default()
+        };
+        // The rows aren't parsed, so the invalid row doesn't matter.
+        assert_eq!(
+            CSVFile::read_header(&filename, &options).unwrap(),
+            vec!["id", "name"]
+        );
+
+        fs::write(&filename, "").unwrap();
+        assert!(CSVFile::read_header(&filename, &options).is_err());
+        fs::remove_file(&filename).unwrap();
+        assert!(CSVFile::read_header(&filename, &options).is_err());
+    }
+
+    #[test]
+    fn test_project() {
+        let file = CSVFile::from_text(
+            "id,name,age\n1,Thomas,19\n2,Yoshiip,20\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+
+        let projected = file
+            .project(&[("age", "years"), ("id", "id"), ("id", "key")])
+            .unwrap();
+        assert_eq!(projected.columns, vec!["years", "id", "key"]);
+        assert_eq!(
+            projected.rows,
+            vec![vec!["19", "1", "1"], vec!["20", "2", "2"]]
+        );
+        assert_eq!(projected.delimiter, ',');
+
+        assert!(file.project(&[("email", "email")]).is_err());
+        assert!(file.project(&[("id", "x"), ("name", "x")]).is_err());
+        assert_eq!(file.project(&[]).unwrap().columns.len(), 0);
+
+        let ragged = CSVFile::from_text("id,name\n1\n2,John\n", &ReaderOptions::default()).unwrap();
+        let projected = ragged.project(&[("name", "n"), ("id", "i")]).unwrap();
+        assert_eq!(projected.rows, vec![vec!["", "1"], vec!["John", "2"]]);
+    }
+
+    #[test]
+    fn test_update_where() {
+        use std::sync::{Arc, Mutex};
+
+        let text = "name,age\nThomas,19\nYoshiip,20\nJohn,31\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = Arc::clone(&events);
+        file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+
+        // The row of Yoshiip matches but isn't changed.
+        let changed = file
+            .update_where(
+                |row| row[1].parse::<u32>().unwrap() < 25,
+                |row| row[1] = "19".to_string(),
+            )
+            .unwrap();
+        assert_eq!(changed, 1);
+        assert_eq!(file.rows[1], vec!["Yoshiip", "19"]);
+        assert_eq!(events.lock().unwrap().len(), 1);
+
+        file.add_validator(Validator::column(
+            &"name".to_string(),
+            "the name is required",
+            |value| !value.is_empty(),
+        ))
+        .unwrap();
+        assert!(file.update_where(|_| true, |row| row[0].clear()).is_err());
+        assert!(file
+            .update_where(|_| true, |row| row.push(String::new()))
+            .is_err());
+        assert_eq!(file.rows[0], vec!["Thomas", "19"]);
+        assert_eq!(file.update_where(|_| false, |row| row.clear()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_where() {
+        let text = "name,age\nThomas,19\nYoshiip,20\nJohn,31\nAnna,17\n";
+        let options = ReaderOptions {
+            preserve_quotes: true,
+            ..ReaderOptions::default()
+        };
+        let mut file = CSVFile::from_text(text, &options).unwrap();
+        file.set_quoted(&CSVCoords { row: 2, column: 0 }, true);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = std::sync::Arc::clone(&events);
+        file.on_change(move |event| events_clone.lock().unwrap().push(event.clone()));
+
+        let removed = file.delete_where(|row| row[1].parse::<u32>().unwrap() < 20);
+        assert_eq!(removed, vec![0, 3]);
+        assert_eq!(file.rows, vec![vec!["Yoshiip", "20"], vec!["John", "31"]]);
+        assert!(file.is_quoted(&CSVCoords { row: 1, column: 0 }));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::RowRemoved { row: 3 },
+                ChangeEvent::RowRemoved { row: 0 }
+            ]
+        );
+
+        assert!(file.delete_where(|_| false).is_empty());
+        assert_eq!(file.delete_where(|_| true), vec![0, 1]);
+        assert!(file.has_no_rows());
+    }
+
+    #[test]
+    fn test_upsert_rows() {
+        let text = "id,name\n1,Thomas\n2,Yoshiip\n2,John\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let rows = vec![
+            vec!["2".to_string(), "Yoshiip Smith".to_string()],
+            vec!["3".to_string(), "Anna".to_string()],
+            vec!["3".to_string(), "Anna Smith".to_string()],
+        ];
+
+        assert_eq!(file.upsert_rows(&"id".to_string(), &rows).unwrap(), (1, 1));
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["1", "Thomas"],
+                vec!["2", "Yoshiip Smith"],
+                vec!["2", "John"],
+                vec!["3", "Anna Smith"]
+            ]
+        );
+
+        assert!(file.upsert_rows(&"email".to_string(), &rows).is_err());
+        let invalid = vec![
+            vec!["4".to_string(), "Jane".to_string()],
+            vec!["5".to_string()],
+        ];
+        assert!(file.upsert_rows(&"id".to_string(), &invalid).is_err());
+        assert_eq!(file.count_rows(), 4);
+
+        // The second row is too short to have a key, so it's never replaced.
+        let mut ragged = CSVFile::from_text("k,v\n1,2\n3\n", &ReaderOptions::default()).unwrap();
+        let rows = vec![
+            vec!["1".to_string(), "2".to_string()],
+            vec!["1".to_string(), "4".to_string()],
+        ];
+        assert_eq!(ragged.upsert_rows(&"v".to_string(), &rows).unwrap(), (1, 1));
+        assert_eq!(ragged.rows, vec![vec!["1", "2"], vec!["3"], vec!["1", "4"]]);
+        let short = vec![vec!["5".to_string()]];
+        assert!(ragged.upsert_rows(&"v".to_string(), &short).is_err());
+    }
+
+    #[test]
+    fn test_add_row_partial() {
+        let columns = vec!["name".to_string(), "country".to_string(), "age".to_string()];
+        let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+        file.set_column_default(&"country".to_string(), &"France".to_string())
+            .unwrap();
+        assert!(file
+            .set_column_default(&"email".to_string(), &String::new())
+            .is_err());
+
+        let row = HashMap::from([
+            ("name".to_string(), "Yoshiip".to_string()),
+            ("country".to_string(), "Japan".to_string()),
+        ]);
+        file.add_row_partial(&row).unwrap();
+        file.add_row_partial(&HashMap::new()).unwrap();
+        assert_eq!(
+            file.rows,
+            vec![vec!["Yoshiip", "Japan", ""], vec!["", "France", ""]]
+        );
+
+        let unknown = HashMap::from([("email".to_string(), "y@x.org".to_string())]);
+        assert!(file.add_row_partial(&unknown).is_err());
+        assert_eq!(file.count_rows(), 2);
+
+        file.remove_column(1).unwrap();
+        file.add_column(&"country".to_string()).unwrap();
+        assert_eq!(file.column_default(&"country".to_string()), None);
+    }
+
+    #[test]
+    fn test_row_builder() {
+        let columns = vec!["name".to_string(), "age".to_string()];
+        let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+        file.set_column_aliases(&HashMap::from([(
+            "full_name".to_string(),
+            vec!["name".to_string()],
+        )]));
+        file.set_column_default(&"age".to_string(), &"0".to_string())
+            .unwrap();
+
+        let row = RowBuilder::new(&file)
+            .set("full_name", "Thomas")
+            .set("age", "18")
+            .set("age", "19")
+            .build()
+            .unwrap();
+        assert_eq!(row, vec!["Thomas", "19"]);
+        assert_eq!(
+            RowBuilder::new(&file).set("name", "John").build().unwrap(),
+            vec!["John", "0"]
+        );
+
+        let error = RowBuilder::new(&file)
+            .set("email", "t@x.org")
+            .set("name", "Thomas")
+            .set("phone", "0600")
+            .build()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "The column email doesn't exist");
+    }
+
+    #[test]
+    fn test_a1_references() {
+        for (reference, row, column) in [
+            ("A1", 0, 0),
+            ("z10", 9, 25),
+            ("AA3", 2, 26),
+            ("AZ1", 0, 51),
+            ("BA1", 0, 52),
+            ("ZZ1", 0, 701),
+            ("AAA1", 0, 702),
+            ("$C$12", 11, 2),
+        ] {
+            let coords = CSVCoords::from_a1(reference).unwrap();
+            assert_eq!((coords.row, coords.column), (row, column), "{}", reference);
+            assert_eq!(
+                CSVCoords::from_a1(&coords.to_a1()).unwrap(),
+                coords,
+                "{}",
+                reference
+            );
+        }
+        for reference in ["", "A", "12", "A0", "A-1", "A1B", "$$A1", "É1", "A 1"] {
+            assert!(CSVCoords::from_a1(reference).is_err(), "{}", reference);
+        }
+
+        let file = CSVFile::from_text("a,b\n1,2\n3,4\n", &ReaderOptions::default()).unwrap();
+        assert_eq!(file.get_cell_a1("A2"), Some(&"3".to_string()));
+        assert_eq!(file.get_cell_a1("A3"), None);
+        assert_eq!(file.get_cell_a1("?"), None);
+    }
+
+    #[test]
+    fn test_get_and_set_range() {
+        let text = "a,b,c\n1,2,3\n4,5,6\n7,8,9\n";
+        let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+        let coords = |reference| CSVCoords::from_a1(reference).unwrap();
+
+        let block = file.get_range(&coords("A2"), &coords("B3")).unwrap();
+        assert_eq!(block.columns, vec!["a", "b"]);
+        assert_eq!(block.rows, vec![vec!["4", "5"], vec!["7", "8"]]);
+        assert_eq!(
+            file.get_range(&coords("C1"), &coords("C1")).unwrap().rows,
+            vec![vec!["3"]]
+        );
+        assert!(file.get_range(&coords("B2"), &coords("A3")).is_err());
+        assert!(file.get_range(&coords("A1"), &coords("D1")).is_err());
+
+        // Pastes the block one column to the right of where it was copied.
+        file.set_range(&coords("B1"), &block).unwrap();
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["1", "4", "5"],
+                vec!["4", "7", "8"],
+                vec!["7", "8", "9"]
+            ]
+        );
+        assert!(file.set_range(&coords("C1"), &block).is_err());
+        assert!(file.set_range(&coords("A3"), &block).is_err());
+
+        file.add_validator(Validator::column(
+            &"c".to_string(),
+            "the value isn't a number",
+            |value| value.parse::<u32>().is_ok(),
+        ))
+        .unwrap();
+        let invalid = CSVFile::from_text("x\n10\nNaN\n", &ReaderOptions::default()).unwrap();
+        assert!(file.set_range(&coords("C1"), &invalid).is_err());
+        assert_eq!(file.rows[0], vec!["1", "4", "5"]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_workbook_directory() {
+        let directory = String::from("test_workbook");
+        let mut workbook = CSVWorkbook::new();
+        let orders = CSVFile::from_text("order,item\n1,A1\n", &ReaderOptions::default()).unwrap();
+        let items = CSVFile::from_text("sku,price\nA1,9.99\n", &ReaderOptions::default()).unwrap();
+        workbook
+            .add_sheet(&"orders".to_string(), orders.clone())
+            .unwrap();
+        workbook.add_sheet(&"items".to_string(), items).unwrap();
+        assert!(workbook.add_sheet(&"orders".to_string(), orders).is_err());
+        workbook
+            .write_dir(&directory, &WriterOptions::default())
+            .unwrap();
+        fs::write(format!("{}/notes.txt", directory), "not a sheet").unwrap();
+
+        let read = CSVWorkbook::from_dir(&directory, &ReaderOptions::default()).unwrap();
+        assert_eq!(read.sheet_names(), vec!["items", "orders"]);
+        assert_eq!(
+            read.sheet(&"orders".to_string()).unwrap().rows,
+            vec![vec!["1", "A1"]]
+        );
+
+        let mut invalid = CSVWorkbook::new();
+        invalid
+            .add_sheet(
+                &"../escaped".to_string(),
+                read.sheet(&"items".to_string()).unwrap().clone(),
+            )
+            .unwrap();
+        assert!(invalid
+            .write_dir(&directory, &WriterOptions::default())
+            .is_err());
+        assert!(workbook.remove_sheet(&"items".to_string()).is_some());
+        assert_eq!(workbook.len(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "zip")]
+    fn test_workbook_zip() {
+        // The orders are compressed with dynamic Huffman codes, the customers
+        // with the fixed ones, and the items are stored without compression.
+        let workbook =
+            CSVWorkbook::from_zip(&"test_workbook.zip".to_string(), &ReaderOptions::default())
+                .unwrap();
+        assert_eq!(workbook.sheet_names(), vec!["orders", "customers", "items"]);
+        let orders = workbook.sheet(&"orders".to_string()).unwrap();
+        assert_eq!(orders.count_rows(), 300);
+        assert_eq!(orders.rows[299], vec!["300", "c29", "91.51"]);
+        assert_eq!(
+            workbook.sheet(&"customers".to_string()).unwrap().rows[1],
+            vec!["c2", "Yoshiip, Jr"]
+        );
+
+        let filename = String::from("test_workbook_written.zip");
+        workbook
+            .write_zip(&filename, &WriterOptions::default())
+            .unwrap();
+        let read = CSVWorkbook::from_zip(&filename, &ReaderOptions::default()).unwrap();
+        assert_eq!(read.sheet_names(), workbook.sheet_names());
+        for (name, file) in workbook.sheets() {
+            assert_eq!(read.sheet(name).unwrap().rows, file.rows);
+        }
+
+        // Alters the last value of the stored entry, whose checksum no longer matches.
+        let mut data = fs::read(&filename).unwrap();
+        let idx = data.windows(4).position(|w| w == b"9.99").unwrap();
+        data[idx] = b'8';
+        fs::write(&filename, &data).unwrap();
+        assert!(CSVWorkbook::from_zip(&filename, &ReaderOptions::default()).is_err());
+        fs::write(&filename, "not an archive").unwrap();
+        assert!(CSVWorkbook::from_zip(&filename, &ReaderOptions::default()).is_err());
+
+        // The decompression stops once the size announced by the archive is exceeded.
+        let mut data = fs::read("test_workbook.zip").unwrap();
+        let idx = (0..data.len())
+            .find(|&i| {
+                data[i..].starts_with(b"PK\x01\x02")
+                    && data[i + 46..].starts_with(b"export/orders.csv")
+            })
+            .unwrap();
+        data[idx + 24..idx + 28].copy_from_slice(&10u32.to_le_bytes());
+        fs::write(&filename, &data).unwrap();
+        let error = CSVWorkbook::from_zip(&filename, &ReaderOptions::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("larger than the 10 bytes expected"));
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_convert() {
+        let input = String::from("test_convert_in.csv");
+        let output = String::from("test_convert_out.csv");
+        fs::write(
+            &input,
+            "id\tnote\tprice\n1\ta, b\t1.5\n2\t\t=1+1\n3\t\"x\ny\"\t2",
+        )
+        .unwrap();
+
+        let from = ReaderOptions {
+            delimiter: '\t',
+            ..ReaderOptions::default()
+        };
+        let mut to = WriterOptions {
+            delimiter: Some(';'),
+            line_ending: Some(LineEnding::CrLf),
+            byte_order_mark: Some(true),
+            escape_formulas: true,
+            null_value: "NULL".to_string(),
+            ..WriterOptions::default()
+        };
+        to.set_column_format(
+            &"price".to_string(),
+            ColumnFormat {
+                decimals: Some(2),
+                ..ColumnFormat::default()
+            },
+        );
+        assert_eq!(convert(&input, &output, &from, &to).unwrap(), 3);
+        let converted = fs::read_to_string(&output).unwrap();
+        assert_eq!(
+            converted,
+            "\u{feff}id;note;price\r\n1;a, b;1.50\r\n2;NULL;'=1+1\r\n3;\"x\ny\";2.00\r\n"
+        );
+        // The same rows as writing the whole file with the same options.
+        let file = CSVFile::new_with_options(&input, &from).unwrap();
+        assert_eq!(file.to_string_with_options(&to) + "\r\n", converted);
+
+        assert!(convert(&input, &input, &from, &to).is_err());
+        assert!(fs::read_to_string(&input).unwrap().starts_with("id\t"));
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_normalize_file() {
+        let input = "test_normalize_in.csv".to_string();
+        let output = "test_normalize_out.csv".to_string();
+        fs::write(
+            &input,
+            b"\xef\xbb\xbfname,age\r\nThomas,19\nYoshi\xffp,20\rAlex,21",
+        )
+        .unwrap();
+
+        let report = normalize_file(&input, &output, &NormalizeOptions::default()).unwrap();
+        assert_eq!(
+            report,
+            NormalizeReport {
+                line_breaks_replaced: 2,
+                invalid_sequences: 1,
+                byte_order_mark_removed: true,
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "name,age\nThomas,19\nYoshi\u{fffd}p,20\nAlex,21"
+        );
+
+        // UTF-16 is detected from its byte order mark
+        let mut bytes = vec![0xff, 0xfe];
+        for unit in "a,b\n\u{e9},\u{1f600}\r\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        fs::write(&input, bytes).unwrap();
+        let options = NormalizeOptions {
+            line_ending: LineEnding::CrLf,
+            ..NormalizeOptions::default()
+        };
+        let report = normalize_file(&input, &output, &options).unwrap();
+        assert_eq!(report.line_breaks_replaced, 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "a,b\r\n\u{e9},\u{1f600}\r\n"
+        );
+        assert!(normalize_file(&input, &input, &options).is_err());
+
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_compare_columns() {
+        let expected = CSVFile::from_text(
+            "id,price,label,unit\n1,0.3,a,kg\n2,1.5,b,kg\n3,2,c,kg\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let actual = CSVFile::from_text(
+            "id,label,price,rate\n2,b,1.75,x\n1,a,0.30000000000000004,x\n4,d,9,x\n4,d,9,x\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let id = "id".to_string();
+
+        let comparison = expected.compare_columns(&actual, &id, 0.01).unwrap();
+        assert!(!comparison.is_match());
+        assert_eq!(
+            comparison.differences,
+            vec![CellDifference {
+                key: "2".to_string(),
+                column: "price".to_string(),
+                expected: "1.5".to_string(),
+                actual: "1.75".to_string(),
+                kind: DifferenceKind::Numeric { difference: 0.25 },
+            }]
+        );
+        assert_eq!(comparison.missing_keys, vec!["3"]);
+        assert_eq!(comparison.extra_keys, vec!["4"]);
+        assert_eq!(comparison.missing_columns, vec!["unit"]);
+        assert_eq!(comparison.extra_columns, vec!["rate"]);
+
+        // without tolerance, the rounding error is reported
+        let comparison = expected.compare_columns(&actual, &id, 0.0).unwrap();
+        assert_eq!(comparison.differences.len(), 2);
+        assert!(expected
+            .compare_columns(&expected, &id, 0.0)
+            .unwrap()
+            .is_match());
+        assert!(expected.compare_columns(&actual, &id, -1.0).is_err());
+        assert!(expected
+            .compare_columns(&actual, &"unit".to_string(), 0.0)
+            .is_err());
+
+        let ragged = CSVFile::from_text("id,a,b\n1\n2,x\n", &ReaderOptions::default()).unwrap();
+        let other = CSVFile::from_text("a,id,b\n,1\nx\n", &ReaderOptions::default()).unwrap();
+        let comparison = ragged.compare_columns(&other, &id, 0.0).unwrap();
+        assert!(comparison.differences.is_empty());
+        assert_eq!(comparison.missing_keys, vec!["2"]);
+        assert_eq!(comparison.extra_keys, vec![""]);
+    }
+
+    #[test]
+    fn test_find_duplicate_rows() {
+        let mut text = String::from("a,b\n");
+        for i in 0..10_000 {
+            text.push_str(&format!("{},{}\n", i % 2500, i % 5000));
+        }
+        let file = CSVFile::from_text(&text, &ReaderOptions::default()).unwrap();
+        let groups = file.find_duplicate_rows();
+        assert_eq!(groups.len(), 5000);
+        assert_eq!(groups[0], vec![0, 5000]);
+        assert!(groups.iter().all(|g| g.len() == 2));
+
+        let file = CSVFile::from_text("a\n1\n2\n", &ReaderOptions::default()).unwrap();
+        assert!(file.find_duplicate_rows().is_empty());
+    }
+
+    #[test]
+    fn test_preallocation_from_size() {
+        let mut text = String::from("id,value\n");
+        for i in 0..1000 {
+            text.push_str(&format!("{},{}\n", i % 10, i % 10));
+        }
+        let file = CSVFile::from_text(&text, &ReaderOptions::default()).unwrap();
+        assert_eq!(file.rows.len(), 1000);
+        assert_eq!(file.rows.capacity(), 1000);
+    }
+
+    #[test]
+    fn test_read_crlf_lines() {
+        let text = "a,b\r\n1,2\r\n3,\"4\"\r\n5,6";
+        let mut reader =
+            CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+        assert_eq!(reader.columns(), &vec!["a", "b"]);
+        let rows: Vec<Vec<String>> = (&mut reader).collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows, vec![vec!["1", "2"], vec!["3", "4"], vec!["5", "6"]]);
+        assert_eq!(reader.bytes_read(), text.len() as u64);
+    }
+
+    #[test]
+    fn test_read_with_projection() {
+        let text = "id,name,city\n1,Thomas,Paris\n2,\"Doe, John\",\"Lyon\"\n3,Alice\n";
+        let options = ReaderOptions {
+            projection: Some(vec!["city".to_string(), "name".to_string()]),
+            preserve_quotes: true,
+            lossless: true,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::from_text(text, &options).unwrap();
+        assert_eq!(file.columns, vec!["city", "name"]);
+        assert_eq!(
+            file.rows,
+            vec![
+                vec!["Paris", "Thomas"],
+                vec!["Lyon", "Doe, John"],
+                vec!["", "Alice"]
+            ]
+        );
+        assert!(file.is_quoted(&CSVCoords { row: 1, column: 0 }));
+        assert!(file.is_quoted(&CSVCoords { row: 1, column: 1 }));
+        assert_eq!(
+            file.to_string(),
+            "city,name\nParis,Thomas\n\"Lyon\",\"Doe, John\"\n,Alice\n"
+        );
+
+        let options = ReaderOptions {
+            projection: Some(vec!["country".to_string()]),
+            ..ReaderOptions::default()
+        };
+        assert!(CSVFile::from_text(text, &options).is_err());
+    }
+
+    #[test]
+    fn test_columnar_table() {
+        let file = CSVFile::from_text(
+            "name,score\nThomas,10\nYoshiip,\nAlice,20\n",
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        let mut table = file.to_table();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.count_rows(), 3);
+        assert_eq!(
+            table.get_row(1),
+            Some(vec![&"Yoshiip".to_string(), &"".to_string()])
+        );
+        assert_eq!(
+            table.get_cell(&CSVCoords { row: 2, column: 0 }),
+            Some(&"Alice".to_string())
+        );
+        assert_eq!(
+            table.column_stats(&"score".to_string()),
+            file.column_stats(&"score".to_string())
+        );
+
+        table.column_mut(&"score".to_string()).unwrap()[1] = "15".to_string();
+        assert_eq!(
+            table.numeric_values(&"score".to_string()),
+            Some(vec![10.0, 15.0, 20.0])
+        );
+
+        assert!(table
+            .add_column(&"city".to_string(), vec!["Paris".to_string()])
+            .is_err());
+        table
+            .add_column(
+                &"city".to_string(),
+                vec!["Paris".to_string(), "Lyon".to_string(), "Nice".to_string()],
+            )
+            .unwrap();
+        table
+            .add_row(&vec![
+                "Bob".to_string(),
+                "5".to_string(),
+                "Lille".to_string(),
+            ])
+            .unwrap();
+        assert_eq!(table.remove_column(1).unwrap().len(), 4);
+
+        let file = table.to_csv_file();
+        assert_eq!(file.columns, vec!["name", "city"]);
+        assert_eq!(file.rows[3], vec!["Bob", "Lille"]);
+
+        assert!(CSVTable::build(
+            &vec!["a".to_string(), "b".to_string()],
+            &vec![vec!["1".to_string()], vec![]],
+            &','
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut text = String::from("id,status\n");
+        for i in 0..100 {
+            text.push_str(&format!(
+                "{},{}\n",
+                i,
+                if i % 2 == 0 { "active" } else { "inactive" }
+            ));
+        }
+        let reader = CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+        let mut interned = InternedCSVFile::from_csv_reader(reader).unwrap();
+        let stats = interned.intern_stats();
+        assert_eq!(stats.values, 200);
+        assert_eq!(stats.distinct, 102);
+        assert!(stats.compression_ratio() > 2.0);
+        assert!(std::sync::Arc::ptr_eq(
+            &interned.rows[0][1],
+            &interned.rows[2][1]
+        ));
+        assert_eq!(
+            interned.get_cell(&CSVCoords { row: 1, column: 1 }),
+            Some("inactive")
+        );
+
+        assert!(interned.add_row(&vec!["100".to_string()]).is_err());
+        interned
+            .add_row(&vec!["100".to_string(), "active".to_string()])
+            .unwrap();
+        assert_eq!(interned.intern_stats().distinct, 103);
+
+        let file = interned.to_csv_file();
+        assert_eq!(file.rows.len(), 101);
+        assert_eq!(file.intern().intern_stats(), interned.intern_stats());
+    }
+
+    #[test]
+    fn test_read_stats() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+        fn count_allocations() -> u64 {
+            ALLOCATIONS.fetch_add(10, Ordering::SeqCst)
+        }
+
+        let options = ReaderOptions {
+            allocation_counter: Some(count_allocations),
+            clock: Some(crate::system_clock),
+            ..ReaderOptions::default()
+        };
+        let mut reader = CSVReader::from_reader("a,b\n1,2\n3,4\n".as_bytes(), &options).unwrap();
+        assert!(reader.next().is_some());
+        assert_eq!(reader.stats().rows, 1);
+        assert_eq!(reader.stats().bytes, 8);
+        while reader.next().is_some() {}
+        let stats = reader.stats();
+        assert_eq!(stats.rows, 2);
+        assert_eq!(stats.bytes, 12);
+        assert!(stats.allocations.unwrap() >= 10);
+        assert!(stats.elapsed.is_some());
+        assert!(stats.rows_per_second().is_some());
+        assert_eq!(reader.stats().elapsed, stats.elapsed);
+
+        let untimed = CSVFile::from_text("a\n1\n", &ReaderOptions::default()).unwrap();
+        assert_eq!(untimed.read_stats().unwrap().elapsed, None);
+        assert_eq!(untimed.read_stats().unwrap().rows_per_second(), None);
+
+        let file = CSVFile::build(&vec!["a".to_string()], &vec![], &',').unwrap();
+        assert!(file.read_stats().is_none());
+    }
+
+    #[test]
+    fn test_core_parser() {
+        let fields = vec![
+            "Thomas".to_string(),
+            "The best, and only, Godoter".to_string(),
+            "C:\\Users".to_string(),
+            "\"quoted\"".to_string(),
+        ];
+        let line = parser::write_line(&fields, ',');
+        assert_eq!(parser::parse_line(&line, ',').unwrap(), fields);
+        assert!(parser::validate_fields(&fields, 4).is_ok());
+        assert_eq!(
+            parser::validate_fields(&fields, 3),
+            Err(parser::ParseError::FieldCount {
+                expected: 3,
+                found: 4
+            })
+        );
+
+        let mut reused = vec!["kept".to_string()];
+        let mut quoted = Vec::new();
+        parser::parse_line_into("a,\"b\"", ',', &mut reused, Some(&mut quoted)).unwrap();
+        assert_eq!(reused, vec!["kept", "a", "b"]);
+        assert_eq!(quoted, vec![1]);
+
+        assert_eq!(
+            parser::parse_line("a,\"b", ','),
+            Err(parser::ParseError::UnclosedQuote)
+        );
+        assert_eq!(
+            parser::parse_line("a,b\\", ','),
+            Err(parser::ParseError::InvalidEscape)
+        );
+    }
+
+    #[test]
+    fn test_parse_line_with_options() {
+        use std::borrow::Cow;
+
+        let line = "Thomas,\"The best, and only, Godoter\",C:\\\\Users,\"\\\"quoted\\\"\",";
+        let options = parser::LineOptions::default();
+        let fields = parser::parse_line_with_options(line, &options).unwrap();
+        assert_eq!(fields, parser::parse_line(line, ',').unwrap());
+        let borrowed: Vec<bool> = fields
+            .iter()
+            .map(|field| matches!(field, Cow::Borrowed(_)))
+            .collect();
+        assert_eq!(borrowed, vec![true, true, false, false, true]);
+
+        let options = parser::LineOptions {
+            delimiter: '\t',
+            quote: '|',
+            escape: Escape::Backslash,
+        };
+        assert_eq!(
+            parser::parse_line_with_options("|a\tb|\t\"c\"\t\\|", &options).unwrap(),
+            vec!["a\tb", "\"c\"", "|"]
+        );
+        assert_eq!(
+            parser::parse_line_with_options("|a", &options),
+            Err(parser::ParseError::UnclosedQuote)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_preserve_quotes_round_trip() {
+        let filename = String::from("test_preserve_quotes.csv");
+        let source = "\"name\",age,city\n\"Thomas\",20,Paris\nYoshiip,99,\"Lyon, France\"\n";
+        fs::write(&filename, source).unwrap();
+
+        let options = ReaderOptions {
+            preserve_quotes: true,
+            ..ReaderOptions::default()
+        };
+        let mut file = CSVFile::new_with_options(&filename, &options).unwrap();
+        assert!(file.is_quoted(&CSVCoords { row: 0, column: 0 }));
+        assert!(!file.is_quoted(&CSVCoords { row: 0, column: 1 }));
+        assert_eq!(file.to_string(), source);
+
+        file.rows[0][1] = "21".to_string();
+        assert_eq!(
+            file.to_string(),
+            "\"name\",age,city\n\"Thomas\",21,Paris\nYoshiip,99,\"Lyon, France\"\n"
+        );
+
+        file.remove_row(0).unwrap();
+        file.remove_column(1).unwrap();
+        assert_eq!(
+            file.to_string(),
+            "\"name\",city\nYoshiip,\"Lyon, France\"\n"
+        );
+
+        let file = CSVFile::new(&filename, &',').unwrap();
+        assert_eq!(
+            file.to_string(),
+            "name,age,city\nThomas,20,Paris\nYoshiip,99,\"Lyon, France\"\n"
+        );
+
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_lossless_mode() {
+        let filename = String::from("test_lossless.csv");
+        let source = "name , age\n\"Thomas\",  20\n'Yoshiip',99\nLua,7\n";
+        fs::write(&filename, source).unwrap();
+
+        let options = ReaderOptions {
+            lossless: true,
+            trim: Trim::All,
+            ..ReaderOptions::default()
+        };
+        let mut file = CSVFile::new_with_options(&filename, &options).unwrap();
+        assert_eq!(file.rows[0], vec!["Thomas", "20"]);
+        assert_eq!(file.to_string(), source);
+
+        // only the modified row is serialized again
+        file.rows[1][1] = "100".to_string();
+        assert_eq!(
+            file.to_string(),
+            "name , age\n\"Thomas\",  20\n'Yoshiip',100\nLua,7\n"
+        );
+
+        // the raw lines follow the rows when rows are removed
+        file.remove_row(0).unwrap();
+        assert_eq!(file.to_string(), "name , age\n'Yoshiip',100\nLua,7\n");
+
+        // the raw lines can't be used once the delimiter changes
+        file.set_delimiter(&';');
+        assert_eq!(file.to_string(), "name;age\n'Yoshiip';100\nLua;7\n");
+
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_lossless_mode_keeps_line_breaks_and_byte_order_mark() {
+        let options = ReaderOptions {
+            lossless: true,
+            ..ReaderOptions::default()
+        };
+        let source = "\u{feff}a,b\r\n1,2\r\n\"x\r\ny\",3\r\n";
+        let mut file = CSVFile::from_text(source, &options).unwrap();
+        assert_eq!(file.to_string(), source);
+
+        file.rows[0][1] = "4".to_string();
+        assert_eq!(file.to_string(), "\u{feff}a,b\r\n1,4\r\n\"x\r\ny\",3\r\n");
+
+        // the options still take precedence over the source
+        let options = WriterOptions {
+            line_ending: Some(LineEnding::Lf),
+            byte_order_mark: Some(false),
+            ..WriterOptions::default()
+        };
+        assert_eq!(
+            file.to_string_with_options(&options),
+            "a,b\n1,4\n\"x\r\ny\",3\n"
+        );
+    }
+
+    #[test]
+    fn test_validation_report() {
+        let columns = vec![
+            "a".to_string(),
+            "".to_string(),
+            "a".to_string(),
+            "b ".to_string(),
+        ];
+        let data = vec![
+            vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                " 4".to_string(),
+            ],
+            vec![
+                "5".to_string(),
+                "6".to_string(),
+                "7".to_string(),
+                "8".to_string(),
+            ],
+        ];
+        let mut csv_file = CSVFile::build(&columns, &data, &',').unwrap();
+        csv_file.rows[1].push("9".to_string());
+
+        let report = csv_file.check_validity();
+        assert!(!report.is_valid());
+        assert!(report.has_warnings());
+        assert_eq!(
+            report.duplicate_columns,
+            vec![DuplicateColumn {
+                name: "a".to_string(),
+                indexes: vec![0, 2]
+            }]
+        );
+        assert_eq!(report.ragged_rows, vec![RaggedRow { row: 1, length: 5 }]);
+        assert_eq!(report.empty_columns, vec![1]);
+        assert_eq!(report.columns_with_whitespace, vec![3]);
+        assert_eq!(
+            report.cells_with_whitespace,
+            vec![CSVCoords { row: 0, column: 3 }]
+        );
+
+        let csv_file = CSVFile::build(&get_fake_columns(), &get_fake_rows(), &',').unwrap();
+        let report = csv_file.check_validity();
+        assert!(report.is_valid());
+        assert!(!report.has_warnings());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let columns = vec!["a\u{1}".to_string(), "b".to_string()];
+        let data = vec![
+            vec!["1\t2".to_string(), "\u{FEFF}x\u{7F}".to_string()],
+            vec!["clean".to_string(), "\0".to_string()],
+        ];
+        let mut csv_file = CSVFile::build(&columns, &data, &',').unwrap();
+
+        let options = SanitizeOptions {
+            action: SanitizeAction::Report,
+            ..SanitizeOptions::default()
+        };
+        let report = csv_file.sanitize(&options);
+        assert_eq!(report.issues.len(), 4);
+        assert_eq!(report.issues[0].row, None);
+        assert_eq!(report.issues[0].kind, SanitizeIssueKind::ControlCharacter);
+        assert_eq!(report.issues[1].kind, SanitizeIssueKind::Noncharacter);
+        assert_eq!(report.issues[2].position, 2);
+        assert_eq!(report.issues[3].kind, SanitizeIssueKind::NullByte);
+        assert_eq!(csv_file.rows, data); // nothing was modified
+
+        let report = csv_file.sanitize(&SanitizeOptions::default());
+        assert_eq!(report.issues.len(), 4);
+        assert_eq!(csv_file.columns, vec!["a", "b"]);
+        assert_eq!(csv_file.rows[0], vec!["1\t2", "x"]);
+        assert_eq!(csv_file.rows[1], vec!["clean", ""]);
+        assert!(csv_file.sanitize(&SanitizeOptions::default()).is_clean());
+
+        // The values spanning several lines are left intact.
+        let text = "a,b\n\"x\ny\",\"1\r\n2\"\n";
+        let options = ReaderOptions {
+            escape: Escape::DoubleQuote,
+            ..ReaderOptions::default()
+        };
+        let mut multiline = CSVFile::from_text(text, &options).unwrap();
+        assert!(multiline.sanitize(&SanitizeOptions::default()).is_clean());
+        assert_eq!(multiline.rows[0], vec!["x\ny", "1\r\n2"]);
+    }
+
+    #[test]
+    fn test_write_with_escaped_formulas() {
+        let columns = vec!["=name".to_string(), "value".to_string()];
+        let rows = vec![
+            vec![
+                "=HYPERLINK(\"http://evil\")".to_string(),
+                "-12.5".to_string(),
+            ],
+            vec!["@SUM(A1:A2)".to_string(), "+33 6 12".to_string()],
+            vec!["safe".to_string(), "-cmd".to_string()],
+        ];
+        let mut csv_file = CSVFile::build(&columns, &rows, &',').unwrap();
+        csv_file.set_quoted(&CSVCoords { row: 0, column: 0 }, true);
+
+        let options = WriterOptions {
+            escape_formulas: true,
+            ..WriterOptions::default()
+        };
+        assert_eq!(
+            csv_file.to_string_with_options(&options),
+            "'=name,value\n\"'=HYPERLINK(\\\"http://evil\\\")\",-12.5\n'@SUM(A1:A2),'+33 6 12\nsafe,'-cmd\n"
+        );
+        assert_eq!(csv_file.rows, rows);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_progress_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = Arc::clone(&calls);
+        let hook = ProgressHook::new(move |rows, bytes| {
+            calls_clone.lock().unwrap().push((rows, bytes));
+        })
+        .every(2);
+
+        let filename = String::from("test_progress.csv");
+        fs::write(&filename, "a,b\n1,2\n3,4\n5,6\n").unwrap();
+        let options = ReaderOptions {
+            on_progress: Some(hook.clone()),
+            ..ReaderOptions::default()
+        };
+        let mut csv_file = CSVFile::new_with_options(&filename, &options).unwrap();
+        fs::remove_file(&filename).unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec![(2, 12), (3, 16)]);
+
+        calls.lock().unwrap().clear();
+        let other = CSVFile::build(
+            &vec!["c".to_string()],
+            &vec![vec!["7".to_string()], vec!["8".to_string()]],
+            &',',
+        )
+        .unwrap();
+        csv_file.merge_with_progress(&other, Some(&hook)).unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec![(2, 0)]);
+        assert_eq!(csv_file.rows[1], vec!["3", "4", "8"]);
+    }
+
+    #[test]
+    fn test_streaming_reader() {
+        let text = "a,b\n1,\"2, 3\"\n4,5\n";
+        let mut reader =
+            CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+        assert_eq!(reader.columns(), &vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(reader.next().unwrap().unwrap(), vec!["1", "2, 3"]);
+        assert_eq!(reader.rows_read(), 1);
+        assert_eq!(reader.next().unwrap().unwrap(), vec!["4", "5"]);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.bytes_read(), text.len() as u64);
+
+        let text = "a,b\n1,\"2\n";
+        let mut reader =
+            CSVReader::from_reader(text.as_bytes(), &ReaderOptions::default()).unwrap();
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        assert!(CSVReader::from_reader("".as_bytes(), &ReaderOptions::default()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_cancel_reading() {
+        let token = CancellationToken::new();
+        let options = ReaderOptions {
+            cancellation: Some(token.clone()),
+            ..ReaderOptions::default()
+        };
+        let text = "a\n1\n2\n3\n";
+        let mut reader = CSVReader::from_reader(text.as_bytes(), &options).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), vec!["1"]);
+        token.cancel();
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+        assert!(Cancelled::is_cause_of(&error));
+        assert_eq!(error.to_string(), "The operation was cancelled");
+        assert!(reader.next().is_none());
+
+        let filename = String::from("test_cancel.csv");
+        fs::write(&filename, text).unwrap();
+        let result = CSVFile::new_with_options(&filename, &options);
+        fs::remove_file(&filename).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let body = "name;age\n\"Thomas\";20\nYoshiip;99\n";
+        let options = ReaderOptions {
+            preserve_quotes: true,
+            ..ReaderOptions::with_delimiter(&';')
+        };
+        let file = CSVFile::from_reader(body.as_bytes(), &options).unwrap();
+        assert_eq!(file.columns, vec!["name", "age"]);
+        assert_eq!(file.rows, vec![vec!["Thomas", "20"], vec!["Yoshiip", "99"]]);
+        assert_eq!(file.to_string(), body);
+    }
+
+    /// Answers a single HTTP request on a local port with the given response,
+    /// after a delay, and returns the URL to request.
+    #[cfg(feature = "http")]
+    fn serve_once(response: &'static str, delay: std::time::Duration) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data.csv", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while request.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                line.clear();
+            }
+            std::thread::sleep(delay);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        url
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_from_url() {
+        use std::time::Duration;
+
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\na,b\n1,2\n3,4\n",
+            Duration::ZERO,
+        );
+        let file = CSVFile::from_url(&url, &ReaderOptions::default()).unwrap();
+        assert_eq!(file.columns, vec!["a", "b"]);
+        assert_eq!(file.rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+
+        let url = serve_once(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            Duration::ZERO,
+        );
+        let error = CSVFile::from_url(&url, &ReaderOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("Couldn't download"));
+
+        // curl could read the local files, neither directly nor through a redirection
+        assert!(CSVFile::from_url("file:///etc/hostname", &ReaderOptions::default()).is_err());
+        let url = serve_once(
+            "HTTP/1.1 302 Found\r\nLocation: file:///etc/hostname\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            Duration::ZERO,
+        );
+        assert!(CSVFile::from_url(&url, &ReaderOptions::default()).is_err());
+
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\na,b\n",
+            Duration::from_secs(3),
+        );
+        let error = CSVFile::from_url_with_timeout(
+            &url,
+            &ReaderOptions::default(),
+            Duration::from_millis(500),
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("Couldn't download"));
+    }
+
+    #[test]
+    #[cfg(feature = "sqlite")]
+    fn test_sqlite() {
+        let database = String::from("test_sqlite.db");
+        let _ = fs::remove_file(&database);
+        let text = "id,name,active,score\n1,\"O'Brien, \"\"Jr\"\"\",yes,\n2,Yoshiip,no,4.5\n";
+        let options = ReaderOptions {
+            escape: Escape::DoubleQuote,
+            ..ReaderOptions::default()
+        };
+        let file = CSVFile::from_text(text, &options).unwrap();
+        let table = String::from("people");
+        file.to_sqlite(&database, &table).unwrap();
+        // The table already exists.
+        assert!(file.to_sqlite(&database, &table).is_err());
+
+        let read = CSVFile::from_sqlite(&database, "SELECT * FROM people ORDER BY id").unwrap();
+        assert_eq!(read.columns, vec!["id", "name", "active", "score"]);
+        assert_eq!(
+            read.rows,
+            vec![
+                vec!["1", "O'Brien, \"Jr\"", "1", ""],
+                vec!["2", "Yoshiip", "0", "4.5"],
+            ]
+        );
+        let typed = CSVFile::from_sqlite(
+            &database,
+            "SELECT typeof(id), typeof(score) FROM people WHERE id = 2",
+        )
+        .unwrap();
+        assert_eq!(typed.rows, vec![vec!["integer", "real"]]);
+
+        let empty = CSVFile::from_sqlite(&database, "SELECT * FROM people WHERE id > 2").unwrap();
+        assert!(empty.columns.is_empty());
+        assert!(CSVFile::from_sqlite(&database, "SELECT * FROM missing").is_err());
+
+        let schema = CSVSchema::default().with_column("id", ColumnType::Integer, true);
+        assert!(file
+            .to_sqlite_with_schema(&database, &"partial".to_string(), &schema)
+            .is_err());
+        fs::remove_file(&database).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "arrow")]
+    fn test_record_batch() {
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::{Date32Type, Float64Type, Int64Type};
+        use arrow_schema::DataType;
+
+        let text =
+            "id,name,score,active,since\n1,Thomas,2.5,yes,1970-01-02\n2,,,no,\n3,John,1e3,true\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let batch = file.to_record_batch().unwrap();
+        let types: Vec<&DataType> = batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|field| field.data_type())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                &DataType::Int64,
+                &DataType::Utf8,
+                &DataType::Float64,
+                &DataType::Boolean,
+                &DataType::Date32
+            ]
+        );
+        assert!(!batch.schema_ref().field(0).is_nullable());
+        assert_eq!(batch.column(0).as_primitive::<Int64Type>().value(2), 3);
+        // the empty text is kept, the other empty values are nulls
+        assert_eq!(batch.column(1).as_string::<i32>().value(1), "");
+        assert!(batch.column(2).is_null(1));
+        assert_eq!(
+            batch.column(2).as_primitive::<Float64Type>().value(2),
+            1000.0
+        );
+        assert!(batch.column(3).as_boolean().value(0));
+        assert_eq!(batch.column(4).as_primitive::<Date32Type>().value(0), 1);
+        assert!(batch.column(4).is_null(2));
+
+        let back = CSVFile::from_record_batch(&batch).unwrap();
+        assert_eq!(back.columns, file.columns);
+        assert_eq!(
+            back.rows,
+            vec![
+                vec!["1", "Thomas", "2.5", "true", "1970-01-02"],
+                vec!["2", "", "", "false", ""],
+                vec!["3", "John", "1000.0", "true", ""],
+            ]
+        );
+
+        let schema = CSVSchema::default()
+            .with_column("id", ColumnType::Integer, true)
+            .with_column("name", ColumnType::Integer, false);
+        assert!(file.to_record_batch_with_schema(&schema).is_err());
+        let schema = schema
+            .with_column("score", ColumnType::Number, false)
+            .with_column("active", ColumnType::Boolean, false)
+            .with_column("since", ColumnType::Date, true);
+        // the names aren't integers, and a required date is missing
+        let error = file.to_record_batch_with_schema(&schema).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[cfg(feature = "polars")]
+    fn test_polars() {
+        use polars_core::prelude::DataType;
+
+        let text =
+            "id,name,score,active,since\n1,Thomas,2.5,yes,1970-01-02\n2,,,no,\n3,John,1e3,true\n";
+        let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+
+        let frame = file.to_polars().unwrap();
+        let types: Vec<&DataType> = frame.columns().iter().map(|c| c.dtype()).collect();
+        assert_eq!(
+            types,
+            vec![
+                &DataType::Int64,
+                &DataType::String,
+                &DataType::Float64,
+                &DataType::Boolean,
+                &DataType::Date
+            ]
+        );
+        // the empty text is kept, the other empty values are nulls
+        assert_eq!(frame.column("name").unwrap().null_count(), 0);
+        assert_eq!(frame.column("score").unwrap().null_count(), 1);
+        assert_eq!(frame.column("since").unwrap().null_count(), 2);
+
+        let back = CSVFile::from_polars(&frame).unwrap();
+        assert_eq!(back.columns, file.columns);
+        assert_eq!(
+            back.rows,
+            vec![
+                vec!["1", "Thomas", "2.5", "true", "1970-01-02"],
+                vec!["2", "", "", "false", ""],
+                vec!["3", "John", "1000.0", "true", ""],
+            ]
+        );
+
+        let schema = CSVSchema::default()
+            .with_column("id", ColumnType::Integer, true)
+            .with_column("name", ColumnType::Integer, false);
+        assert!(file.to_polars_with_schema(&schema).is_err());
+        let schema = schema
+            .with_column("score", ColumnType::Number, false)
+            .with_column("active", ColumnType::Boolean, false)
+            .with_column("since", ColumnType::Date, false);
+        let error = file.to_polars_with_schema(&schema).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_write_parquet() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let filename = String::from("test_write_parquet.parquet");
+        let file =
+            CSVFile::from_text("name,age\nThomas,20\nJohn,\n", &ReaderOptions::default()).unwrap();
+        file.write_parquet(&filename).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&filename).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let read = CSVFile::from_record_batch(&batches[0]).unwrap();
+        assert_eq!(read.columns, file.columns);
+        assert_eq!(read.rows, file.rows);
+        fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_sql_inserts() {
+        let columns = vec!["na`me".to_string(), "path".to_string()];
+        let rows = vec![
+            vec!["it's".to_string(), r"C:\dir".to_string()],
+            vec!["b".to_string(), "".to_string()],
+            vec!["c".to_string(), "3".to_string()],
+        ];
+        let csv_file = CSVFile::build(&columns, &rows, &',').unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        csv_file
+            .write_sql_inserts(&mut output, &"t".to_string(), SqlDialect::MySQL, 2)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "INSERT INTO `t` (`na``me`, `path`) VALUES\n('it''s', 'C:\\\\dir'),\n('b', '');\nINSERT INTO `t` (`na``me`, `path`) VALUES\n('c', '3');\n"
+        );
+
+        let sql = csv_file.to_sql_inserts(&"t".to_string(), SqlDialect::SQLite);
+        assert!(sql
+            .starts_with("INSERT INTO \"t\" (\"na`me\", \"path\") VALUES\n('it''s', 'C:\\dir'),"));
+
+        let empty = CSVFile::build(&columns, &vec![], &',').unwrap();
+        assert_eq!(
+            empty.to_sql_inserts(&"t".to_string(), SqlDialect::Postgres),
+            ""
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_fixed_width_round_trip() {
+        use crate::fixed_width::{self, FixedWidthColumn, FixedWidthSpec};
+
+        let spec = FixedWidthSpec {
+            fill: '_',
+            skip_first_line: true,
+            ..FixedWidthSpec::new(vec![
+                FixedWidthColumn::new("id", 4),
+                FixedWidthColumn::new("city", 6),
+            ])
+        };
+        let filename = String::from("test_fixed_width.txt");
+        fs::write(&filename, "HEADER\n0001Paris_\n0002Lyon__\n\n0003Nice\n").unwrap();
+        let csv_file = fixed_width::read(&filename, &spec).unwrap();
+        assert_eq!(csv_file.columns, vec!["id", "city"]);
+        assert_eq!(
+            csv_file.rows,
+            vec![
+                vec!["0001", "Paris"],
+                vec!["0002", "Lyon"],
+                vec!["0003", "Nice"]
+            ]
+        );
+
+        fixed_width::write(&csv_file, &filename, &spec).unwrap();
+        let contents = fs::read_to_string(&filename).unwrap();
+        assert_eq!(contents, "0001Paris_\n0002Lyon__\n0003Nice__\n");
+        fs::remove_file(&filename).unwrap();
+
+        let too_narrow = FixedWidthSpec::new(vec![FixedWidthColumn::new("city", 4)]);
+        assert!(fixed_width::to_string(&csv_file, &too_narrow).is_err());
+        let unknown = FixedWidthSpec::new(vec![FixedWidthColumn::new("zip", 5)]);
+        assert!(fixed_width::to_string(&csv_file, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_parse_quoted_records() {
+        let records =
+            crate::paste::parse_quoted_records("a,\"b,\"\"c\"\"\"\n5\" screen,\"x\ny\"", &',')
+                .unwrap();
+        assert_eq!(
+            records,
+            vec![vec!["a", "b,\"c\""], vec!["5\" screen", "x\ny"]]
+        );
+
+        let records = crate::paste::parse_quoted_records("a\r\n\r\nb\r\n", &',').unwrap();
+        assert_eq!(records, vec![vec!["a"], vec![""], vec!["b"]]);
+
+        let records = crate::paste::parse_quoted_records("\"x\r\n\ry\"\rz", &',').unwrap();
+        assert_eq!(records, vec![vec!["x\r\n\ry"], vec!["z"]]);
+
+        assert!(crate::paste::parse_quoted_records("\"a", &',').is_err());
+        assert!(crate::paste::parse_quoted_records("a\n\"b\nc", &',').is_err());
+    }
+
+    #[test]
+    fn test_from_pasted_text() {
+        let pasted = "a\tb\r\n1\t\"x\ty\"\r\n\t\\n\r\n";
+        let csv_file = CSVFile::from_pasted_text(pasted).unwrap();
+        assert_eq!(csv_file.rows, vec![vec!["1", "x\ty"], vec!["", "\\n"]]);
+
+        assert!(CSVFile::from_pasted_text("").is_err());
+        assert!(CSVFile::from_pasted_text("a\tb\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_column_stats() {
+        let columns = vec!["name".to_string(), "score".to_string()];
+        let data = vec![
+            vec!["a".to_string(), "10".to_string()],
+            vec!["b".to_string(), "".to_string()],
+            vec!["a".to_string(), "-2.5".to_string()],
+            vec!["c".to_string(), "n/a".to_string()],
+        ];
+        let csv_file = CSVFile::build(&columns, &data, &',').unwrap();
+
+        let stats = csv_file.column_stats(&"score".to_string()).unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.empty, 1);
+        assert_eq!(stats.distinct, 4);
+        assert_eq!(stats.numeric, 2);
+        assert_eq!(stats.sum, Some(7.5));
+        assert_eq!(stats.min, Some(-2.5));
+        assert_eq!(stats.max, Some(10.0));
+        assert_eq!(stats.mean, Some(3.75));
+
+        let stats = csv_file.column_stats(&"name".to_string()).unwrap();
+        assert_eq!(stats.distinct, 3);
+        assert_eq!(stats.mean, None);
+
+        assert!(csv_file.column_stats(&"unknown".to_string()).is_none());
+        assert_eq!(
+            csv_file.numeric_values(&"score".to_string()),
+            Some(vec![10.0, -2.5])
+        );
+    }
 }
@@ -0,0 +1,360 @@
+//! Computations on the rows of files ordered by time.
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+
+use crate::stats::parse_number;
+use crate::CSVFile;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// The length of the periods produced by [resample](`CSVFile::resample`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Seconds(u32),
+    Minutes(u32),
+    Hours(u32),
+    Days(u32),
+}
+
+impl Interval {
+    /// Returns the length of the interval in seconds (at least one).
+    pub fn as_seconds(&self) -> i64 {
+        let seconds = match self {
+            Interval::Seconds(n) => *n as i64,
+            Interval::Minutes(n) => *n as i64 * 60,
+            Interval::Hours(n) => *n as i64 * 3600,
+            Interval::Days(n) => *n as i64 * SECONDS_PER_DAY,
+        };
+        seconds.max(1)
+    }
+}
+
+/// How several numbers are combined into one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    /// The number of numeric values.
+    Count,
+    /// The first numeric value, in the order of the rows.
+    First,
+    /// The last numeric value, in the order of the rows.
+    Last,
+}
+
+impl Aggregation {
+    /// Combines the numbers, or returns `None` if there are none (except for `Count`).
+    pub fn apply(&self, numbers: &[f64]) -> Option<f64> {
+        if numbers.is_empty() {
+            return if *self == Aggregation::Count {
+                Some(0.0)
+            } else {
+                None
+            };
+        }
+        Some(match self {
+            Aggregation::Sum => numbers.iter().sum(),
+            Aggregation::Mean => numbers.iter().sum::<f64>() / numbers.len() as f64,
+            Aggregation::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregation::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregation::Count => numbers.len() as f64,
+            Aggregation::First => numbers[0],
+            Aggregation::Last => numbers[numbers.len() - 1],
+        })
+    }
+}
+
+/// Returns the number of days between 1970-01-01 and the given date
+/// (see <http://howardhinnant.github.io/date_algorithms.html>).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the year, month and day of the given number of days since 1970-01-01.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Reads a fixed number of digits.
+fn digits(text: &str, range: std::ops::Range<usize>) -> Option<i64> {
+    let part = text.get(range)?;
+    if part.bytes().all(|b| b.is_ascii_digit()) {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Parses a timestamp into a number of seconds since 1970-01-01 00:00:00 UTC.
+///
+/// The accepted formats are a Unix timestamp in seconds, a date (`2024-03-01`),
+/// or a date and a time separated by a space or a `T` (`2024-03-01 14:30`, `2024-03-01T14:30:05`),
+/// optionally followed by fractions of a second (ignored) and by `Z` or an offset like `+02:00`.
+pub fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Some(seconds);
+    }
+
+    if value.get(4..5)? != "-" || value.get(7..8)? != "-" {
+        return None;
+    }
+    let (year, month, day) = (
+        digits(value, 0..4)?,
+        digits(value, 5..7)?,
+        digits(value, 8..10)?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+
+    let rest = &value[10..];
+    if rest.is_empty() {
+        return Some(seconds);
+    }
+    if !rest.starts_with([' ', 'T']) || rest.get(3..4)? != ":" {
+        return None;
+    }
+    let (hours, minutes) = (digits(rest, 1..3)?, digits(rest, 4..6)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    seconds += hours * 3600 + minutes * 60;
+
+    let mut rest = &rest[6..];
+    if rest.starts_with(':') {
+        let secs = digits(rest, 1..3)?;
+        if secs > 60 {
+            return None;
+        }
+        seconds += secs;
+        rest = &rest[3..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+    }
+
+    match rest {
+        "" | "Z" => Some(seconds),
+        offset if offset.len() == 6 && offset.get(3..4)? == ":" => {
+            let shift = digits(offset, 1..3)? * 3600 + digits(offset, 4..6)? * 60;
+            match &offset[..1] {
+                "+" => Some(seconds - shift),
+                "-" => Some(seconds + shift),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Writes a number of seconds since 1970-01-01 as `YYYY-MM-DD HH:MM:SS` (UTC),
+/// or as `YYYY-MM-DD` if `date_only` is `true`.
+pub fn format_timestamp(seconds: i64, date_only: bool) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    if date_only {
+        return format!("{:04}-{:02}-{:02}", year, month, day);
+    }
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+impl CSVFile {
+    /// Groups the rows into periods of the given length, according to a timestamp column,
+    /// and combines the numbers of every other column with the given aggregation.
+    ///
+    /// The periods start at 1970-01-01 00:00:00 UTC and only the periods containing rows
+    /// are returned, sorted by time. The timestamp column of the result holds
+    /// the start of each period, written as a date when the interval is a number of days.
+    /// The values that aren't numbers are ignored, as are the values of the ragged rows
+    /// beyond the last column, and a period without any number in a column gets an empty value.
+    ///
+    /// It may return an error if the column doesn't exist, if a row doesn't have a timestamp,
+    /// or if a value can't be read by [parse_timestamp](`parse_timestamp`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{Aggregation, CSVFile, Interval, ReaderOptions};
+    /// let text = "time,requests\n\
+    ///     2024-03-01 10:05,4\n\
+    ///     2024-03-01 10:40,6\n\
+    ///     2024-03-01 12:15,1\n";
+    /// let file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let hourly = file.resample(&"time".to_string(), Interval::Hours(1), Aggregation::Sum).unwrap();
+    /// assert_eq!(hourly.rows, vec![
+    ///     vec!["2024-03-01 10:00:00", "10"],
+    ///     vec!["2024-03-01 12:00:00", "1"],
+    /// ]);
+    /// ```
+    pub fn resample(
+        &self,
+        timestamp_column: &String,
+        interval: Interval,
+        aggregation: Aggregation,
+    ) -> Result<CSVFile, Error> {
+        let time_idx = self.get_column_idx(timestamp_column).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", timestamp_column),
+            )
+        })?;
+        let length = interval.as_seconds();
+
+        let mut buckets: BTreeMap<i64, Vec<Vec<f64>>> = BTreeMap::new();
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            let value = row.get(time_idx).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The row of index {} doesn't have a value in the column {}",
+                        row_idx, timestamp_column
+                    ),
+                )
+            })?;
+            let seconds = parse_timestamp(value).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The value \"{}\" of the column {} isn't a timestamp",
+                        value, timestamp_column
+                    ),
+                )
+            })?;
+            let numbers = buckets
+                .entry(seconds.div_euclid(length) * length)
+                .or_insert_with(|| vec![Vec::new(); self.len()]);
+            for (i, value) in row.iter().enumerate().take(self.len()) {
+                if let Some(number) = parse_number(value).filter(|_| i != time_idx) {
+                    numbers[i].push(number);
+                }
+            }
+        }
+
+        let date_only = length % SECONDS_PER_DAY == 0;
+        let rows = buckets
+            .into_iter()
+            .map(|(start, numbers)| {
+                numbers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, numbers)| {
+                        if i == time_idx {
+                            format_timestamp(start, date_only)
+                        } else {
+                            aggregation
+                                .apply(numbers)
+                                .map(|n| n.to_string())
+                                .unwrap_or_default()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(CSVFile::from_rows(
+            self.delimiter,
+            self.columns.clone(),
+            rows,
+        ))
+    }
+
+    /// Adds a column holding a computation over a sliding window of rows of a numeric column:
+    /// each value combines the numbers of the current row and of the `window - 1` rows before it.
+    /// The values that aren't numbers, as well as the values missing from the ragged rows,
+    /// are ignored, and the first rows, which don't have enough rows before them
+    /// to fill the window, get an empty value.
+    ///
+    /// It may return an error if the column doesn't exist, if the new column already exists,
+    /// or if the window is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{Aggregation, CSVFile, ReaderOptions};
+    /// let text = "temperature\n10\n14\n12\n16\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// file.rolling(&"temperature".to_string(), 2, Aggregation::Mean, &"mean".to_string()).unwrap();
+    /// let means: Vec<&str> = file.rows.iter().map(|row| row[1].as_str()).collect();
+    /// assert_eq!(means, vec!["", "12", "13", "14"]);
+    /// ```
+    pub fn rolling(
+        &mut self,
+        column_name: &String,
+        window: usize,
+        aggregation: Aggregation,
+        new_column: &String,
+    ) -> Result<(), Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+        if window == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The window must contain at least one row",
+            ));
+        }
+
+        let numbers: Vec<Option<f64>> = self
+            .rows
+            .iter()
+            .map(|row| row.get(idx).and_then(|value| parse_number(value)))
+            .collect();
+        let values: Vec<String> = (0..numbers.len())
+            .map(|i| {
+                if i + 1 < window {
+                    return String::new();
+                }
+                let window: Vec<f64> = numbers[i + 1 - window..=i]
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .collect();
+                aggregation
+                    .apply(&window)
+                    .map(|n| n.to_string())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        self.add_column(new_column)?;
+        if let Err(error) = self.fill_column(new_column, &values) {
+            self.remove_column(self.len() - 1)?;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Conversion of the amounts and measures exported with their symbols into plain numbers.
+
+use std::io::{Error, ErrorKind};
+
+use crate::CSVFile;
+
+/// Converts an amount such as `$1,234.50`, `1.234,50 €` or `(12.00)` into a plain number
+/// (`1234.50`, `1234.50` and `-12.00`), keeping all its digits.
+/// It returns `None` if the value doesn't hold a number.
+fn parse_amount(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    // Accountants write the negative amounts between parentheses.
+    let (negative, trimmed) = match trimmed.strip_prefix('(') {
+        Some(inner) => (true, inner.strip_suffix(')')?),
+        None => (false, trimmed),
+    };
+
+    // The symbols and the codes, such as `$` or `EUR`, and the sign
+    // are around the number, which can also start with its decimal separator.
+    let chars: Vec<char> = trimmed.chars().collect();
+    let start = (0..chars.len()).find(|&i| {
+        chars[i].is_ascii_digit()
+            || (matches!(chars[i], '.' | ',') && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+    })?;
+    let end = chars.iter().rposition(char::is_ascii_digit)?;
+    let negative = negative || chars[..start].contains(&'-') || chars[end + 1..].contains(&'-');
+
+    let mut number = String::new();
+    for &c in &chars[start..=end] {
+        match c {
+            '0'..='9' | '.' | ',' => number.push(c),
+            // The spaces and the apostrophes separate the thousands in some countries.
+            '\'' => {}
+            c if c.is_whitespace() => {}
+            _ => return None,
+        }
+    }
+
+    // The last separator is the decimal one, unless it's repeated or it looks like
+    // it separates the thousands (a single comma followed by three digits).
+    let last_separator = number.rfind(['.', ',']);
+    let decimal = last_separator.filter(|&i| {
+        let separator = number.as_bytes()[i] as char;
+        let repeated = number.matches(separator).count() > 1;
+        let other = if separator == '.' { ',' } else { '.' };
+        let thousands = separator == ',' && !number.contains(other) && number.len() - i - 1 == 3;
+        !repeated && !thousands
+    });
+    let (integer, fraction) = match decimal {
+        Some(i) => (&number[..i], &number[i + 1..]),
+        None => (number.as_str(), ""),
+    };
+    let integer: String = integer.chars().filter(char::is_ascii_digit).collect();
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut amount = String::new();
+    if negative {
+        amount.push('-');
+    }
+    amount.push_str(if integer.is_empty() { "0" } else { &integer });
+    if !fraction.is_empty() {
+        amount.push('.');
+        amount.push_str(fraction);
+    }
+    Some(amount)
+}
+
+impl CSVFile {
+    /// Removes the given units from the beginning and the end of the values of a column,
+    /// along with the whitespace around them, such as `$` in `$ 12` or `kg` in `12kg`.
+    /// The values without any of the units are left untouched,
+    /// and the values missing from the ragged rows are left empty.
+    /// It may return an error if the column doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let mut file = CSVFile::from_text("weight\n12kg\n 3.5 kg\n7\n", &ReaderOptions::default()).unwrap();
+    /// file.strip_units(&"weight".to_string(), &["kg", "g"]).unwrap();
+    /// assert_eq!(file.rows, vec![vec!["12"], vec!["3.5"], vec!["7"]]);
+    /// ```
+    pub fn strip_units(&mut self, column_name: &String, units: &[&str]) -> Result<(), Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+
+        let values: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let value = row.get(idx).map_or("", String::as_str);
+                let mut stripped = value.trim();
+                if let Some(rest) = units.iter().find_map(|unit| stripped.strip_prefix(unit)) {
+                    stripped = rest.trim_start();
+                }
+                if let Some(rest) = units.iter().find_map(|unit| stripped.strip_suffix(unit)) {
+                    stripped = rest.trim_end();
+                }
+                if stripped.len() == value.trim().len() {
+                    value.to_string()
+                } else {
+                    stripped.to_string()
+                }
+            })
+            .collect();
+        self.fill_column(column_name, &values)
+    }
+
+    /// Converts the amounts of a column into plain numbers, removing the currency symbols
+    /// and codes and the thousands separators, such as `$1,234.50` or `1 234,50 €`
+    /// which both become `1234.50`. The amounts between parentheses are negative,
+    /// as in the accounting exports. The digits are kept as they are,
+    /// so the amounts don't lose any precision. The empty values stay empty,
+    /// as do the values missing from the ragged rows.
+    ///
+    /// A single comma followed by three digits, as in `1,234`, is a thousands separator,
+    /// otherwise the last separator is the decimal one.
+    ///
+    /// It may return an error if the column doesn't exist, or if a value isn't an amount,
+    /// in which case the column isn't modified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "price\n\"$1,234.50\"\n\"1.234,50 €\"\n(12.00)\nEUR -3\n\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    /// file.parse_currency(&"price".to_string()).unwrap();
+    /// assert_eq!(file.rows, vec![
+    ///     vec!["1234.50"],
+    ///     vec!["1234.50"],
+    ///     vec!["-12.00"],
+    ///     vec!["-3"],
+    ///     vec![""],
+    /// ]);
+    /// ```
+    pub fn parse_currency(&mut self, column_name: &String) -> Result<(), Error> {
+        let idx = self.get_column_idx(column_name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", column_name),
+            )
+        })?;
+
+        let values = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let value = row.get(idx).map_or("", String::as_str);
+                if value.trim().is_empty() {
+                    return Ok(String::new());
+                }
+                parse_amount(value).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The value \"{}\" of the column {} in the row of index {} isn't an amount",
+                            value, column_name, i
+                        ),
+                    )
+                })
+            })
+            .collect::<Result<Vec<String>, Error>>()?;
+        self.fill_column(column_name, &values)
+    }
+}
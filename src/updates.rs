@@ -0,0 +1,216 @@
+//! Modifications of the rows in the manner of SQL: `UPDATE`, `DELETE` and upserts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Error, ErrorKind};
+
+use crate::{CSVFile, ChangeEvent};
+
+/// Returns an error if a row doesn't have the expected number of values.
+fn check_length(row: &[String], expected: usize) -> Result<(), Error> {
+    if row.len() != expected {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Invalid number of fields, {} were given, but expected {}",
+                row.len(),
+                expected
+            ),
+        ));
+    }
+    Ok(())
+}
+
+impl CSVFile {
+    /// Modifies all the rows matching the predicate, like `UPDATE ... WHERE` in SQL,
+    /// and returns the number of rows that were actually changed.
+    ///
+    /// The modified rows are checked by the [validators](`crate::Validator`) before any of them
+    /// is replaced, so the file is left untouched if one of them is rejected,
+    /// and each modified cell is reported to the [observers](`CSVFile::on_change`).
+    ///
+    /// It may return an error if a modified row doesn't have as many values as there are columns,
+    /// or if it's rejected by a validator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "name,status\nThomas,active\nYoshiip,inactive\nJohn,inactive\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let changed = file
+    ///     .update_where(|row| row[1] == "inactive", |row| row[1] = "archived".to_string())
+    ///     .unwrap();
+    /// assert_eq!(changed, 2);
+    /// assert_eq!(file.rows[2], vec!["John", "archived"]);
+    /// ```
+    pub fn update_where<P, F>(&mut self, predicate: P, mut update: F) -> Result<usize, Error>
+    where
+        P: Fn(&Vec<String>) -> bool,
+        F: FnMut(&mut Vec<String>),
+    {
+        let mut updated: Vec<(usize, Vec<String>)> = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            if !predicate(row) {
+                continue;
+            }
+            let mut new_row = row.clone();
+            update(&mut new_row);
+            check_length(&new_row, self.len())?;
+            if new_row != *row {
+                self.validate_row(&new_row)?;
+                updated.push((i, new_row));
+            }
+        }
+
+        let changed = updated.len();
+        for (i, new_row) in updated {
+            self.replace_row(i, new_row);
+        }
+
+        Ok(changed)
+    }
+
+    /// Removes all the rows matching the predicate, like `DELETE ... WHERE` in SQL,
+    /// and returns the indexes they had before the removal, in increasing order,
+    /// so that the removed rows can be reported.
+    ///
+    /// Each removed row is reported to the [observers](`CSVFile::on_change`),
+    /// starting with the last one, so that the index of each event is valid when it's received.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "email,name\nt@x.org,Thomas\n,Yoshiip\nj@x.org,John\n,Anna\n";
+    /// let mut file = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let removed = file.delete_where(|row| row[0].is_empty());
+    /// assert_eq!(removed, vec![1, 3]);
+    /// assert_eq!(file.rows, vec![vec!["t@x.org", "Thomas"], vec!["j@x.org", "John"]]);
+    /// ```
+    pub fn delete_where<P>(&mut self, predicate: P) -> Vec<usize>
+    where
+        P: Fn(&Vec<String>) -> bool,
+    {
+        let mut removed: Vec<usize> = Vec::new();
+        let mut new_indexes: Vec<Option<usize>> = Vec::with_capacity(self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            if predicate(row) {
+                removed.push(i);
+                new_indexes.push(None);
+            } else {
+                new_indexes.push(Some(i - removed.len()));
+            }
+        }
+        if removed.is_empty() {
+            return removed;
+        }
+
+        let mut i = 0;
+        self.rows.retain(|_| {
+            i += 1;
+            new_indexes[i - 1].is_some()
+        });
+        self.remap_source(|r| new_indexes.get(r).copied().flatten(), Some);
+        for &row in removed.iter().rev() {
+            self.emit(ChangeEvent::RowRemoved { row });
+        }
+
+        removed
+    }
+
+    /// Replaces the rows whose value in the key column matches the one of a given row,
+    /// and appends the given rows whose key doesn't exist yet, so that the file can be used
+    /// as a small database (e.g. a list of contacts updated from another export).
+    /// It returns the number of rows that were replaced and the number of rows that were appended.
+    ///
+    /// Only the first row with a given key is replaced. If several given rows have the same key,
+    /// the last one wins. The existing rows too short to have a key are never replaced. The rows are checked by the [validators](`crate::Validator`)
+    /// before the file is modified, so the file is left untouched if one of them is rejected.
+    ///
+    /// It may return an error if the key column doesn't exist, if a given row doesn't have
+    /// as many values as there are columns, or if it's rejected by a validator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, ReaderOptions};
+    /// let text = "email,name\nt@x.org,Thomas\ny@x.org,Yoshiip\n";
+    /// let mut contacts = CSVFile::from_text(text, &ReaderOptions::default()).unwrap();
+    ///
+    /// let rows = vec![
+    ///     vec!["y@x.org".to_string(), "Yoshiip Smith".to_string()],
+    ///     vec!["j@x.org".to_string(), "John".to_string()],
+    /// ];
+    /// let (updated, appended) = contacts.upsert_rows(&"email".to_string(), &rows).unwrap();
+    /// assert_eq!((updated, appended), (1, 1));
+    /// assert_eq!(contacts.rows[1], vec!["y@x.org", "Yoshiip Smith"]);
+    /// assert_eq!(contacts.rows[2], vec!["j@x.org", "John"]);
+    /// ```
+    pub fn upsert_rows(
+        &mut self,
+        key_column: &String,
+        rows: &Vec<Vec<String>>,
+    ) -> Result<(usize, usize), Error> {
+        let key_idx = self.get_column_idx(key_column).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The column {} doesn't exist", key_column),
+            )
+        })?;
+
+        let mut index: HashMap<&String, usize> = HashMap::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            if let Some(key) = row.get(key_idx) {
+                index.entry(key).or_insert(i);
+            }
+        }
+        let existing = self.rows.len();
+        let mut updated: BTreeMap<usize, &Vec<String>> = BTreeMap::new();
+        let mut appended: Vec<&Vec<String>> = Vec::new();
+        for row in rows {
+            check_length(row, self.len())?;
+            let key = &row[key_idx];
+            match index.get(key) {
+                Some(&i) if i < existing => {
+                    updated.insert(i, row);
+                }
+                Some(&i) => appended[i - existing] = row,
+                None => {
+                    index.insert(key, existing + appended.len());
+                    appended.push(row);
+                }
+            }
+        }
+        for row in updated.values().chain(appended.iter()) {
+            self.validate_row(row)?;
+        }
+
+        let counts = (updated.len(), appended.len());
+        let updated: Vec<(usize, Vec<String>)> = updated
+            .into_iter()
+            .map(|(i, row)| (i, row.clone()))
+            .collect();
+        let appended: Vec<Vec<String>> = appended.into_iter().cloned().collect();
+        for (i, row) in updated {
+            self.replace_row(i, row);
+        }
+        for row in appended {
+            self.rows.push(row);
+            self.emit(ChangeEvent::RowAdded {
+                row: self.rows.len() - 1,
+            });
+        }
+
+        Ok(counts)
+    }
+
+    /// Replaces a row, and reports each modified cell to the observers.
+    pub(crate) fn replace_row(&mut self, row_idx: usize, new_row: Vec<String>) {
+        let old_row = std::mem::replace(&mut self.rows[row_idx], new_row);
+        if self.is_observed() {
+            self.emit_row_changes(row_idx, &old_row);
+        }
+    }
+}
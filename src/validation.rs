@@ -0,0 +1,129 @@
+//! The report produced by [CSVFile::check_validity](`crate::CSVFile::check_validity`).
+
+use std::collections::HashMap;
+
+use crate::{CSVCoords, CSVFile};
+
+/// A column name used more than once, with the indexes of all its occurrences.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateColumn {
+    pub name: String,
+    pub indexes: Vec<usize>,
+}
+
+/// A row that doesn't have as many fields as there are columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaggedRow {
+    pub row: usize,
+    pub length: usize,
+}
+
+/// The result of the validation of a CSV file.
+///
+/// Duplicated column names and ragged rows make the file invalid.
+/// Empty column names and values surrounded by whitespace are only
+/// reported as warnings, since they are allowed but often unintended.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The column names used more than once.
+    pub duplicate_columns: Vec<DuplicateColumn>,
+    /// The rows that don't have the right number of fields.
+    pub ragged_rows: Vec<RaggedRow>,
+    /// The indexes of the columns that have an empty name.
+    pub empty_columns: Vec<usize>,
+    /// The indexes of the columns whose name starts or ends with whitespace.
+    pub columns_with_whitespace: Vec<usize>,
+    /// The cells whose value starts or ends with whitespace.
+    pub cells_with_whitespace: Vec<CSVCoords>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if there are no duplicated columns and no ragged rows.
+    pub fn is_valid(&self) -> bool {
+        self.duplicate_columns.is_empty() && self.ragged_rows.is_empty()
+    }
+
+    /// Returns `true` if some columns have an empty name or if some values
+    /// are surrounded by whitespace.
+    pub fn has_warnings(&self) -> bool {
+        !self.empty_columns.is_empty()
+            || !self.columns_with_whitespace.is_empty()
+            || !self.cells_with_whitespace.is_empty()
+    }
+}
+
+/// Returns `true` if the value starts or ends with whitespace.
+fn has_surrounding_whitespace(value: &str) -> bool {
+    value.starts_with(char::is_whitespace) || value.ends_with(char::is_whitespace)
+}
+
+impl CSVFile {
+    /// Checks if the CSV file is valid and reports the problems that were found.
+    /// It checks for duplicates in the columns and if the rows have the right length,
+    /// and it also warns about empty column names and values surrounded by whitespace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CSVFile;
+    /// let columns = vec!["a".to_string(), "a".to_string(), " b".to_string()];
+    /// let rows = vec![vec!["1".to_string(), "2".to_string(), "3".to_string()]];
+    /// let mut file = CSVFile::build(&columns, &rows, &',').unwrap();
+    /// file.rows[0].pop();
+    ///
+    /// let report = file.check_validity();
+    /// assert!(!report.is_valid());
+    /// assert_eq!(report.duplicate_columns[0].indexes, vec![0, 1]);
+    /// assert_eq!(report.ragged_rows[0].length, 2);
+    /// assert_eq!(report.columns_with_whitespace, vec![2]);
+    /// ```
+    pub fn check_validity(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        // Check for duplicates in the columns
+        let mut occurrences: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            occurrences.entry(column.as_str()).or_default().push(i);
+            if column.is_empty() {
+                report.empty_columns.push(i);
+            } else if has_surrounding_whitespace(column) {
+                report.columns_with_whitespace.push(i);
+            }
+        }
+        for (name, indexes) in occurrences {
+            if indexes.len() > 1 {
+                report.duplicate_columns.push(DuplicateColumn {
+                    name: name.to_string(),
+                    indexes,
+                });
+            }
+        }
+        report.duplicate_columns.sort_by_key(|d| d.indexes[0]);
+
+        // Make sure the rows have the right length
+        let number_of_columns = self.len();
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.len() != number_of_columns {
+                report.ragged_rows.push(RaggedRow {
+                    row: i,
+                    length: row.len(),
+                });
+            }
+            for (j, cell) in row.iter().enumerate() {
+                if has_surrounding_whitespace(cell) {
+                    report
+                        .cells_with_whitespace
+                        .push(CSVCoords { row: i, column: j });
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Returns `true` if the CSV file is valid,
+    /// meaning there are no duplicates in the columns and all the rows have the right length.
+    pub fn is_valid(&self) -> bool {
+        self.check_validity().is_valid()
+    }
+}
@@ -0,0 +1,174 @@
+//! Validators checking the values written into a [CSVFile](`crate::CSVFile`).
+
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+
+use crate::{CSVCoords, CSVFile};
+
+type ValueCheck = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+type RowCheck = Arc<dyn Fn(&[String]) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+enum Check {
+    Value(ValueCheck),
+    Row(RowCheck),
+}
+
+/// A rule checked every time a row is added or a cell is modified
+/// with [add_row](`CSVFile::add_row`), [fill_column](`CSVFile::fill_column`)
+/// or [set_cell](`CSVFile::set_cell`). A write that breaks a rule is rejected
+/// and leaves the file unchanged.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, Validator};
+/// let columns = vec!["name".to_string(), "age".to_string()];
+/// let mut file = CSVFile::build(&columns, &vec![], &',').unwrap();
+/// file.add_validator(Validator::column(&"age".to_string(), "age must parse as u8", |value| {
+///     value.parse::<u8>().is_ok()
+/// })).unwrap();
+///
+/// assert!(file.add_row(&vec!["Thomas".to_string(), "19".to_string()]).is_ok());
+/// assert!(file.add_row(&vec!["Yoshiip".to_string(), "old".to_string()]).is_err());
+/// assert_eq!(file.count_rows(), 1);
+/// ```
+#[derive(Clone)]
+pub struct Validator {
+    column: Option<String>,
+    description: String,
+    check: Check,
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Validator {{ column: {:?}, description: {:?} }}",
+            self.column, self.description
+        )
+    }
+}
+
+impl Validator {
+    /// Creates a validator checking each value of a column.
+    pub fn column<F>(column_name: &String, description: &str, check: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            column: Some(column_name.clone()),
+            description: description.to_string(),
+            check: Check::Value(Arc::new(check)),
+        }
+    }
+
+    /// Creates a validator checking whole rows,
+    /// for the rules that involve several columns.
+    pub fn row<F>(description: &str, check: F) -> Self
+    where
+        F: Fn(&[String]) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            column: None,
+            description: description.to_string(),
+            check: Check::Row(Arc::new(check)),
+        }
+    }
+
+    /// Returns the column checked by the validator, or `None` if it checks whole rows.
+    pub fn column_name(&self) -> Option<&String> {
+        self.column.as_ref()
+    }
+
+    /// Returns the description of the rule, used in the error messages.
+    pub fn description(&self) -> &String {
+        &self.description
+    }
+}
+
+impl CSVFile {
+    /// Registers a validator, checked on every following call to
+    /// [add_row](`CSVFile::add_row`), [fill_column](`CSVFile::fill_column`)
+    /// and [set_cell](`CSVFile::set_cell`).
+    /// It may return an error if the validator checks a column that doesn't exist.
+    /// The rows that are already in the file aren't checked.
+    pub fn add_validator(&mut self, mut validator: Validator) -> Result<(), Error> {
+        if let Some(column_name) = &validator.column {
+            match self.resolve_column(column_name) {
+                // The validator follows the column even if the aliases change.
+                Some(name) => validator.column = Some(name.clone()),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("The column {} doesn't exist", column_name),
+                    ))
+                }
+            }
+        }
+
+        self.validators.push(validator);
+
+        Ok(())
+    }
+
+    /// Returns the validators registered on the file.
+    pub fn validators(&self) -> &Vec<Validator> {
+        &self.validators
+    }
+
+    /// Removes all the validators of the file.
+    pub fn clear_validators(&mut self) {
+        self.validators.clear();
+    }
+
+    /// Checks a row against all the validators.
+    pub(crate) fn validate_row(&self, row: &[String]) -> Result<(), Error> {
+        for validator in &self.validators {
+            match &validator.check {
+                Check::Value(check) => {
+                    let column_name = validator.column.as_ref().unwrap();
+                    let value = self
+                        .get_column_idx(column_name)
+                        .and_then(|idx| row.get(idx));
+                    if let Some(value) = value.filter(|value| !check(value)) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "The value \"{}\" of the column {} was rejected: {}",
+                                value, column_name, validator.description
+                            ),
+                        ));
+                    }
+                }
+                Check::Row(check) => {
+                    if !check(row) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("The row was rejected: {}", validator.description),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the new value of a cell against the validators of its column,
+    /// and its row against the validators of whole rows.
+    pub(crate) fn validate_cell(
+        &self,
+        coordinates: &CSVCoords,
+        value: &String,
+    ) -> Result<(), Error> {
+        if self.validators.is_empty() {
+            return Ok(());
+        }
+
+        let mut row = self.rows[coordinates.row].clone();
+        row[coordinates.column] = value.clone();
+        self.validate_row(&row)
+    }
+}
@@ -0,0 +1,163 @@
+//! Typed values of the cells, between the raw strings and the structures of the users.
+
+use std::fmt;
+
+use crate::stats::parse_number;
+use crate::timeseries::{format_timestamp, parse_timestamp};
+use crate::{CSVCoords, CSVFile, ColumnType};
+
+/// The value of a cell, with the type it looks like it has.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvValue {
+    /// An empty value.
+    Null,
+    /// A whole number that fits in 64 bits.
+    Int(i64),
+    /// Any other number.
+    Float(f64),
+    /// `true` or `false`, in any case.
+    Bool(bool),
+    /// Any other value, kept as it is.
+    Text(String),
+    /// A date written as `YYYY-MM-DD`, as a number of seconds since 1970-01-01 (midnight, UTC),
+    /// like [parse_timestamp](`crate::parse_timestamp`).
+    Date(i64),
+}
+
+impl CsvValue {
+    /// Parses a value, trying the types from the most specific one: integers, then numbers,
+    /// booleans and dates. The surrounding whitespace is ignored, except for the text,
+    /// and the blank values are null.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::CsvValue;
+    /// assert_eq!(CsvValue::parse(" 42 "), CsvValue::Int(42));
+    /// assert_eq!(CsvValue::parse("4.2"), CsvValue::Float(4.2));
+    /// assert_eq!(CsvValue::parse("TRUE"), CsvValue::Bool(true));
+    /// assert_eq!(CsvValue::parse("1970-01-02"), CsvValue::Date(86400));
+    /// assert_eq!(CsvValue::parse(""), CsvValue::Null);
+    /// assert_eq!(CsvValue::parse("Thomas").to_string(), "Thomas");
+    /// ```
+    pub fn parse(value: &str) -> Self {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            CsvValue::Null
+        } else if let Ok(n) = trimmed.parse::<i64>() {
+            CsvValue::Int(n)
+        } else if let Some(n) = parse_number(trimmed) {
+            CsvValue::Float(n)
+        } else if trimmed.eq_ignore_ascii_case("true") {
+            CsvValue::Bool(true)
+        } else if trimmed.eq_ignore_ascii_case("false") {
+            CsvValue::Bool(false)
+        } else if let Some(seconds) = ColumnType::Date
+            .coerce(trimmed)
+            .and_then(|date| parse_timestamp(&date))
+        {
+            CsvValue::Date(seconds)
+        } else {
+            CsvValue::Text(value.to_string())
+        }
+    }
+
+    /// Returns `true` if the value is [Null](`CsvValue::Null`).
+    pub fn is_null(&self) -> bool {
+        matches!(self, CsvValue::Null)
+    }
+
+    /// Returns the integer, if the value is one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            CsvValue::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if the value is an integer or a float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CsvValue::Int(n) => Some(*n as f64),
+            CsvValue::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean, if the value is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CsvValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the text, if the value is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CsvValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of a column holding the value, or `None` if the value is null.
+    pub fn column_type(&self) -> Option<ColumnType> {
+        match self {
+            CsvValue::Null => None,
+            CsvValue::Int(_) => Some(ColumnType::Integer),
+            CsvValue::Float(_) => Some(ColumnType::Number),
+            CsvValue::Bool(_) => Some(ColumnType::Boolean),
+            CsvValue::Text(_) => Some(ColumnType::Text),
+            CsvValue::Date(_) => Some(ColumnType::Date),
+        }
+    }
+}
+
+/// Formats the value as it would be written in a CSV file,
+/// an empty string for [Null](`CsvValue::Null`).
+impl fmt::Display for CsvValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvValue::Null => Ok(()),
+            CsvValue::Int(n) => write!(f, "{}", n),
+            CsvValue::Float(n) => write!(f, "{}", n),
+            CsvValue::Bool(b) => write!(f, "{}", b),
+            CsvValue::Text(text) => write!(f, "{}", text),
+            CsvValue::Date(seconds) => write!(f, "{}", format_timestamp(*seconds, true)),
+        }
+    }
+}
+
+impl CSVFile {
+    /// Returns the typed value of a cell (see [CsvValue::parse](`CsvValue::parse`)),
+    /// or `None` if the coordinates are out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVCoords, CSVFile, CsvValue, ReaderOptions};
+    /// let file = CSVFile::from_text("name,age\nThomas,19\n", &ReaderOptions::default()).unwrap();
+    /// assert_eq!(file.typed_cell(&CSVCoords { row: 0, column: 1 }), Some(CsvValue::Int(19)));
+    /// assert_eq!(file.typed_cell(&CSVCoords { row: 1, column: 1 }), None);
+    /// ```
+    pub fn typed_cell(&self, coordinates: &CSVCoords) -> Option<CsvValue> {
+        self.get_cell(coordinates)
+            .map(|value| CsvValue::parse(value))
+    }
+
+    /// Returns the typed values of a column (see [CsvValue::parse](`CsvValue::parse`)),
+    /// or `None` if the column doesn't exist.
+    /// The type of each value is guessed on its own, so a column may mix several types.
+    pub fn typed_column(&self, column_name: &String) -> Option<Vec<CsvValue>> {
+        let idx = self.get_column_idx(column_name)?;
+        Some(
+            self.rows
+                .iter()
+                .map(|row| {
+                    row.get(idx)
+                        .map_or(CsvValue::Null, |value| CsvValue::parse(value))
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,177 @@
+//! Several named CSV files managed as a single unit, like the sheets of a workbook.
+
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io::{Error, ErrorKind};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::CSVFile;
+#[cfg(feature = "fs")]
+use crate::{ReaderOptions, WriterOptions};
+
+/// A set of related CSV files (e.g. orders, customers and items), each named like the sheets of a workbook.
+/// The sheets are kept in the order they were added.
+///
+/// With the `fs` feature, a workbook can be read from a directory of CSV files
+/// and written back to one, each sheet being named after its file.
+/// The `zip` feature does the same with zip archives.
+///
+/// # Example
+///
+/// ```
+/// # use csv_tools::{CSVFile, CSVWorkbook, ReaderOptions};
+/// let mut workbook = CSVWorkbook::new();
+/// let orders = CSVFile::from_text("order,customer\n1,c2\n", &ReaderOptions::default()).unwrap();
+/// let customers = CSVFile::from_text("id,name\nc2,Yoshiip\n", &ReaderOptions::default()).unwrap();
+/// workbook.add_sheet(&"orders".to_string(), orders).unwrap();
+/// workbook.add_sheet(&"customers".to_string(), customers).unwrap();
+///
+/// let customers = workbook.sheet(&"customers".to_string()).unwrap().clone();
+/// let orders = workbook.sheet_mut(&"orders".to_string()).unwrap();
+/// orders.enrich_from(&customers, &"customer".to_string(), &"id".to_string(), &vec!["name".to_string()]).unwrap();
+/// assert_eq!(orders.rows, vec![vec!["1", "c2", "Yoshiip"]]);
+/// assert_eq!(workbook.sheet_names(), vec!["orders", "customers"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CSVWorkbook {
+    sheets: Vec<(String, CSVFile)>,
+}
+
+impl CSVWorkbook {
+    /// Creates a workbook without any sheet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sheet after the other ones.
+    /// It may return an error if a sheet with the same name already exists.
+    pub fn add_sheet(&mut self, name: &String, file: CSVFile) -> Result<(), Error> {
+        if self.sheet(name).is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The sheet {} already exists", name),
+            ));
+        }
+        self.sheets.push((name.clone(), file));
+        Ok(())
+    }
+
+    /// Removes a sheet and returns it.
+    pub fn remove_sheet(&mut self, name: &String) -> Option<CSVFile> {
+        let idx = self.sheets.iter().position(|(sheet, _)| sheet == name)?;
+        Some(self.sheets.remove(idx).1)
+    }
+
+    /// Gets a sheet by name.
+    pub fn sheet(&self, name: &String) -> Option<&CSVFile> {
+        self.sheets
+            .iter()
+            .find(|(sheet, _)| sheet == name)
+            .map(|(_, file)| file)
+    }
+
+    /// Gets a sheet by name, to modify it.
+    pub fn sheet_mut(&mut self, name: &String) -> Option<&mut CSVFile> {
+        self.sheets
+            .iter_mut()
+            .find(|(sheet, _)| sheet == name)
+            .map(|(_, file)| file)
+    }
+
+    /// Returns the names of the sheets, in order.
+    pub fn sheet_names(&self) -> Vec<&String> {
+        self.sheets.iter().map(|(name, _)| name).collect()
+    }
+
+    /// Returns an iterator over the sheets and their names, in order.
+    pub fn sheets(&self) -> impl Iterator<Item = (&String, &CSVFile)> {
+        self.sheets.iter().map(|(name, file)| (name, file))
+    }
+
+    /// Returns the number of sheets.
+    pub fn len(&self) -> usize {
+        self.sheets.len()
+    }
+
+    /// Returns `true` if the workbook doesn't have any sheet.
+    pub fn is_empty(&self) -> bool {
+        self.sheets.is_empty()
+    }
+
+    /// Reads all the files of a directory whose extension is `.csv` (in any case),
+    /// each becoming a sheet named after its file without the extension.
+    /// The sheets are sorted by name, and the subdirectories are ignored.
+    ///
+    /// It may return an error if the directory can't be read or if a file can't be parsed.
+    #[cfg(feature = "fs")]
+    pub fn from_dir(directory: &String, options: &ReaderOptions) -> Result<Self, Error> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let is_csv = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+            if is_csv && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut workbook = Self::new();
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let file = CSVFile::new_with_options(&path.to_string_lossy().to_string(), options)?;
+            workbook.add_sheet(&name, file)?;
+        }
+        Ok(workbook)
+    }
+
+    /// Writes each sheet to a file of the directory named after the sheet, with the `.csv` extension,
+    /// replacing the existing files. The directory is created if it doesn't exist.
+    ///
+    /// It may return an error if a sheet name can't be used as a file name
+    /// (if it's empty or contains a path separator), or if a file can't be written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, CSVWorkbook, ReaderOptions, WriterOptions};
+    /// # let directory = std::env::temp_dir().join("csv_tools_workbook_doc").to_string_lossy().to_string();
+    /// let mut workbook = CSVWorkbook::new();
+    /// let items = CSVFile::from_text("sku,price\nA1,9.99\n", &ReaderOptions::default()).unwrap();
+    /// workbook.add_sheet(&"items".to_string(), items).unwrap();
+    /// workbook.write_dir(&directory, &WriterOptions::default()).unwrap();
+    ///
+    /// let workbook = CSVWorkbook::from_dir(&directory, &ReaderOptions::default()).unwrap();
+    /// assert_eq!(workbook.sheet(&"items".to_string()).unwrap().rows, vec![vec!["A1", "9.99"]]);
+    /// # std::fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_dir(&self, directory: &String, options: &WriterOptions) -> Result<(), Error> {
+        for (name, _) in &self.sheets {
+            check_sheet_name(name)?;
+        }
+        fs::create_dir_all(directory)?;
+        for (name, file) in &self.sheets {
+            let path = Path::new(directory).join(format!("{}.csv", name));
+            file.write_with_options(&path.to_string_lossy().to_string(), options)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns an error if a sheet name can't be used as the name of a file.
+#[cfg(feature = "fs")]
+pub(crate) fn check_sheet_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("The sheet name {} can't be used as a file name", name),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,322 @@
+//! Reading and writing the CSV files of a zip archive as the sheets of a workbook.
+//!
+//! Only what's needed for the exports made of several CSV files is supported:
+//! the entries are either stored or compressed with DEFLATE, and the archives
+//! can't be encrypted, span several disks, or use the extensions for files larger than 4 GiB (Zip64).
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::inflate::inflate;
+use crate::timeseries::civil_from_days;
+use crate::workbook::check_sheet_name;
+use crate::{CSVFile, CSVWorkbook, ReaderOptions, WriterOptions};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+/// Tells that the names of the entries are encoded in UTF-8.
+const UTF8_FLAG: u16 = 1 << 11;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// The CRC-32 checksum of the content of an entry.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn invalid_archive(message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Invalid zip archive: {}", message),
+    )
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| invalid_archive("unexpected end of the archive"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| invalid_archive("unexpected end of the archive"))
+}
+
+/// Returns the offset of the record ending the archive,
+/// which is followed by a comment of at most 65535 bytes.
+fn find_end_of_central_directory(data: &[u8]) -> Result<usize, Error> {
+    let last = data
+        .len()
+        .checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)
+        .ok_or_else(|| invalid_archive("the file is too short"))?;
+    let first = last.saturating_sub(u16::MAX as usize);
+    (first..=last)
+        .rev()
+        .find(|&offset| read_u32(data, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or_else(|| invalid_archive("the end of the central directory is missing"))
+}
+
+/// An entry of an archive, as described by the central directory.
+struct Entry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    compressed_size: usize,
+    size: usize,
+    local_header_offset: usize,
+}
+
+impl Entry {
+    /// Returns the decompressed content of the entry, checking its size and checksum.
+    fn content(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.flags & 1 == 1 {
+            return Err(invalid_archive(&format!("{} is encrypted", self.name)));
+        }
+        let offset = self.local_header_offset;
+        if read_u32(data, offset)? != LOCAL_HEADER_SIGNATURE {
+            return Err(invalid_archive(&format!(
+                "the header of {} is missing",
+                self.name
+            )));
+        }
+        // The lengths of the local header may differ from the ones of the central directory.
+        let start = offset
+            + 30
+            + read_u16(data, offset + 26)? as usize
+            + read_u16(data, offset + 28)? as usize;
+        let compressed = data
+            .get(start..start + self.compressed_size)
+            .ok_or_else(|| invalid_archive("unexpected end of the archive"))?;
+        let content = match self.method {
+            STORED => compressed.to_vec(),
+            // The decompression stops as soon as the content is larger than announced.
+            DEFLATED => inflate(compressed, self.size)?,
+            method => {
+                return Err(invalid_archive(&format!(
+                    "the compression method {} of {} isn't supported",
+                    method, self.name
+                )))
+            }
+        };
+        if content.len() != self.size || crc32(&content) != self.crc {
+            return Err(invalid_archive(&format!("{} is corrupted", self.name)));
+        }
+        Ok(content)
+    }
+}
+
+/// Reads the list of the entries of an archive.
+fn read_entries(data: &[u8]) -> Result<Vec<Entry>, Error> {
+    let end = find_end_of_central_directory(data)?;
+    let count = read_u16(data, end + 10)? as usize;
+    let directory_offset = read_u32(data, end + 16)?;
+    if count == u16::MAX as usize || directory_offset == u32::MAX {
+        return Err(invalid_archive("Zip64 archives aren't supported"));
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    let mut offset = directory_offset as usize;
+    for _ in 0..count {
+        if read_u32(data, offset)? != CENTRAL_HEADER_SIGNATURE {
+            return Err(invalid_archive("invalid entry of the central directory"));
+        }
+        let name_length = read_u16(data, offset + 28)? as usize;
+        let extra_length = read_u16(data, offset + 30)? as usize;
+        let comment_length = read_u16(data, offset + 32)? as usize;
+        let name = data
+            .get(offset + 46..offset + 46 + name_length)
+            .ok_or_else(|| invalid_archive("unexpected end of the archive"))?;
+        entries.push(Entry {
+            // The names that aren't flagged as UTF-8 are usually ASCII anyway.
+            name: String::from_utf8_lossy(name).to_string(),
+            flags: read_u16(data, offset + 8)?,
+            method: read_u16(data, offset + 10)?,
+            crc: read_u32(data, offset + 16)?,
+            compressed_size: read_u32(data, offset + 20)? as usize,
+            size: read_u32(data, offset + 24)? as usize,
+            local_header_offset: read_u32(data, offset + 42)? as usize,
+        });
+        offset += 46 + name_length + extra_length + comment_length;
+    }
+    Ok(entries)
+}
+
+/// Returns the time and the date of the current time in the format of MS-DOS used by the archives.
+fn dos_date_time() -> (u16, u16) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let seconds_of_day = seconds.rem_euclid(86_400);
+    let time = ((seconds_of_day / 3600) << 11)
+        | ((seconds_of_day % 3600 / 60) << 5)
+        | ((seconds_of_day % 60) / 2);
+    // The dates before 1980 can't be represented.
+    let date = ((year.clamp(1980, 2107) - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+impl CSVWorkbook {
+    /// Reads all the entries of a zip archive whose name ends with `.csv` (in any case),
+    /// each becoming a sheet named after its file without the directories and the extension,
+    /// in the order of the archive. The other entries are ignored, as well as the metadata
+    /// added by macOS in the `__MACOSX` directory.
+    ///
+    /// It may return an error if the archive can't be read or isn't supported,
+    /// if a file can't be parsed, or if two files have the same name.
+    pub fn from_zip(filename: &String, options: &ReaderOptions) -> Result<Self, Error> {
+        let data = fs::read(filename)?;
+        let mut workbook = Self::new();
+        for entry in read_entries(&data)? {
+            let path = Path::new(&entry.name);
+            let is_csv = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+            if !is_csv || entry.name.ends_with('/') || entry.name.starts_with("__MACOSX/") {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let content = entry.content(&data)?;
+            let file = CSVFile::from_reader(content.as_slice(), options)?;
+            workbook.add_sheet(&name, file)?;
+        }
+        Ok(workbook)
+    }
+
+    /// Writes each sheet to an entry of a new zip archive named after the sheet,
+    /// with the `.csv` extension, replacing the archive if it exists.
+    /// The entries are stored without compression.
+    ///
+    /// It may return an error if a sheet name can't be used as a file name
+    /// (if it's empty or contains a path separator), if the archive can't be written,
+    /// or if it would be too large for an archive without the Zip64 extensions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use csv_tools::{CSVFile, CSVWorkbook, ReaderOptions, WriterOptions};
+    /// # let filename = std::env::temp_dir().join("csv_tools_zip_doc.zip").to_string_lossy().to_string();
+    /// let mut workbook = CSVWorkbook::new();
+    /// let items = CSVFile::from_text("sku,price\nA1,9.99\n", &ReaderOptions::default()).unwrap();
+    /// workbook.add_sheet(&"items".to_string(), items).unwrap();
+    /// workbook.write_zip(&filename, &WriterOptions::default()).unwrap();
+    ///
+    /// let workbook = CSVWorkbook::from_zip(&filename, &ReaderOptions::default()).unwrap();
+    /// assert_eq!(workbook.sheet(&"items".to_string()).unwrap().rows, vec![vec!["A1", "9.99"]]);
+    /// # std::fs::remove_file(&filename).unwrap();
+    /// ```
+    pub fn write_zip(&self, filename: &String, options: &WriterOptions) -> Result<(), Error> {
+        for (name, _) in self.sheets() {
+            check_sheet_name(name)?;
+        }
+        if self.len() >= u16::MAX as usize {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Too many sheets for a zip archive",
+            ));
+        }
+        let too_large = || {
+            Error::new(
+                ErrorKind::InvalidData,
+                "The workbook is too large for a zip archive",
+            )
+        };
+
+        let (time, date) = dos_date_time();
+        let mut writer = BufWriter::new(File::create(filename)?);
+        let mut central_directory: Vec<u8> = Vec::new();
+        let mut offset: u32 = 0;
+        for (name, file) in self.sheets() {
+            let name = format!("{}.csv", name);
+            if name.len() > u16::MAX as usize {
+                return Err(too_large());
+            }
+            let mut content: Vec<u8> = Vec::new();
+            file.write_to(&mut content, options)?;
+            let size = u32::try_from(content.len()).map_err(|_| too_large())?;
+            let crc = crc32(&content);
+
+            // The fields shared by the local header and the central directory,
+            // from the version needed to extract the entry to the length of its extra field.
+            let mut common: Vec<u8> = Vec::with_capacity(26);
+            common.extend_from_slice(&10u16.to_le_bytes());
+            common.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+            common.extend_from_slice(&STORED.to_le_bytes());
+            common.extend_from_slice(&time.to_le_bytes());
+            common.extend_from_slice(&date.to_le_bytes());
+            common.extend_from_slice(&crc.to_le_bytes());
+            common.extend_from_slice(&size.to_le_bytes());
+            common.extend_from_slice(&size.to_le_bytes());
+            common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            common.extend_from_slice(&0u16.to_le_bytes());
+
+            writer.write_all(&LOCAL_HEADER_SIGNATURE.to_le_bytes())?;
+            writer.write_all(&common)?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&content)?;
+
+            central_directory.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            // made by version 2.0, on MS-DOS
+            central_directory.extend_from_slice(&20u16.to_le_bytes());
+            central_directory.extend_from_slice(&common);
+            // the length of the comment, the disk, and the attributes
+            central_directory.extend_from_slice(&[0; 10]);
+            central_directory.extend_from_slice(&offset.to_le_bytes());
+            central_directory.extend_from_slice(name.as_bytes());
+
+            offset = (30 + name.len() + content.len())
+                .try_into()
+                .ok()
+                .and_then(|length: u32| offset.checked_add(length))
+                .ok_or_else(too_large)?;
+        }
+
+        let directory_size = u32::try_from(central_directory.len()).map_err(|_| too_large())?;
+        offset.checked_add(directory_size).ok_or_else(too_large)?;
+        writer.write_all(&central_directory)?;
+        writer.write_all(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes())?;
+        // the number of this disk, and of the disk where the central directory starts
+        writer.write_all(&[0; 4])?;
+        writer.write_all(&(self.len() as u16).to_le_bytes())?;
+        writer.write_all(&(self.len() as u16).to_le_bytes())?;
+        writer.write_all(&directory_size.to_le_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+        // the length of the comment
+        writer.write_all(&[0; 2])?;
+        writer.flush()
+    }
+}